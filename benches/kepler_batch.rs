@@ -0,0 +1,55 @@
+//! Compares solving Kepler's equation one orbit at a time
+//! with the batch solver on 10k orbits.
+//!
+//! ```
+//! cargo bench --bench kepler_batch --features simd
+//! ```
+
+#![allow(non_snake_case)]
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use keplerian_elements::astro::elliptic::{
+    estimate_anomaly, estimate_anomaly_batch,
+};
+use keplerian_elements::constants::TWO_PI;
+use keplerian_elements::Num;
+
+const ORBITS: usize = 10_000;
+const ROUNDS: usize = 20;
+const TOLERANCE: Num = 1e-5;
+
+fn main() {
+    let Ms: Vec<Num> = (0..ORBITS)
+        .map(|i| (i as Num * 0.618).rem_euclid(1.0) * TWO_PI)
+        .collect();
+    let es: Vec<Num> = (0..ORBITS)
+        .map(|i| (i as Num * 0.414).rem_euclid(1.0) * 0.7)
+        .collect();
+
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        for (&M, &e) in Ms.iter().zip(&es) {
+            black_box(estimate_anomaly(black_box(M), black_box(e), TOLERANCE));
+        }
+    }
+    let scalar = start.elapsed() / ROUNDS as u32;
+
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        black_box(estimate_anomaly_batch(
+            black_box(&Ms),
+            black_box(&es),
+            TOLERANCE,
+        ));
+    }
+    let batch = start.elapsed() / ROUNDS as u32;
+
+    println!("scalar: {scalar:.2?}");
+    println!("batch:  {batch:.2?}");
+    println!(
+        "speedup: {:.2}x",
+        scalar.as_secs_f64() / batch.as_secs_f64()
+    );
+}
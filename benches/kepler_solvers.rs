@@ -0,0 +1,85 @@
+//! Compares Newton's method with the fixed point iteration
+//! for solving Kepler's equation across the eccentricity range.
+//!
+//! The fixed point iteration wins below e ≈ 0.05 and quickly falls behind
+//! above that, which is where `estimate_anomaly_circular` switches methods.
+//! The fused Newton evaluates sin and cos together and Halley's method
+//! trades a second derivative for fewer iterations at high eccentricity.
+//!
+//! ```
+//! cargo bench --bench kepler_solvers
+//! ```
+
+#![allow(non_snake_case)]
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use keplerian_elements::constants::TWO_PI;
+use keplerian_elements::math::{
+    fixed_point_iteration, halley_approx, newton_approx, newton_approx_fused,
+};
+use keplerian_elements::Num;
+
+const SAMPLES: usize = 100_000;
+const TOLERANCE: Num = 1e-5;
+
+fn bench(f: impl Fn(Num) -> Num) -> Duration {
+    let start = Instant::now();
+
+    for i in 0..SAMPLES {
+        let M = i as Num / SAMPLES as Num * TWO_PI;
+        black_box(f(black_box(M)));
+    }
+
+    start.elapsed()
+}
+
+fn main() {
+    println!(
+        "{:>6} {:>14} {:>14} {:>14} {:>14}",
+        "e", "newton", "fused newton", "halley", "fixed point"
+    );
+
+    for e in [0.0, 0.01, 0.02, 0.05, 0.1, 0.2, 0.5, 0.8, 0.9] {
+        let newton = bench(|M| {
+            newton_approx(
+                |E| E - e * E.sin() - M,
+                |E| 1.0 - e * E.cos(),
+                M,
+                TOLERANCE,
+            )
+        });
+
+        let fused = bench(|M| {
+            newton_approx_fused(
+                |E| {
+                    let (sin, cos) = E.sin_cos();
+                    (E - e * sin - M, 1.0 - e * cos)
+                },
+                M,
+                TOLERANCE,
+            )
+        });
+
+        let halley = bench(|M| {
+            halley_approx(
+                |E| E - e * E.sin() - M,
+                |E| 1.0 - e * E.cos(),
+                |E| e * E.sin(),
+                M,
+                TOLERANCE,
+            )
+        });
+
+        let fixed_point = bench(|M| {
+            fixed_point_iteration(|E| M + e * E.sin(), M, TOLERANCE, 10_000)
+                .unwrap_or(Num::NAN)
+        });
+
+        println!(
+            "{e:>6} {newton:>14.2?} {fused:>14.2?} {halley:>14.2?} \
+             {fixed_point:>14.2?}"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+//! Compares computing the positions of 10k orbits one at a time
+//! with the batch conversion.
+//!
+//! Expect the batch to be about 2.5x faster than the naive loop on a single
+//! core, and about 5x with the `simd` feature (f32). The parallel variant
+//! scales further with the number of cores.
+//!
+//! ```
+//! cargo bench --bench kepler_to_cartesian --features simd,rayon
+//! ```
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use keplerian_elements::constants::{EARTH_MASS, PI, TWO_PI};
+use keplerian_elements::utils::kepler_to_cartesian_batch;
+#[cfg(feature = "rayon")]
+use keplerian_elements::utils::kepler_to_cartesian_batch_par;
+use keplerian_elements::{KeplerianElements, Num, Vec3};
+
+const ORBITS: usize = 10_000;
+const ROUNDS: usize = 20;
+const TOLERANCE: Num = 1e-5;
+const EPOCH: Num = 1_000.0;
+
+fn main() {
+    let elements: Vec<KeplerianElements> = (0..ORBITS)
+        .map(|i| {
+            let i = i as Num;
+
+            KeplerianElements {
+                semi_major_axis: 7e6 + (i * 0.271).rem_euclid(1.0) * 3e7,
+                eccentricity: (i * 0.414).rem_euclid(1.0) * 0.7,
+                inclination: (i * 0.173).rem_euclid(1.0) * PI,
+                right_ascension_of_the_ascending_node: (i * 0.618)
+                    .rem_euclid(1.0)
+                    * TWO_PI,
+                argument_of_periapsis: (i * 0.733).rem_euclid(1.0) * TWO_PI,
+                mean_anomaly_at_epoch: (i * 0.577).rem_euclid(1.0) * TWO_PI,
+                epoch: 0.0,
+            }
+        })
+        .collect();
+    let masses = vec![EARTH_MASS; ORBITS];
+    let mut positions = vec![Vec3::ZERO; ORBITS];
+
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        for (elements, position) in elements.iter().zip(&mut positions) {
+            *position = black_box(elements)
+                .state_vectors_at_epoch(EARTH_MASS, EPOCH, TOLERANCE)
+                .position;
+        }
+        black_box(&positions);
+    }
+    let naive = start.elapsed() / ROUNDS as u32;
+
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        kepler_to_cartesian_batch(
+            black_box(&elements),
+            &masses,
+            EPOCH,
+            TOLERANCE,
+            &mut positions,
+        );
+        black_box(&positions);
+    }
+    let batch = start.elapsed() / ROUNDS as u32;
+
+    println!("naive: {naive:.2?}");
+    println!("batch: {batch:.2?}");
+    println!("speedup: {:.2}x", naive.as_secs_f64() / batch.as_secs_f64());
+
+    #[cfg(feature = "rayon")]
+    {
+        let start = Instant::now();
+        for _ in 0..ROUNDS {
+            kepler_to_cartesian_batch_par(
+                black_box(&elements),
+                &masses,
+                EPOCH,
+                TOLERANCE,
+                &mut positions,
+            );
+            black_box(&positions);
+        }
+        let parallel = start.elapsed() / ROUNDS as u32;
+
+        println!("parallel: {parallel:.2?}");
+        println!(
+            "speedup: {:.2}x",
+            naive.as_secs_f64() / parallel.as_secs_f64()
+        );
+    }
+}
@@ -1,20 +1,41 @@
+#![allow(clippy::excessive_precision)]
+
+use std::collections::HashMap;
+use std::error::Error;
 use std::f32::consts::PI;
+use std::fs;
 
 use bevy::core_pipeline::bloom::BloomSettings;
 use bevy::pbr::NotShadowCaster;
 use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
 use bevy_egui::egui::{ComboBox, DragValue, Ui};
 use bevy_egui::{egui, EguiContexts, EguiPlugin};
 use keplerian_elements::astro::standard_gravitational_parameter;
-use keplerian_elements::constants::AU;
+use keplerian_elements::constants::{
+    ASTRONOMICAL_UNIT, SECONDS_PER_DAY, SECONDS_PER_HOUR,
+};
+use keplerian_elements::system::{Body, BodySystem};
+use keplerian_elements::trajectory::{
+    self, ClosestApproach, ManeuverNode, SegmentEnd, TrajectorySegment,
+    TrajectorySettings,
+};
 use keplerian_elements::utils::{yup2zup, zup2yup};
-use keplerian_elements::{KeplerianElements, StateVectors};
+use keplerian_elements::{KeplerSolver, KeplerianElements, StateVectors};
+use serde::{Deserialize, Serialize};
 use smooth_bevy_cameras::controllers::orbit::{
     OrbitCameraBundle, OrbitCameraController, OrbitCameraPlugin,
 };
 use smooth_bevy_cameras::{LookTransform, LookTransformPlugin};
 
-const USE_REAL_SOLAR_SYSTEM: bool = false;
+/// Epoch scales of the warp buttons, bound to the keys 1 to 4
+const TIME_WARP_PRESETS: [f32; 4] = [1.0, 1_000.0, 100_000.0, 1_000_000.0];
+
+/// Seconds between the clicks of a double click
+const DOUBLE_CLICK_TIME: f32 = 0.3;
+
+/// Smallest pick radius in pixels, so distant planets stay clickable
+const MIN_PICK_RADIUS: f32 = 8.0;
 
 fn main() {
     App::new()
@@ -24,39 +45,125 @@ fn main() {
         .add_plugins(EguiPlugin)
         .add_systems(Startup, setup)
         .add_systems(Update, ui)
+        .add_systems(Update, simulator_window)
+        .add_systems(Update, time_control_keys)
         .add_systems(Update, update_epoch)
-        .add_systems(Update, draw_orbits)
+        .add_systems(Update, draw_orbits.after(update_planets))
         .add_systems(Update, update_planets)
         .add_systems(Update, update_star)
         .add_systems(Update, draw_axis)
-        .add_systems(Update, draw_soi)
+        .add_systems(Update, draw_soi.after(update_planets))
+        .add_systems(Update, update_trajectory.after(update_planets))
+        .add_systems(Update, draw_trajectory.after(update_trajectory))
         .add_systems(Update, update_camera_focus)
+        .add_systems(Update, draw_labels.after(update_planets))
+        .add_systems(Update, pick_planets.after(update_planets))
+        .add_systems(Update, save_and_load.after(ui))
+        .add_systems(Update, add_and_delete_planets.after(ui))
+        .add_systems(Update, apply_preset.after(ui))
         .run();
 }
 
 #[derive(Resource)]
 struct State {
+    /// The system on screen, switched on the next update
+    /// when `pending_preset` is set
+    preset: Preset,
+    pending_preset: Option<Preset>,
+
     star_mass: f32,
+    star_radius: f32,
     tolerance: f32,
 
     epoch: f32,
-    update_epoch: bool,
+    paused: bool,
+    /// Runs the clock backwards at the same scale
+    rewind: bool,
     epoch_scale: f32,
+    /// Requested by the step buttons, applied on the next update
+    pending_epoch_step: f32,
 
     draw_orbits: bool,
     orbit_subdivisions: u32,
+    /// Colors the orbits from blue at the apoapsis to red at the periapsis
+    color_by_speed: bool,
+    show_speed_legend: bool,
     show_nodes: bool,
-    show_peri_and_apo_apsis: bool,
+    show_line_of_nodes: bool,
+    show_apsis_markers: bool,
     show_position_and_velocity: bool,
 
     draw_soi: bool,
 
+    show_labels: bool,
+    label_size: f32,
+
     draw_axis: bool,
     axis_scale: f32,
 
     distance_scaling: f32,
+    /// Exaggerates the sizes of the bodies, which are invisible to scale
+    body_scaling: f32,
     velocity_scaling: f32,
     focus_mode: FocusMode,
+    /// By name, like the focus
+    selected_planet: Option<String>,
+
+    system_path: String,
+    /// Requested by the file buttons, applied on the next update
+    pending_file_action: Option<FileAction>,
+    /// Result of the last save or load
+    file_status: Option<String>,
+
+    new_planet: NewPlanet,
+    /// Requested by the add button, spawned on the next update
+    pending_new_planet: Option<NewPlanet>,
+    /// The delete button asks for a confirmation first
+    confirm_delete: bool,
+    pending_delete: Option<String>,
+}
+
+/// Planet set up in the "Add planet" section
+#[derive(Debug, Clone)]
+struct NewPlanet {
+    name: String,
+    mass: f32,
+    radius: f32,
+    color: [f32; 3],
+    /// By name, the star when `None`
+    parent: Option<String>,
+    /// Starts the orbit from the state vectors instead of the elements
+    from_state_vectors: bool,
+    orbit: KeplerianElements,
+    state_vectors: StateVectors,
+}
+
+impl Default for NewPlanet {
+    fn default() -> Self {
+        Self {
+            name: "Comet".to_string(),
+            mass: 1e13,
+            radius: 1_000_000.0,
+            color: [1.0, 1.0, 1.0],
+            parent: None,
+            from_state_vectors: false,
+            orbit: KeplerianElements {
+                semi_major_axis: 5.0 * ASTRONOMICAL_UNIT,
+                eccentricity: 0.9,
+                ..KeplerianElements::default()
+            },
+            state_vectors: StateVectors::new(
+                ASTRONOMICAL_UNIT * Vec3::X,
+                Vec3::ZERO,
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileAction {
+    Save,
+    Load,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -69,13 +176,160 @@ enum FocusMode {
 #[derive(Component)]
 struct Planet {
     orbit: KeplerianElements,
+    /// Relative to the parent
     state_vectors: StateVectors,
+    solver: KeplerSolver,
     mass: f32,
+    radius: f32,
+    /// The star when `None`
+    parent: Option<Entity>,
+    /// Mass, radius and global position of the parent,
+    /// updated with the state vectors
+    parent_mass: f32,
+    parent_radius: f32,
+    parent_position: Vec3,
+}
+
+impl Planet {
+    fn new(
+        orbit: KeplerianElements,
+        mass: f32,
+        radius: f32,
+        parent: Option<Entity>,
+    ) -> Self {
+        Self {
+            orbit,
+            state_vectors: StateVectors::default(),
+            solver: KeplerSolver::new(),
+            mass,
+            radius,
+            parent,
+            parent_mass: 0.0,
+            parent_radius: 0.0,
+            parent_position: Vec3::ZERO,
+        }
+    }
+
+    fn global_position(&self) -> Vec3 {
+        self.parent_position + self.state_vectors.position
+    }
 }
 
 #[derive(Component)]
 struct Star;
 
+/// Mesh shared by the planets, for the ones spawned after the setup
+#[derive(Resource)]
+struct PlanetMesh(Handle<Mesh>);
+
+/// Everything needed to restore the viewer, saved as JSON
+#[derive(Serialize, Deserialize)]
+struct SavedSystem {
+    star_mass: f32,
+    star_radius: f32,
+    epoch: f32,
+    epoch_scale: f32,
+    distance_scaling: f32,
+    body_scaling: f32,
+    planets: Vec<PlanetData>,
+}
+
+/// Planet as saved to a file or listed by a preset, in SI units
+#[derive(Serialize, Deserialize)]
+struct PlanetData {
+    name: String,
+    mass: f32,
+    radius: f32,
+    color: [f32; 4],
+    /// By name, the star when `None`
+    #[serde(default)]
+    parent: Option<String>,
+    /// Orbits are saved as their elements,
+    /// the state vectors follow from them at the saved epoch
+    orbit: KeplerianElements,
+}
+
+/// Patched-conic trajectory of a spacecraft through the planets,
+/// recalculated every frame from the launch state
+#[derive(Resource)]
+struct TrajectorySimulator {
+    enabled: bool,
+    /// By name, the star when `None` or when there's no such planet
+    parent: Option<String>,
+    /// Relative to the parent at the launch epoch
+    state_vectors: StateVectors,
+    epoch: f32,
+    /// Burns along the trajectory, applied in chronological order
+    nodes: Vec<ManeuverNode>,
+    settings: TrajectorySettings,
+    show_ticks: bool,
+    show_tick_labels: bool,
+    /// Seconds of flight time between the ticks
+    tick_interval: f32,
+    /// Widens the interval to a tick per second of real time
+    /// at the current warp
+    scale_ticks_with_warp: bool,
+    /// By name, the planet to find the closest approach to
+    target: Option<String>,
+    /// Of the last recalculation
+    segments: Vec<SimulatedSegment>,
+    ticks: Vec<TrajectoryTick>,
+    approach: Option<TargetApproach>,
+}
+
+impl Default for TrajectorySimulator {
+    fn default() -> Self {
+        // Just past the escape velocity of a low Earth orbit,
+        // out into an orbit around the Sun
+        Self {
+            enabled: true,
+            parent: Some("Earth".to_string()),
+            state_vectors: StateVectors::new(
+                6_700_000.0 * Vec3::X,
+                11_300.0 * Vec3::Y,
+            ),
+            epoch: 0.0,
+            nodes: Vec::new(),
+            settings: TrajectorySettings::default(),
+            show_ticks: true,
+            show_tick_labels: false,
+            tick_interval: SECONDS_PER_DAY,
+            scale_ticks_with_warp: true,
+            target: None,
+            segments: Vec::new(),
+            ticks: Vec::new(),
+            approach: None,
+        }
+    }
+}
+
+/// Graduation of the flight time along the simulated trajectory
+struct TrajectoryTick {
+    epoch: f32,
+    /// Global, around the parent of the segment where it is now
+    position: Vec3,
+}
+
+/// Closest approach of the simulated trajectory to the target
+struct TargetApproach {
+    approach: ClosestApproach,
+    /// Global, around the parent of the segment where it is now
+    craft_position: Vec3,
+    /// Where the target is at the approach, in the same frame
+    target_position: Vec3,
+}
+
+/// Segment of the simulated trajectory, sampled for drawing
+struct SimulatedSegment {
+    segment: TrajectorySegment,
+    parent_name: String,
+    /// Global position of the parent at the current epoch,
+    /// the segments are drawn around the parents where they are now
+    parent_position: Vec3,
+    /// Relative to the parent, from the start to the end of the segment
+    points: Vec<Vec3>,
+}
+
 fn ui(
     mut egui_context: EguiContexts,
     mut state: ResMut<State>,
@@ -83,48 +337,36 @@ fn ui(
     mut camera: Query<&mut OrbitCameraController>,
 ) {
     egui::Window::new("Settings").show(egui_context.ctx_mut(), |ui| {
+        ComboBox::from_label("Preset")
+            .selected_text(state.preset.name())
+            .show_ui(ui, |ui| {
+                for preset in Preset::ALL {
+                    if ui
+                        .selectable_label(state.preset == preset, preset.name())
+                        .clicked()
+                    {
+                        state.pending_preset = Some(preset);
+                    }
+                }
+            });
+
         ui.collapsing("Orbits", |ui| {
             for (mut planet, name) in planets.iter_mut() {
                 ui.collapsing(name.as_str(), |ui| {
                     ui.label(name.to_string());
 
                     value_slider(ui, "Mass", &mut planet.mass);
+                    value_slider(ui, "Radius", &mut planet.radius);
 
                     // --- Elements ---
+                    let parent_mass = planet.parent_mass;
+
                     ui.collapsing("Orbital Elements", |ui| {
                         let orbit = &mut planet.orbit;
-                        value_slider_min_max(
-                            ui,
-                            "Semi major axis",
-                            &mut orbit.semi_major_axis,
-                            f32::MIN,
-                            f32::MAX,
-                        );
-                        value_slider(
-                            ui,
-                            "Eccentricity",
-                            &mut orbit.eccentricity,
-                        );
-                        value_slider(ui, "Inclination", &mut orbit.inclination);
-                        value_slider(
-                            ui,
-                            "Longitude of ascending node",
-                            &mut orbit.right_ascension_of_the_ascending_node,
-                        );
-                        value_slider(
-                            ui,
-                            "Argument of periapsis",
-                            &mut orbit.argument_of_periapsis,
-                        );
-                        value_slider(
-                            ui,
-                            "Mean anomaly",
-                            &mut orbit.mean_anomaly_at_epoch,
-                        );
-                        value_slider(ui, "Epoch", &mut orbit.epoch);
+                        elements_sliders(ui, orbit);
 
                         let v = orbit.true_anomaly_at_epoch(
-                            state.star_mass,
+                            parent_mass,
                             state.epoch,
                             state.tolerance,
                         );
@@ -132,10 +374,8 @@ fn ui(
                         ui.label(format!("True anomaly: {v}",));
 
                         let e = orbit.eccentricity;
-                        let h =
-                            orbit.specific_angular_momentum(state.star_mass);
-                        let μ =
-                            standard_gravitational_parameter(state.star_mass);
+                        let h = orbit.specific_angular_momentum(parent_mass);
+                        let μ = standard_gravitational_parameter(parent_mass);
 
                         let r = (h.powi(2) / μ) / (1.0 + e * v.cos());
                         ui.label(format!("r: {r}"));
@@ -151,7 +391,7 @@ fn ui(
 
                         planet.state_vectors =
                             planet.orbit.state_vectors_at_epoch(
-                                state.star_mass,
+                                parent_mass,
                                 state.epoch,
                                 state.tolerance,
                             );
@@ -188,8 +428,7 @@ fn ui(
                                 * state.velocity_scaling),
                         );
 
-                        planet.orbit =
-                            sv.to_elements(state.star_mass, state.epoch);
+                        planet.orbit = sv.to_elements(parent_mass, state.epoch);
                     });
                 });
             }
@@ -204,16 +443,22 @@ fn ui(
                 100.0,
             );
             value_slider(ui, "Mass", &mut state.star_mass);
+            value_slider(ui, "Radius", &mut state.star_radius);
             value_slider(ui, "Epoch", &mut state.epoch);
             value_slider(ui, "Epoch scale", &mut state.epoch_scale);
-            ui.checkbox(&mut state.update_epoch, "Update Epoch");
 
             ui.checkbox(&mut state.draw_orbits, "Draw orbits");
             if state.draw_orbits {
                 ui.checkbox(&mut state.show_nodes, "Show nodes");
+                if state.show_nodes {
+                    ui.checkbox(
+                        &mut state.show_line_of_nodes,
+                        "Show line of nodes",
+                    );
+                }
                 ui.checkbox(
-                    &mut state.show_peri_and_apo_apsis,
-                    "Show peri and apo apsis",
+                    &mut state.show_apsis_markers,
+                    "Show apsis markers",
                 );
 
                 ui.checkbox(
@@ -226,27 +471,141 @@ fn ui(
                     "Orbit subdivisions",
                     &mut state.orbit_subdivisions,
                 );
+
+                ui.checkbox(&mut state.color_by_speed, "Color by speed");
+                if state.color_by_speed {
+                    ui.checkbox(&mut state.show_speed_legend, "Show legend");
+                }
             }
 
             ui.checkbox(&mut state.draw_soi, "Draw SOI");
 
+            ui.checkbox(&mut state.show_labels, "Show labels");
+            if state.show_labels {
+                value_slider_min_max(
+                    ui,
+                    "Label size",
+                    &mut state.label_size,
+                    6.0,
+                    48.0,
+                );
+            }
+
             ui.checkbox(&mut state.draw_axis, "Draw axis");
             if state.draw_axis {
                 value_slider(ui, "Axis scale", &mut state.axis_scale);
             }
 
+            let speed = 0.01 * state.distance_scaling;
             value_slider_min_max_with_speed(
                 ui,
                 "Distance scaling",
                 &mut state.distance_scaling,
-                0.000001,
+                1e-12,
+                1.0,
+                speed,
+            );
+            value_slider_min_max(
+                ui,
+                "Body scaling",
+                &mut state.body_scaling,
                 1.0,
-                0.0000001,
+                10_000.0,
             );
 
             value_slider(ui, "Velocity scaling", &mut state.velocity_scaling);
         });
 
+        ui.collapsing("Add planet", |ui| {
+            let epoch = state.epoch;
+            let parent_mass = planets
+                .iter()
+                .find(|(_, name)| {
+                    Some(name.as_str()) == state.new_planet.parent.as_deref()
+                })
+                .map_or(state.star_mass, |(planet, _)| planet.mass);
+            let new = &mut state.new_planet;
+
+            ui.horizontal(|ui| {
+                ui.label("Name");
+                ui.text_edit_singleline(&mut new.name);
+            });
+            value_slider(ui, "Mass", &mut new.mass);
+            value_slider(ui, "Radius", &mut new.radius);
+            ui.horizontal(|ui| {
+                ui.label("Color");
+                ui.color_edit_button_rgb(&mut new.color);
+            });
+            ComboBox::from_label("Parent")
+                .selected_text(new.parent.as_deref().unwrap_or("Sun"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut new.parent, None, "Sun");
+                    for (_, name) in planets.iter() {
+                        ui.selectable_value(
+                            &mut new.parent,
+                            Some(name.to_string()),
+                            name.as_str(),
+                        );
+                    }
+                });
+
+            ui.checkbox(&mut new.from_state_vectors, "From state vectors");
+            if new.from_state_vectors {
+                let sv = &mut new.state_vectors;
+                vec3_drag(ui, "Position", &mut sv.position);
+                vec3_drag(ui, "Velocity", &mut sv.velocity);
+
+                if ui.button("Circular velocity").clicked() {
+                    let r = sv.position.length();
+                    let mu = standard_gravitational_parameter(parent_mass);
+                    let direction =
+                        Vec3::Z.cross(sv.position).normalize_or_zero();
+                    sv.velocity = direction * (mu / r).sqrt();
+                }
+            } else {
+                elements_sliders(ui, &mut new.orbit);
+            }
+
+            let name_taken =
+                planets.iter().any(|(_, n)| n.as_str() == new.name);
+            if name_taken {
+                ui.label("A planet with this name already exists");
+            }
+
+            let add = ui.add_enabled(
+                !name_taken && !new.name.is_empty(),
+                egui::Button::new("Add planet"),
+            );
+            if add.clicked() {
+                let mut planet = new.clone();
+                if planet.from_state_vectors {
+                    planet.orbit =
+                        planet.state_vectors.to_elements(parent_mass, epoch);
+                }
+                state.pending_new_planet = Some(planet);
+            }
+        });
+
+        ui.collapsing("File", |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Path");
+                ui.text_edit_singleline(&mut state.system_path);
+            });
+
+            ui.horizontal(|ui| {
+                if ui.button("Save system").clicked() {
+                    state.pending_file_action = Some(FileAction::Save);
+                }
+                if ui.button("Load system").clicked() {
+                    state.pending_file_action = Some(FileAction::Load);
+                }
+            });
+
+            if let Some(status) = &state.file_status {
+                ui.label(status);
+            }
+        });
+
         if let Ok(mut camera) = camera.get_single_mut() {
             ui.collapsing("Camera", |ui| {
                 ui.label("Mouse rotate sensitivity");
@@ -284,6 +643,66 @@ fn ui(
         }
     });
 
+    egui::TopBottomPanel::bottom("Time").show(egui_context.ctx_mut(), |ui| {
+        ui.horizontal(|ui| {
+            let label = if state.paused { "Play" } else { "Pause" };
+            if ui.button(label).clicked() {
+                state.paused = !state.paused;
+            }
+
+            ui.toggle_value(&mut state.rewind, "Rewind");
+
+            ui.separator();
+
+            for preset in TIME_WARP_PRESETS {
+                let selected = !state.paused && state.epoch_scale == preset;
+                if ui
+                    .selectable_label(selected, format!("{preset}x"))
+                    .clicked()
+                {
+                    state.epoch_scale = preset;
+                    state.paused = false;
+                }
+            }
+
+            ui.separator();
+
+            let direction = if state.rewind { -1.0 } else { 1.0 };
+            let sign = if state.rewind { "-" } else { "+" };
+            if ui.button(format!("{sign}1 hour")).clicked() {
+                state.pending_epoch_step += direction * SECONDS_PER_HOUR;
+            }
+            if ui.button(format!("{sign}1 day")).clicked() {
+                state.pending_epoch_step += direction * SECONDS_PER_DAY;
+            }
+
+            ui.separator();
+
+            // Before the start the days count down, T-1.50
+            ui.label(format!(
+                "T{:+.2} days at {}{}x",
+                state.epoch / SECONDS_PER_DAY,
+                if state.rewind { "-" } else { "" },
+                state.epoch_scale
+            ));
+        });
+    });
+
+    if state.draw_orbits && state.color_by_speed && state.show_speed_legend {
+        egui::Window::new("Speed").show(egui_context.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Apoapsis");
+                for i in 0..=10 {
+                    let [r, g, b, _] =
+                        speed_color(i as f32 / 10.0).as_rgba_u8();
+                    ui.colored_label(egui::Color32::from_rgb(r, g, b), "■");
+                }
+                ui.label("Periapsis");
+            });
+            ui.label("Normalized per orbit, slowest to fastest");
+        });
+    }
+
     egui::Window::new("About").show(egui_context.ctx_mut(), |ui| {
         ui.heading("Hello!");
 
@@ -297,6 +716,12 @@ fn ui(
         ui.label("You can use the right click and drag, but it's not very efficient");
 
         ui.label("Use the focus window to focus on a different celestial object");
+        ui.label("Click a planet to select it, double click to focus on it");
+        ui.label("Switch between the systems with the preset in the settings");
+
+        ui.label("Space to pause, 1 to 4 for the time warp presets");
+        ui.label("H and D step the time by an hour or a day");
+        ui.label("R to rewind, the steps follow the direction of the clock");
     });
 
     egui::Window::new("Focus").show(egui_context.ctx_mut(), |ui| {
@@ -308,10 +733,7 @@ fn ui(
         ComboBox::from_label("Choose focus")
             .selected_text(&current)
             .show_ui(ui, |ui| {
-                if ui
-                    .selectable_label(current == "Sun".to_string(), "Sun")
-                    .clicked()
-                {
+                if ui.selectable_label(current == "Sun", "Sun").clicked() {
                     state.focus_mode = FocusMode::Sun;
                 }
 
@@ -319,7 +741,7 @@ fn ui(
                     if ui
                         .selectable_label(
                             current == name.to_string(),
-                            &name.to_string(),
+                            name.to_string(),
                         )
                         .clicked()
                     {
@@ -327,6 +749,269 @@ fn ui(
                     }
                 }
             });
+
+        if let Some(selected) = state.selected_planet.clone() {
+            ui.horizontal(|ui| {
+                ui.label(format!("Selected: {selected}"));
+                if ui.button("Focus").clicked() {
+                    state.focus_mode = FocusMode::Planet(selected.clone());
+                }
+                if ui.button("Delete").clicked() {
+                    state.confirm_delete = true;
+                }
+            });
+
+            if state.confirm_delete {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Delete {selected}?"));
+                    if ui.button("Yes").clicked() {
+                        state.pending_delete = Some(selected);
+                        state.confirm_delete = false;
+                    }
+                    if ui.button("No").clicked() {
+                        state.confirm_delete = false;
+                    }
+                });
+            }
+        }
+    });
+}
+
+fn simulator_window(
+    mut egui_context: EguiContexts,
+    mut simulator: ResMut<TrajectorySimulator>,
+    state: Res<State>,
+    planets: Query<(&Planet, &Name)>,
+) {
+    egui::Window::new("Trajectory").show(egui_context.ctx_mut(), |ui| {
+        let simulator = simulator.as_mut();
+        ui.checkbox(&mut simulator.enabled, "Simulate trajectory");
+        if !simulator.enabled {
+            return;
+        }
+
+        let parent_mass = planets
+            .iter()
+            .find(|(_, name)| {
+                Some(name.as_str()) == simulator.parent.as_deref()
+            })
+            .map_or(state.star_mass, |(planet, _)| planet.mass);
+
+        ComboBox::from_label("Launch from")
+            .selected_text(simulator.parent.as_deref().unwrap_or("Sun"))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut simulator.parent, None, "Sun");
+                for (_, name) in planets.iter() {
+                    ui.selectable_value(
+                        &mut simulator.parent,
+                        Some(name.to_string()),
+                        name.as_str(),
+                    );
+                }
+            });
+
+        let sv = &mut simulator.state_vectors;
+        vec3_drag(ui, "Position", &mut sv.position);
+        vec3_drag(ui, "Velocity", &mut sv.velocity);
+
+        if ui.button("Circular velocity").clicked() {
+            let r = sv.position.length();
+            let mu = standard_gravitational_parameter(parent_mass);
+            let direction = Vec3::Z.cross(sv.position).normalize_or_zero();
+            sv.velocity = direction * (mu / r).sqrt();
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Launch epoch");
+            ui.add(DragValue::new(&mut simulator.epoch));
+            if ui.button("Now").clicked() {
+                simulator.epoch = state.epoch;
+            }
+        });
+
+        ui.collapsing("Maneuver nodes", |ui| {
+            let launch = simulator.epoch;
+            let mut removed = None;
+
+            for (n, node) in simulator.nodes.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Node {}", n + 1));
+                    if ui.button("Remove").clicked() {
+                        removed = Some(n);
+                    }
+                });
+
+                // After the launch, the epochs are too large to drag
+                let mut days = (node.epoch - launch) / SECONDS_PER_DAY;
+                ui.horizontal(|ui| {
+                    ui.label("Days after launch");
+                    let drag = DragValue::new(&mut days).speed(0.01);
+                    if ui.add(drag).changed() {
+                        node.epoch = launch + days * SECONDS_PER_DAY;
+                    }
+                });
+
+                for (name, value) in [
+                    ("Prograde", &mut node.prograde),
+                    ("Normal", &mut node.normal),
+                    ("Radial", &mut node.radial),
+                ] {
+                    ui.horizontal(|ui| {
+                        ui.label(name);
+                        ui.add(DragValue::new(value).suffix(" m/s"));
+                    });
+                }
+            }
+
+            if let Some(n) = removed {
+                simulator.nodes.remove(n);
+            }
+
+            // Half an orbit ahead, at the opposite apsis of a burn there
+            if ui.button("Add node").clicked() {
+                let orbit = simulator
+                    .state_vectors
+                    .to_elements(parent_mass, simulator.epoch);
+                let ahead = if orbit.eccentricity < 1.0 {
+                    0.5 * orbit.period(parent_mass)
+                } else {
+                    SECONDS_PER_DAY
+                };
+
+                simulator.nodes.push(ManeuverNode {
+                    epoch: simulator.epoch + ahead,
+                    ..ManeuverNode::default()
+                });
+            }
+        });
+
+        let settings = &mut simulator.settings;
+        ui.horizontal(|ui| {
+            ui.label("Max segments");
+            ui.add(
+                DragValue::new(&mut settings.max_segments).clamp_range(1..=64),
+            );
+        });
+        // Written back only when dragged, so the duration doesn't creep
+        let mut days = settings.max_segment_duration / SECONDS_PER_DAY;
+        ui.horizontal(|ui| {
+            ui.label("Max segment days");
+            let drag = DragValue::new(&mut days).clamp_range(1.0..=1e5);
+            if ui.add(drag).changed() {
+                settings.max_segment_duration = days * SECONDS_PER_DAY;
+            }
+        });
+
+        ui.checkbox(&mut simulator.show_ticks, "Show time ticks");
+        if simulator.show_ticks {
+            ui.checkbox(&mut simulator.show_tick_labels, "Show tick labels");
+
+            let mut hours = simulator.tick_interval / SECONDS_PER_HOUR;
+            ui.horizontal(|ui| {
+                ui.label("Tick interval hours");
+                let drag = DragValue::new(&mut hours)
+                    .speed(0.1)
+                    .clamp_range(0.01..=1e6);
+                if ui.add(drag).changed() {
+                    simulator.tick_interval = hours * SECONDS_PER_HOUR;
+                }
+            });
+            ui.checkbox(
+                &mut simulator.scale_ticks_with_warp,
+                "Scale ticks with the warp",
+            );
+        }
+
+        ComboBox::from_label("Target")
+            .selected_text(simulator.target.as_deref().unwrap_or("None"))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut simulator.target, None, "None");
+                for (_, name) in planets.iter() {
+                    ui.selectable_value(
+                        &mut simulator.target,
+                        Some(name.to_string()),
+                        name.as_str(),
+                    );
+                }
+            });
+
+        if let (Some(target), Some(closest)) =
+            (&simulator.target, &simulator.approach)
+        {
+            let approach = &closest.approach;
+            ui.label(format!(
+                "Closest approach to {target} at T{:+.2} days",
+                approach.epoch / SECONDS_PER_DAY
+            ));
+            ui.label(format!(
+                "Miss distance: {:.0} km",
+                approach.distance / 1000.0
+            ));
+            ui.label(format!(
+                "Relative speed: {:.1} m/s",
+                approach.relative_speed
+            ));
+        }
+
+        ui.separator();
+
+        if simulator.segments.is_empty() {
+            ui.label("No trajectory, the launch state has no orbit");
+        }
+        for simulated in &simulator.segments {
+            let segment = &simulated.segment;
+            ui.label(format!(
+                "{}: T{:+.2} to T{:+.2} days, {}",
+                simulated.parent_name,
+                segment.start_epoch / SECONDS_PER_DAY,
+                segment.end_epoch / SECONDS_PER_DAY,
+                segment_end_name(segment.end),
+            ));
+        }
+    });
+}
+
+fn segment_end_name(end: SegmentEnd) -> &'static str {
+    match end {
+        SegmentEnd::Loop => "loops",
+        SegmentEnd::SoiExit => "escapes",
+        SegmentEnd::SoiEntry(_) => "encounter",
+        SegmentEnd::Impact => "impact",
+        SegmentEnd::DurationLimit => "duration limit",
+        SegmentEnd::Maneuver(_) => "burn",
+    }
+}
+
+fn elements_sliders(ui: &mut Ui, orbit: &mut KeplerianElements) {
+    value_slider_min_max(
+        ui,
+        "Semi major axis",
+        &mut orbit.semi_major_axis,
+        f32::MIN,
+        f32::MAX,
+    );
+    value_slider(ui, "Eccentricity", &mut orbit.eccentricity);
+    value_slider(ui, "Inclination", &mut orbit.inclination);
+    value_slider(
+        ui,
+        "Longitude of ascending node",
+        &mut orbit.right_ascension_of_the_ascending_node,
+    );
+    value_slider(
+        ui,
+        "Argument of periapsis",
+        &mut orbit.argument_of_periapsis,
+    );
+    value_slider(ui, "Mean anomaly", &mut orbit.mean_anomaly_at_epoch);
+    value_slider(ui, "Epoch", &mut orbit.epoch);
+}
+
+fn vec3_drag(ui: &mut Ui, name: &str, value: &mut Vec3) {
+    ui.horizontal(|ui| {
+        ui.label(name);
+        ui.add(DragValue::new(&mut value.x).prefix("x: "));
+        ui.add(DragValue::new(&mut value.y).prefix("y: "));
+        ui.add(DragValue::new(&mut value.z).prefix("z: "));
     });
 }
 
@@ -381,24 +1066,43 @@ fn setup(
         brightness: 0.01,
     });
 
+    // The scales and the star come with the preset
     commands.insert_resource(State {
+        preset: Preset::SolarSystem,
+        pending_preset: Some(Preset::SolarSystem),
         tolerance: 0.01,
-        // Sun Mass
-        star_mass: 1.989e7,
+        star_mass: 0.0,
+        star_radius: 0.0,
         epoch: 0.0,
-        epoch_scale: 1000.0,
-        update_epoch: true,
+        epoch_scale: 0.0,
+        paused: false,
+        rewind: false,
+        pending_epoch_step: 0.0,
         draw_orbits: true,
         orbit_subdivisions: 100,
+        color_by_speed: false,
+        show_speed_legend: true,
         show_nodes: false,
-        show_peri_and_apo_apsis: false,
+        show_line_of_nodes: true,
+        show_apsis_markers: false,
         show_position_and_velocity: false,
         draw_soi: true,
+        show_labels: true,
+        label_size: 14.0,
         draw_axis: true,
         axis_scale: 1000.0,
-        distance_scaling: 1e-6,
-        velocity_scaling: 10000000000000.0,
+        distance_scaling: 0.0,
+        body_scaling: 0.0,
+        velocity_scaling: 0.0,
         focus_mode: FocusMode::Sun,
+        selected_planet: None,
+        system_path: "system.json".to_string(),
+        pending_file_action: None,
+        file_status: None,
+        new_planet: NewPlanet::default(),
+        pending_new_planet: None,
+        confirm_delete: false,
+        pending_delete: None,
     });
 
     let sphere = meshes.add(
@@ -436,23 +1140,25 @@ fn setup(
         .insert(NotShadowCaster)
         .insert(Star);
 
-    if USE_REAL_SOLAR_SYSTEM {
-        spawn_solar_system(&mut commands, sphere, materials.as_mut());
-    } else {
-        spawn_test_system(&mut commands, sphere, materials.as_mut());
-    }
+    commands.insert_resource(PlanetMesh(sphere));
+    commands.insert_resource(TrajectorySimulator::default());
 
+    // Far enough for the outer planets
     commands
-        .spawn(Camera3dBundle::default())
+        .spawn(Camera3dBundle {
+            projection: PerspectiveProjection {
+                far: 1_000_000.0,
+                ..default()
+            }
+            .into(),
+            ..default()
+        })
         .insert(BloomSettings::OLD_SCHOOL)
         .insert(OrbitCameraBundle::new(
-            {
-                let mut controller = OrbitCameraController::default();
-
-                controller.mouse_rotate_sensitivity = Vec2::ONE * 1.0;
-                controller.mouse_translate_sensitivity = Vec2::ONE * 10.0;
-
-                controller
+            OrbitCameraController {
+                mouse_rotate_sensitivity: Vec2::ONE * 1.0,
+                mouse_translate_sensitivity: Vec2::ONE * 10.0,
+                ..Default::default()
             },
             Vec3::new(-2.0, 5.0, 5.0),
             Vec3::new(0., 0., 0.),
@@ -460,261 +1166,529 @@ fn setup(
         ));
 }
 
-fn spawn_test_system(
-    commands: &mut Commands,
-    sphere: Handle<Mesh>,
-    materials: &mut Assets<StandardMaterial>,
-) {
-    let mut planet_material = |color: Color| {
-        materials.add(StandardMaterial {
-            base_color: color,
-            emissive: color,
-            perceptual_roughness: 1.0,
-            ..Default::default()
-        })
-    };
-
-    commands
-        .spawn(PbrBundle {
-            mesh: sphere.clone(),
-            material: planet_material(Color::BEIGE),
-            ..Default::default()
-        })
-        .insert(Planet {
-            orbit: KeplerianElements {
-                semi_major_axis: 0.38709927 * AU,
-                eccentricity: 0.20563593,
-                inclination: 0.12,
-                right_ascension_of_the_ascending_node: 0.84,
-                argument_of_periapsis: 1.35,
-                mean_anomaly_at_epoch: 4.40,
-                epoch: 0.0, // Example epoch year
-            },
-            state_vectors: StateVectors::default(),
-            mass: 3.285,
-        })
-        .insert(Name::new("Test Planet"));
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Preset {
+    Kerbol,
+    SolarSystem,
+    Empty,
 }
 
-fn spawn_solar_system(
-    commands: &mut Commands,
-    sphere: Handle<Mesh>,
-    materials: &mut Assets<StandardMaterial>,
-) {
-    let mut planet_material = |color: Color| {
-        materials.add(StandardMaterial {
-            base_color: color,
-            emissive: color,
-            perceptual_roughness: 1.0,
-            ..Default::default()
-        })
-    };
+impl Preset {
+    const ALL: [Preset; 3] =
+        [Preset::Kerbol, Preset::SolarSystem, Preset::Empty];
 
-    commands
-        .spawn(PbrBundle {
-            mesh: sphere.clone(),
-            material: planet_material(Color::BEIGE),
-            ..Default::default()
-        })
-        .insert(Planet {
-            orbit: KeplerianElements {
-                semi_major_axis: 0.38709927 * AU,
-                eccentricity: 0.20563593,
-                inclination: 0.12,
-                right_ascension_of_the_ascending_node: 0.84,
-                argument_of_periapsis: 1.35,
-                mean_anomaly_at_epoch: 4.40,
-                epoch: 0.0, // Example epoch year
-            },
-            state_vectors: StateVectors::default(),
-            mass: 3.285,
-        })
-        .insert(Name::new("Mercury"));
+    fn name(self) -> &'static str {
+        match self {
+            Preset::Kerbol => "Kerbol",
+            Preset::SolarSystem => "Solar system",
+            Preset::Empty => "Empty",
+        }
+    }
 
-    commands
-        .spawn(PbrBundle {
-            mesh: sphere.clone(),
-            material: planet_material(Color::ORANGE),
-            ..Default::default()
-        })
-        .insert(Planet {
-            orbit: KeplerianElements {
-                semi_major_axis: 0.7233 * AU,
-                eccentricity: 0.00676,
-                inclination: 0.0593,
-                right_ascension_of_the_ascending_node: 1.34,
-                argument_of_periapsis: 2.30,
-                mean_anomaly_at_epoch: 3.17,
-                epoch: 0.0,
-            },
-            state_vectors: StateVectors::default(),
-            mass: 4.867e1,
-        })
-        .insert(Name::new("Venus"));
+    /// The catalog of the system, shared with the catalog tests
+    fn catalog(self) -> BodySystem {
+        let catalog = match self {
+            Preset::Kerbol => BodySystem::from_toml(include_str!(
+                "../tests/fixtures/kerbol.toml"
+            )),
+            // Keeps the Sun of the solar system
+            Preset::SolarSystem | Preset::Empty => BodySystem::from_json(
+                include_str!("../tests/fixtures/solar_system.json"),
+            ),
+        };
 
-    commands
-        .spawn(PbrBundle {
-            mesh: sphere.clone(),
-            material: planet_material(Color::BLUE),
-            ..Default::default()
-        })
-        .insert(Planet {
-            orbit: KeplerianElements {
-                eccentricity: 0.01673,
-                semi_major_axis: 1.0000 * AU,
-                inclination: 0.01,
-                right_ascension_of_the_ascending_node: 0.0,
-                argument_of_periapsis: 1.7964674,
-                mean_anomaly_at_epoch: 0.0,
-                epoch: 0.0,
-            },
-            state_vectors: StateVectors::default(),
-            mass: 5.972e1,
-        })
-        .insert(Name::new("Earth"));
+        catalog.expect("The preset catalogs are valid")
+    }
 
-    commands
-        .spawn(PbrBundle {
-            mesh: sphere.clone(),
-            material: planet_material(Color::RED),
-            ..Default::default()
-        })
-        .insert(Planet {
-            orbit: KeplerianElements {
-                eccentricity: 0.09339410,
-                semi_major_axis: 1.52371034 * AU,
-                inclination: 0.03232349774693498376462675303241,
-                right_ascension_of_the_ascending_node:
-                    0.86760317116638123268876668101569,
-                argument_of_periapsis: 5.8657025501025428421251399347365,
-                mean_anomaly_at_epoch: 6.2034237603634456152598740984391,
-                epoch: 0.0,
-            },
-            state_vectors: StateVectors::default(),
-            mass: 0.642,
-        })
-        .insert(Name::new("Mars"));
+    /// Mass and radius of the star, the root of the catalog
+    fn star(self) -> (f32, f32) {
+        let catalog = self.catalog();
+        let star = catalog
+            .bodies()
+            .iter()
+            .find(|body| body.parent.is_none())
+            .expect("The preset catalogs have a star");
 
-    commands
-        .spawn(PbrBundle {
-            mesh: sphere.clone(),
-            material: planet_material(Color::GREEN),
-            ..Default::default()
-        })
-        .insert(Planet {
-            orbit: KeplerianElements {
-                eccentricity: 0.04854,
-                semi_major_axis: 5.2025 * AU,
-                inclination: 0.02267182698340634120423874308267,
-                right_ascension_of_the_ascending_node:
-                    1.7503907068251131326967694717172,
-                argument_of_periapsis: 0.24905848425959083062701067266333,
-                mean_anomaly_at_epoch: 0.59917153220965334375790304082214,
-                epoch: 0.0,
-            },
-            state_vectors: StateVectors::default(),
-            mass: 1.898e4,
-        })
-        .insert(Name::new("Jupiter"));
+        (star.mass, star.radius.unwrap_or_default())
+    }
 
-    commands
-        .spawn(PbrBundle {
-            mesh: sphere.clone(),
-            material: planet_material(Color::YELLOW_GREEN),
-            ..Default::default()
-        })
-        .insert(Planet {
-            orbit: KeplerianElements {
-                eccentricity: 0.05551,
-                semi_major_axis: 9.5415 * AU,
-                inclination: 0.04352851154473857964847684776611,
-                right_ascension_of_the_ascending_node:
-                    1.9833921619663561312160821893105,
-                argument_of_periapsis: 1.6207127434019344451313392476185,
-                mean_anomaly_at_epoch: 0.8740608893987602521233843368591,
-                epoch: 0.0,
-            },
-            state_vectors: StateVectors::default(),
-            mass: 5.683e3,
-        })
-        .insert(Name::new("Saturn"));
+    /// Distance, body and velocity scaling, epoch scale
+    /// and the distance of the camera, the systems differ
+    /// by orders of magnitude
+    fn scales(self) -> (f32, f32, f32, f32, f32) {
+        match self {
+            Preset::Kerbol => (1e-8, 10.0, 1e5, 100_000.0, 300.0),
+            // Small enough for the Galilean moons to clear Jupiter
+            Preset::SolarSystem | Preset::Empty => {
+                (1e-8, 3.0, 1e6, 1_000_000.0, 4_000.0)
+            }
+        }
+    }
 
-    commands
-        .spawn(PbrBundle {
-            mesh: sphere.clone(),
-            material: planet_material(Color::ALICE_BLUE),
-            ..Default::default()
-        })
-        .insert(Planet {
-            orbit: KeplerianElements {
-                eccentricity: 0.04686,
-                semi_major_axis: 19.188 * AU,
-                inclination: 0.01349139511791616762962012964042,
-                right_ascension_of_the_ascending_node:
-                    1.2908455147750061550927616923742,
-                argument_of_periapsis: 3.0094712292138224894895199921049,
-                mean_anomaly_at_epoch: 5.4838245097661835306942363945912,
-                epoch: 0.0,
-            },
-            state_vectors: StateVectors::default(),
-            mass: 8.681e2,
-        })
-        .insert(Name::new("Uranus"));
+    fn planets(self) -> Vec<PlanetData> {
+        if self == Preset::Empty {
+            return Vec::new();
+        }
+
+        let catalog = self.catalog();
+
+        catalog
+            .bodies()
+            .iter()
+            .filter_map(|body| {
+                let parent = catalog.body(body.parent?)?;
+
+                Some(PlanetData {
+                    name: body.name.clone(),
+                    mass: body.mass,
+                    radius: body.radius.unwrap_or_default(),
+                    color: preset_color(&body.name).as_rgba_f32(),
+                    // The moons orbit a planet, the rest the star
+                    parent: parent.parent.map(|_| parent.name.clone()),
+                    orbit: body.elements,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Colors of the bodies in the preset catalogs
+fn preset_color(name: &str) -> Color {
+    match name {
+        "Moho" => Color::rgb(0.6, 0.4, 0.3),
+        "Eve" => Color::PURPLE,
+        "Kerbin" => Color::BLUE,
+        "Mun" => Color::GRAY,
+        "Minmus" => Color::rgb(0.6, 0.9, 0.8),
+        "Duna" => Color::ORANGE_RED,
+        "Ike" => Color::DARK_GRAY,
+        "Dres" => Color::GRAY,
+        "Jool" => Color::LIME_GREEN,
+        "Eeloo" => Color::ALICE_BLUE,
+        "Mercury" => Color::BEIGE,
+        "Venus" => Color::ORANGE,
+        "Earth" => Color::BLUE,
+        "Mars" => Color::RED,
+        "Jupiter" => Color::GREEN,
+        "Saturn" => Color::YELLOW_GREEN,
+        "Uranus" => Color::ALICE_BLUE,
+        "Neptune" => Color::MIDNIGHT_BLUE,
+        "Moon" => Color::GRAY,
+        "Io" => Color::YELLOW,
+        "Europa" => Color::BISQUE,
+        "Ganymede" => Color::SILVER,
+        "Callisto" => Color::DARK_GRAY,
+        "Titan" => Color::GOLD,
+        _ => Color::WHITE,
+    }
+}
+
+/// Replaces the planets and the scales with the pending preset
+fn apply_preset(
+    mut commands: Commands,
+    mut state: ResMut<State>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut look: Query<&mut LookTransform>,
+    sphere: Res<PlanetMesh>,
+    planets: Query<Entity, With<Planet>>,
+) {
+    let Some(preset) = state.pending_preset.take() else {
+        return;
+    };
+
+    for entity in planets.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
 
+    spawn_planets(&mut commands, &sphere, &mut materials, preset.planets());
+
+    let (distance, bodies, velocity, epoch_scale, camera_distance) =
+        preset.scales();
+    (state.star_mass, state.star_radius) = preset.star();
+    state.distance_scaling = distance;
+    state.body_scaling = bodies;
+    state.velocity_scaling = velocity;
+    state.epoch_scale = epoch_scale;
+    state.preset = preset;
+    state.focus_mode = FocusMode::Sun;
+    state.selected_planet = None;
+
+    if let Ok(mut look) = look.get_single_mut() {
+        look.target = Vec3::ZERO;
+        look.eye = Vec3::new(-2.0, 5.0, 5.0).normalize() * camera_distance;
+    }
+}
+
+fn time_control_keys(
+    mut egui_context: EguiContexts,
+    keys: Res<Input<KeyCode>>,
+    mut state: ResMut<State>,
+) {
+    // Typing into a text field
+    if egui_context.ctx_mut().wants_keyboard_input() {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Space) {
+        state.paused = !state.paused;
+    }
+
+    let preset_keys =
+        [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4];
+    for (key, preset) in preset_keys.into_iter().zip(TIME_WARP_PRESETS) {
+        if keys.just_pressed(key) {
+            state.epoch_scale = preset;
+            state.paused = false;
+        }
+    }
+
+    if keys.just_pressed(KeyCode::R) {
+        state.rewind = !state.rewind;
+    }
+
+    let direction = if state.rewind { -1.0 } else { 1.0 };
+    if keys.just_pressed(KeyCode::H) {
+        state.pending_epoch_step += direction * SECONDS_PER_HOUR;
+    }
+    if keys.just_pressed(KeyCode::D) {
+        state.pending_epoch_step += direction * SECONDS_PER_DAY;
+    }
+}
+
+fn update_epoch(time: Res<Time>, mut state: ResMut<State>) {
+    if !state.paused {
+        let direction = if state.rewind { -1.0 } else { 1.0 };
+        state.epoch += direction * state.epoch_scale * time.delta_seconds();
+    }
+
+    state.epoch += state.pending_epoch_step;
+    state.pending_epoch_step = 0.0;
+}
+
+fn save_and_load(
+    mut commands: Commands,
+    mut state: ResMut<State>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    sphere: Res<PlanetMesh>,
+    planets: Query<(Entity, &Planet, &Name, &Handle<StandardMaterial>)>,
+) {
+    let Some(action) = state.pending_file_action.take() else {
+        return;
+    };
+
+    match action {
+        FileAction::Save => {
+            let names: HashMap<Entity, String> = planets
+                .iter()
+                .map(|(entity, _, name, _)| (entity, name.to_string()))
+                .collect();
+
+            let saved = SavedSystem {
+                star_mass: state.star_mass,
+                star_radius: state.star_radius,
+                epoch: state.epoch,
+                epoch_scale: state.epoch_scale,
+                distance_scaling: state.distance_scaling,
+                body_scaling: state.body_scaling,
+                planets: planets
+                    .iter()
+                    .map(|(_, planet, name, material)| PlanetData {
+                        name: name.to_string(),
+                        mass: planet.mass,
+                        radius: planet.radius,
+                        color: materials
+                            .get(material)
+                            .map_or(Color::WHITE, |m| m.base_color)
+                            .as_rgba_f32(),
+                        parent: planet
+                            .parent
+                            .and_then(|parent| names.get(&parent).cloned()),
+                        orbit: planet.orbit,
+                    })
+                    .collect(),
+            };
+
+            state.file_status =
+                Some(match save_system(&state.system_path, &saved) {
+                    Ok(()) => format!("Saved to {}", state.system_path),
+                    Err(err) => format!("Failed to save: {err}"),
+                });
+        }
+        FileAction::Load => {
+            let saved = match load_system(&state.system_path) {
+                Ok(saved) => saved,
+                Err(err) => {
+                    state.file_status = Some(format!("Failed to load: {err}"));
+                    return;
+                }
+            };
+
+            for (entity, ..) in planets.iter() {
+                commands.entity(entity).despawn_recursive();
+            }
+
+            spawn_planets(
+                &mut commands,
+                &sphere,
+                &mut materials,
+                saved.planets,
+            );
+
+            state.star_mass = saved.star_mass;
+            state.star_radius = saved.star_radius;
+            state.epoch = saved.epoch;
+            state.epoch_scale = saved.epoch_scale;
+            state.distance_scaling = saved.distance_scaling;
+            state.body_scaling = saved.body_scaling;
+            state.selected_planet = None;
+            state.file_status = Some(format!("Loaded {}", state.system_path));
+        }
+    }
+}
+
+fn add_and_delete_planets(
+    mut commands: Commands,
+    mut state: ResMut<State>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    sphere: Res<PlanetMesh>,
+    planets: Query<(Entity, &Name, &Planet)>,
+) {
+    if let Some(planet) = state.pending_new_planet.take() {
+        let [r, g, b] = planet.color;
+        let parent = planets
+            .iter()
+            .find(|(_, name, _)| {
+                Some(name.as_str()) == planet.parent.as_deref()
+            })
+            .map(|(entity, ..)| entity);
+
+        let entity = commands.spawn_empty().id();
+        spawn_planet(
+            &mut commands,
+            entity,
+            &sphere,
+            &mut materials,
+            planet.name,
+            Color::rgb(r, g, b),
+            Planet::new(planet.orbit, planet.mass, planet.radius, parent),
+        );
+    }
+
+    if let Some(deleted) = state.pending_delete.take() {
+        // Along with its moons
+        let deleted_entities: Vec<Entity> = planets
+            .iter()
+            .filter(|(_, name, _)| name.as_str() == deleted)
+            .map(|(entity, ..)| entity)
+            .collect();
+
+        for (entity, _, planet) in planets.iter() {
+            let is_moon =
+                planet.parent.is_some_and(|p| deleted_entities.contains(&p));
+            if deleted_entities.contains(&entity) || is_moon {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+
+        if state.focus_mode == FocusMode::Planet(deleted.clone()) {
+            state.focus_mode = FocusMode::Sun;
+        }
+        if state.selected_planet.as_ref() == Some(&deleted) {
+            state.selected_planet = None;
+        }
+    }
+}
+
+/// Spawns the planets, the parents are looked up by name among them
+fn spawn_planets(
+    commands: &mut Commands,
+    sphere: &PlanetMesh,
+    materials: &mut Assets<StandardMaterial>,
+    planets: Vec<PlanetData>,
+) {
+    // Reserved first, so the moons can be listed before their planets
+    let entities: Vec<Entity> = planets
+        .iter()
+        .map(|_| commands.spawn_empty().id())
+        .collect();
+    let by_name: HashMap<String, Entity> = planets
+        .iter()
+        .zip(&entities)
+        .map(|(planet, entity)| (planet.name.clone(), *entity))
+        .collect();
+
+    for (planet, entity) in planets.into_iter().zip(entities) {
+        let [r, g, b, a] = planet.color;
+        let parent = planet
+            .parent
+            .as_ref()
+            .and_then(|parent| by_name.get(parent).copied());
+
+        spawn_planet(
+            commands,
+            entity,
+            sphere,
+            materials,
+            planet.name,
+            Color::rgba(r, g, b, a),
+            Planet::new(planet.orbit, planet.mass, planet.radius, parent),
+        );
+    }
+}
+
+fn spawn_planet(
+    commands: &mut Commands,
+    entity: Entity,
+    sphere: &PlanetMesh,
+    materials: &mut Assets<StandardMaterial>,
+    name: String,
+    color: Color,
+    planet: Planet,
+) {
     commands
-        .spawn(PbrBundle {
-            mesh: sphere.clone(),
-            material: planet_material(Color::MIDNIGHT_BLUE),
+        .entity(entity)
+        .insert(PbrBundle {
+            mesh: sphere.0.clone(),
+            material: materials.add(StandardMaterial {
+                base_color: color,
+                emissive: color,
+                perceptual_roughness: 1.0,
+                ..Default::default()
+            }),
             ..Default::default()
         })
-        .insert(Planet {
-            orbit: KeplerianElements {
-                eccentricity: 0.00895,
-                semi_major_axis: 30.070 * AU,
-                inclination: 0.03089232776029963351154932660225,
-                right_ascension_of_the_ascending_node:
-                    2.3001694212033269494277320637911,
-                argument_of_periapsis: 0.81471969483095304650797885073048,
-                mean_anomaly_at_epoch: 5.3096406504171494389172520558961,
-                epoch: 0.0,
-            },
-            state_vectors: StateVectors::default(),
-            mass: 1.024e3,
-        })
-        .insert(Name::new("Neptune"));
+        .insert(planet)
+        .insert(Name::new(name));
 }
 
-fn update_epoch(time: Res<Time>, mut state: ResMut<State>) {
-    if state.update_epoch {
-        state.epoch += state.epoch_scale * time.delta_seconds();
-    }
+fn save_system(path: &str, system: &SavedSystem) -> Result<(), Box<dyn Error>> {
+    fs::write(path, serde_json::to_string_pretty(system)?)?;
+
+    Ok(())
+}
+
+fn load_system(path: &str) -> Result<SavedSystem, Box<dyn Error>> {
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
 }
 
 fn update_planets(
-    mut query: Query<(&mut Transform, &mut Planet)>,
+    mut query: Query<(Entity, &mut Transform, &mut Planet, &Name)>,
     state: Res<State>,
 ) {
-    for (mut transform, mut planet) in query.iter_mut() {
-        planet.state_vectors = planet.orbit.state_vectors_at_epoch(
-            state.star_mass,
-            state.epoch,
-            state.tolerance,
-        );
+    let planets: Vec<_> = query
+        .iter()
+        .map(|(entity, _, planet, name)| (entity, planet, name))
+        .collect();
+    let system = planet_system(&planets, &state);
+    let bodies = system.bodies();
+
+    // Relative to the parents with the warm started solvers,
+    // the star has none
+    let mut relative = vec![None; bodies.len()];
+    for (index, (_, _, mut planet, _)) in (1..).zip(query.iter_mut()) {
+        // The moons orbit their planets instead of the star
+        let parent = bodies[index].parent.unwrap_or_default();
+        planet.parent_mass = bodies[parent].mass;
+        planet.parent_radius = bodies[parent].radius.unwrap_or_default();
+
+        let parent_mass = planet.parent_mass;
+        let Planet {
+            orbit,
+            solver,
+            state_vectors,
+            ..
+        } = planet.as_mut();
+
+        // While paused solve from scratch every frame,
+        // so the warm started solver doesn't creep at a fixed epoch
+        *state_vectors = if state.paused {
+            solver.reset();
+            orbit.state_vectors_at_epoch(
+                parent_mass,
+                state.epoch,
+                state.tolerance,
+            )
+        } else {
+            solver.state_vectors_at_epoch(
+                orbit,
+                parent_mass,
+                state.epoch,
+                state.tolerance,
+            )
+        };
 
-        let position = zup2yup(planet.state_vectors.position);
+        relative[index] = Some(*state_vectors);
+    }
+
+    let globals = system.global_states_from_relative(&relative);
+
+    for (index, (_, mut transform, mut planet, _)) in
+        (1..).zip(query.iter_mut())
+    {
+        let parent = bodies[index].parent.unwrap_or_default();
+        planet.parent_position = globals[parent].position;
+
+        let position = zup2yup(globals[index].position);
 
         transform.translation = position * state.distance_scaling;
-        transform.scale = Vec3::ONE * mass2radius(state.as_ref(), planet.mass);
+        transform.scale =
+            Vec3::ONE * body_radius(state.as_ref(), planet.radius);
     }
 }
 
+/// The star and the `planets` in their order, the star first.
+///
+/// The planets of a loaded file that links the parents in a cycle
+/// orbit the star instead.
+fn planet_system(
+    planets: &[(Entity, &Planet, &Name)],
+    state: &State,
+) -> BodySystem {
+    let indices: HashMap<Entity, usize> = (1..)
+        .zip(planets)
+        .map(|(index, (entity, ..))| (*entity, index))
+        .collect();
+
+    let star = Body {
+        name: "Star".to_string(),
+        mass: state.star_mass,
+        radius: Some(state.star_radius),
+        parent: None,
+        elements: KeplerianElements::default(),
+    };
+    let mut bodies: Vec<Body> = std::iter::once(star)
+        .chain(planets.iter().map(|(_, planet, name)| {
+            Body {
+                name: name.to_string(),
+                mass: planet.mass,
+                radius: Some(planet.radius),
+                parent: Some(
+                    planet
+                        .parent
+                        .and_then(|parent| indices.get(&parent).copied())
+                        .unwrap_or(0),
+                ),
+                elements: planet.orbit,
+            }
+        }))
+        .collect();
+
+    BodySystem::from_bodies(bodies.clone()).unwrap_or_else(|_| {
+        for body in &mut bodies[1..] {
+            body.parent = Some(0);
+        }
+
+        BodySystem::from_bodies(bodies).expect("The star is the only root")
+    })
+}
+
 fn update_star(
     mut query: Query<&mut Transform, With<Star>>,
     state: Res<State>,
 ) {
     for mut transform in query.iter_mut() {
         transform.scale =
-            Vec3::ONE * mass2radius(state.as_ref(), state.star_mass);
+            Vec3::ONE * body_radius(state.as_ref(), state.star_radius);
     }
 }
 
@@ -741,91 +1715,162 @@ fn update_camera_focus(
 
 fn draw_orbits(
     mut lines: Gizmos,
+    mut egui_context: EguiContexts,
     planets: Query<(&Planet, &Handle<StandardMaterial>)>,
     materials: Res<Assets<StandardMaterial>>,
     state: Res<State>,
-    camera: Query<&GlobalTransform, With<Camera>>,
+    camera: Query<(&Camera, &GlobalTransform)>,
 ) {
     if !state.draw_orbits {
         return;
     }
 
-    let camera = camera.single();
-    let camera_position = camera.translation();
+    let (camera, camera_transform) = camera.single();
+    let camera_position = camera_transform.translation();
+    let painter = egui_context
+        .ctx_mut()
+        .layer_painter(egui::LayerId::background());
 
     for (planet, mat) in planets.iter() {
         let orbit = &planet.orbit;
         let color = materials.get(mat).unwrap().base_color;
 
-        let first_position =
-            zup2yup(orbit.position_at_true_anomaly(state.star_mass, 0.0))
-                * state.distance_scaling;
-        let mut prev_position = first_position.clone();
+        // Around the parent, the star for the planets
+        let mass = planet.parent_mass;
+        let origin = zup2yup(planet.parent_position) * state.distance_scaling;
+
+        // Vis-viva, the speed is the lowest at the apoapsis
+        // and the highest at the periapsis
+        let mu = standard_gravitational_parameter(mass);
+        let a = orbit.semi_major_axis;
+        let speed = |r: f32| (mu * (2.0 / r - 1.0 / a)).max(0.0).sqrt();
+        let slowest = speed(a * (1.0 + orbit.eccentricity));
+        let fastest = speed(a * (1.0 - orbit.eccentricity));
+
+        let segment_color = |position: Vec3| {
+            if !state.color_by_speed {
+                return color;
+            }
 
-        let step = (2.0 * PI) / state.orbit_subdivisions as f32;
+            let range = fastest - slowest;
+            if range <= f32::EPSILON * fastest {
+                return speed_color(0.5);
+            }
 
-        for i in 0..state.orbit_subdivisions {
-            let t = i as f32 * step;
+            speed_color((speed(position.length()) - slowest) / range)
+        };
 
-            let position = orbit.position_at_true_anomaly(state.star_mass, t);
-            let position = zup2yup(position) * state.distance_scaling;
+        let first_position = orbit.position_at_true_anomaly(mass, 0.0);
+        let mut prev_position = first_position;
 
-            lines.line(prev_position, position, color);
+        let step = (2.0 * PI) / state.orbit_subdivisions as f32;
+
+        for i in 0..=state.orbit_subdivisions {
+            // Close the loop
+            let position = if i == state.orbit_subdivisions {
+                first_position
+            } else {
+                let t = i as f32 * step;
+                orbit.position_at_true_anomaly(mass, t)
+            };
+
+            lines.line(
+                origin + zup2yup(prev_position) * state.distance_scaling,
+                origin + zup2yup(position) * state.distance_scaling,
+                segment_color(0.5 * (prev_position + position)),
+            );
 
             prev_position = position;
         }
 
-        // Close the loop
-        lines.line(prev_position, first_position, color);
-
         let mut debug_arrows = DebugArrows::new(&mut lines, camera_position);
 
         if state.show_position_and_velocity {
             let StateVectors { position, velocity } = orbit
-                .state_vectors_at_epoch(
-                    state.star_mass,
-                    state.epoch,
-                    state.tolerance,
-                );
+                .state_vectors_at_epoch(mass, state.epoch, state.tolerance);
 
             let position = zup2yup(position);
             let velocity = zup2yup(velocity);
 
-            let p = position * state.distance_scaling;
+            let p = origin + position * state.distance_scaling;
             let v = velocity * state.distance_scaling * state.velocity_scaling;
 
-            debug_arrows.draw_arrow(Vec3::ZERO, p, color);
+            debug_arrows.draw_arrow(origin, p, color);
             debug_arrows.draw_arrow(p, p + v, Color::RED);
         }
 
-        if state.show_nodes {
-            debug_arrows.draw_arrow(
-                Vec3::ZERO,
-                zup2yup(orbit.ascending_node(state.star_mass))
-                    * state.distance_scaling,
-                Color::YELLOW_GREEN,
-            );
-            debug_arrows.draw_arrow(
-                Vec3::ZERO,
-                zup2yup(orbit.descending_node(state.star_mass))
-                    * state.distance_scaling,
-                Color::YELLOW,
-            );
+        // Relative to the ecliptic, the equators of the parents
+        // aren't modelled. The nodes of an equatorial orbit are undefined
+        if state.show_nodes && orbit.inclination.abs() > f32::EPSILON {
+            let ascending = origin
+                + zup2yup(orbit.ascending_node(mass)) * state.distance_scaling;
+            let descending = origin
+                + zup2yup(orbit.descending_node(mass)) * state.distance_scaling;
+
+            if state.show_line_of_nodes {
+                lines.line(ascending, descending, Color::GRAY);
+            }
+
+            for (label, position, node_color) in [
+                ("AN", ascending, Color::YELLOW_GREEN),
+                ("DN", descending, Color::YELLOW),
+            ] {
+                let size =
+                    NODE_MARKER_SIZE * camera_position.distance(position);
+                lines.circle(
+                    position,
+                    camera_position - position,
+                    size,
+                    node_color,
+                );
+
+                if let Some(screen) =
+                    camera.world_to_viewport(camera_transform, position)
+                {
+                    painter.text(
+                        egui::pos2(screen.x, screen.y - 10.0),
+                        egui::Align2::CENTER_BOTTOM,
+                        label,
+                        egui::FontId::proportional(12.0),
+                        egui::Color32::WHITE,
+                    );
+                }
+            }
         }
 
-        if state.show_peri_and_apo_apsis {
-            debug_arrows.draw_arrow(
-                Vec3::ZERO,
-                zup2yup(orbit.periapsis(state.star_mass))
-                    * state.distance_scaling,
-                Color::WHITE,
-            );
-            debug_arrows.draw_arrow(
-                Vec3::ZERO,
-                zup2yup(orbit.apoapsis(state.star_mass))
-                    * state.distance_scaling,
-                Color::WHITE,
-            );
+        if state.show_apsis_markers {
+            let mut apsides = vec![("Pe", orbit.periapsis(mass))];
+            if orbit.eccentricity < 1.0 {
+                apsides.push(("Ap", orbit.apoapsis(mass)));
+            }
+
+            for (label, apsis) in apsides {
+                let position = origin + zup2yup(apsis) * state.distance_scaling;
+                let size =
+                    APSIS_MARKER_SIZE * camera_position.distance(position);
+                draw_diamond(
+                    &mut lines,
+                    position,
+                    camera_position,
+                    size,
+                    color,
+                );
+
+                if let Some(screen) =
+                    camera.world_to_viewport(camera_transform, position)
+                {
+                    painter.text(
+                        egui::pos2(screen.x, screen.y - 10.0),
+                        egui::Align2::CENTER_BOTTOM,
+                        format!(
+                            "{label} {:.0} km",
+                            (apsis.length() - planet.parent_radius) / 1000.0
+                        ),
+                        egui::FontId::proportional(12.0),
+                        egui::Color32::WHITE,
+                    );
+                }
+            }
         }
     }
 }
@@ -848,11 +1893,10 @@ fn draw_soi(
         let r = planet.state_vectors.position.length();
 
         let soi =
-            keplerian_elements::astro::soi(r, planet.mass, state.star_mass)
+            keplerian_elements::astro::soi(r, planet.mass, planet.parent_mass)
                 * state.distance_scaling;
 
-        let pos =
-            zup2yup(planet.state_vectors.position) * state.distance_scaling;
+        let pos = zup2yup(planet.global_position()) * state.distance_scaling;
 
         let to_camera = (camera_position - pos).normalize();
         let planet_camera_radial = to_camera.cross(pos).normalize();
@@ -873,6 +1917,388 @@ fn draw_soi(
     }
 }
 
+/// Recalculates the trajectory through the planets where they are now
+fn update_trajectory(
+    mut simulator: ResMut<TrajectorySimulator>,
+    state: Res<State>,
+    query: Query<(Entity, &Planet, &Name)>,
+) {
+    simulator.segments.clear();
+    simulator.ticks.clear();
+    simulator.approach = None;
+    if !simulator.enabled {
+        return;
+    }
+
+    let planets: Vec<_> = query.iter().collect();
+    let system = planet_system(&planets, &state);
+    let globals: Vec<Vec3> = std::iter::once(Vec3::ZERO)
+        .chain(
+            planets
+                .iter()
+                .map(|(_, planet, _)| planet.global_position()),
+        )
+        .collect();
+
+    let parent = simulator
+        .parent
+        .as_deref()
+        .and_then(|name| system.index_of(name))
+        .unwrap_or(0);
+    let launch = simulator.state_vectors;
+
+    // Without a mass to orbit or an angular momentum there's no conic
+    let h = launch.position.cross(launch.velocity);
+    if system.bodies()[parent].mass <= 0.0 || h.length_squared() <= 0.0 {
+        return;
+    }
+
+    let settings = TrajectorySettings {
+        tolerance: state.tolerance,
+        ..simulator.settings
+    };
+    let segments = trajectory::recalculate_with_maneuvers(
+        &system,
+        parent,
+        launch,
+        simulator.epoch,
+        &simulator.nodes,
+        &settings,
+    );
+
+    let mut interval = simulator.tick_interval;
+    if simulator.scale_ticks_with_warp {
+        interval = interval.max(state.epoch_scale.abs() * TICK_WARP_SECONDS);
+    }
+    simulator.ticks = trajectory::ticks(&segments, interval, MAX_TICKS)
+        .into_iter()
+        .map(|(index, epoch)| {
+            let segment = &segments[index];
+            let relative = segment.state_at(&system, epoch, state.tolerance);
+
+            TrajectoryTick {
+                epoch,
+                position: globals[segment.parent] + relative.position,
+            }
+        })
+        .collect();
+
+    let target = simulator
+        .target
+        .as_deref()
+        .and_then(|name| system.index_of(name));
+    // Scanned at the resolution the segments are drawn with
+    simulator.approach = target
+        .and_then(|target| {
+            trajectory::closest_approach(
+                &system,
+                &segments,
+                target,
+                state.orbit_subdivisions as usize,
+                state.tolerance,
+            )
+            .map(|approach| (target, approach))
+        })
+        .map(|(target, approach)| {
+            let segment = &segments[approach.segment];
+            let mass = |body: usize| system.bodies()[body].mass;
+            let global = |body| {
+                system
+                    .global_state(body, mass, approach.epoch, state.tolerance)
+                    .position
+            };
+
+            // The target relative to the parent at the approach,
+            // around the parent where it is now like the segments
+            let parent_position = globals[segment.parent];
+            let craft = segment
+                .state_at(&system, approach.epoch, state.tolerance)
+                .position;
+
+            TargetApproach {
+                approach,
+                craft_position: parent_position + craft,
+                target_position: parent_position + global(target)
+                    - global(segment.parent),
+            }
+        });
+
+    simulator.segments = segments
+        .into_iter()
+        .map(|segment| {
+            let subdivisions = state.orbit_subdivisions.max(1);
+            let step =
+                (segment.end_epoch - segment.start_epoch) / subdivisions as f32;
+            let points = (0..=subdivisions)
+                .map(|k| {
+                    let epoch = segment.start_epoch + k as f32 * step;
+                    segment.state_at(&system, epoch, state.tolerance).position
+                })
+                .collect();
+
+            SimulatedSegment {
+                segment,
+                parent_name: system.bodies()[segment.parent].name.clone(),
+                parent_position: globals[segment.parent],
+                points,
+            }
+        })
+        .collect();
+}
+
+/// The trajectory in white, after the first burn in orange,
+/// with the burns marked where they split it, the closest approach
+/// to the target and ticks of the flight time
+fn draw_trajectory(
+    mut lines: Gizmos,
+    mut egui_context: EguiContexts,
+    simulator: Res<TrajectorySimulator>,
+    state: Res<State>,
+    camera: Query<(&Camera, &GlobalTransform)>,
+) {
+    let (camera, camera_transform) = camera.single();
+    let camera_position = camera_transform.translation();
+    let painter = egui_context
+        .ctx_mut()
+        .layer_painter(egui::LayerId::background());
+    let mut color = Color::WHITE;
+
+    for simulated in &simulator.segments {
+        let origin =
+            zup2yup(simulated.parent_position) * state.distance_scaling;
+        let point = |p: Vec3| origin + zup2yup(p) * state.distance_scaling;
+
+        for pair in simulated.points.windows(2) {
+            lines.line(point(pair[0]), point(pair[1]), color);
+        }
+
+        if let (SegmentEnd::Maneuver(_), Some(&burn)) =
+            (simulated.segment.end, simulated.points.last())
+        {
+            let position = point(burn);
+            let size =
+                MANEUVER_MARKER_SIZE * camera_position.distance(position);
+            lines.circle(
+                position,
+                camera_position - position,
+                size,
+                Color::CYAN,
+            );
+
+            color = Color::ORANGE;
+        }
+    }
+
+    if let Some(closest) = &simulator.approach {
+        let craft = zup2yup(closest.craft_position) * state.distance_scaling;
+        let target = zup2yup(closest.target_position) * state.distance_scaling;
+
+        lines.line(craft, target, Color::FUCHSIA);
+        for position in [craft, target] {
+            let size =
+                APPROACH_MARKER_SIZE * camera_position.distance(position);
+            draw_diamond(
+                &mut lines,
+                position,
+                camera_position,
+                size,
+                Color::FUCHSIA,
+            );
+        }
+    }
+
+    if !simulator.show_ticks {
+        return;
+    }
+
+    for tick in &simulator.ticks {
+        let position = zup2yup(tick.position) * state.distance_scaling;
+        let size = TICK_MARKER_SIZE * camera_position.distance(position);
+        draw_cross(&mut lines, position, camera_position, size, Color::GRAY);
+
+        if !simulator.show_tick_labels {
+            continue;
+        }
+        if let Some(screen) =
+            camera.world_to_viewport(camera_transform, position)
+        {
+            painter.text(
+                egui::pos2(screen.x, screen.y - 8.0),
+                egui::Align2::CENTER_BOTTOM,
+                format!("T{:+.2} d", tick.epoch / SECONDS_PER_DAY),
+                egui::FontId::proportional(10.0),
+                egui::Color32::GRAY,
+            );
+        }
+    }
+}
+
+/// Names of the planets next to them, behind the windows.
+/// Labels further than the camera target fade out.
+fn draw_labels(
+    mut egui_context: EguiContexts,
+    state: Res<State>,
+    planets: Query<(&Transform, &Name), With<Planet>>,
+    camera: Query<(&Camera, &GlobalTransform, &LookTransform)>,
+) {
+    if !state.show_labels {
+        return;
+    }
+
+    let (camera, camera_transform, look) = camera.single();
+    let camera_position = camera_transform.translation();
+    let painter = egui_context
+        .ctx_mut()
+        .layer_painter(egui::LayerId::background());
+    let font = egui::FontId::proportional(state.label_size);
+
+    for (transform, name) in planets.iter() {
+        let position = transform.translation;
+        let Some(screen) = camera.world_to_viewport(camera_transform, position)
+        else {
+            continue;
+        };
+
+        let distance = camera_position.distance(position);
+        let fade =
+            (2.0 - distance / look.radius().max(f32::EPSILON)).clamp(0.2, 1.0);
+
+        painter.text(
+            egui::pos2(screen.x + 0.5 * state.label_size, screen.y),
+            egui::Align2::LEFT_CENTER,
+            name.as_str(),
+            font.clone(),
+            egui::Color32::from_white_alpha((fade * 255.0) as u8),
+        );
+    }
+}
+
+/// Planet clicked last, to detect double clicks
+struct LastClick {
+    name: String,
+    time: f32,
+}
+
+/// Casts a ray from the cursor to hover and select the planets,
+/// a double click focuses on the planet
+#[allow(clippy::too_many_arguments)]
+fn pick_planets(
+    mut egui_context: EguiContexts,
+    mut state: ResMut<State>,
+    mut last_click: Local<Option<LastClick>>,
+    time: Res<Time>,
+    buttons: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<(&Camera, &GlobalTransform)>,
+    planets: Query<(&Transform, &Name), With<Planet>>,
+) {
+    let (camera, camera_transform) = camera.single();
+    let ctx = egui_context.ctx_mut();
+    let painter = ctx.layer_painter(egui::LayerId::background());
+
+    // Screen position and radius, at least the pick radius
+    let screen_circle = |transform: &Transform| {
+        let center = transform.translation;
+        let edge = center + camera_transform.right() * transform.scale.x;
+        let screen = camera.world_to_viewport(camera_transform, center)?;
+        let radius = camera
+            .world_to_viewport(camera_transform, edge)
+            .map_or(0.0, |edge| edge.distance(screen));
+
+        Some((screen, radius.max(MIN_PICK_RADIUS)))
+    };
+    let highlight = |transform: &Transform, color| {
+        if let Some((screen, radius)) = screen_circle(transform) {
+            painter.circle_stroke(
+                egui::pos2(screen.x, screen.y),
+                radius + 2.0,
+                egui::Stroke::new(1.5, color),
+            );
+        }
+    };
+
+    for (transform, name) in planets.iter() {
+        if state.selected_planet.as_deref() == Some(name.as_str()) {
+            highlight(transform, egui::Color32::YELLOW);
+        }
+    }
+
+    let cursor = window.single().cursor_position();
+    let Some(cursor) = cursor.filter(|_| !ctx.is_pointer_over_area()) else {
+        return;
+    };
+    let Some(ray) = camera.viewport_to_world(camera_transform, cursor) else {
+        return;
+    };
+
+    // Nearest planet along the ray hit by it or close to the cursor
+    let hovered = planets
+        .iter()
+        .filter_map(|(transform, name)| {
+            let center = transform.translation;
+            let along = (center - ray.origin).dot(ray.direction);
+            let miss = ray.get_point(along).distance(center);
+            let (screen, radius) = screen_circle(transform)?;
+
+            let hit =
+                miss <= transform.scale.x || screen.distance(cursor) <= radius;
+            (along > 0.0 && hit).then_some((along, transform, name))
+        })
+        .min_by(|a, b| a.0.total_cmp(&b.0));
+
+    let Some((_, transform, name)) = hovered else {
+        return;
+    };
+    highlight(transform, egui::Color32::WHITE);
+
+    // Ctrl and drag rotates the camera
+    if !buttons.just_pressed(MouseButton::Left)
+        || keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+    {
+        return;
+    }
+
+    let now = time.elapsed_seconds();
+    let double_click = last_click.as_ref().is_some_and(|last| {
+        last.name == name.as_str() && now - last.time <= DOUBLE_CLICK_TIME
+    });
+
+    state.selected_planet = Some(name.to_string());
+    if double_click {
+        state.focus_mode = FocusMode::Planet(name.to_string());
+        *last_click = None;
+    } else {
+        *last_click = Some(LastClick {
+            name: name.to_string(),
+            time: now,
+        });
+    }
+}
+
+/// Size of the apsis diamonds relative to their distance to the camera
+const APSIS_MARKER_SIZE: f32 = 0.01;
+
+/// Size of the node circles relative to their distance to the camera
+const NODE_MARKER_SIZE: f32 = 0.008;
+
+/// Size of the maneuver node circles relative to their distance
+/// to the camera
+const MANEUVER_MARKER_SIZE: f32 = 0.012;
+
+/// Size of the closest approach diamonds relative to their distance
+/// to the camera
+const APPROACH_MARKER_SIZE: f32 = 0.01;
+
+/// Size of the time tick crosses relative to their distance to the camera
+const TICK_MARKER_SIZE: f32 = 0.005;
+
+/// Most ticks along a trajectory, the interval is doubled past it
+const MAX_TICKS: usize = 200;
+
+/// Real seconds between the ticks when they're scaled with the warp
+const TICK_WARP_SECONDS: f32 = 1.0;
+
 const ARROW_WING_LENGTH: f32 = 1.0;
 const ARROW_WING_ANGLE: f32 = 30.0;
 
@@ -920,6 +2346,43 @@ impl<'a, 'g> DebugArrows<'a, 'g> {
     }
 }
 
+/// Diamond facing the camera
+fn draw_diamond(
+    lines: &mut Gizmos,
+    center: Vec3,
+    camera_position: Vec3,
+    size: f32,
+    color: Color,
+) {
+    let to_camera = (camera_position - center).normalize();
+    // Level with the horizon, unless looking straight down
+    let side =
+        to_camera.cross(Vec3::Y).try_normalize().unwrap_or(Vec3::X) * size;
+    let up = to_camera.cross(side);
+
+    let corners = [center + side, center + up, center - side, center - up];
+    for (i, corner) in corners.iter().enumerate() {
+        lines.line(*corner, corners[(i + 1) % corners.len()], color);
+    }
+}
+
+/// Cross facing the camera, level with the horizon like [`draw_diamond`]
+fn draw_cross(
+    lines: &mut Gizmos,
+    center: Vec3,
+    camera_position: Vec3,
+    size: f32,
+    color: Color,
+) {
+    let to_camera = (camera_position - center).normalize();
+    let side =
+        to_camera.cross(Vec3::Y).try_normalize().unwrap_or(Vec3::X) * size;
+    let up = to_camera.cross(side);
+
+    lines.line(center - side, center + side, color);
+    lines.line(center - up, center + up, color);
+}
+
 /// Finds the closest point on the line segment defined by `a` and `b` to `pos`.
 /// By definition the lines given by a and b and the pos and found point must be perpendicular.
 fn closest_point(pos: Vec3, a: Vec3, b: Vec3) -> Vec3 {
@@ -937,10 +2400,15 @@ fn closest_point(pos: Vec3, a: Vec3, b: Vec3) -> Vec3 {
     }
 }
 
+/// Blue for the slowest to red for the fastest, `t` in [0, 1]
+fn speed_color(t: f32) -> Color {
+    Color::hsl(240.0 * (1.0 - t.clamp(0.0, 1.0)), 1.0, 0.5)
+}
+
 fn deg2rad(deg: f32) -> f32 {
     deg * std::f32::consts::PI / 180.0
 }
 
-fn mass2radius(state: &State, mass: f32) -> f32 {
-    mass * state.distance_scaling
+fn body_radius(state: &State, radius: f32) -> f32 {
+    radius * state.distance_scaling * state.body_scaling
 }
@@ -0,0 +1,217 @@
+//! Analysis of numerically propagated trajectories.
+//!
+//! Converts sampled Cartesian states into the history of the osculating
+//! elements and fits their secular drifts, e.g. to check the J2 theory
+//! against a numerical propagator.
+
+use crate::math::angle_diff;
+use crate::{KeplerianElements, Num, StateVectors};
+
+/// Secular drift rates of the elements, per second
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ElementRates {
+    pub semi_major_axis: Num,
+    pub eccentricity: Num,
+    pub inclination: Num,
+    pub right_ascension_of_the_ascending_node: Num,
+    pub argument_of_periapsis: Num,
+    /// Includes the mean motion
+    pub mean_anomaly: Num,
+}
+
+/// Osculating elements of each `(epoch, state)` sample.
+///
+/// The angles Ω, ω and M are unwrapped, so consecutive samples never
+/// jump by 2π and the series can be plotted or fit. The samples have to be
+/// dense enough for every angle to move by less than π between them.
+/// The mean anomaly of each sample is the one at its own epoch.
+pub fn osculating_history(
+    states: &[(Num, StateVectors)],
+    mass: Num,
+) -> Vec<(Num, KeplerianElements)> {
+    let mut history: Vec<(Num, KeplerianElements)> =
+        Vec::with_capacity(states.len());
+
+    for &(epoch, state) in states {
+        let mut elements = state.to_elements(mass, epoch);
+
+        if let Some((_, previous)) = history.last() {
+            let unwrap = |angle: Num, previous: Num| {
+                previous + angle_diff(angle, previous)
+            };
+
+            elements.right_ascension_of_the_ascending_node = unwrap(
+                elements.right_ascension_of_the_ascending_node,
+                previous.right_ascension_of_the_ascending_node,
+            );
+            elements.argument_of_periapsis = unwrap(
+                elements.argument_of_periapsis,
+                previous.argument_of_periapsis,
+            );
+            elements.mean_anomaly_at_epoch = unwrap(
+                elements.mean_anomaly_at_epoch,
+                previous.mean_anomaly_at_epoch,
+            );
+        }
+
+        history.push((epoch, elements));
+    }
+
+    history
+}
+
+/// Least-squares linear fit of every element of an (unwrapped) history,
+/// see [`osculating_history`].
+///
+/// Returns zero rates for fewer than two distinct epochs.
+pub fn secular_rates(history: &[(Num, KeplerianElements)]) -> ElementRates {
+    let slope = |element: fn(&KeplerianElements) -> Num| {
+        linear_fit_slope(history.iter().map(|(t, e)| (*t, element(e))))
+    };
+
+    ElementRates {
+        semi_major_axis: slope(|e| e.semi_major_axis),
+        eccentricity: slope(|e| e.eccentricity),
+        inclination: slope(|e| e.inclination),
+        right_ascension_of_the_ascending_node: slope(|e| {
+            e.right_ascension_of_the_ascending_node
+        }),
+        argument_of_periapsis: slope(|e| e.argument_of_periapsis),
+        mean_anomaly: slope(|e| e.mean_anomaly_at_epoch),
+    }
+}
+
+/// Slope of the least-squares line through the points
+fn linear_fit_slope(points: impl Iterator<Item = (Num, Num)> + Clone) -> Num {
+    let count = points.clone().count() as Num;
+    if count == 0.0 {
+        return 0.0;
+    }
+
+    let (sum_t, sum_x) = points
+        .clone()
+        .fold((0.0, 0.0), |(st, sx), (t, x)| (st + t, sx + x));
+    let (mean_t, mean_x) = (sum_t / count, sum_x / count);
+
+    let (covariance, variance) = points.fold((0.0, 0.0), |(c, v), (t, x)| {
+        let dt = t - mean_t;
+        (c + dt * (x - mean_x), v + dt * dt)
+    });
+
+    if variance == 0.0 {
+        0.0
+    } else {
+        covariance / variance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::astro::perturbations::J2;
+    use crate::astro::{
+        perturbed_ode_rhs, rk4_step, standard_gravitational_parameter,
+        Perturbation,
+    };
+    use crate::constants::{EARTH_J2, EARTH_MASS, EARTH_RADIUS};
+    use crate::{PI, TWO_PI};
+
+    const TOLERANCE: Num = 1e-5;
+    const STEPS_PER_PERIOD: usize = 300;
+
+    fn orbit() -> KeplerianElements {
+        KeplerianElements {
+            semi_major_axis: 7_000_000.0,
+            eccentricity: 0.01,
+            inclination: 0.9,
+            // J2 drags the node below zero
+            right_ascension_of_the_ascending_node: 0.01,
+            argument_of_periapsis: 1.0,
+            ..KeplerianElements::default()
+        }
+    }
+
+    fn integrate(
+        perturbations: Vec<Perturbation>,
+        periods: usize,
+    ) -> Vec<(Num, StateVectors)> {
+        let orbit = orbit();
+        let rhs = perturbed_ode_rhs(EARTH_MASS, perturbations);
+        let dt = orbit.period(EARTH_MASS) / STEPS_PER_PERIOD as Num;
+
+        let mut y = orbit
+            .state_vectors_at_epoch(EARTH_MASS, 0.0, TOLERANCE)
+            .as_array();
+
+        (0..periods * STEPS_PER_PERIOD)
+            .map(|step| {
+                let t = step as Num * dt;
+                let sample = (t, StateVectors::from_array(y));
+                y = rk4_step(&rhs, t, y, dt);
+                sample
+            })
+            .collect()
+    }
+
+    #[test]
+    fn two_body_elements_are_constant() {
+        let history = osculating_history(&integrate(vec![], 3), EARTH_MASS);
+        let orbit = orbit();
+
+        for pair in history.windows(2) {
+            let (a, b) = (pair[0].1, pair[1].1);
+            let jump = b.mean_anomaly_at_epoch - a.mean_anomaly_at_epoch;
+            assert!(jump.abs() < PI, "{a:?} {b:?}");
+        }
+
+        assert!(history.iter().all(|(_, e)| {
+            (e.right_ascension_of_the_ascending_node - 0.01).abs() < 1e-3
+        }));
+
+        let rates = secular_rates(&history);
+        let period = orbit.period(EARTH_MASS);
+
+        let a_drift = rates.semi_major_axis.abs() * period;
+        assert!(a_drift < 1e-5 * orbit.semi_major_axis, "{rates:?}");
+        assert!(rates.eccentricity.abs() * period < 1e-5, "{rates:?}");
+        assert!(rates.inclination.abs() * period < 1e-5, "{rates:?}");
+        assert!(
+            rates.right_ascension_of_the_ascending_node.abs() * period < 1e-5,
+            "{rates:?}"
+        );
+
+        let n = TWO_PI / period;
+        assert!((rates.mean_anomaly / n - 1.0).abs() < 1e-4, "{rates:?}");
+    }
+
+    #[test]
+    fn j2_nodal_drift() {
+        let μ = standard_gravitational_parameter(EARTH_MASS);
+
+        let j2 = J2 {
+            j2: EARTH_J2,
+            radius: EARTH_RADIUS,
+            mu: μ,
+        }
+        .perturbation();
+
+        let history = osculating_history(&integrate(vec![j2], 10), EARTH_MASS);
+        let rates = secular_rates(&history);
+
+        let expected =
+            orbit().secular_raan_rate(EARTH_RADIUS, EARTH_J2, EARTH_MASS);
+        let fitted = rates.right_ascension_of_the_ascending_node;
+        assert!(
+            (fitted / expected - 1.0).abs() < 0.05,
+            "{fitted} {expected}"
+        );
+    }
+
+    #[test]
+    fn fit_of_a_line() {
+        let points = (0..10).map(|t| (t as Num, 3.0 * t as Num + 1.0));
+        assert!((linear_fit_slope(points) - 3.0).abs() < 1e-5);
+
+        assert_eq!(secular_rates(&[]), ElementRates::default());
+    }
+}
@@ -0,0 +1,258 @@
+//! [`approx`] trait implementations.
+//!
+//! The node, the argument of periapsis and the elliptic mean anomaly are
+//! compared by their smallest angular distance, so values on both sides
+//! of the 0/2π seam compare equal. The inclination, limited to [0, π],
+//! and the hyperbolic mean anomaly, which doesn't wrap, compare plainly.
+//! The semi-major axis is compared relatively in [`RelativeEq`],
+//! everything else absolutely.
+
+use approx::{AbsDiffEq, RelativeEq, UlpsEq};
+
+use crate::math::{angle_diff, wrap_angle};
+use crate::{KeplerianElements, Num, StateVectors, TWO_PI};
+
+/// Pairs of angles, with whether they're periodic and compared
+/// by their angular distance or compared plainly
+fn angle_pairs(
+    a: &KeplerianElements,
+    b: &KeplerianElements,
+) -> [(Num, Num, bool); 4] {
+    // The hyperbolic mean anomaly grows without bound instead of wrapping
+    let periodic_M = a.is_elliptical() && b.is_elliptical();
+
+    [
+        (a.inclination, b.inclination, false),
+        (
+            a.right_ascension_of_the_ascending_node,
+            b.right_ascension_of_the_ascending_node,
+            true,
+        ),
+        (a.argument_of_periapsis, b.argument_of_periapsis, true),
+        (a.mean_anomaly_at_epoch, b.mean_anomaly_at_epoch, periodic_M),
+    ]
+}
+
+impl AbsDiffEq for KeplerianElements {
+    type Epsilon = Num;
+
+    fn default_epsilon() -> Num {
+        Num::default_epsilon()
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: Num) -> bool {
+        self.semi_major_axis
+            .abs_diff_eq(&other.semi_major_axis, epsilon)
+            && self.eccentricity.abs_diff_eq(&other.eccentricity, epsilon)
+            && self.epoch.abs_diff_eq(&other.epoch, epsilon)
+            && angle_pairs(self, other)
+                .into_iter()
+                .all(|(a, b, periodic)| {
+                    if periodic {
+                        angle_diff(a, b).abs() <= epsilon
+                    } else {
+                        a.abs_diff_eq(&b, epsilon)
+                    }
+                })
+    }
+}
+
+impl RelativeEq for KeplerianElements {
+    fn default_max_relative() -> Num {
+        Num::default_max_relative()
+    }
+
+    fn relative_eq(
+        &self,
+        other: &Self,
+        epsilon: Num,
+        max_relative: Num,
+    ) -> bool {
+        self.semi_major_axis.relative_eq(
+            &other.semi_major_axis,
+            epsilon,
+            max_relative,
+        ) && self.eccentricity.relative_eq(
+            &other.eccentricity,
+            epsilon,
+            max_relative,
+        ) && self.epoch.relative_eq(&other.epoch, epsilon, max_relative)
+            && angle_pairs(self, other)
+                .into_iter()
+                .all(|(a, b, periodic)| {
+                    if !periodic {
+                        return a.relative_eq(&b, epsilon, max_relative);
+                    }
+
+                    // Relative to a full turn, not to the angle itself
+                    let diff = angle_diff(a, b).abs();
+                    diff <= epsilon || diff <= max_relative * TWO_PI
+                })
+    }
+}
+
+impl UlpsEq for KeplerianElements {
+    fn default_max_ulps() -> u32 {
+        Num::default_max_ulps()
+    }
+
+    fn ulps_eq(&self, other: &Self, epsilon: Num, max_ulps: u32) -> bool {
+        self.semi_major_axis
+            .ulps_eq(&other.semi_major_axis, epsilon, max_ulps)
+            && self
+                .eccentricity
+                .ulps_eq(&other.eccentricity, epsilon, max_ulps)
+            && self.epoch.ulps_eq(&other.epoch, epsilon, max_ulps)
+            && angle_pairs(self, other)
+                .into_iter()
+                .all(|(a, b, periodic)| {
+                    if !periodic {
+                        return a.ulps_eq(&b, epsilon, max_ulps);
+                    }
+
+                    angle_diff(a, b).abs() <= epsilon
+                        || wrap_angle(a).ulps_eq(
+                            &wrap_angle(b),
+                            epsilon,
+                            max_ulps,
+                        )
+                })
+    }
+}
+
+impl AbsDiffEq for StateVectors {
+    type Epsilon = Num;
+
+    fn default_epsilon() -> Num {
+        Num::default_epsilon()
+    }
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: Num) -> bool {
+        self.position.abs_diff_eq(other.position, epsilon)
+            && self.velocity.abs_diff_eq(other.velocity, epsilon)
+    }
+}
+
+impl RelativeEq for StateVectors {
+    fn default_max_relative() -> Num {
+        Num::default_max_relative()
+    }
+
+    fn relative_eq(
+        &self,
+        other: &Self,
+        epsilon: Num,
+        max_relative: Num,
+    ) -> bool {
+        self.position
+            .relative_eq(&other.position, epsilon, max_relative)
+            && self
+                .velocity
+                .relative_eq(&other.velocity, epsilon, max_relative)
+    }
+}
+
+impl UlpsEq for StateVectors {
+    fn default_max_ulps() -> u32 {
+        Num::default_max_ulps()
+    }
+
+    fn ulps_eq(&self, other: &Self, epsilon: Num, max_ulps: u32) -> bool {
+        self.position.ulps_eq(&other.position, epsilon, max_ulps)
+            && self.velocity.ulps_eq(&other.velocity, epsilon, max_ulps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::{
+        assert_abs_diff_eq, assert_abs_diff_ne, assert_relative_eq,
+        assert_relative_ne, assert_ulps_eq, assert_ulps_ne,
+    };
+
+    use super::*;
+    use crate::vec3;
+
+    const ELEMENTS: KeplerianElements = KeplerianElements {
+        eccentricity: 0.1,
+        semi_major_axis: 1.0e6,
+        inclination: 0.5,
+        right_ascension_of_the_ascending_node: 1.0,
+        argument_of_periapsis: 2.0,
+        mean_anomaly_at_epoch: 0.0001,
+        epoch: 0.0,
+    };
+
+    #[test]
+    fn angles_compare_across_the_seam() {
+        let other = KeplerianElements {
+            mean_anomaly_at_epoch: TWO_PI - 0.0001,
+            ..ELEMENTS
+        };
+
+        assert_abs_diff_eq!(ELEMENTS, other, epsilon = 0.001);
+        assert_relative_eq!(ELEMENTS, other, epsilon = 0.001);
+        assert_ulps_eq!(ELEMENTS, other, epsilon = 0.001);
+        assert_abs_diff_ne!(ELEMENTS, other, epsilon = 0.0001);
+    }
+
+    #[test]
+    fn angles_compare_after_full_turns() {
+        let other = KeplerianElements {
+            right_ascension_of_the_ascending_node: 1.0 + 2.0 * TWO_PI,
+            ..ELEMENTS
+        };
+
+        assert_abs_diff_eq!(ELEMENTS, other, epsilon = 0.001);
+    }
+
+    #[test]
+    fn inclination_and_hyperbolic_mean_anomaly_dont_wrap() {
+        let other = KeplerianElements {
+            inclination: 0.5 + TWO_PI,
+            ..ELEMENTS
+        };
+        assert_abs_diff_ne!(ELEMENTS, other, epsilon = 0.001);
+
+        let hyperbolic = KeplerianElements {
+            eccentricity: 1.5,
+            semi_major_axis: -1.0e6,
+            ..ELEMENTS
+        };
+        let other = KeplerianElements {
+            mean_anomaly_at_epoch: 0.0001 + TWO_PI,
+            ..hyperbolic
+        };
+        assert_abs_diff_ne!(hyperbolic, other, epsilon = 0.001);
+        assert_relative_ne!(hyperbolic, other, max_relative = 0.001);
+        assert_ulps_ne!(hyperbolic, other, epsilon = 0.001);
+
+        // Still wrapping when only one of them is elliptic
+        let elliptic = KeplerianElements {
+            mean_anomaly_at_epoch: 0.0001 + TWO_PI,
+            ..ELEMENTS
+        };
+        assert_abs_diff_eq!(ELEMENTS, elliptic, epsilon = 0.001);
+    }
+
+    #[test]
+    fn semi_major_axis_compares_relatively() {
+        let other = KeplerianElements {
+            semi_major_axis: 1.0e6 + 0.5,
+            ..ELEMENTS
+        };
+
+        assert_relative_eq!(ELEMENTS, other, max_relative = 1e-6);
+        assert_abs_diff_ne!(ELEMENTS, other, epsilon = 0.1);
+    }
+
+    #[test]
+    fn state_vectors() {
+        let a = StateVectors::new(vec3(1.0, 2.0, 3.0), vec3(4.0, 5.0, 6.0));
+        let b = StateVectors::new(vec3(1.0, 2.0, 3.0001), vec3(4.0, 5.0, 6.0));
+
+        assert_abs_diff_eq!(a, b, epsilon = 0.001);
+        assert_relative_eq!(a, b, max_relative = 0.001);
+        assert_abs_diff_ne!(a, b, epsilon = 0.00001);
+    }
+}
@@ -1,8 +1,39 @@
 use crate::constants::{G, TWO_PI};
-use crate::Num;
+use crate::math::{golden_section_search, safe_acos};
+use crate::{vec3, MeanMotion, Num, StateVectors, Vec3};
 
+pub mod atmosphere;
+pub mod b_plane;
+pub mod capture;
+pub mod design;
 pub mod elliptic;
+pub mod flyby;
+pub mod hill;
 pub mod hyperbolic;
+pub mod interplanetary;
+pub mod iod;
+pub mod lagrange_points;
+pub mod lambert;
+pub mod launch;
+pub mod launch_vehicle;
+pub mod maneuver;
+pub mod observation;
+pub mod occlusion;
+pub mod perturbations;
+pub mod resonance;
+pub mod sgp4;
+pub mod tisserand;
+pub mod transfer;
+pub mod visibility;
+
+/// Number of bisection steps solving for the sphere of activity
+const SPHERE_OF_ACTIVITY_BISECTION_STEPS: usize = 60;
+
+/// Number of golden section steps optimizing the plane change split
+const PLANE_CHANGE_SPLIT_STEPS: usize = 60;
+
+/// Number of bisection steps spending the delta-v budget of a transfer
+const MINIMUM_TIME_TRANSFER_BISECTION_STEPS: usize = 60;
 
 /// https://en.wikipedia.org/wiki/Standard_gravitational_parameter
 #[inline]
@@ -14,6 +45,550 @@ pub fn soi(r: Num, m1: Num, m2: Num) -> Num {
     r * (m1 / m2).powf(2.0 / 5.0)
 }
 
+/// Radius of the sphere of activity of a body of mass `m_body` orbiting
+/// `m_central` at the distance `r`.
+///
+/// Solves Laplace's criterion, the equality of the perturbation to main
+/// acceleration ratios around both bodies, exactly along the line joining
+/// them, where the surface is closest to the body. [`soi`] is the leading
+/// order of the criterion without the directional factor (1 + 3cos²θ)^(-1/10),
+/// so for small mass ratios it's larger by about 2^(1/5) ≈ 1.15.
+///
+/// SRC: https://en.wikipedia.org/wiki/Sphere_of_influence_(astrodynamics)
+pub fn sphere_of_activity(r: Num, m_body: Num, m_central: Num) -> Num {
+    let q = m_body / m_central;
+
+    // In units of r, with x the distance from the body towards the central
+    // one. Both sides are rearranged to avoid cancellations for small x.
+    let criterion = |x: Num| {
+        let body = q.powi(2) * (1.0 - x).powi(3) * (1.0 + x) / x.powi(2);
+        let central = x.powi(3) * (2.0 - x) / (1.0 - x).powi(2);
+
+        body - central
+    };
+
+    let (mut lo, mut hi): (Num, Num) = (0.0, 1.0);
+    for _ in 0..SPHERE_OF_ACTIVITY_BISECTION_STEPS {
+        let mid = 0.5 * (lo + hi);
+
+        if criterion(mid) > 0.0 {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    0.5 * (lo + hi) * r
+}
+
+/// How much larger the [`soi`] is than the [`sphere_of_activity`]
+pub fn soi_vs_soa_ratio(r: Num, m_body: Num, m_central: Num) -> Num {
+    soi(r, m_body, m_central) / sphere_of_activity(r, m_body, m_central)
+}
+
 pub fn period(a: Num, mass: Num) -> Num {
     TWO_PI * (a.powi(3) / standard_gravitational_parameter(mass)).sqrt()
 }
+
+/// Time of flight of the Hohmann transfer between circular orbits of radii
+/// `a1` and `a2`, see [`transfer::hohmann_time_of_flight`].
+///
+/// It's the minimum energy transfer, not the fastest one,
+/// see [`minimum_time_transfer`].
+pub fn hohmann_transfer_time(a1: Num, a2: Num, mass: Num) -> Num {
+    transfer::hohmann_time_of_flight(
+        standard_gravitational_parameter(mass),
+        a1,
+        a2,
+    )
+}
+
+/// Fastest transfer between circular orbits of radii `a1` and `a2`
+/// with the total delta-v within `dv_budget`.
+///
+/// Both burns are instantaneous: the departure one is tangential, along
+/// the orbital velocity, and the arrival one matches the circular velocity.
+/// More delta-v at the departure shortens the transfer, up to hyperbolic
+/// trajectories for large budgets, so the departure speed is bisected to
+/// spend the whole budget. Going down takes the same time as the reversed
+/// transfer going up.
+///
+/// Returns the time of flight and the delta-v spent, or `None` if the budget
+/// doesn't cover the Hohmann transfer.
+pub fn minimum_time_transfer(
+    a1: Num,
+    a2: Num,
+    mass: Num,
+    dv_budget: Num,
+) -> Option<(Num, Num)> {
+    let μ = standard_gravitational_parameter(mass);
+    let (r1, r2) = (a1.min(a2), a1.max(a2));
+
+    // Tangential departure from the periapsis at r1 with the speed v
+    let transfer = |v: Num| {
+        let h = r1 * v;
+        let e = r1 * v.powi(2) / μ - 1.0;
+        let θ = safe_acos((h.powi(2) / (μ * r2) - 1.0) / e);
+        let a = (2.0 / r1 - v.powi(2) / μ).recip();
+        let n = (μ / a.abs().powi(3)).sqrt();
+
+        let M = if e < 1.0 {
+            let E = elliptic::eccentric_anomaly(θ, e);
+            E - e * E.sin()
+        } else {
+            let F = hyperbolic::hyperbolic_anomaly(θ, e);
+            e * F.sinh() - F
+        };
+
+        let radial = μ / h * e * θ.sin();
+        let tangential = h / r2;
+        let arrival = radial.hypot(tangential - (μ / r2).sqrt());
+        let departure = v - (μ / r1).sqrt();
+
+        (M / n, departure + arrival)
+    };
+
+    let v_hohmann = (2.0 * μ * r2 / (r1 * (r1 + r2))).sqrt();
+    if transfer(v_hohmann).1 > dv_budget {
+        return None;
+    }
+
+    let (mut lo, mut hi) = (v_hohmann, 2.0 * v_hohmann);
+    while transfer(hi).1 <= dv_budget {
+        lo = hi;
+        hi *= 2.0;
+    }
+
+    for _ in 0..MINIMUM_TIME_TRANSFER_BISECTION_STEPS {
+        let mid = 0.5 * (lo + hi);
+
+        if transfer(mid).1 <= dv_budget {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    Some(transfer(lo))
+}
+
+/// Mean motion in revolutions per day, as used by TLEs
+pub fn mean_motion_rev_per_day(a: Num, mass: Num) -> Num {
+    MeanMotion::from_semi_major_axis(a, standard_gravitational_parameter(mass))
+        .rev_per_day()
+}
+
+/// Gravitational acceleration towards a point mass at the origin.
+///
+/// a = -μr/|r|³
+pub fn two_body_acceleration(mass: Num, position: Vec3) -> Vec3 {
+    let μ = standard_gravitational_parameter(mass);
+
+    -μ * position / position.length().powi(3)
+}
+
+/// Right hand side of the two body equations of motion,
+/// for use with external ODE solvers.
+///
+/// The state is `[x, y, z, vx, vy, vz]`
+/// and the derivatives are `[vx, vy, vz, ax, ay, az]`.
+pub fn two_body_ode_rhs(mass: Num) -> impl Fn(Num, &[Num; 6], &mut [Num; 6]) {
+    perturbed_ode_rhs(mass, vec![])
+}
+
+/// Additional acceleration as a function of position, velocity and time
+pub type Perturbation = Box<dyn Fn(Vec3, Vec3, Num) -> Vec3>;
+
+/// Like [`two_body_ode_rhs`] but with additional accelerations.
+///
+/// The accelerations returned by the perturbations are added
+/// to the two body one.
+pub fn perturbed_ode_rhs(
+    mass: Num,
+    perturbations: Vec<Perturbation>,
+) -> impl Fn(Num, &[Num; 6], &mut [Num; 6]) {
+    move |t, y, dydt| {
+        let position = vec3(y[0], y[1], y[2]);
+        let velocity = vec3(y[3], y[4], y[5]);
+
+        let mut acceleration = two_body_acceleration(mass, position);
+
+        for perturbation in &perturbations {
+            acceleration += perturbation(position, velocity, t);
+        }
+
+        *dydt = [
+            velocity.x,
+            velocity.y,
+            velocity.z,
+            acceleration.x,
+            acceleration.y,
+            acceleration.z,
+        ];
+    }
+}
+
+/// Single fixed step of the classic Runge-Kutta method,
+/// for checking the analytical models against integration in tests
+#[cfg(test)]
+pub(crate) fn rk4_step(
+    rhs: impl Fn(Num, &[Num; 6], &mut [Num; 6]),
+    t: Num,
+    y: [Num; 6],
+    dt: Num,
+) -> [Num; 6] {
+    let add = |y: [Num; 6], k: [Num; 6], h: Num| {
+        std::array::from_fn(|i| y[i] + k[i] * h)
+    };
+
+    let mut k1 = [0.0; 6];
+    let mut k2 = [0.0; 6];
+    let mut k3 = [0.0; 6];
+    let mut k4 = [0.0; 6];
+
+    rhs(t, &y, &mut k1);
+    rhs(t + dt / 2.0, &add(y, k1, dt / 2.0), &mut k2);
+    rhs(t + dt / 2.0, &add(y, k2, dt / 2.0), &mut k3);
+    rhs(t + dt, &add(y, k3, dt), &mut k4);
+
+    std::array::from_fn(|i| {
+        y[i] + dt / 6.0 * (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i])
+    })
+}
+
+/// Converts a delta-v given in the RTN frame of `sv`
+/// (radial, transverse, normal) to the inertial frame.
+pub fn impulsive_burn_eci_from_rtn(sv: &StateVectors, dv_rtn: Vec3) -> Vec3 {
+    sv.from_rtn_frame(dv_rtn)
+}
+
+/// Inverse of [`impulsive_burn_eci_from_rtn`]
+pub fn impulsive_burn_rtn_from_eci(sv: &StateVectors, dv_eci: Vec3) -> Vec3 {
+    sv.to_rtn_frame(dv_eci)
+}
+
+/// Hohmann transfer between circular orbits combined with a plane change
+/// split between its two burns
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CombinedTransfer {
+    pub burn1_dv: Num,
+    pub burn2_dv: Num,
+    /// Part of the inclination change done by the first burn
+    pub plane_change_at_burn1: Num,
+    /// Part of the inclination change done by the second burn
+    pub plane_change_at_burn2: Num,
+    pub total_dv: Num,
+}
+
+/// Hohmann transfer from a circular orbit of radius `r1` to one of
+/// radius `r2` that also changes the inclination by `delta_i`,
+/// with the plane change split between the two burns to minimize
+/// the total delta-v.
+///
+/// Each burn changes both the speed and the direction, so its delta-v
+/// follows from the law of cosines:
+/// Δv² = v₁² + v₂² - 2 v₁ v₂ cos(Δi)
+pub fn optimize_combined_plane_change_and_transfer(
+    r1: Num,
+    r2: Num,
+    delta_i: Num,
+    mass: Num,
+) -> CombinedTransfer {
+    let transfer = |split: Num| combined_transfer(r1, r2, delta_i, split, mass);
+
+    let split = golden_section_search(
+        |split| transfer(split).total_dv,
+        0.0,
+        1.0,
+        PLANE_CHANGE_SPLIT_STEPS,
+    );
+
+    // The optimum may sit at either end
+    [transfer(0.0), transfer(split), transfer(1.0)]
+        .into_iter()
+        .min_by(|a, b| a.total_dv.total_cmp(&b.total_dv))
+        .unwrap()
+}
+
+/// Hohmann transfer doing the fraction `split` of the plane change
+/// at the first burn and the rest at the second one
+fn combined_transfer(
+    r1: Num,
+    r2: Num,
+    delta_i: Num,
+    split: Num,
+    mass: Num,
+) -> CombinedTransfer {
+    let μ = standard_gravitational_parameter(mass);
+    let a = (r1 + r2) / 2.0;
+
+    let circular = |r: Num| (μ / r).sqrt();
+    let transfer = |r: Num| (μ * (2.0 / r - 1.0 / a)).sqrt();
+
+    let burn = |v1: Num, v2: Num, θ: Num| {
+        (v1.powi(2) + v2.powi(2) - 2.0 * v1 * v2 * θ.cos())
+            .max(0.0)
+            .sqrt()
+    };
+
+    let plane_change_at_burn1 = split * delta_i;
+    let plane_change_at_burn2 = delta_i - plane_change_at_burn1;
+
+    let burn1_dv = burn(circular(r1), transfer(r1), plane_change_at_burn1);
+    let burn2_dv = burn(transfer(r2), circular(r2), plane_change_at_burn2);
+
+    CombinedTransfer {
+        burn1_dv,
+        burn2_dv,
+        plane_change_at_burn1,
+        plane_change_at_burn2,
+        total_dv: burn1_dv + burn2_dv,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{ASTRONOMICAL_UNIT, EARTH_MASS, SUN_MASS};
+    use crate::{KeplerianElements, PI};
+
+    const MASS: Num = 100_000_000_000.0;
+
+    #[test]
+    fn hohmann_transfer_time_leo_to_geo() {
+        let (leo, geo) = (6_678_000.0, 42_164_000.0);
+
+        let μ = standard_gravitational_parameter(EARTH_MASS);
+        let a_t: Num = 0.5 * (leo + geo);
+        let expected = PI * (a_t.powi(3) / μ).sqrt();
+
+        let time = hohmann_transfer_time(leo, geo, EARTH_MASS);
+        assert!((time / expected - 1.0).abs() < 1e-6, "{time}");
+
+        let hohmann = transfer::hohmann(μ, leo, geo).unwrap();
+        assert_eq!(time, hohmann.time_of_flight);
+        assert_eq!(time, hohmann_transfer_time(geo, leo, EARTH_MASS));
+    }
+
+    #[test]
+    fn minimum_time_transfer_trades_delta_v_for_time() {
+        let (leo, geo) = (6_678_000.0, 42_164_000.0);
+
+        let μ = standard_gravitational_parameter(EARTH_MASS);
+        let hohmann = transfer::hohmann(μ, leo, geo).unwrap();
+        let hohmann_dv = hohmann.total_delta_v();
+
+        assert_eq!(
+            minimum_time_transfer(leo, geo, EARTH_MASS, 0.9 * hohmann_dv),
+            None
+        );
+
+        // Just enough is the Hohmann transfer
+        let (time, dv) =
+            minimum_time_transfer(leo, geo, EARTH_MASS, hohmann_dv + 0.1)
+                .unwrap();
+        assert!((time / hohmann.time_of_flight - 1.0).abs() < 1e-2, "{time}");
+        assert!(dv <= hohmann_dv + 0.1);
+
+        let mut last = time;
+        for budget in [5_000.0, 10_000.0, 100_000.0, 1_000_000.0] {
+            let (time, dv) =
+                minimum_time_transfer(leo, geo, EARTH_MASS, budget).unwrap();
+
+            assert!(time < last, "{time} {last}");
+            assert!((dv / budget - 1.0).abs() < 1e-3, "{dv} {budget}");
+            last = time;
+
+            // Going down takes as long
+            let (down, _) =
+                minimum_time_transfer(geo, leo, EARTH_MASS, budget).unwrap();
+            assert_eq!(down, time);
+        }
+
+        // Approaching the straight line at the departure speed
+        assert!(last < 1e-2 * hohmann.time_of_flight, "{last}");
+    }
+
+    #[test]
+    fn two_body_ode_rhs_derivatives() {
+        let μ = standard_gravitational_parameter(MASS);
+        let rhs = two_body_ode_rhs(MASS);
+
+        let y = [2.0, 0.0, 0.0, 0.0, 1.0, 0.5];
+        let mut dydt = [0.0; 6];
+
+        rhs(0.0, &y, &mut dydt);
+
+        assert_eq!(dydt, [0.0, 1.0, 0.5, -μ / 4.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn two_body_ode_rhs_follows_the_orbit() {
+        let elements = KeplerianElements {
+            eccentricity: 0.1,
+            semi_major_axis: 1.0,
+            inclination: 0.3,
+            right_ascension_of_the_ascending_node: 0.5,
+            argument_of_periapsis: 0.2,
+            mean_anomaly_at_epoch: 0.0,
+            epoch: 0.0,
+        };
+
+        let rhs = two_body_ode_rhs(MASS);
+        let period = elements.period(MASS);
+        let steps = 10_000;
+        let dt = period / 4.0 / steps as Num;
+
+        let mut y = elements.state_vectors_at_epoch(MASS, 0.0, 1e-6).as_array();
+        let mut dydt = [0.0; 6];
+
+        // Semi-implicit Euler over a quarter of the orbit
+        for i in 0..steps {
+            rhs(i as Num * dt, &y, &mut dydt);
+
+            for k in 3..6 {
+                y[k] += dydt[k] * dt;
+            }
+            for k in 0..3 {
+                y[k] += y[k + 3] * dt;
+            }
+        }
+
+        let exp = elements.state_vectors_at_epoch(MASS, period / 4.0, 1e-6);
+        let position = vec3(y[0], y[1], y[2]);
+
+        assert!(
+            position.distance(exp.position) < 0.01,
+            "{position:?} not equal {:?}",
+            exp.position
+        );
+    }
+
+    #[test]
+    fn impulsive_burn_round_trip() {
+        let sv = StateVectors::new(vec3(1.0, 0.5, -0.2), vec3(-0.3, 2.0, 0.7));
+
+        for dv_rtn in [Vec3::X, Vec3::Y, Vec3::Z, vec3(0.3, -1.5, 2.0)] {
+            let dv_eci = impulsive_burn_eci_from_rtn(&sv, dv_rtn);
+
+            assert!((dv_eci.length() - dv_rtn.length()).abs() < 1e-5);
+
+            let back = impulsive_burn_rtn_from_eci(&sv, dv_eci);
+            assert!(back.abs_diff_eq(dv_rtn, 1e-5), "{back:?} != {dv_rtn:?}");
+        }
+
+        // Radial is along the position, normal along the angular momentum
+        let radial = impulsive_burn_eci_from_rtn(&sv, Vec3::X);
+        let normal = impulsive_burn_eci_from_rtn(&sv, Vec3::Z);
+        assert!(radial.abs_diff_eq(sv.position.normalize(), 1e-5));
+        assert!(normal
+            .abs_diff_eq(sv.position.cross(sv.velocity).normalize(), 1e-5));
+    }
+
+    #[test]
+    fn canonical_burns() {
+        let elements = KeplerianElements {
+            eccentricity: 0.2,
+            semi_major_axis: 1.0,
+            inclination: 0.3,
+            right_ascension_of_the_ascending_node: 0.5,
+            argument_of_periapsis: 0.2,
+            mean_anomaly_at_epoch: 0.0,
+            epoch: 0.0,
+        };
+        let v = 1.3;
+
+        let prograde = elements.dv_prograde_at_true_anomaly(0.5, v, MASS);
+        let normal = elements.dv_normal_at_true_anomaly(0.5, v, MASS);
+        let radial = elements.dv_radial_at_true_anomaly(0.5, v, MASS);
+
+        for dv in [prograde, normal, radial] {
+            assert!((dv.length() - 0.5).abs() < 1e-5);
+        }
+
+        let velocity = elements.velocity_at_true_anomaly(MASS, v);
+        let position = elements.position_at_true_anomaly(MASS, v);
+        assert!(prograde.abs_diff_eq(0.5 * velocity.normalize(), 1e-5));
+        assert!(radial.abs_diff_eq(0.5 * position.normalize(), 1e-5));
+        assert!(normal.dot(velocity).abs() < 1e-5);
+        assert!(normal.dot(position).abs() < 1e-5);
+    }
+
+    #[test]
+    fn perturbed_ode_rhs_adds_accelerations() {
+        let rhs = perturbed_ode_rhs(
+            MASS,
+            vec![Box::new(|_, _, _| Vec3::Z), Box::new(|_, v, t| v * t)],
+        );
+
+        let y = [2.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+        let mut dydt = [0.0; 6];
+        rhs(3.0, &y, &mut dydt);
+
+        let two_body = two_body_acceleration(MASS, vec3(2.0, 0.0, 0.0));
+        let exp = two_body + Vec3::Z + vec3(0.0, 3.0, 0.0);
+
+        assert_eq!(&dydt[3..], &exp.to_array());
+    }
+
+    #[test]
+    fn combined_plane_change_without_inclination_is_hohmann() {
+        let μ = standard_gravitational_parameter(MASS);
+
+        for (r1, r2) in [(1.0, 3.0), (3.0, 1.0)] {
+            let combined =
+                optimize_combined_plane_change_and_transfer(r1, r2, 0.0, MASS);
+            let hohmann = transfer::hohmann(μ, r1, r2).unwrap();
+
+            assert!(
+                (combined.burn1_dv - hohmann.delta_v_departure).abs() < 1e-4
+            );
+            assert!((combined.burn2_dv - hohmann.delta_v_arrival).abs() < 1e-4);
+            assert_eq!(combined.plane_change_at_burn1, 0.0);
+            assert_eq!(combined.plane_change_at_burn2, 0.0);
+        }
+    }
+
+    #[test]
+    fn plane_change_is_cheaper_where_slower() {
+        for (r2, Δi) in [(2.0, 0.1), (6.0, 0.5), (20.0, 1.0)] {
+            let all_at_burn1 = combined_transfer(1.0, r2, Δi, 1.0, MASS);
+            let all_at_burn2 = combined_transfer(1.0, r2, Δi, 0.0, MASS);
+            assert!(all_at_burn2.total_dv < all_at_burn1.total_dv);
+
+            let optimal =
+                optimize_combined_plane_change_and_transfer(1.0, r2, Δi, MASS);
+            assert!(optimal.total_dv <= all_at_burn2.total_dv);
+            assert!(
+                optimal.plane_change_at_burn2 > optimal.plane_change_at_burn1,
+                "{optimal:?}"
+            );
+            assert!(
+                (optimal.plane_change_at_burn1 + optimal.plane_change_at_burn2
+                    - Δi)
+                    .abs()
+                    < 1e-6
+            );
+        }
+    }
+
+    #[test]
+    fn sphere_of_activity_is_slightly_smaller_than_soi() {
+        // Close to 2^(1/5) for small mass ratios, departing from it
+        // for heavier bodies
+        for (r, m, tolerance) in [
+            (ASTRONOMICAL_UNIT, EARTH_MASS, 5e-3),
+            (7.785e11, 1.898e27, 0.05),
+        ] {
+            let soa = sphere_of_activity(r, m, SUN_MASS);
+            let ratio = soi_vs_soa_ratio(r, m, SUN_MASS);
+
+            assert!(soa < soi(r, m, SUN_MASS));
+            assert!((ratio - 1.1487).abs() < tolerance, "{ratio}");
+        }
+
+        // The Earth's is about 0.8 million km
+        let earth = sphere_of_activity(ASTRONOMICAL_UNIT, EARTH_MASS, SUN_MASS);
+        assert!((earth - 8.1e8).abs() < 0.1e8, "{earth}");
+    }
+}
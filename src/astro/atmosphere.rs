@@ -0,0 +1,90 @@
+//! Exponential model of the Earth's atmosphere.
+//!
+//! The density decays exponentially within each altitude band,
+//! with the base density and the scale height of the band.
+//!
+//! SRC: Vallado, Fundamentals of Astrodynamics and Applications,
+//! table 8-4 (CIRA-72)
+
+use crate::Num;
+
+/// (base altitude [m], base density [kg/m³], scale height [m])
+const BANDS: [(Num, Num, Num); 19] = [
+    (100_000.0, 5.297e-7, 5_877.0),
+    (110_000.0, 9.661e-8, 7_263.0),
+    (120_000.0, 2.438e-8, 9_473.0),
+    (130_000.0, 8.484e-9, 12_636.0),
+    (140_000.0, 3.845e-9, 16_149.0),
+    (150_000.0, 2.070e-9, 22_523.0),
+    (180_000.0, 5.464e-10, 29_740.0),
+    (200_000.0, 2.789e-10, 37_105.0),
+    (250_000.0, 7.248e-11, 45_546.0),
+    (300_000.0, 2.418e-11, 53_628.0),
+    (350_000.0, 9.518e-12, 53_298.0),
+    (400_000.0, 3.725e-12, 58_515.0),
+    (450_000.0, 1.585e-12, 60_828.0),
+    (500_000.0, 6.967e-13, 63_822.0),
+    (600_000.0, 1.454e-13, 71_835.0),
+    (700_000.0, 3.614e-14, 88_667.0),
+    (800_000.0, 1.170e-14, 124_640.0),
+    (900_000.0, 5.245e-15, 181_050.0),
+    (1_000_000.0, 3.019e-15, 268_000.0),
+];
+
+/// Altitude of the entry interface, where reentry is considered to begin
+pub const ENTRY_INTERFACE_ALTITUDE: Num = 120_000.0;
+
+/// Atmosphere of a body, e.g. for
+/// [`crate::KeplerianElements::reentry_decay_time_estimate`]
+#[derive(Debug, Clone, Copy)]
+pub struct Atmosphere {
+    /// Density at the given altitude above the surface, in kg/m³
+    pub density: fn(Num) -> Num,
+    /// Altitude where reentry is considered to begin
+    pub entry_interface_altitude: Num,
+}
+
+impl Atmosphere {
+    /// The exponential model of the Earth's atmosphere
+    pub const EARTH: Self = Self {
+        density,
+        entry_interface_altitude: ENTRY_INTERFACE_ALTITUDE,
+    };
+}
+
+/// Density at the given altitude above the surface, in kg/m³.
+///
+/// Below 100 km the lowest band is extrapolated,
+/// above 1000 km the highest one.
+pub fn density(altitude: Num) -> Num {
+    let (base, density, scale_height) = BANDS
+        .iter()
+        .rev()
+        .find(|(base, ..)| altitude >= *base)
+        .unwrap_or(&BANDS[0]);
+
+    density * (-(altitude - base) / scale_height).exp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn density_is_continuous_and_decreasing() {
+        for pair in BANDS.windows(2) {
+            let (base, ..) = pair[1];
+
+            let below = density(base - 1.0);
+            let above = density(base);
+            assert!((below / above - 1.0).abs() < 0.05, "{base}");
+        }
+
+        let mut last = Num::MAX;
+        for km in (80..1200).step_by(10) {
+            let rho = density(km as Num * 1_000.0);
+            assert!(rho < last, "{km}");
+            last = rho;
+        }
+    }
+}
@@ -0,0 +1,154 @@
+//! B-plane targeting for hyperbolic flybys.
+//!
+//! The B-plane passes through the center of the body, perpendicular
+//! to the incoming asymptote S. T lies in the reference (x-y) plane,
+//! T = S × Z normalized, and R = S × T completes the basis.
+//! When S is along Z, T is any vector perpendicular to it.
+//! The B-vector points from the center to where the incoming asymptote
+//! pierces the plane.
+//!
+//! SRC: https://en.wikipedia.org/wiki/B-plane
+
+use super::standard_gravitational_parameter;
+use crate::{KeplerianElements, Num, Vec3};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BPlane {
+    pub B_T: Num,
+    pub B_R: Num,
+    /// Angle of the B-vector measured from T towards R
+    pub theta: Num,
+}
+
+impl BPlane {
+    /// Length of the B-vector, the impact parameter
+    pub fn magnitude(&self) -> Num {
+        self.B_T.hypot(self.B_R)
+    }
+}
+
+/// B-plane coordinates of a hyperbolic trajectory.
+///
+/// The B-vector only depends on the shape and orientation of the orbit,
+/// the mass is taken for symmetry with [`to_periapsis_radius`].
+/// The elements must be hyperbolic.
+pub fn from_hyperbolic_elements(
+    elements: &KeplerianElements,
+    _mass: Num,
+) -> BPlane {
+    debug_assert!(elements.is_hyperbolic());
+
+    let e = elements.eccentricity;
+
+    let h = elements.normal();
+    let s = elements.incoming_asymptote();
+
+    // The semi-minor axis is the distance of the asymptote from the focus
+    let b = elements.semi_major_axis * (e.powi(2) - 1.0).sqrt();
+    let b_vector = b * s.cross(h);
+
+    let (t, r) = basis(s);
+
+    let B_T = b_vector.dot(t);
+    let B_R = b_vector.dot(r);
+
+    BPlane {
+        B_T,
+        B_R,
+        theta: B_R.atan2(B_T),
+    }
+}
+
+/// T and R axes of the B-plane for the incoming asymptote `s`,
+/// which must be normalized
+pub fn basis(s: Vec3) -> (Vec3, Vec3) {
+    let t = s
+        .cross(Vec3::Z)
+        .try_normalize()
+        .unwrap_or_else(|| s.any_orthonormal_vector());
+
+    (t, s.cross(t))
+}
+
+/// Periapsis radius of the flyby targeted at `b_plane`
+/// with the hyperbolic excess speed `v_inf`.
+///
+/// r_p = μ / v∞² * (sqrt(1 + (B * v∞² / μ)²) - 1)
+pub fn to_periapsis_radius(b_plane: &BPlane, v_inf: Num, mass: Num) -> Num {
+    let μ = standard_gravitational_parameter(mass);
+    let k = μ / v_inf.powi(2);
+
+    k * ((1.0 + (b_plane.magnitude() / k).powi(2)).sqrt() - 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PI;
+
+    const JUPITER_MASS: Num = 1.898e27;
+
+    /// Hyperbola with the given periapsis radius and excess speed
+    fn flyby(
+        periapsis_radius: Num,
+        v_inf: Num,
+        inclination: Num,
+    ) -> KeplerianElements {
+        let μ = standard_gravitational_parameter(JUPITER_MASS);
+        let a = μ / v_inf.powi(2);
+
+        KeplerianElements {
+            eccentricity: 1.0 + periapsis_radius / a,
+            semi_major_axis: a,
+            inclination,
+            right_ascension_of_the_ascending_node: 0.4,
+            argument_of_periapsis: 1.3,
+            mean_anomaly_at_epoch: -2.0,
+            epoch: 0.0,
+        }
+    }
+
+    #[test]
+    fn impact_parameter_of_a_jupiter_flyby() {
+        // Ten Jupiter radii at 10 km/s
+        let periapsis_radius = 714_920_000.0;
+        let v_inf = 10_000.0;
+
+        let elements = flyby(periapsis_radius, v_inf, 0.3);
+        let b_plane = from_hyperbolic_elements(&elements, JUPITER_MASS);
+
+        let r = to_periapsis_radius(&b_plane, v_inf, JUPITER_MASS);
+        assert!((r / periapsis_radius - 1.0).abs() < 1e-4, "{r}");
+
+        // Gravitational focusing, B = r_p sqrt(1 + 2μ / (r_p v∞²))
+        // from the conservation of energy and angular momentum
+        let μ = standard_gravitational_parameter(JUPITER_MASS);
+        let expected = periapsis_radius
+            * (1.0 + 2.0 * μ / (periapsis_radius * v_inf.powi(2))).sqrt();
+        assert!((b_plane.magnitude() / expected - 1.0).abs() < 1e-4);
+        assert!(b_plane.magnitude() > periapsis_radius);
+    }
+
+    #[test]
+    fn equatorial_flybys_have_no_r_component() {
+        let prograde =
+            from_hyperbolic_elements(&flyby(1e8, 5_000.0, 0.0), JUPITER_MASS);
+        assert!(prograde.B_R.abs() / prograde.B_T < 1e-5, "{prograde:?}");
+        assert!(prograde.theta.abs() < 1e-5);
+
+        let retrograde =
+            from_hyperbolic_elements(&flyby(1e8, 5_000.0, PI), JUPITER_MASS);
+        assert!((retrograde.theta.abs() - PI).abs() < 1e-5, "{retrograde:?}");
+    }
+
+    #[test]
+    fn asymptote_along_the_reference_axis() {
+        for s in [Vec3::Z, -Vec3::Z, Vec3::X] {
+            let (t, r) = basis(s);
+
+            assert!(t.is_normalized() && r.is_normalized(), "{t} {r}");
+            assert!(t.dot(s).abs() < 1e-6 && r.dot(s).abs() < 1e-6);
+            assert!(t.dot(r).abs() < 1e-6);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use super::standard_gravitational_parameter;
+use crate::Num;
+
+/// Delta-v needed to capture from a hyperbolic approach into a circular orbit.
+///
+/// The capture burn is done at the periapsis of the approach hyperbola
+/// and puts the spacecraft on an ellipse with its apoapsis at
+/// `target_orbit_radius`. The orbit is then circularized at the apoapsis.
+/// If the target radius equals the periapsis radius this is a single burn.
+///
+/// `v_inf` is the hyperbolic excess velocity, everything is in SI units.
+pub fn delta_v_for_orbit_insertion(
+    v_inf: Num,
+    periapsis_radius: Num,
+    target_orbit_radius: Num,
+    mass: Num,
+) -> Num {
+    let μ = standard_gravitational_parameter(mass);
+
+    let rp = periapsis_radius;
+    let ra = target_orbit_radius;
+
+    let capture = delta_v_for_elliptic_capture(v_inf, rp, ra, mass);
+
+    // Circularization at the apoapsis of the capture ellipse
+    let v_apoapsis = (2.0 * μ * rp / (ra * (rp + ra))).sqrt();
+    let v_circular = (μ / ra).sqrt();
+
+    capture + (v_circular - v_apoapsis).abs()
+}
+
+/// Delta-v needed at the periapsis of a hyperbolic approach to capture
+/// into an ellipse with the given apoapsis.
+///
+/// `v_inf` is the hyperbolic excess velocity, everything is in SI units.
+pub fn delta_v_for_elliptic_capture(
+    v_inf: Num,
+    periapsis_radius: Num,
+    target_apoapsis: Num,
+    mass: Num,
+) -> Num {
+    let μ = standard_gravitational_parameter(mass);
+
+    let rp = periapsis_radius;
+    let ra = target_apoapsis;
+
+    // Vis-viva at the periapsis of the hyperbola and the capture ellipse
+    let v_hyperbolic = (v_inf.powi(2) + 2.0 * μ / rp).sqrt();
+    let v_elliptic = (2.0 * μ * ra / (rp * (rp + ra))).sqrt();
+
+    v_hyperbolic - v_elliptic
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MARS_MASS: Num = 6.4171e23;
+    const MARS_RADIUS: Num = 3_389_500.0;
+
+    // Hohmann transfer arrival at Mars
+    const V_INF: Num = 2_648.0;
+
+    #[test]
+    fn mars_orbit_insertion_circular() {
+        let rp = MARS_RADIUS + 300_000.0;
+
+        let Δv = delta_v_for_orbit_insertion(V_INF, rp, rp, MARS_MASS);
+
+        assert!((Δv - 2_090.0).abs() < 10.0, "Δv = {Δv}");
+    }
+
+    #[test]
+    fn mars_orbit_insertion_elliptic() {
+        let rp = MARS_RADIUS + 300_000.0;
+        let ra = MARS_RADIUS + 33_000_000.0;
+
+        let Δv = delta_v_for_elliptic_capture(V_INF, rp, ra, MARS_MASS);
+
+        // Going to a highly elliptic orbit is a lot cheaper
+        assert!((Δv - 907.0).abs() < 10.0, "Δv = {Δv}");
+    }
+
+    #[test]
+    fn circular_is_elliptic_capture_with_apoapsis_at_periapsis() {
+        let rp = MARS_RADIUS + 300_000.0;
+
+        let circular = delta_v_for_orbit_insertion(V_INF, rp, rp, MARS_MASS);
+        let elliptic = delta_v_for_elliptic_capture(V_INF, rp, rp, MARS_MASS);
+
+        assert!((circular - elliptic).abs() < 0.01);
+    }
+}
@@ -0,0 +1,239 @@
+//! Orbit design helpers.
+
+use crate::constants::G;
+use crate::math::fixed_point_iteration;
+use crate::{KeplerianElements, Num, PI};
+
+/// Convergence tolerance of the repeat ground track semi-major axis, in meters
+const REPEAT_GROUND_TRACK_TOLERANCE: Num = 1.0;
+
+const REPEAT_GROUND_TRACK_MAX_STEPS: usize = 100;
+
+/// How close 5cos²i - 1 has to be to zero to count as
+/// the critical inclination
+const CRITICAL_INCLINATION_TOLERANCE: Num = 1e-3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrozenOrbitSolution {
+    /// Secular rate of the argument of periapsis due to J2
+    pub apsidal_rate: Num,
+    /// Whether the inclination is (close to) 63.43° or 116.57°,
+    /// where the apsidal rate vanishes
+    pub is_critical_inclination: bool,
+    /// Eccentricity and argument of periapsis that freeze
+    /// the eccentricity vector. `None` at the critical inclination,
+    /// where any combination is frozen.
+    pub frozen: Option<(Num, Num)>,
+}
+
+/// Finds the frozen orbit for the given semi-major axis and inclination.
+///
+/// J2 alone only rotates the eccentricity vector, it's the odd zonal J3 that
+/// sets its frozen length. Away from the critical inclination the classic
+/// small eccentricity solution is used:
+/// e = -J3 / (2 J2) * R / a * sin(i), with ω = 90°
+/// (or ω = 270° when that comes out negative).
+///
+/// SRC: https://en.wikipedia.org/wiki/Frozen_orbit
+pub fn frozen_orbit(
+    mu: Num,
+    j2: Num,
+    j3: Num,
+    body_radius: Num,
+    semi_major_axis: Num,
+    inclination: Num,
+) -> FrozenOrbitSolution {
+    let circular = KeplerianElements {
+        semi_major_axis,
+        inclination,
+        ..KeplerianElements::default()
+    };
+
+    let apsidal_rate =
+        circular.secular_argument_of_periapsis_rate(body_radius, j2, mu / G);
+
+    let is_critical_inclination = (5.0 * inclination.cos().powi(2) - 1.0).abs()
+        < CRITICAL_INCLINATION_TOLERANCE;
+
+    let frozen = (!is_critical_inclination).then(|| {
+        let e = -j3 / (2.0 * j2)
+            * (body_radius / semi_major_axis)
+            * inclination.sin();
+
+        if e >= 0.0 {
+            (e, PI / 2.0)
+        } else {
+            (-e, 1.5 * PI)
+        }
+    });
+
+    FrozenOrbitSolution {
+        apsidal_rate,
+        is_critical_inclination,
+        frozen,
+    }
+}
+
+/// Semi-major axis of a circular orbit that completes `revs` nodal
+/// revolutions in exactly `days` nodal days, i.e. whose ground track
+/// repeats after `days` days.
+///
+/// Both the nodal period and the nodal day include the secular J2 drifts
+/// of the argument of latitude and of the ascending node, so the axis
+/// is found iteratively. Returns `None` for zero `revs` or `days`,
+/// when the iteration doesn't converge or the orbit ends up
+/// below the surface.
+///
+/// SRC: https://en.wikipedia.org/wiki/Repeat_orbit
+pub fn repeat_ground_track(
+    mu: Num,
+    j2: Num,
+    body_radius: Num,
+    body_rotation_rate: Num,
+    revs: u32,
+    days: u32,
+    inclination: Num,
+) -> Option<Num> {
+    if revs == 0 || days == 0 {
+        return None;
+    }
+
+    let ratio = revs as Num / days as Num;
+    let mass = mu / G;
+
+    let a_from_n = |n: Num| (mu / n.powi(2)).cbrt();
+
+    let next_a = |a: Num| {
+        let circular = KeplerianElements {
+            semi_major_axis: a,
+            inclination,
+            ..KeplerianElements::default()
+        };
+        let n = (mu / a.powi(3)).sqrt();
+
+        let raan_rate = circular.secular_raan_rate(body_radius, j2, mass);
+        let argument_of_latitude_rate =
+            n + circular.secular_argument_of_periapsis_rate(
+                body_radius,
+                j2,
+                mass,
+            ) + circular.secular_mean_anomaly_rate(body_radius, j2, mass);
+
+        // revs * 2π / u̇ = days * 2π / (ω_body - Ω̇),
+        // with both rates proportional to n
+        let n = ratio * body_rotation_rate * n
+            / (argument_of_latitude_rate + ratio * raan_rate);
+
+        a_from_n(n)
+    };
+
+    let a = fixed_point_iteration(
+        next_a,
+        a_from_n(ratio * body_rotation_rate),
+        REPEAT_GROUND_TRACK_TOLERANCE,
+        REPEAT_GROUND_TRACK_MAX_STEPS,
+    )
+    .ok()?;
+
+    (a > body_radius).then_some(a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{EARTH_J2, EARTH_RADIUS};
+
+    const EARTH_MU: Num = 3.986_004_4e14;
+    const EARTH_J3: Num = -2.532_7e-6;
+    const EARTH_ROTATION_RATE: Num = 7.292_115e-5;
+
+    #[test]
+    fn critical_inclination() {
+        let critical = (1.0 / Num::sqrt(5.0)).acos();
+
+        for inclination in [critical, PI - critical] {
+            let solution = frozen_orbit(
+                EARTH_MU,
+                EARTH_J2,
+                EARTH_J3,
+                EARTH_RADIUS,
+                26_560_000.0,
+                inclination,
+            );
+
+            assert!(solution.is_critical_inclination);
+            assert!(solution.apsidal_rate.abs() < 1e-12, "{solution:?}");
+            assert_eq!(solution.frozen, None);
+        }
+    }
+
+    #[test]
+    fn sun_synchronous_leo() {
+        let solution = frozen_orbit(
+            EARTH_MU,
+            EARTH_J2,
+            EARTH_J3,
+            EARTH_RADIUS,
+            EARTH_RADIUS + 800_000.0,
+            98.6 * PI / 180.0,
+        );
+
+        assert!(!solution.is_critical_inclination);
+        assert!(solution.apsidal_rate < 0.0);
+
+        let (e, ω) = solution.frozen.unwrap();
+        assert!((e - 1.03e-3).abs() < 5e-5, "{e}");
+        assert_eq!(ω, PI / 2.0);
+    }
+
+    #[test]
+    fn repeat_ground_track_without_j2_is_keplerian() {
+        let a = repeat_ground_track(
+            EARTH_MU,
+            0.0,
+            EARTH_RADIUS,
+            EARTH_ROTATION_RATE,
+            15,
+            1,
+            1.0,
+        )
+        .unwrap();
+
+        let n = 15.0 * EARTH_ROTATION_RATE;
+        let expected = (EARTH_MU / n.powi(2)).cbrt();
+        assert!((a - expected).abs() < 1.0, "{a} != {expected}");
+    }
+
+    #[test]
+    fn landsat_repeat_cycle() {
+        let a = repeat_ground_track(
+            EARTH_MU,
+            EARTH_J2,
+            EARTH_RADIUS,
+            EARTH_ROTATION_RATE,
+            233,
+            16,
+            98.2 * PI / 180.0,
+        )
+        .unwrap();
+
+        let altitude = a - EARTH_RADIUS;
+        // First order J2 only, so within ten kilometers
+        assert!((altitude - 705_000.0).abs() < 10_000.0, "{altitude}");
+    }
+
+    #[test]
+    fn repeat_ground_track_degenerate() {
+        let args = (EARTH_MU, EARTH_J2, EARTH_RADIUS, EARTH_ROTATION_RATE);
+
+        assert_eq!(
+            repeat_ground_track(args.0, args.1, args.2, args.3, 0, 1, 1.0),
+            None
+        );
+        // Faster than a surface grazing orbit
+        assert_eq!(
+            repeat_ground_track(args.0, args.1, args.2, args.3, 20, 1, 1.0),
+            None
+        );
+    }
+}
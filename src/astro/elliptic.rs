@@ -1,4 +1,7 @@
-use crate::math::newton_approx;
+use crate::math::{
+    fixed_point_iteration, halley_approx, newton_approx_fused,
+    newton_approx_fused_counted,
+};
 use crate::Num;
 
 use super::standard_gravitational_parameter;
@@ -17,22 +20,180 @@ pub fn estimate_anomaly(
     e: Num,
     tolerance: Num,
 ) -> Num {
-    newton_approx(
-        // f(E) = E - e*sin(E) - M
+    newton_approx_fused(|E| kepler_equation(E, e, M), M, tolerance)
+}
+
+/// Same as [`estimate_anomaly`] but using Halley's method,
+/// which takes fewer iterations for highly eccentric orbits.
+pub fn estimate_anomaly_halley(M: Num, e: Num, tolerance: Num) -> Num {
+    halley_approx(
         |E| E - (e * E.sin()) - M,
-        // f'(E) = 1 - e*cos(E)
         |E| 1.0 - (e * E.cos()),
+        // f''(E) = e*sin(E)
+        |E| e * E.sin(),
         M,
         tolerance,
     )
 }
 
+/// f(E) = E - e*sin(E) - M and f'(E) = 1 - e*cos(E)
+fn kepler_equation(E: Num, e: Num, M: Num) -> (Num, Num) {
+    let (sin, cos) = E.sin_cos();
+
+    (E - (e * sin) - M, 1.0 - (e * cos))
+}
+
+/// Same as [`estimate_anomaly`] but starting from the given initial guess
+/// of the eccentric anomaly `E0`.
+///
+/// Returns the eccentric anomaly and the number of iterations taken.
+pub fn estimate_anomaly_with_guess(
+    M: Num,
+    e: Num,
+    E0: Num,
+    tolerance: Num,
+) -> (Num, usize) {
+    newton_approx_fused_counted(|E| kepler_equation(E, e, M), E0, tolerance)
+}
+
+/// Eccentricity below which [`estimate_anomaly_circular`]
+/// uses fixed point iteration
+pub const FIXED_POINT_MAX_ECCENTRICITY: Num = 0.05;
+
+const FIXED_POINT_MAX_STEPS: usize = 100;
+
+/// Same as [`estimate_anomaly`] but faster for near circular orbits.
+///
+/// For e < 0.05 Kepler's equation is solved with the fixed point iteration
+/// E = M + e * sin(E)
+/// which converges quickly and avoids the cosine of Newton's method.
+/// Falls back to Newton's method for higher eccentricities
+/// or if the iteration doesn't converge.
+pub fn estimate_anomaly_circular(M: Num, e: Num, tolerance: Num) -> Num {
+    if e < FIXED_POINT_MAX_ECCENTRICITY {
+        let E = fixed_point_iteration(
+            |E| M + e * E.sin(),
+            M,
+            tolerance,
+            FIXED_POINT_MAX_STEPS,
+        );
+
+        if let Ok(E) = E {
+            return E;
+        }
+    }
+
+    estimate_anomaly(M, e, tolerance)
+}
+
+#[cfg(all(feature = "simd", not(feature = "f64")))]
+type NumxN = wide::f32x8;
+#[cfg(all(feature = "simd", feature = "f64"))]
+type NumxN = wide::f64x4;
+
+#[cfg(feature = "simd")]
+const LANES: usize = std::mem::size_of::<NumxN>() / std::mem::size_of::<Num>();
+
+#[cfg(feature = "simd")]
+const SIMD_MAX_STEPS: usize = 100;
+
+/// Solves Kepler's equation for many orbits at once.
+///
+/// With the `simd` feature enabled the Newton iteration runs on several
+/// orbits simultaneously, otherwise this is the same as calling
+/// [`estimate_anomaly`] for every orbit.
+pub fn estimate_anomaly_batch(
+    // Mean anomalies
+    Ms: &[Num],
+    // Eccentricities
+    es: &[Num],
+    tolerance: Num,
+) -> Vec<Num> {
+    assert_eq!(Ms.len(), es.len());
+
+    #[cfg(feature = "simd")]
+    {
+        let mut anomalies = Vec::with_capacity(Ms.len());
+
+        let M_chunks = Ms.chunks_exact(LANES);
+        let e_chunks = es.chunks_exact(LANES);
+
+        let M_remainder = M_chunks.remainder();
+        let e_remainder = e_chunks.remainder();
+
+        for (M, e) in M_chunks.zip(e_chunks) {
+            let M = M.try_into().unwrap();
+            let e = e.try_into().unwrap();
+
+            anomalies.extend(estimate_anomaly_lanes(M, e, tolerance));
+        }
+
+        for (&M, &e) in M_remainder.iter().zip(e_remainder) {
+            anomalies.push(estimate_anomaly(M, e, tolerance));
+        }
+
+        anomalies
+    }
+
+    #[cfg(not(feature = "simd"))]
+    {
+        Ms.iter()
+            .zip(es)
+            .map(|(&M, &e)| estimate_anomaly(M, e, tolerance))
+            .collect()
+    }
+}
+
+/// Newton's method on all lanes at once,
+/// converged lanes are masked off and keep their value
+#[cfg(feature = "simd")]
+fn estimate_anomaly_lanes(
+    M: [Num; LANES],
+    e: [Num; LANES],
+    tolerance: Num,
+) -> [Num; LANES] {
+    use wide::CmpLt;
+
+    let M_lanes = NumxN::new(M);
+    let e_lanes = e;
+    let e = NumxN::new(e);
+    let tolerance_lanes = NumxN::splat(tolerance);
+
+    let mut E = M_lanes;
+    let mut converged = NumxN::splat(0.0);
+
+    for _ in 0..SIMD_MAX_STEPS {
+        let E_next = E - (E - e * E.sin() - M_lanes) / (1.0 - e * E.cos());
+        let E_next = converged.blend(E, E_next);
+
+        converged |= (E_next - E).abs().cmp_lt(tolerance_lanes);
+        E = E_next;
+
+        if converged.all() {
+            return E.to_array();
+        }
+    }
+
+    // Let the scalar solver deal with the stragglers
+    let mask = converged.move_mask();
+    let mut E = E.to_array();
+
+    for lane in 0..LANES {
+        if mask & (1 << lane) == 0 {
+            E[lane] = estimate_anomaly(M[lane], e_lanes[lane], tolerance);
+        }
+    }
+
+    E
+}
+
 /// Mean motion
 /// https://en.wikipedia.org/wiki/Mean_anomaly
 pub fn mean_motion(h: Num, e: Num, mass: Num) -> Num {
     let μ = standard_gravitational_parameter(mass);
 
-    (μ.powi(2) / h.powi(3)) * (1.0 - e.powi(2)).powi(3).sqrt()
+    // μ² alone overflows f32 for stellar masses
+    ((μ / h).powi(2) / h) * (1.0 - e.powi(2)).powi(3).sqrt()
 }
 
 pub fn true_anomaly(E: Num, e: Num) -> Num {
@@ -40,3 +201,85 @@ pub fn true_anomaly(E: Num, e: Num) -> Num {
     // https://orbital-mechanics.space/time-since-periapsis-and-keplers-equation/elliptical-orbits.html#equation-eq-eccentric-anomaly-true-anomaly-ellipse
     2.0 * ((E / 2.0).tan() / ((1.0 - e) / (1.0 + e)).sqrt()).atan()
 }
+
+/// Inverse of [`true_anomaly`], E in (-π, π]
+pub fn eccentric_anomaly(v: Num, e: Num) -> Num {
+    let (sin, cos) = (v / 2.0).sin_cos();
+
+    2.0 * ((1.0 - e).sqrt() * sin).atan2((1.0 + e).sqrt() * cos)
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+    use crate::constants::TWO_PI;
+
+    #[test_case(0.0, 0.0)]
+    #[test_case(1.0, 0.01)]
+    #[test_case(3.0, 0.049)]
+    #[test_case(5.0, 0.3)]
+    #[test_case(2.0, 0.9)]
+    fn estimate_anomaly_circular_matches_newton(M: Num, e: Num) {
+        let E = estimate_anomaly_circular(M, e, 1e-6);
+        let exp = estimate_anomaly(M, e, 1e-6);
+
+        assert!((E - exp).abs() < 1e-4, "{E} not equal {exp}");
+        assert!((E - e * E.sin() - M).abs() < 1e-4);
+    }
+
+    #[test_case(0.5, 0.3)]
+    #[test_case(2.0, 0.9)]
+    #[test_case(0.1, 0.99)]
+    #[test_case(6.0, 0.999)]
+    fn estimate_anomaly_halley_matches_newton(M: Num, e: Num) {
+        let E = estimate_anomaly_halley(M, e, 1e-5);
+        let exp = estimate_anomaly(M, e, 1e-5);
+
+        assert!((E - exp).abs() < 1e-4, "{E} not equal {exp}");
+    }
+
+    #[test]
+    fn hyperbolic_halley_matches_newton() {
+        use crate::astro::hyperbolic;
+
+        for (M, e) in [(0.5, 1.1), (-3.0, 1.5), (20.0, 3.0)] {
+            let F = hyperbolic::estimate_anomaly_halley(M, e, 1e-5);
+            let exp = hyperbolic::estimate_anomaly(M, e, 1e-5);
+
+            assert!((F - exp).abs() < 1e-4, "{F} not equal {exp}");
+            assert!((e * F.sinh() - F - M).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn estimate_anomaly_batch_matches_scalar() {
+        // A simple LCG is enough to get some spread in the inputs
+        let mut seed: u32 = 12345;
+        let mut random = || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (seed >> 8) as Num / (1 << 24) as Num
+        };
+
+        let n = 1003;
+        let Ms: Vec<Num> = (0..n).map(|_| random() * TWO_PI).collect();
+        let es: Vec<Num> = (0..n).map(|_| random() * 0.95).collect();
+
+        let batch = estimate_anomaly_batch(&Ms, &es, 1e-5);
+
+        assert_eq!(batch.len(), n);
+
+        for i in 0..n {
+            let exp = estimate_anomaly(Ms[i], es[i], 1e-5);
+
+            assert!(
+                (batch[i] - exp).abs() < 1e-3,
+                "M = {}, e = {}: {} not equal {exp}",
+                Ms[i],
+                es[i],
+                batch[i]
+            );
+        }
+    }
+}
@@ -0,0 +1,167 @@
+//! Unpowered gravity assists, in the patched conic approximation.
+//!
+//! The flyby is described in the frame of the planet. Its orientation
+//! follows the B-plane convention of [`super::b_plane`],
+//! with S along the incoming excess velocity.
+//!
+//! SRC: https://en.wikipedia.org/wiki/Gravity_assist
+
+use super::b_plane;
+use crate::{Num, StateVectors, Vec3};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlybyResult {
+    /// Outgoing hyperbolic excess velocity
+    pub v_out_planet_frame: Vec3,
+    /// Angle between the incoming and the outgoing excess velocities
+    pub turn_angle: Num,
+    /// State at the closest approach, relative to the planet
+    pub periapsis_state: StateVectors,
+}
+
+impl FlybyResult {
+    /// Outgoing velocity once the velocity of the planet is added back
+    pub fn heliocentric_velocity(&self, planet_velocity: Vec3) -> Vec3 {
+        self.v_out_planet_frame + planet_velocity
+    }
+
+    /// Change of the heliocentric velocity gained from the flyby.
+    ///
+    /// The velocity of the planet cancels out, so this is simply
+    /// the change of the excess velocity.
+    pub fn heliocentric_delta_v(&self, v_in_planet_frame: Vec3) -> Vec3 {
+        self.v_out_planet_frame - v_in_planet_frame
+    }
+}
+
+/// Flyby passing at `periapsis_radius` from the center of the planet.
+///
+/// `b_plane_orientation` is the angle of the B-vector measured from T
+/// towards R, the excess velocity is turned away from the B-vector,
+/// towards the planet, by δ = 2 asin(1 / (1 + r_p v∞² / μ)).
+///
+/// `v_in_planet_frame` must not be zero.
+pub fn flyby(
+    v_in_planet_frame: Vec3,
+    planet_mu: Num,
+    periapsis_radius: Num,
+    b_plane_orientation: Num,
+) -> FlybyResult {
+    let v_inf = v_in_planet_frame.length();
+    let s = v_in_planet_frame / v_inf;
+
+    let (t, r) = b_plane::basis(s);
+
+    let (sin_θ, cos_θ) = b_plane_orientation.sin_cos();
+    let b = cos_θ * t + sin_θ * r;
+
+    let turn_angle = 2.0
+        * (1.0 / (1.0 + periapsis_radius * v_inf.powi(2) / planet_mu)).asin();
+    let (sin_δ, cos_δ) = turn_angle.sin_cos();
+
+    let s_out = cos_δ * s - sin_δ * b;
+
+    // The periapsis lies on the bisector of the asymptotes,
+    // on the side of the B-vector
+    let v_periapsis =
+        (v_inf.powi(2) + 2.0 * planet_mu / periapsis_radius).sqrt();
+    let periapsis_state = StateVectors {
+        position: periapsis_radius * (s - s_out).normalize(),
+        velocity: v_periapsis * (s + s_out).normalize(),
+    };
+
+    FlybyResult {
+        v_out_planet_frame: v_inf * s_out,
+        turn_angle,
+        periapsis_state,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{vec3, PI};
+
+    const JUPITER_MU: Num = 1.266_865_3e17;
+    const PERIAPSIS_RADIUS: Num = 721_670_000.0;
+
+    fn v_in() -> Vec3 {
+        vec3(8_000.0, -6_000.0, 0.0)
+    }
+
+    #[test]
+    fn speed_is_conserved() {
+        for orientation in [0.0, 0.7, PI / 2.0, 2.5, PI] {
+            let result =
+                flyby(v_in(), JUPITER_MU, PERIAPSIS_RADIUS, orientation);
+
+            let v_out = result.v_out_planet_frame;
+            assert!((v_out.length() / v_in().length() - 1.0).abs() < 1e-5);
+
+            let angle = v_in().angle_between(v_out);
+            assert!(
+                (angle - result.turn_angle).abs() < 1e-4,
+                "{angle} != {}",
+                result.turn_angle
+            );
+        }
+    }
+
+    #[test]
+    fn turn_angle_shrinks_with_distance() {
+        let close = flyby(v_in(), JUPITER_MU, PERIAPSIS_RADIUS, 0.0);
+        let far = flyby(v_in(), JUPITER_MU, 10.0 * PERIAPSIS_RADIUS, 0.0);
+
+        assert!(far.turn_angle < close.turn_angle);
+        assert!(close.turn_angle > 0.0 && close.turn_angle < PI);
+    }
+
+    #[test]
+    fn flipped_orientation_mirrors_the_out_of_plane_component() {
+        let up = flyby(v_in(), JUPITER_MU, PERIAPSIS_RADIUS, 1.0);
+        let down = flyby(v_in(), JUPITER_MU, PERIAPSIS_RADIUS, 1.0 + PI);
+
+        let (up, down) = (up.v_out_planet_frame, down.v_out_planet_frame);
+        assert!(up.z.abs() > 1.0, "{up}");
+        assert!((up.z + down.z).abs() < 1e-2, "{up} {down}");
+
+        // Both still head roughly the same way
+        assert!(up.truncate().dot(v_in().truncate()) > 0.0);
+    }
+
+    #[test]
+    fn periapsis_state_is_on_the_hyperbola() {
+        let result = flyby(v_in(), JUPITER_MU, PERIAPSIS_RADIUS, 0.4);
+        let StateVectors { position, velocity } = result.periapsis_state;
+
+        assert!((position.length() / PERIAPSIS_RADIUS - 1.0).abs() < 1e-5);
+        assert!(position.normalize().dot(velocity.normalize()).abs() < 1e-5);
+
+        // Same energy as far away from the planet
+        let energy =
+            velocity.length_squared() / 2.0 - JUPITER_MU / position.length();
+        let expected = v_in().length_squared() / 2.0;
+        assert!((energy / expected - 1.0).abs() < 1e-3, "{energy}");
+
+        // The flyby turns in the plane of the periapsis state
+        let h = position.cross(velocity).normalize();
+        assert!(h.dot(v_in().normalize()).abs() < 1e-5);
+        assert!(h.dot(result.v_out_planet_frame.normalize()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn heliocentric_delta_v() {
+        let planet_velocity = vec3(0.0, 13_000.0, 0.0);
+        let result = flyby(v_in(), JUPITER_MU, PERIAPSIS_RADIUS, 0.0);
+
+        let before = v_in() + planet_velocity;
+        let after = result.heliocentric_velocity(planet_velocity);
+
+        let Δv = result.heliocentric_delta_v(v_in());
+        assert!((after - before - Δv).length() < 1e-2);
+
+        // |Δv| = 2 v∞ sin(δ / 2)
+        let max = 2.0 * v_in().length() * (result.turn_angle / 2.0).sin();
+        assert!((Δv.length() / max - 1.0).abs() < 1e-5, "{Δv}");
+    }
+}
@@ -0,0 +1,243 @@
+//! Clohessy-Wiltshire (Hill) equations.
+//!
+//! Linearized motion relative to a target on a circular orbit.
+//! The relative state is `[x, y, z, ẋ, ẏ, ż]` in the RTN frame of the target:
+//! x radial, y along-track and z cross-track.
+//!
+//! SRC: https://en.wikipedia.org/wiki/Clohessy%E2%80%93Wiltshire_equations
+
+use crate::math::golden_section_search;
+use crate::{vec3, Mat3, Num, Vec3, TWO_PI};
+
+/// Samples per period when searching for a free drift intercept
+const INTERCEPT_SAMPLES_PER_PERIOD: usize = 360;
+
+/// How many periods ahead to search for a free drift intercept
+const INTERCEPT_SEARCH_PERIODS: usize = 10;
+
+/// Largest miss distance, relative to the initial separation,
+/// that still counts as an intercept
+const INTERCEPT_RELATIVE_TOLERANCE: Num = 1e-3;
+
+/// Number of golden section steps refining the closest approach
+const INTERCEPT_REFINEMENT_STEPS: usize = 60;
+
+/// Propagates the relative state by `t` seconds,
+/// `n` is the mean motion of the target orbit.
+pub fn propagate(initial_rtn: [Num; 6], n: Num, t: Num) -> [Num; 6] {
+    let [x0, y0, z0, vx0, vy0, vz0] = initial_rtn;
+    let (r0, v0) = (vec3(x0, y0, z0), vec3(vx0, vy0, vz0));
+
+    let [rr, rv, vr, vv] = state_transition(n, t);
+
+    let [x, y, z] = (rr * r0 + rv * v0).to_array();
+    let [vx, vy, vz] = (vr * r0 + vv * v0).to_array();
+
+    [x, y, z, vx, vy, vz]
+}
+
+/// Initial velocity for which the relative motion starting at
+/// `initial_position_rtn` doesn't drift along-track, i.e. ẏ = -2nx.
+///
+/// The radial and cross-track velocities are left at zero.
+pub fn drift_corrected_v0(initial_position_rtn: Vec3, n: Num) -> Vec3 {
+    vec3(0.0, -2.0 * n * initial_position_rtn.x, 0.0)
+}
+
+/// Blocks of the state transition matrix over `t`:
+/// r(t) = Φrr r0 + Φrv v0 and v(t) = Φvr r0 + Φvv v0
+fn state_transition(n: Num, t: Num) -> [Mat3; 4] {
+    let nt = n * t;
+    let (s, c) = nt.sin_cos();
+
+    let rr = Mat3::from_cols(
+        vec3(4.0 - 3.0 * c, 6.0 * (s - nt), 0.0),
+        vec3(0.0, 1.0, 0.0),
+        vec3(0.0, 0.0, c),
+    );
+    let rv = Mat3::from_cols(
+        vec3(s / n, -2.0 * (1.0 - c) / n, 0.0),
+        vec3(2.0 * (1.0 - c) / n, (4.0 * s - 3.0 * nt) / n, 0.0),
+        vec3(0.0, 0.0, s / n),
+    );
+    let vr = Mat3::from_cols(
+        vec3(3.0 * n * s, -6.0 * n * (1.0 - c), 0.0),
+        Vec3::ZERO,
+        vec3(0.0, 0.0, -n * s),
+    );
+    let vv = Mat3::from_cols(
+        vec3(c, -2.0 * s, 0.0),
+        vec3(2.0 * s, 4.0 * c - 3.0, 0.0),
+        vec3(0.0, 0.0, c),
+    );
+
+    [rr, rv, vr, vv]
+}
+
+/// Two-impulse transfer of the chaser to the `target_rtn` position
+/// in `transfer_time` seconds, stopping there.
+///
+/// Returns the impulses at the departure and at the arrival, in RTN.
+/// The transfer is singular when `transfer_time` is a multiple
+/// of the orbital period.
+///
+/// SRC: Vallado, Fundamentals of Astrodynamics and Applications,
+/// section 6.8
+pub fn cw_targeting_burn(
+    chaser_rtn: [Num; 6],
+    target_rtn: Vec3,
+    transfer_time: Num,
+    n: Num,
+) -> (Vec3, Vec3) {
+    let [x, y, z, vx, vy, vz] = chaser_rtn;
+    let (r0, v0) = (vec3(x, y, z), vec3(vx, vy, vz));
+
+    let [rr, rv, vr, vv] = state_transition(n, transfer_time);
+
+    let v0_transfer = rv.inverse() * (target_rtn - rr * r0);
+    let v_arrival = vr * r0 + vv * v0_transfer;
+
+    (v0_transfer - v0, -v_arrival)
+}
+
+/// Time at which the free drifting chaser passes through `target_rtn`,
+/// searching the next few orbits.
+///
+/// The relative distance is sampled and its minima are refined,
+/// the first one closer than a small fraction of the initial separation
+/// counts as the intercept. Returns `None` if the chaser misses.
+pub fn cw_free_drift_intercept_time(
+    chaser_rtn: [Num; 6],
+    target_rtn: Vec3,
+    n: Num,
+) -> Option<Num> {
+    let distance = |t: Num| {
+        let [x, y, z, ..] = propagate(chaser_rtn, n, t);
+        vec3(x, y, z).distance(target_rtn)
+    };
+
+    let tolerance = INTERCEPT_RELATIVE_TOLERANCE * distance(0.0);
+    let step = TWO_PI / n / INTERCEPT_SAMPLES_PER_PERIOD as Num;
+    let samples = INTERCEPT_SAMPLES_PER_PERIOD * INTERCEPT_SEARCH_PERIODS;
+
+    for k in 1..samples {
+        let t = k as Num * step;
+
+        let is_minimum = distance(t) <= distance(t - step)
+            && distance(t) <= distance(t + step);
+        if !is_minimum {
+            continue;
+        }
+
+        let t = golden_section_search(
+            distance,
+            t - step,
+            t + step,
+            INTERCEPT_REFINEMENT_STEPS,
+        );
+        if distance(t) <= tolerance {
+            return Some(t);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TWO_PI;
+
+    /// Mean motion of a ~400 km LEO
+    const N: Num = 1.13e-3;
+
+    fn initial_state() -> [Num; 6] {
+        let position = vec3(100.0, 50.0, 20.0);
+        let v0 = drift_corrected_v0(position, N);
+
+        [position.x, position.y, position.z, v0.x, v0.y, v0.z]
+    }
+
+    #[test]
+    fn drift_corrected_orbit_is_closed() {
+        let initial = initial_state();
+        let period = TWO_PI / N;
+
+        for revolutions in [1.0, 2.0, 10.0] {
+            let state = propagate(initial, N, revolutions * period);
+
+            for (a, b) in state.iter().zip(initial) {
+                assert!((a - b).abs() < 1e-2, "{state:?} != {initial:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn relative_orbit_is_a_two_by_one_ellipse() {
+        let initial = initial_state();
+        let period = TWO_PI / N;
+
+        let (mut x_min, mut x_max) = (Num::MAX, Num::MIN);
+        let (mut y_min, mut y_max) = (Num::MAX, Num::MIN);
+
+        for step in 0..360 {
+            let t = period * step as Num / 360.0;
+            let [x, y, ..] = propagate(initial, N, t);
+
+            x_min = x_min.min(x);
+            x_max = x_max.max(x);
+            y_min = y_min.min(y);
+            y_max = y_max.max(y);
+        }
+
+        let ratio = (y_max - y_min) / (x_max - x_min);
+        assert!((ratio - 2.0).abs() < 1e-2, "{ratio}");
+    }
+
+    #[test]
+    fn uncorrected_orbit_drifts() {
+        let [x, y, z, ..] = initial_state();
+        let initial = [x, y, z, 0.0, 0.0, 0.0];
+
+        let state = propagate(initial, N, TWO_PI / N);
+
+        // Drifts by 12πx every revolution
+        let drift = state[1] - y;
+        assert!((drift + 12.0 * crate::PI * x).abs() < 1e-1, "{drift}");
+    }
+
+    #[test]
+    fn targeting_stops_at_the_target() {
+        let chaser = [200.0, -1_000.0, 50.0, 0.1, 0.0, -0.05];
+        let target = vec3(0.0, -50.0, 0.0);
+        let transfer_time = 0.4 * TWO_PI / N;
+
+        let (departure, arrival) =
+            cw_targeting_burn(chaser, target, transfer_time, N);
+
+        let [x, y, z, vx, vy, vz] = chaser;
+        let v0 = vec3(vx, vy, vz) + departure;
+        let state = propagate([x, y, z, v0.x, v0.y, v0.z], N, transfer_time);
+
+        let [x, y, z, vx, vy, vz] = state;
+        assert!(vec3(x, y, z).distance(target) < 1e-2, "{state:?}");
+        assert!((vec3(vx, vy, vz) + arrival).length() < 1e-5, "{state:?}");
+    }
+
+    #[test]
+    fn free_drift_intercept() {
+        let chaser = [100.0, 50.0, 20.0, 0.05, -0.1, 0.01];
+        let t = 0.7 * TWO_PI / N;
+
+        let [x, y, z, ..] = propagate(chaser, N, t);
+        let target = vec3(x, y, z);
+
+        let intercept =
+            cw_free_drift_intercept_time(chaser, target, N).unwrap();
+        assert!((intercept - t).abs() < 1.0, "{intercept} != {t}");
+
+        // Off the drifting path
+        let miss = target + vec3(0.0, 0.0, 100.0);
+        assert_eq!(cw_free_drift_intercept_time(chaser, miss, N), None);
+    }
+}
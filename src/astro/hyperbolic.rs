@@ -1,5 +1,5 @@
 use super::standard_gravitational_parameter;
-use crate::math::newton_approx;
+use crate::math::{halley_approx, newton_approx_fused};
 use crate::Num;
 
 /// Hyperbolic Anomaly (F) is given by the equation:
@@ -10,25 +10,76 @@ use crate::Num;
 ///
 /// https://orbital-mechanics.space/time-since-periapsis-and-keplers-equation/hyperbolic-trajectories.html#equation-eq-hyperbolic-keplers-equation
 pub fn estimate_anomaly(M: Num, e: Num, tolerance: Num) -> Num {
-    newton_approx(
+    newton_approx_fused(
         // f(F) = e * sinh(F) - F - M
-        |F| (e * F.sinh()) - F - M,
         // f'(F) = e * cosh(F) - 1
+        |F| ((e * F.sinh()) - F - M, e * F.cosh() - 1.0),
+        initial_guess(M, e),
+        tolerance,
+    )
+}
+
+/// Same as [`estimate_anomaly`] but using Halley's method,
+/// which takes fewer iterations for highly eccentric orbits.
+pub fn estimate_anomaly_halley(M: Num, e: Num, tolerance: Num) -> Num {
+    halley_approx(
+        |F| (e * F.sinh()) - F - M,
         |F| e * F.cosh() - 1.0,
-        M,
+        // f''(F) = e * sinh(F)
+        |F| e * F.sinh(),
+        initial_guess(M, e),
         tolerance,
     )
 }
 
+/// asinh(M / e), close to F far from the periapsis where e * sinh(F)
+/// dominates. Starting from M instead overflows sinh for |M| in
+/// the hundreds.
+fn initial_guess(M: Num, e: Num) -> Num {
+    (M / e).asinh()
+}
+
 /// Hyperbolic mean motion
 /// SRC: https://orbital-mechanics.space/time-since-periapsis-and-keplers-equation/hyperbolic-trajectories.html#equation-eq-hyperbolic-mean-anomaly
 pub fn mean_motion(h: Num, e: Num, mass: Num) -> Num {
     let μ = standard_gravitational_parameter(mass);
 
-    (μ.powi(2) / h.powi(3)) * (e.powi(2) - 1.0).powi(3).sqrt()
+    // μ² alone overflows f32 for stellar masses
+    ((μ / h).powi(2) / h) * (e.powi(2) - 1.0).powi(3).sqrt()
 }
 
 pub fn true_anomaly(F: Num, e: Num) -> Num {
     // https://orbital-mechanics.space/time-since-periapsis-and-keplers-equation/hyperbolic-trajectories.html#equation-eq-eccentric-anomaly-true-anomaly-hyperbola
     2.0 * ((F / 2.0).tanh() / ((e - 1.0) / (e + 1.0)).sqrt()).atan()
 }
+
+/// Inverse of [`true_anomaly`], only defined between the asymptotes
+pub fn hyperbolic_anomaly(v: Num, e: Num) -> Num {
+    2.0 * (((e - 1.0) / (e + 1.0)).sqrt() * (v / 2.0).tan()).atanh()
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case(1.01 ; "barely hyperbolic")]
+    #[test_case(1.5 ; "moderate")]
+    #[test_case(2.0 ; "fast escape")]
+    #[test_case(1_000.0 ; "almost straight")]
+    fn estimate_anomaly_far_from_periapsis(e: Num) {
+        for M in [-2_000.0, -100.0, -50.0, 0.001, 60.0, 80.0, 100.0, 2_000.0] {
+            let tolerance = 1e-5 * Num::abs(M).max(1.0);
+
+            for F in [
+                estimate_anomaly(M, e, 1e-5),
+                estimate_anomaly_halley(M, e, 1e-5),
+            ] {
+                let residual = e * F.sinh() - F - M;
+
+                assert!(residual.abs() <= tolerance, "{M} {e} {F}");
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! Departure and arrival energies of interplanetary trajectories.
+//!
+//! C3 is the characteristic energy, i.e. the square of the hyperbolic
+//! excess velocity, in m²/s² (launch vehicle charts use km²/s²).
+//!
+//! SRC: https://en.wikipedia.org/wiki/Characteristic_energy
+
+use super::standard_gravitational_parameter;
+use crate::{Num, StateVectors};
+
+/// Delta-v of the departure burn from a circular parking orbit
+/// that reaches the given C3.
+///
+/// From the vis-viva equation: v² = C3 + 2μ/r at the burn.
+pub fn c3_to_departure_dv(
+    c3: Num,
+    parking_orbit_radius: Num,
+    mass: Num,
+) -> Num {
+    let μ = standard_gravitational_parameter(mass);
+    let r = parking_orbit_radius;
+
+    (c3 + 2.0 * μ / r).sqrt() - (μ / r).sqrt()
+}
+
+/// Inverse of [`c3_to_departure_dv`]
+pub fn departure_dv_to_c3(
+    dv: Num,
+    parking_orbit_radius: Num,
+    mass: Num,
+) -> Num {
+    let μ = standard_gravitational_parameter(mass);
+    let r = parking_orbit_radius;
+
+    (dv + (μ / r).sqrt()).powi(2) - 2.0 * μ / r
+}
+
+/// Hyperbolic excess velocity of the arriving state,
+/// relative to the body of the given mass.
+///
+/// Returns 0 if the state is already bound to the body.
+pub fn v_inf_from_arrival_sv(arrival_sv: &StateVectors, mass: Num) -> Num {
+    let μ = standard_gravitational_parameter(mass);
+
+    let v = arrival_sv.velocity.length();
+    let r = arrival_sv.position.length();
+
+    // Twice the specific orbital energy
+    (v.powi(2) - 2.0 * μ / r).max(0.0).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::EARTH_MASS;
+    use crate::{vec3, KeplerianElements};
+
+    const PARKING_ORBIT: Num = 6_578_000.0;
+
+    #[test]
+    fn zero_c3_is_escape_velocity() {
+        let μ = standard_gravitational_parameter(EARTH_MASS);
+
+        let dv = c3_to_departure_dv(0.0, PARKING_ORBIT, EARTH_MASS);
+
+        let escape = (2.0 * μ / PARKING_ORBIT).sqrt();
+        let circular = (μ / PARKING_ORBIT).sqrt();
+        assert!((dv - (escape - circular)).abs() < 1e-2, "{dv}");
+
+        // About 3.2 km/s from a 200 km orbit
+        assert!((dv - 3_220.0).abs() < 10.0, "{dv}");
+    }
+
+    #[test]
+    fn c3_round_trip() {
+        // Typical Mars departure, 10 km²/s²
+        let c3 = 10e6;
+
+        let dv = c3_to_departure_dv(c3, PARKING_ORBIT, EARTH_MASS);
+        let back = departure_dv_to_c3(dv, PARKING_ORBIT, EARTH_MASS);
+
+        assert!((back / c3 - 1.0).abs() < 1e-3, "{back}");
+        assert!((dv - 3_670.0).abs() < 20.0, "{dv}");
+    }
+
+    #[test]
+    fn v_inf_of_a_hyperbola() {
+        let μ = standard_gravitational_parameter(EARTH_MASS);
+        let a = 20_000_000.0;
+
+        let hyperbola = KeplerianElements {
+            eccentricity: 1.5,
+            semi_major_axis: a,
+            mean_anomaly_at_epoch: 0.3,
+            ..KeplerianElements::default()
+        };
+        let sv = hyperbola.state_vectors_at_epoch(EARTH_MASS, 0.0, 1e-5);
+
+        let v_inf = v_inf_from_arrival_sv(&sv, EARTH_MASS);
+        let exp = (μ / a).sqrt();
+        assert!((v_inf / exp - 1.0).abs() < 1e-3, "{v_inf} != {exp}");
+
+        let bound = StateVectors::new(
+            vec3(PARKING_ORBIT, 0.0, 0.0),
+            vec3(0.0, 7_800.0, 0.0),
+        );
+        assert_eq!(v_inf_from_arrival_sv(&bound, EARTH_MASS), 0.0);
+    }
+}
@@ -0,0 +1,392 @@
+//! Orbit determination from angles-only observations.
+//!
+//! Observations are unit vectors pointing from the central body
+//! towards the orbiting body at a given time.
+
+use std::fmt;
+
+use crate::{KeplerianElements, Num, Vec3};
+
+/// Number of fitted elements: a, e, i, Ω, ω, M₀
+const NUM_PARAMS: usize = 6;
+
+/// Every observation constrains two degrees of freedom
+const MIN_OBSERVATIONS: usize = NUM_PARAMS / 2;
+
+const MAX_ITERATIONS: usize = 50;
+
+/// Relative step used for the finite difference partials
+const STEP: Num = 1e-3;
+
+/// Pivots smaller than this, relative to the largest diagonal element,
+/// are treated as zero
+const SINGULAR_THRESHOLD: f64 = 1e-12;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IodError {
+    /// Too few observations to determine all the elements
+    NotEnoughObservations { required: usize, provided: usize },
+    /// The normal equations can't be solved, e.g. because all
+    /// the observations are taken at the same time
+    SingularMatrix,
+    /// The corrections didn't drop below the tolerance
+    DidNotConverge { iterations: usize },
+    /// An iterate left the elliptic orbits, or came closer to a parabolic
+    /// one than the finite differences allow
+    NotElliptic,
+}
+
+impl fmt::Display for IodError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotEnoughObservations { required, provided } => write!(
+                f,
+                "At least {required} observations are required, got {provided}"
+            ),
+            Self::SingularMatrix => write!(f, "Normal equations are singular"),
+            Self::DidNotConverge { iterations } => {
+                write!(f, "Failed to converge after {iterations} iterations")
+            }
+            Self::NotElliptic => write!(f, "The orbit estimate isn't elliptic"),
+        }
+    }
+}
+
+impl std::error::Error for IodError {}
+
+/// Improves an orbit estimate by minimizing the squared residuals
+/// of the observed directions (Gauss-Newton).
+///
+/// `observations` are `(time, direction_unit_vector)` pairs.
+/// The epoch of `initial` is kept, the remaining six elements are fitted.
+/// Iterates until the largest correction is below `tolerance`,
+/// with the semi-major axis correction taken relative to the axis itself.
+///
+/// Only elliptic orbits are fitted, the fit fails with
+/// [`IodError::NotElliptic`] once an iterate reaches e ≥ 1.
+///
+/// SRC: https://en.wikipedia.org/wiki/Gauss%E2%80%93Newton_algorithm
+pub fn differential_correction(
+    initial: &KeplerianElements,
+    observations: &[(Num, Vec3)],
+    mass: Num,
+    tolerance: Num,
+) -> Result<KeplerianElements, IodError> {
+    if observations.len() < MIN_OBSERVATIONS {
+        return Err(IodError::NotEnoughObservations {
+            required: MIN_OBSERVATIONS,
+            provided: observations.len(),
+        });
+    }
+
+    let mut params = to_params(initial);
+
+    for _ in 0..MAX_ITERATIONS {
+        // The finite differences around the iterate have to stay elliptic
+        if params[0] <= 0.0 || params[1] + STEP >= 1.0 {
+            return Err(IodError::NotElliptic);
+        }
+
+        let scale = [params[0], 1.0, 1.0, 1.0, 1.0, 1.0];
+
+        let mut normal = [[0.0; NUM_PARAMS]; NUM_PARAMS];
+        let mut rhs = [0.0; NUM_PARAMS];
+
+        for &(time, observed) in observations {
+            let computed = direction(initial, &params, mass, time, tolerance);
+            let residual = observed.normalize() - computed;
+
+            // Partials of the direction w.r.t. the scaled parameters
+            let partials: [Vec3; NUM_PARAMS] = std::array::from_fn(|j| {
+                let h = STEP * scale[j];
+
+                let mut forward = params;
+                forward[j] += h;
+                let mut backward = params;
+                backward[j] -= h;
+
+                let forward =
+                    direction(initial, &forward, mass, time, tolerance);
+                let backward =
+                    direction(initial, &backward, mass, time, tolerance);
+
+                (forward - backward) / (2.0 * STEP)
+            });
+
+            for j in 0..NUM_PARAMS {
+                rhs[j] += dot(partials[j], residual);
+
+                for k in 0..NUM_PARAMS {
+                    normal[j][k] += dot(partials[j], partials[k]);
+                }
+            }
+        }
+
+        let correction = solve(normal, rhs).ok_or(IodError::SingularMatrix)?;
+
+        let mut max_correction: Num = 0.0;
+        for j in 0..NUM_PARAMS {
+            let correction = correction[j] as Num;
+
+            params[j] += correction * scale[j];
+            max_correction = max_correction.max(correction.abs());
+        }
+
+        params[1] = params[1].max(0.0);
+
+        if max_correction < tolerance {
+            return Ok(from_params(initial, &params).wrapped());
+        }
+    }
+
+    Err(IodError::DidNotConverge {
+        iterations: MAX_ITERATIONS,
+    })
+}
+
+fn to_params(elements: &KeplerianElements) -> [Num; NUM_PARAMS] {
+    let [a, e, i, Ω, ω, M, _] = elements.as_array();
+
+    [a, e, i, Ω, ω, M]
+}
+
+fn from_params(
+    initial: &KeplerianElements,
+    params: &[Num; NUM_PARAMS],
+) -> KeplerianElements {
+    let [a, e, i, Ω, ω, M] = *params;
+
+    KeplerianElements::new(a, e, i, Ω, ω, M, initial.epoch)
+}
+
+fn direction(
+    initial: &KeplerianElements,
+    params: &[Num; NUM_PARAMS],
+    mass: Num,
+    time: Num,
+    tolerance: Num,
+) -> Vec3 {
+    from_params(initial, params)
+        .state_vectors_at_epoch(mass, time, tolerance)
+        .position
+        .normalize()
+}
+
+/// Dot product accumulated in f64 so that the normal equations
+/// of rank deficient problems stay (numerically) singular
+fn dot(a: Vec3, b: Vec3) -> f64 {
+    a.to_array()
+        .into_iter()
+        .zip(b.to_array())
+        .map(|(a, b)| widen(a) * widen(b))
+        .sum()
+}
+
+/// Lossless conversion to f64, a no-op with the `f64` feature
+#[cfg(not(feature = "f64"))]
+fn widen(x: Num) -> f64 {
+    f64::from(x)
+}
+
+#[cfg(feature = "f64")]
+fn widen(x: Num) -> f64 {
+    x
+}
+
+/// Solves the linear system using Gaussian elimination with partial pivoting
+fn solve(
+    mut m: [[f64; NUM_PARAMS]; NUM_PARAMS],
+    mut b: [f64; NUM_PARAMS],
+) -> Option<[f64; NUM_PARAMS]> {
+    let max_diagonal =
+        (0..NUM_PARAMS).map(|j| m[j][j].abs()).fold(0.0, f64::max);
+
+    for col in 0..NUM_PARAMS {
+        let pivot = (col..NUM_PARAMS)
+            .max_by(|&x, &y| m[x][col].abs().total_cmp(&m[y][col].abs()))?;
+
+        if m[pivot][col].abs() <= SINGULAR_THRESHOLD * max_diagonal {
+            return None;
+        }
+
+        m.swap(col, pivot);
+        b.swap(col, pivot);
+
+        for row in col + 1..NUM_PARAMS {
+            let factor = m[row][col] / m[col][col];
+
+            let pivot_row = m[col];
+            for (x, pivot_x) in m[row][col..].iter_mut().zip(&pivot_row[col..])
+            {
+                *x -= factor * pivot_x;
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+
+    let mut x = [0.0; NUM_PARAMS];
+    for row in (0..NUM_PARAMS).rev() {
+        let sum: f64 = (row + 1..NUM_PARAMS).map(|k| m[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / m[row][row];
+    }
+
+    Some(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::EARTH_MASS;
+    use crate::math::angle_diff;
+    use crate::vec3;
+
+    const TOLERANCE: Num = 1e-5;
+
+    const TRUE_ORBIT: KeplerianElements =
+        KeplerianElements::new(8_000_000.0, 0.1, 0.7, 1.2, 0.4, 2.0, 0.0);
+
+    /// Deterministic noise in the [-1, 1) range
+    fn noise(seed: &mut u32) -> Num {
+        *seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (*seed >> 8) as Num / (1 << 23) as Num - 1.0
+    }
+
+    fn observations(noise_amplitude: Num) -> Vec<(Num, Vec3)> {
+        let period = TRUE_ORBIT.period(EARTH_MASS);
+        let mut seed = 42;
+
+        (0..30)
+            .map(|n| {
+                let time = period * n as Num / 20.0;
+                let direction = TRUE_ORBIT
+                    .state_vectors_at_epoch(EARTH_MASS, time, TOLERANCE)
+                    .position
+                    .normalize();
+
+                let noise =
+                    vec3(noise(&mut seed), noise(&mut seed), noise(&mut seed))
+                        * noise_amplitude;
+
+                (time, (direction + noise).normalize())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn converges_to_the_true_orbit() {
+        let initial = KeplerianElements::new(
+            8_300_000.0,
+            0.13,
+            0.75,
+            1.15,
+            0.5,
+            1.9,
+            0.0,
+        );
+
+        let fitted = differential_correction(
+            &initial,
+            &observations(1e-4),
+            EARTH_MASS,
+            1e-4,
+        )
+        .unwrap();
+
+        let relative_a = (fitted.semi_major_axis - TRUE_ORBIT.semi_major_axis)
+            / TRUE_ORBIT.semi_major_axis;
+        assert!(relative_a.abs() < 1e-3, "{fitted:?}");
+        assert!((fitted.eccentricity - 0.1).abs() < 1e-3, "{fitted:?}");
+
+        let angles = [
+            (fitted.inclination, TRUE_ORBIT.inclination),
+            (
+                fitted.right_ascension_of_the_ascending_node,
+                TRUE_ORBIT.right_ascension_of_the_ascending_node,
+            ),
+            (
+                fitted.argument_of_periapsis,
+                TRUE_ORBIT.argument_of_periapsis,
+            ),
+            (
+                fitted.mean_anomaly_at_epoch,
+                TRUE_ORBIT.mean_anomaly_at_epoch,
+            ),
+        ];
+
+        for (fitted_angle, true_angle) in angles {
+            assert!(
+                angle_diff(fitted_angle, true_angle).abs() < 1e-2,
+                "{fitted:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn not_enough_observations() {
+        let observations = &observations(0.0)[..2];
+
+        assert_eq!(
+            differential_correction(
+                &TRUE_ORBIT,
+                observations,
+                EARTH_MASS,
+                TOLERANCE
+            ),
+            Err(IodError::NotEnoughObservations {
+                required: 3,
+                provided: 2
+            })
+        );
+    }
+
+    #[test]
+    fn simultaneous_observations_are_singular() {
+        let observation = observations(0.0)[0];
+
+        assert_eq!(
+            differential_correction(
+                &TRUE_ORBIT,
+                &[observation; 5],
+                EARTH_MASS,
+                TOLERANCE
+            ),
+            Err(IodError::SingularMatrix)
+        );
+    }
+
+    #[test]
+    fn hyperbolic_estimates_are_rejected() {
+        for eccentricity in [1.0, 1.5, 0.9995] {
+            let initial = KeplerianElements {
+                eccentricity,
+                ..TRUE_ORBIT
+            };
+
+            assert_eq!(
+                differential_correction(
+                    &initial,
+                    &observations(0.0),
+                    EARTH_MASS,
+                    TOLERANCE
+                ),
+                Err(IodError::NotElliptic)
+            );
+        }
+
+        // An elliptic estimate far enough off to be corrected past e = 1
+        let initial = KeplerianElements {
+            semi_major_axis: 20_000_000.0,
+            eccentricity: 0.9,
+            ..TRUE_ORBIT
+        };
+        assert_eq!(
+            differential_correction(
+                &initial,
+                &observations(0.0),
+                EARTH_MASS,
+                TOLERANCE
+            ),
+            Err(IodError::NotElliptic)
+        );
+    }
+}
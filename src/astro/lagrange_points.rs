@@ -0,0 +1,276 @@
+//! Lagrange points of the circular restricted three-body problem.
+//!
+//! The rotating frame has the x axis pointing from the primary towards
+//! the secondary and the z axis along the orbital angular momentum.
+//! [`l1_position`], [`l2_position`] and [`l3_position`] return scalar
+//! distances from the secondary along that axis,
+//! [`l4_position`] and [`l5_position`] positions relative to the barycenter
+//! and [`points`] positions relative to the primary.
+//!
+//! SRC: https://en.wikipedia.org/wiki/Lagrange_point
+
+use crate::math::newton_approx;
+use crate::{vec3, KeplerianElements, Mat3, Num, Vec3};
+
+/// Tolerance of the collinear points, relative to the separation
+const TOLERANCE: Num = 1e-6;
+
+/// Distance of L1 from the secondary, towards the primary
+pub fn l1_position(
+    mass_primary: Num,
+    mass_secondary: Num,
+    separation: Num,
+) -> Num {
+    let μ = mass_ratio(mass_primary, mass_secondary);
+    let p = collinear_point(μ, 1.0 - μ - hill_radius(μ));
+
+    (1.0 - μ - p) * separation
+}
+
+/// Distance of L2 from the secondary, away from the primary
+pub fn l2_position(
+    mass_primary: Num,
+    mass_secondary: Num,
+    separation: Num,
+) -> Num {
+    let μ = mass_ratio(mass_primary, mass_secondary);
+    let p = collinear_point(μ, 1.0 - μ + hill_radius(μ));
+
+    (p - (1.0 - μ)) * separation
+}
+
+/// Distance of L3 from the secondary. L3 lies on the far side
+/// of the primary, so this is slightly less than twice the separation.
+pub fn l3_position(
+    mass_primary: Num,
+    mass_secondary: Num,
+    separation: Num,
+) -> Num {
+    let μ = mass_ratio(mass_primary, mass_secondary);
+    let p = collinear_point(μ, -1.0 - 5.0 * μ / 12.0);
+
+    (1.0 - μ - p) * separation
+}
+
+/// L4, leading the secondary by 60°
+pub fn l4_position(
+    mass_primary: Num,
+    mass_secondary: Num,
+    separation: Num,
+) -> Vec3 {
+    let μ = mass_ratio(mass_primary, mass_secondary);
+
+    separation * vec3(0.5 - μ, Num::sqrt(3.0) / 2.0, 0.0)
+}
+
+/// L5, trailing the secondary by 60°
+pub fn l5_position(
+    mass_primary: Num,
+    mass_secondary: Num,
+    separation: Num,
+) -> Vec3 {
+    let μ = mass_ratio(mass_primary, mass_secondary);
+
+    separation * vec3(0.5 - μ, -Num::sqrt(3.0) / 2.0, 0.0)
+}
+
+/// All five Lagrange points of a pair of bodies
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LagrangePoints {
+    pub l1: Vec3,
+    pub l2: Vec3,
+    pub l3: Vec3,
+    pub l4: Vec3,
+    pub l5: Vec3,
+}
+
+/// Lagrange points in the rotating frame centered at the primary,
+/// with the secondary at `separation` on the x axis
+pub fn points(
+    mass_primary: Num,
+    mass_secondary: Num,
+    separation: Num,
+) -> LagrangePoints {
+    let on_x = |x: Num| vec3(x, 0.0, 0.0);
+
+    // Shift from the barycenter to the primary
+    let μ = mass_ratio(mass_primary, mass_secondary);
+    let barycenter = on_x(μ * separation);
+
+    LagrangePoints {
+        l1: on_x(
+            separation - l1_position(mass_primary, mass_secondary, separation),
+        ),
+        l2: on_x(
+            separation + l2_position(mass_primary, mass_secondary, separation),
+        ),
+        l3: on_x(
+            separation - l3_position(mass_primary, mass_secondary, separation),
+        ),
+        l4: l4_position(mass_primary, mass_secondary, separation) + barycenter,
+        l5: l5_position(mass_primary, mass_secondary, separation) + barycenter,
+    }
+}
+
+/// Lagrange points at `epoch` in the inertial frame of the primary,
+/// given the orbit of the secondary around it.
+///
+/// Assumes a circular orbit: the rotating frame is taken from the current
+/// position and angular momentum of the secondary, and the separation
+/// is its current distance. For eccentric orbits this gives the
+/// instantaneous, pulsating points.
+pub fn points_at_epoch(
+    mass_primary: Num,
+    mass_secondary: Num,
+    secondary: &KeplerianElements,
+    epoch: Num,
+    tolerance: Num,
+) -> LagrangePoints {
+    let sv = secondary.state_vectors_at_epoch(mass_primary, epoch, tolerance);
+
+    let separation = sv.position.length();
+    let x = sv.position / separation;
+    let z = sv.position.cross(sv.velocity).normalize();
+    let rotation = Mat3::from_cols(x, z.cross(x), z);
+
+    let rotating = points(mass_primary, mass_secondary, separation);
+
+    LagrangePoints {
+        l1: rotation * rotating.l1,
+        l2: rotation * rotating.l2,
+        l3: rotation * rotating.l3,
+        l4: rotation * rotating.l4,
+        l5: rotation * rotating.l5,
+    }
+}
+
+/// μ = m2 / (m1 + m2), the mass parameter of the restricted problem
+pub fn mass_ratio(mass_primary: Num, mass_secondary: Num) -> Num {
+    mass_secondary / (mass_primary + mass_secondary)
+}
+
+fn hill_radius(μ: Num) -> Num {
+    (μ / 3.0).cbrt()
+}
+
+/// Solves the balance of gravity and the centrifugal force along the x axis
+/// in units of the separation, with the primary at -μ and the secondary
+/// at 1 - μ.
+fn collinear_point(μ: Num, guess: Num) -> Num {
+    let to_primary = move |p: Num| p + μ;
+    let to_secondary = move |p: Num| p - 1.0 + μ;
+
+    let f = |p: Num| {
+        p - (1.0 - μ) * to_primary(p) / to_primary(p).abs().powi(3)
+            - μ * to_secondary(p) / to_secondary(p).abs().powi(3)
+    };
+    let f_prime = |p: Num| {
+        1.0 + 2.0 * (1.0 - μ) / to_primary(p).abs().powi(3)
+            + 2.0 * μ / to_secondary(p).abs().powi(3)
+    };
+
+    newton_approx(f, f_prime, guess, TOLERANCE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{ASTRONOMICAL_UNIT, EARTH_MASS, SUN_MASS};
+
+    #[test]
+    fn sun_earth_collinear_points() {
+        let l1 = l1_position(SUN_MASS, EARTH_MASS, ASTRONOMICAL_UNIT);
+        let l2 = l2_position(SUN_MASS, EARTH_MASS, ASTRONOMICAL_UNIT);
+        let l3 = l3_position(SUN_MASS, EARTH_MASS, ASTRONOMICAL_UNIT);
+
+        assert!((l1 - 1.49e9).abs() < 0.01e9, "{l1}");
+        assert!((l2 - 1.51e9).abs() < 0.01e9, "{l2}");
+        assert!(l1 < l2);
+
+        assert!((l3 / ASTRONOMICAL_UNIT - 2.0).abs() < 1e-5, "{l3}");
+    }
+
+    #[test]
+    fn equilateral_points() {
+        let μ = mass_ratio(SUN_MASS, EARTH_MASS);
+        let sun = vec3(-μ * ASTRONOMICAL_UNIT, 0.0, 0.0);
+        let earth = vec3((1.0 - μ) * ASTRONOMICAL_UNIT, 0.0, 0.0);
+
+        for l in [
+            l4_position(SUN_MASS, EARTH_MASS, ASTRONOMICAL_UNIT),
+            l5_position(SUN_MASS, EARTH_MASS, ASTRONOMICAL_UNIT),
+        ] {
+            assert!((l.distance(sun) / ASTRONOMICAL_UNIT - 1.0).abs() < 1e-5);
+            assert!((l.distance(earth) / ASTRONOMICAL_UNIT - 1.0).abs() < 1e-5);
+        }
+
+        assert!(l4_position(SUN_MASS, EARTH_MASS, ASTRONOMICAL_UNIT).y > 0.0);
+        assert!(l5_position(SUN_MASS, EARTH_MASS, ASTRONOMICAL_UNIT).y < 0.0);
+    }
+
+    #[test]
+    fn earth_moon_points() {
+        let l1 = l1_position(EARTH_MASS, 7.342e22, 384_400_000.0);
+        let l2 = l2_position(EARTH_MASS, 7.342e22, 384_400_000.0);
+
+        assert!((l1 - 58_000_000.0).abs() < 500_000.0, "{l1}");
+        assert!((l2 - 64_500_000.0).abs() < 500_000.0, "{l2}");
+    }
+
+    #[test]
+    fn points_relative_to_the_primary() {
+        let moon_mass = 7.342e22;
+        let separation = 384_400_000.0;
+
+        let points = points(EARTH_MASS, moon_mass, separation);
+
+        assert!(
+            (points.l1.x / separation - 0.849).abs() < 2e-3,
+            "{points:?}"
+        );
+        assert!(
+            (points.l2.x / separation - 1.168).abs() < 2e-3,
+            "{points:?}"
+        );
+        assert!(
+            (points.l3.x / separation + 0.993).abs() < 2e-3,
+            "{points:?}"
+        );
+
+        for l in [points.l4, points.l5] {
+            assert!((l.length() / separation - 1.0).abs() < 1e-5);
+            assert!((l.x / separation - 0.5).abs() < 1e-5);
+        }
+
+        let sun_earth = super::points(SUN_MASS, EARTH_MASS, ASTRONOMICAL_UNIT);
+        let l1_from_earth = ASTRONOMICAL_UNIT - sun_earth.l1.x;
+        assert!((l1_from_earth - 1.5e9).abs() < 0.02e9, "{l1_from_earth}");
+    }
+
+    #[test]
+    fn points_follow_the_secondary() {
+        let moon_mass = 7.342e22;
+        let moon = KeplerianElements {
+            semi_major_axis: 384_400_000.0,
+            inclination: 0.09,
+            right_ascension_of_the_ascending_node: 0.3,
+            ..KeplerianElements::default()
+        };
+        let epoch = 500_000.0;
+
+        let inertial =
+            points_at_epoch(EARTH_MASS, moon_mass, &moon, epoch, 1e-5);
+        let rotating = points(EARTH_MASS, moon_mass, moon.semi_major_axis);
+
+        let sv = moon.state_vectors_at_epoch(EARTH_MASS, epoch, 1e-5);
+        let direction = sv.position.normalize();
+
+        assert!((inertial.l1 - direction * rotating.l1.x).length() < 1e3);
+        assert!((inertial.l2 - direction * rotating.l2.x).length() < 1e3);
+
+        // L4 leads the Moon in its direction of motion
+        assert!(inertial.l4.dot(sv.velocity) > 0.0);
+        assert!(inertial.l5.dot(sv.velocity) < 0.0);
+        assert!(inertial.l4.dot(moon.normal()).abs() < 1e3);
+    }
+}
@@ -0,0 +1,287 @@
+//! Lambert's problem: the orbit connecting two positions
+//! in a given time of flight.
+//!
+//! Solved with universal variables for transfers of less than
+//! one revolution.
+//!
+//! SRC: Curtis, Orbital Mechanics for Engineering Students, algorithm 5.2
+
+use super::standard_gravitational_parameter;
+use crate::math::safe_acos;
+use crate::{KeplerianElements, Num, StateVectors, Vec3, TWO_PI};
+
+/// Number of bisection steps on the universal variable
+const BISECTION_STEPS: usize = 100;
+
+/// Below this |z| the Stumpff functions use their series expansions
+const STUMPFF_SERIES_THRESHOLD: Num = 1e-3;
+
+/// Smallest sine of the transfer angle, below it the positions
+/// count as collinear
+const COLLINEAR_TOLERANCE: Num = 1e-6;
+
+/// Most negative z tried for fast, hyperbolic transfers,
+/// cosh(√-z) overflows not much further in f32
+const MIN_UNIVERSAL_VARIABLE: Num = -6_400.0;
+
+/// Transfer from `r1` to `r2` taking `time_of_flight` seconds.
+///
+/// The transfer goes around `normal`, the short way when the angle between
+/// the positions is prograde around it and the long way otherwise.
+/// Pass the angular momentum of the initial orbit to stay prograde.
+///
+/// Returns the states on the transfer orbit at the departure and
+/// at the arrival, or `None` when the positions are collinear with the
+/// central body, where the plane of the transfer is undefined.
+/// Transfers close to half a revolution are ill-conditioned,
+/// especially with `f32`.
+pub fn solve(
+    r1: Vec3,
+    r2: Vec3,
+    time_of_flight: Num,
+    mass: Num,
+    normal: Vec3,
+) -> Option<(StateVectors, StateVectors)> {
+    let μ = standard_gravitational_parameter(mass);
+    let (r1_len, r2_len) = (r1.length(), r2.length());
+
+    let cos_Δθ = r1.dot(r2) / (r1_len * r2_len);
+    let mut Δθ = safe_acos(cos_Δθ);
+    if r1.cross(r2).dot(normal) < 0.0 {
+        Δθ = TWO_PI - Δθ;
+    }
+
+    if r1.cross(r2).length() < COLLINEAR_TOLERANCE * r1_len * r2_len {
+        return None;
+    }
+
+    let A = Δθ.sin() * (r1_len * r2_len / (1.0 - cos_Δθ)).sqrt();
+
+    let y = |z: Num| {
+        let (C, S) = stumpff(z);
+        r1_len + r2_len + A * (z * S - 1.0) / C.sqrt()
+    };
+    let time = |z: Num| {
+        let (C, S) = stumpff(z);
+        let y = y(z);
+        ((y / C).powf(1.5) * S + A * y.sqrt()) / μ.sqrt()
+    };
+    // Negative y is on the fast side, the time grows with z
+    let is_too_fast = |z: Num| y(z) < 0.0 || time(z) < time_of_flight;
+
+    let mut hi = TWO_PI.powi(2) * (1.0 - Num::EPSILON);
+    let mut lo = -TWO_PI.powi(2);
+    while !is_too_fast(lo) {
+        if lo <= MIN_UNIVERSAL_VARIABLE {
+            return None;
+        }
+        hi = lo;
+        lo = (2.0 * lo).max(MIN_UNIVERSAL_VARIABLE);
+    }
+
+    for _ in 0..BISECTION_STEPS {
+        let mid = 0.5 * (lo + hi);
+
+        if is_too_fast(mid) {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    // Lagrange coefficients
+    let y = y(hi);
+    let f = 1.0 - y / r1_len;
+    let g = A * (y / μ).sqrt();
+    let g_dot = 1.0 - y / r2_len;
+
+    let v1 = (r2 - f * r1) / g;
+    let v2 = (g_dot * r2 - r1) / g;
+
+    if !(v1.is_finite() && v2.is_finite()) {
+        return None;
+    }
+
+    Some((StateVectors::new(r1, v1), StateVectors::new(r2, v2)))
+}
+
+/// Burn leaving the initial orbit onto the transfer orbit
+pub fn departure_delta_v(
+    departure_sv_transfer: &StateVectors,
+    departure_sv_initial: &StateVectors,
+) -> Vec3 {
+    departure_sv_transfer.velocity - departure_sv_initial.velocity
+}
+
+/// Burn leaving the transfer orbit onto the target orbit
+pub fn arrival_delta_v(
+    arrival_sv_transfer: &StateVectors,
+    arrival_sv_target: &StateVectors,
+) -> Vec3 {
+    arrival_sv_target.velocity - arrival_sv_transfer.velocity
+}
+
+/// Total delta-v, |Δv1| + |Δv2|, of the transfer departing the initial
+/// orbit at `departure_epoch` and meeting the target `time_of_flight`
+/// seconds later, prograde around the initial orbit.
+///
+/// Returns `None` when the transfer can't be solved, see [`solve`].
+pub fn total_delta_v(
+    initial_elements: &KeplerianElements,
+    target_elements: &KeplerianElements,
+    departure_epoch: Num,
+    time_of_flight: Num,
+    mass: Num,
+    tolerance: Num,
+) -> Option<Num> {
+    let initial = initial_elements.state_vectors_at_epoch(
+        mass,
+        departure_epoch,
+        tolerance,
+    );
+    let target = target_elements.state_vectors_at_epoch(
+        mass,
+        departure_epoch + time_of_flight,
+        tolerance,
+    );
+
+    let (departure, arrival) = solve(
+        initial.position,
+        target.position,
+        time_of_flight,
+        mass,
+        initial_elements.normal(),
+    )?;
+
+    Some(
+        departure_delta_v(&departure, &initial).length()
+            + arrival_delta_v(&arrival, &target).length(),
+    )
+}
+
+/// Stumpff functions C(z) and S(z)
+fn stumpff(z: Num) -> (Num, Num) {
+    if z.abs() < STUMPFF_SERIES_THRESHOLD {
+        (0.5 - z / 24.0, 1.0 / 6.0 - z / 120.0)
+    } else if z > 0.0 {
+        let s = z.sqrt();
+        ((1.0 - s.cos()) / z, (s - s.sin()) / s.powi(3))
+    } else {
+        let s = (-z).sqrt();
+        ((s.cosh() - 1.0) / -z, (s.sinh() - s) / s.powi(3))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::astro::transfer::hohmann;
+    use crate::constants::EARTH_MASS;
+    use crate::PI;
+
+    const LEO: Num = 6_678_000.0;
+    const GEO: Num = 42_164_000.0;
+    const TOLERANCE: Num = 1e-5;
+
+    #[test]
+    fn recovers_the_orbit_between_two_states() {
+        let orbit = KeplerianElements {
+            semi_major_axis: 10_000_000.0,
+            eccentricity: 0.3,
+            inclination: 0.5,
+            right_ascension_of_the_ascending_node: 1.0,
+            argument_of_periapsis: 2.0,
+            ..KeplerianElements::default()
+        };
+        let period = orbit.period(EARTH_MASS);
+
+        // Short and long way around
+        for dt in [0.2 * period, 0.7 * period] {
+            let a = orbit.state_vectors_at_epoch(EARTH_MASS, 0.0, TOLERANCE);
+            let b = orbit.state_vectors_at_epoch(EARTH_MASS, dt, TOLERANCE);
+
+            let (departure, arrival) =
+                solve(a.position, b.position, dt, EARTH_MASS, orbit.normal())
+                    .unwrap();
+
+            let v = a.velocity.length();
+            assert!(
+                departure.velocity.abs_diff_eq(a.velocity, 1e-3 * v),
+                "{departure:?} != {a:?}"
+            );
+            assert!(
+                arrival.velocity.abs_diff_eq(b.velocity, 1e-3 * v),
+                "{arrival:?} != {b:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn hyperbolic_transfer() {
+        let r1 = Vec3::new(LEO, 0.0, 0.0);
+        let r2 = Vec3::new(0.0, GEO, 0.0);
+
+        // Much faster than any ellipse
+        let (departure, _) = solve(r1, r2, 600.0, EARTH_MASS, Vec3::Z).unwrap();
+
+        let orbit = departure.to_elements(EARTH_MASS, 0.0);
+        assert!(orbit.is_hyperbolic(), "{orbit:?}");
+    }
+
+    #[test]
+    fn collinear_positions_are_rejected() {
+        let r1 = Vec3::new(LEO, 0.0, 0.0);
+        let r2 = Vec3::new(-GEO, 0.0, 0.0);
+
+        assert_eq!(solve(r1, r2, 10_000.0, EARTH_MASS, Vec3::Z), None);
+    }
+
+    #[test]
+    fn matches_hohmann() {
+        let μ = standard_gravitational_parameter(EARTH_MASS);
+        let hohmann = hohmann(μ, LEO, GEO).unwrap();
+        let tof = hohmann.time_of_flight;
+
+        // Just short of half a revolution, exactly opposite is singular
+        let Δ = 1e-2;
+
+        let initial = KeplerianElements {
+            semi_major_axis: LEO,
+            ..KeplerianElements::default()
+        };
+        let n = (μ / GEO.powi(3)).sqrt();
+        let target = KeplerianElements {
+            semi_major_axis: GEO,
+            mean_anomaly_at_epoch: PI - Δ - n * tof,
+            ..KeplerianElements::default()
+        };
+
+        let total =
+            total_delta_v(&initial, &target, 0.0, tof, EARTH_MASS, TOLERANCE)
+                .unwrap();
+        let expected = hohmann.total_delta_v();
+        assert!((total / expected - 1.0).abs() < 1e-2, "{total} {expected}");
+
+        // The departure burn is prograde
+        let initial_sv =
+            initial.state_vectors_at_epoch(EARTH_MASS, 0.0, TOLERANCE);
+        let target_sv =
+            target.state_vectors_at_epoch(EARTH_MASS, tof, TOLERANCE);
+        let (departure, _) = solve(
+            initial_sv.position,
+            target_sv.position,
+            tof,
+            EARTH_MASS,
+            initial.normal(),
+        )
+        .unwrap();
+
+        let dv1 = departure_delta_v(&departure, &initial_sv);
+        let prograde = initial_sv.velocity.normalize();
+        assert!(
+            (dv1.dot(prograde) - hohmann.delta_v_departure).abs()
+                < 1e-2 * hohmann.delta_v_departure,
+            "{dv1}"
+        );
+    }
+}
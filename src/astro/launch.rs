@@ -0,0 +1,271 @@
+use super::standard_gravitational_parameter;
+use crate::math::{safe_acos, wrap_angle};
+use crate::{Num, PI};
+
+/// Characteristic duration of the steep part of an ascent.
+/// Gravity losses are estimated as the surface gravity times this duration.
+const GRAVITY_LOSS_DURATION: Num = 150.0;
+
+/// Drag losses as a fraction of the orbital velocity.
+/// Roughly matches an Earth-like atmosphere.
+const DRAG_LOSS_FRACTION: Num = 0.05;
+
+/// Approximate delta-v required to reach a circular orbit from the surface.
+///
+/// The ideal cost is that of a Hohmann transfer from the surface
+/// to the target altitude, starting at rest. The velocity of the rotating
+/// surface is subtracted along the launch azimuth and the gravity and drag
+/// losses are added on top.
+///
+/// If the target inclination is lower than the launch latitude the launch
+/// goes due east and the remaining plane change is done in orbit.
+///
+/// All the angles are in radians, everything else is in SI units.
+pub fn delta_v_to_circular_orbit(
+    target_altitude: Num,
+    body_radius: Num,
+    body_mass: Num,
+    launch_latitude: Num,
+    target_inclination: Num,
+    rotation_velocity_at_equator: Num,
+) -> Num {
+    let μ = standard_gravitational_parameter(body_mass);
+
+    let r1 = body_radius;
+    let r2 = body_radius + target_altitude;
+
+    // Transfer orbit periapsis velocity & circularization burn
+    let v_periapsis = (μ / r1).sqrt() * (2.0 * r2 / (r1 + r2)).sqrt();
+    let v_circular = (μ / r2).sqrt();
+    let circularization = v_circular * (1.0 - (2.0 * r1 / (r1 + r2)).sqrt());
+
+    // Remove the velocity we get for free from the rotation of the body
+    let azimuth = optimal_launch_azimuth(launch_latitude, target_inclination);
+    let v_rotation = rotation_velocity_at_equator * launch_latitude.cos();
+    let v_east = v_periapsis * azimuth.sin() - v_rotation;
+    let v_north = v_periapsis * azimuth.cos();
+    let ascent = (v_east.powi(2) + v_north.powi(2)).sqrt();
+
+    // Inclinations below the launch latitude can't be reached directly
+    let Δi = (launch_latitude.abs() - target_inclination).max(0.0);
+    let plane_change = 2.0 * v_circular * (Δi / 2.0).sin();
+
+    let g = μ / body_radius.powi(2);
+    let gravity_losses = g * GRAVITY_LOSS_DURATION;
+    let drag_losses = v_circular * DRAG_LOSS_FRACTION;
+
+    ascent + circularization + plane_change + gravity_losses + drag_losses
+}
+
+/// Launch azimuth (measured clockwise from north) for reaching an orbit
+/// of the given inclination from the given latitude.
+///
+/// sin(β) = cos(i) / cos(φ)
+///
+/// If the inclination can't be reached directly, the azimuth is due east.
+/// Doesn't account for the rotation of the body.
+pub fn optimal_launch_azimuth(
+    launch_latitude: Num,
+    target_inclination: Num,
+) -> Num {
+    let sin_β = target_inclination.cos() / launch_latitude.cos();
+
+    sin_β.clamp(-1.0, 1.0).asin()
+}
+
+/// Northerly and southerly launch azimuths (measured clockwise from north)
+/// that reach an orbit of the given inclination from the given latitude.
+///
+/// sin(β) = cos(i) / cos(φ)
+///
+/// Returns `None` if the inclination can't be reached directly,
+/// i.e. when it's lower than the latitude (or the retrograde equivalent).
+/// Doesn't account for the rotation of the body,
+/// see [`azimuth_for_inclination_rotating`].
+pub fn azimuth_for_inclination(
+    latitude: Num,
+    inclination: Num,
+) -> Option<(Num, Num)> {
+    let sin_β = inclination.cos() / latitude.cos();
+
+    if sin_β.abs() > 1.0 {
+        return None;
+    }
+
+    let β = sin_β.asin();
+
+    Some((wrap_angle(β), wrap_angle(PI - β)))
+}
+
+/// Inclination reached by launching from the given latitude
+/// at the given azimuth, the inverse of [`azimuth_for_inclination`].
+///
+/// cos(i) = cos(φ) sin(β)
+pub fn inclination_for_azimuth(latitude: Num, azimuth: Num) -> Num {
+    safe_acos(latitude.cos() * azimuth.sin())
+}
+
+/// Same as [`azimuth_for_inclination`] but relative to the rotating
+/// surface, for reaching the `orbital_speed` in the inertial frame.
+///
+/// The velocity of the surface is subtracted from the inertial velocity
+/// along the inertial azimuth, so the azimuths tilt away from east.
+pub fn azimuth_for_inclination_rotating(
+    latitude: Num,
+    inclination: Num,
+    orbital_speed: Num,
+    rotation_velocity_at_equator: Num,
+) -> Option<(Num, Num)> {
+    let v_rotation = rotation_velocity_at_equator * latitude.cos();
+
+    let rotating = |β: Num| {
+        let v_east = orbital_speed * β.sin() - v_rotation;
+        let v_north = orbital_speed * β.cos();
+
+        wrap_angle(v_east.atan2(v_north))
+    };
+
+    let (northerly, southerly) =
+        azimuth_for_inclination(latitude, inclination)?;
+
+    Some((rotating(northerly), rotating(southerly)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{EARTH_MASS, EARTH_RADIUS, PI};
+
+    const EARTH_ROTATION_VELOCITY: Num = 465.1;
+
+    #[test]
+    fn low_earth_orbit_from_sea_level() {
+        let latitude: Num = 28.5 * PI / 180.0;
+
+        let Δv = delta_v_to_circular_orbit(
+            200_000.0,
+            EARTH_RADIUS,
+            EARTH_MASS,
+            latitude,
+            latitude,
+            EARTH_ROTATION_VELOCITY,
+        );
+
+        assert!((Δv - 9_400.0).abs() < 200.0, "Δv = {Δv}");
+    }
+
+    #[test]
+    fn equatorial_launch_due_east_gets_maximum_rotation_benefit() {
+        let Δv = |rotation_velocity| {
+            delta_v_to_circular_orbit(
+                200_000.0,
+                EARTH_RADIUS,
+                EARTH_MASS,
+                0.0,
+                0.0,
+                rotation_velocity,
+            )
+        };
+
+        let azimuth = optimal_launch_azimuth(0.0, 0.0);
+        assert!((azimuth - PI / 2.0).abs() < 0.0001);
+
+        let benefit = Δv(0.0) - Δv(EARTH_ROTATION_VELOCITY);
+        assert!((benefit - EARTH_ROTATION_VELOCITY).abs() < 1.0);
+
+        for latitude in [0.2, 0.5, 1.0] {
+            let inclined = delta_v_to_circular_orbit(
+                200_000.0,
+                EARTH_RADIUS,
+                EARTH_MASS,
+                latitude,
+                latitude,
+                EARTH_ROTATION_VELOCITY,
+            );
+            let inclined_without_rotation = delta_v_to_circular_orbit(
+                200_000.0,
+                EARTH_RADIUS,
+                EARTH_MASS,
+                latitude,
+                latitude,
+                0.0,
+            );
+
+            assert!(inclined_without_rotation - inclined < benefit);
+        }
+    }
+
+    #[test]
+    fn due_east_reaches_the_latitude() {
+        for latitude in [0.0, 0.3, -0.5, 1.2] {
+            let i = inclination_for_azimuth(latitude, PI / 2.0);
+            assert!((i - latitude.abs()).abs() < 1e-5, "{i}");
+
+            let (northerly, southerly) =
+                azimuth_for_inclination(latitude, latitude.abs()).unwrap();
+            assert!((northerly - PI / 2.0).abs() < 1e-3, "{northerly}");
+            assert!((southerly - PI / 2.0).abs() < 1e-3, "{southerly}");
+        }
+    }
+
+    #[test]
+    fn polar_orbits_launch_north_or_south() {
+        for latitude in [0.0, 0.3, -0.5, 1.2] {
+            let (northerly, southerly) =
+                azimuth_for_inclination(latitude, PI / 2.0).unwrap();
+
+            assert!(northerly.abs() < 1e-5, "{northerly}");
+            assert!((southerly - PI).abs() < 1e-5, "{southerly}");
+        }
+    }
+
+    #[test]
+    fn azimuth_round_trip() {
+        let latitude = 28.5 * PI / 180.0;
+
+        for inclination in [0.6, 1.0, 2.0, 2.5] {
+            let (northerly, southerly) =
+                azimuth_for_inclination(latitude, inclination).unwrap();
+
+            for azimuth in [northerly, southerly] {
+                let i = inclination_for_azimuth(latitude, azimuth);
+                assert!((i - inclination).abs() < 1e-4, "{i}");
+            }
+        }
+
+        // Below the latitude
+        assert_eq!(azimuth_for_inclination(latitude, 0.3), None);
+        assert_eq!(azimuth_for_inclination(latitude, PI - 0.3), None);
+    }
+
+    #[test]
+    fn rotation_tilts_the_azimuth_away_from_east() {
+        let latitude = 28.5 * PI / 180.0;
+        let inclination = 51.6 * PI / 180.0;
+
+        let (northerly, southerly) =
+            azimuth_for_inclination(latitude, inclination).unwrap();
+        let (rotating_northerly, rotating_southerly) =
+            azimuth_for_inclination_rotating(
+                latitude,
+                inclination,
+                7_800.0,
+                EARTH_ROTATION_VELOCITY,
+            )
+            .unwrap();
+
+        assert!(rotating_northerly < northerly);
+        assert!(rotating_southerly > southerly);
+
+        let (still_northerly, still_southerly) =
+            azimuth_for_inclination_rotating(
+                latitude,
+                inclination,
+                7_800.0,
+                0.0,
+            )
+            .unwrap();
+        assert!((still_northerly - northerly).abs() < 1e-5);
+        assert!((still_southerly - southerly).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,133 @@
+//! Rocket performance.
+//!
+//! Specific impulses are in seconds, masses in kilograms.
+
+use crate::constants::STANDARD_GRAVITY;
+use crate::Num;
+
+/// Delta-v of a burn from `mass_initial` down to `mass_final`
+/// SRC: https://en.wikipedia.org/wiki/Tsiolkovsky_rocket_equation
+pub fn tsiolkovsky(
+    mass_initial: Num,
+    mass_final: Num,
+    specific_impulse: Num,
+) -> Num {
+    specific_impulse * STANDARD_GRAVITY * (mass_initial / mass_final).ln()
+}
+
+/// Ratio of initial to final mass needed for the given delta-v.
+///
+/// Inverse of [`tsiolkovsky`].
+pub fn mass_ratio_from_delta_v(delta_v: Num, specific_impulse: Num) -> Num {
+    (delta_v / (specific_impulse * STANDARD_GRAVITY)).exp()
+}
+
+/// Total delta-v of a stacked vehicle.
+///
+/// `stages` are `(mass_propellant, mass_dry, specific_impulse)`, ordered
+/// from the first stage to fire. Every stage carries all the stages above
+/// it, the payload can be folded into the dry mass of the last one.
+pub fn multi_stage_delta_v(stages: &[(Num, Num, Num)]) -> Num {
+    let mut mass: Num = stages.iter().map(|(prop, dry, _)| prop + dry).sum();
+    let mut delta_v = 0.0;
+
+    for &(mass_propellant, mass_dry, specific_impulse) in stages {
+        let mass_final = mass - mass_propellant;
+
+        delta_v += tsiolkovsky(mass, mass_final, specific_impulse);
+        mass = mass_final - mass_dry;
+    }
+
+    delta_v
+}
+
+/// Mass ratio of every stage when the delta-v is split equally
+/// between `n_stages` stages.
+///
+/// For stages with the same structural coefficient and specific impulse
+/// the equal split minimizes the total mass.
+///
+/// # Panics
+/// If `isp` doesn't have exactly `n_stages` entries.
+pub fn optimal_stage_mass_ratio(
+    total_delta_v: Num,
+    n_stages: usize,
+    isp: &[Num],
+) -> Vec<Num> {
+    assert_eq!(
+        isp.len(),
+        n_stages,
+        "Expected a specific impulse for each of the {n_stages} stages"
+    );
+
+    let stage_delta_v = total_delta_v / n_stages as Num;
+
+    isp.iter()
+        .map(|&isp| mass_ratio_from_delta_v(stage_delta_v, isp))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::astro::launch::delta_v_to_circular_orbit;
+    use crate::constants::{EARTH_MASS, EARTH_RADIUS};
+
+    /// Roughly a Falcon 9 with an expendable first stage
+    /// and a 22.8 t payload folded into the second stage
+    const FALCON_9: [(Num, Num, Num); 2] =
+        [(411_000.0, 22_200.0, 300.0), (107_500.0, 26_800.0, 348.0)];
+
+    #[test]
+    fn rocket_equation_round_trip() {
+        let delta_v = tsiolkovsky(100.0, 25.0, 320.0);
+        assert!(
+            (delta_v - 320.0 * STANDARD_GRAVITY * Num::ln(4.0)).abs() < 1e-2
+        );
+
+        let ratio = mass_ratio_from_delta_v(delta_v, 320.0);
+        assert!((ratio - 4.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn two_stage_rocket_reaches_low_earth_orbit() {
+        // Due east from Cape Canaveral
+        let required = delta_v_to_circular_orbit(
+            200_000.0,
+            EARTH_RADIUS,
+            EARTH_MASS,
+            0.4974,
+            0.4974,
+            465.1,
+        );
+        let available = multi_stage_delta_v(&FALCON_9);
+
+        assert!((required - 9_400.0).abs() < 200.0, "{required}");
+        assert!((available - 9_300.0).abs() < 100.0, "{available}");
+
+        // Upper stage mass ratio: 134.3 t to 26.8 t
+        let upper = tsiolkovsky(134_300.0, 26_800.0, 348.0);
+        assert!((multi_stage_delta_v(&FALCON_9[1..]) - upper).abs() < 1e-2);
+    }
+
+    #[test]
+    fn equal_split_staging() {
+        let ratios = optimal_stage_mass_ratio(9_400.0, 2, &[300.0, 348.0]);
+
+        assert!((ratios[0] - 4.94).abs() < 0.01, "{ratios:?}");
+        assert!((ratios[1] - 3.96).abs() < 0.01, "{ratios:?}");
+
+        let delta_v: Num = ratios
+            .iter()
+            .zip([300.0, 348.0])
+            .map(|(ratio, isp)| tsiolkovsky(*ratio, 1.0, isp))
+            .sum();
+        assert!((delta_v - 9_400.0).abs() < 1.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn stage_count_mismatch() {
+        optimal_stage_mass_ratio(9_400.0, 3, &[300.0, 348.0]);
+    }
+}
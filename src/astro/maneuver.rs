@@ -0,0 +1,165 @@
+//! Single impulse changes of the orbital plane.
+
+use crate::math::wrap_angle;
+use crate::{KeplerianElements, Num, Vec3};
+
+/// Burn rotating the orbit about the polar axis, see [`raan_change_dv`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RaanChange {
+    pub dv: Num,
+    /// Where on the initial orbit the burn takes place
+    pub burn_true_anomaly: Num,
+    /// Time from the epoch of the elements until the burn
+    pub burn_epoch_offset: Num,
+}
+
+/// Cheapest single impulse changing the right ascension of the ascending
+/// node by `delta_raan` while keeping the inclination.
+///
+/// The burn takes place where the initial and the target planes intersect
+/// and rotates the velocity by the angle θ between them, given by the
+/// spherical triangle cos θ = cos²i + sin²i cos ΔΩ.
+/// Only the horizontal velocity is rotated, so Δv = 2 v_h sin(θ / 2)
+/// and of the two intersections the one further from the body is cheaper
+/// on an eccentric orbit.
+///
+/// Without a change the burn is free and placed at the ascending node.
+/// The elements must be elliptical.
+pub fn raan_change_dv(
+    elements: &KeplerianElements,
+    mass: Num,
+    delta_raan: Num,
+) -> RaanChange {
+    debug_assert!(elements.is_elliptical());
+
+    let target = KeplerianElements {
+        right_ascension_of_the_ascending_node: elements
+            .right_ascension_of_the_ascending_node
+            + delta_raan,
+        ..*elements
+    };
+
+    let θ = elements.relative_inclination(&target);
+
+    let p = elements.perifocal_to_equatorial(Vec3::X);
+    let q = elements.perifocal_to_equatorial(Vec3::Y);
+    let true_anomaly =
+        |direction: Vec3| wrap_angle(direction.dot(q).atan2(direction.dot(p)));
+
+    let candidates = match elements.normal_misalignment_axis(&target) {
+        Some(axis) => vec![true_anomaly(axis), true_anomaly(-axis)],
+        None => vec![wrap_angle(-elements.argument_of_periapsis)],
+    };
+
+    let h = elements.specific_angular_momentum(mass);
+
+    candidates
+        .into_iter()
+        .map(|v| {
+            let r = elements.position_at_true_anomaly(mass, v).length();
+
+            // Radial motion is along the rotation axis, so it's unaffected
+            let dv = 2.0 * h / r * (θ / 2.0).sin();
+
+            let burn_epoch_offset = elements
+                .next_epoch_at_true_anomaly(mass, v)
+                .map_or(0.0, |epoch| epoch - elements.epoch);
+
+            RaanChange {
+                dv,
+                burn_true_anomaly: v,
+                burn_epoch_offset,
+            }
+        })
+        .min_by(|a, b| a.dv.total_cmp(&b.dv))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::EARTH_MASS;
+    use crate::{math, Mat3, StateVectors, PI};
+
+    const TOLERANCE: Num = 1e-5;
+
+    fn orbit(eccentricity: Num, inclination: Num) -> KeplerianElements {
+        KeplerianElements {
+            semi_major_axis: 7_000_000.0,
+            eccentricity,
+            inclination,
+            right_ascension_of_the_ascending_node: 0.4,
+            argument_of_periapsis: 1.0,
+            mean_anomaly_at_epoch: 0.3,
+            epoch: 100.0,
+        }
+    }
+
+    #[test]
+    fn no_change_is_free() {
+        let change = raan_change_dv(&orbit(0.1, 0.9), EARTH_MASS, 0.0);
+
+        assert_eq!(change.dv, 0.0);
+    }
+
+    #[test]
+    fn polar_orbit_matches_plane_change() {
+        let elements = orbit(0.0, PI / 2.0);
+        let ΔΩ = 0.2;
+
+        let change = raan_change_dv(&elements, EARTH_MASS, ΔΩ);
+
+        let v = elements.velocity_at_true_anomaly(EARTH_MASS, 0.0).length();
+        let plane_change = 2.0 * v * (ΔΩ / 2.0).sin();
+        assert!((change.dv / plane_change - 1.0).abs() < 1e-4, "{change:?}");
+    }
+
+    #[test]
+    fn burn_rotates_the_node() {
+        let elements = orbit(0.3, 0.9);
+        let ΔΩ = 0.25;
+
+        let change = raan_change_dv(&elements, EARTH_MASS, ΔΩ);
+
+        let epoch = elements.epoch + change.burn_epoch_offset;
+        let sv = elements.state_vectors_at_epoch(EARTH_MASS, epoch, TOLERANCE);
+        let v = elements.true_anomaly_at_epoch(EARTH_MASS, epoch, TOLERANCE);
+        assert!(math::angle_diff(v, change.burn_true_anomaly).abs() < 1e-3);
+
+        // Rotate the velocity about the line of intersection
+        let target = KeplerianElements {
+            right_ascension_of_the_ascending_node: 0.4 + ΔΩ,
+            ..elements
+        };
+        let axis = elements.normal_misalignment_axis(&target).unwrap();
+        let rotation =
+            Mat3::from_axis_angle(axis, elements.relative_inclination(&target));
+        let velocity = rotation * sv.velocity;
+
+        assert!(
+            ((velocity - sv.velocity).length() / change.dv - 1.0).abs() < 1e-3,
+            "{change:?}"
+        );
+
+        let after = StateVectors::new(sv.position, velocity)
+            .to_elements(EARTH_MASS, epoch);
+        assert!((after.inclination - 0.9).abs() < 1e-4, "{after:?}");
+        assert!(
+            math::angle_diff(
+                after.right_ascension_of_the_ascending_node,
+                0.4 + ΔΩ
+            )
+            .abs()
+                < 1e-4,
+            "{after:?}"
+        );
+
+        // The other intersection is closer to the body, so dearer
+        let other = wrap_angle(change.burn_true_anomaly + PI);
+        let r = elements.position_at_true_anomaly(EARTH_MASS, v).length();
+        let r_other = elements
+            .position_at_true_anomaly(EARTH_MASS, other)
+            .length();
+        assert!(r > r_other);
+    }
+}
@@ -0,0 +1,419 @@
+//! Pass prediction for ground stations and apparent separations.
+//!
+//! The central body is a sphere rotating around the z axis, with the prime
+//! meridian aligned with the x axis at `t = 0`.
+
+use super::visibility::elevation_angle;
+use crate::{vec3, KeplerianElements, Num, StateVectors, Vec3};
+
+/// Number of halvings used to refine the rise, set and culmination times
+const REFINEMENT_STEPS: usize = 40;
+
+/// Samples per period of the fastest orbit when searching for conjunctions
+const CONJUNCTION_SAMPLES_PER_PERIOD: Num = 64.0;
+
+/// Location of an observer on the surface of the central body
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GroundStation {
+    /// Geocentric latitude in radians
+    pub latitude: Num,
+    /// Longitude in radians, east positive
+    pub longitude: Num,
+    /// Distance from the center of the body
+    pub radius: Num,
+}
+
+impl GroundStation {
+    /// Inertial position of the station at time `t`,
+    /// `body_rotation` is the angular velocity of the body in rad/s
+    pub fn position_at(&self, body_rotation: Num, t: Num) -> Vec3 {
+        let longitude = self.longitude + body_rotation * t;
+
+        self.radius
+            * vec3(
+                self.latitude.cos() * longitude.cos(),
+                self.latitude.cos() * longitude.sin(),
+                self.latitude.sin(),
+            )
+    }
+}
+
+/// A time span during which a satellite is above the minimum elevation.
+///
+/// `rise` is `None` if the satellite was already visible at the start
+/// of the searched interval, `set` is `None` if it's still visible
+/// at the end of it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VisibilityWindow {
+    pub rise: Option<Num>,
+    pub set: Option<Num>,
+    pub max_elevation: Num,
+    pub max_elevation_epoch: Num,
+}
+
+/// Finds all the passes of the satellite over the station
+/// between `t_start` and `t_end`.
+///
+/// The elevation is sampled every `step` seconds, so passes shorter than
+/// the step can be missed. The crossings of `min_elevation` are then
+/// refined with bisection. No windows are found for a non-positive step.
+#[allow(clippy::too_many_arguments)]
+pub fn visibility_windows(
+    elements: &KeplerianElements,
+    mass: Num,
+    station: &GroundStation,
+    body_rotation: Num,
+    min_elevation: Num,
+    t_start: Num,
+    t_end: Num,
+    step: Num,
+    tolerance: Num,
+) -> Vec<VisibilityWindow> {
+    if step.is_nan() || step <= 0.0 {
+        return vec![];
+    }
+
+    let elevation = |t: Num| {
+        let satellite = elements.state_vectors_at_epoch(mass, t, tolerance);
+        let station = station.position_at(body_rotation, t);
+
+        elevation_angle(satellite.position, station, station)
+    };
+    let above = |t: Num| elevation(t) >= min_elevation;
+
+    let mut windows = vec![];
+
+    let mut current = above(t_start).then_some(VisibilityWindow {
+        rise: None,
+        set: None,
+        max_elevation: elevation(t_start),
+        max_elevation_epoch: t_start,
+    });
+
+    let mut t = t_start;
+    while t < t_end {
+        let next = (t + step).min(t_end);
+        let next_elevation = elevation(next);
+        let next_above = next_elevation >= min_elevation;
+
+        match (current.as_mut(), next_above) {
+            (None, true) => {
+                let rise = bisect(above, t, next);
+
+                current = Some(VisibilityWindow {
+                    rise: Some(rise),
+                    set: None,
+                    max_elevation: next_elevation,
+                    max_elevation_epoch: next,
+                });
+            }
+            (Some(window), false) => {
+                window.set = Some(bisect(above, t, next));
+
+                windows.push(refine_culmination(*window, elevation, step));
+                current = None;
+            }
+            (Some(window), true) => {
+                if next_elevation > window.max_elevation {
+                    window.max_elevation = next_elevation;
+                    window.max_elevation_epoch = next;
+                }
+            }
+            (None, false) => {}
+        }
+
+        t = next;
+    }
+
+    if let Some(window) = current {
+        windows.push(refine_culmination(window, elevation, step));
+    }
+
+    windows
+}
+
+/// Angle between the directions from `observer` to `a` and to `b`
+pub fn angular_separation(
+    observer: &StateVectors,
+    a: &StateVectors,
+    b: &StateVectors,
+) -> Num {
+    let to_a = a.position - observer.position;
+    let to_b = b.position - observer.position;
+
+    to_a.cross(to_b).length().atan2(to_a.dot(to_b))
+}
+
+/// First epoch in `[t_start, t_end]` at which the angular separation of
+/// `a` and `b`, as seen from `observer`, drops below `threshold_angle`.
+///
+/// The separation is sampled a fixed number of times per period
+/// of the fastest elliptical orbit and the crossing is refined
+/// with bisection, so approaches shorter than a sample can be missed.
+#[allow(clippy::too_many_arguments)]
+pub fn next_conjunction(
+    observer: &KeplerianElements,
+    a: &KeplerianElements,
+    b: &KeplerianElements,
+    mass: Num,
+    threshold_angle: Num,
+    t_start: Num,
+    t_end: Num,
+    tolerance: Num,
+) -> Option<Num> {
+    let separation = |t: Num| {
+        angular_separation(
+            &observer.state_vectors_at_epoch(mass, t, tolerance),
+            &a.state_vectors_at_epoch(mass, t, tolerance),
+            &b.state_vectors_at_epoch(mass, t, tolerance),
+        )
+    };
+    let close = |t: Num| separation(t) < threshold_angle;
+
+    if close(t_start) {
+        return Some(t_start);
+    }
+
+    let shortest_period = [observer, a, b]
+        .into_iter()
+        .filter(|elements| elements.is_elliptical())
+        .map(|elements| elements.period(mass))
+        .fold(t_end - t_start, Num::min);
+    let step = shortest_period / CONJUNCTION_SAMPLES_PER_PERIOD;
+    // A zero period would never advance
+    if step.is_nan() || step <= 0.0 {
+        return None;
+    }
+
+    let mut t = t_start;
+    while t < t_end {
+        let next = (t + step).min(t_end);
+
+        if close(next) {
+            return Some(bisect(close, t, next));
+        }
+
+        t = next;
+    }
+
+    None
+}
+
+/// Time in `[a, b]` at which `above` switches its value
+fn bisect(above: impl Fn(Num) -> bool, mut a: Num, mut b: Num) -> Num {
+    let above_at_a = above(a);
+
+    for _ in 0..REFINEMENT_STEPS {
+        let mid = 0.5 * (a + b);
+        if mid <= a || mid >= b {
+            break;
+        }
+
+        if above(mid) == above_at_a {
+            a = mid;
+        } else {
+            b = mid;
+        }
+    }
+
+    0.5 * (a + b)
+}
+
+/// Refines the sampled maximum elevation with a ternary search
+/// within a step around it, keeping it inside the window.
+fn refine_culmination(
+    mut window: VisibilityWindow,
+    elevation: impl Fn(Num) -> Num,
+    step: Num,
+) -> VisibilityWindow {
+    let t = window.max_elevation_epoch;
+
+    let mut a = window.rise.map_or(t - step, |rise| rise.max(t - step));
+    let mut b = window.set.map_or(t + step, |set| set.min(t + step));
+
+    for _ in 0..REFINEMENT_STEPS {
+        let m1 = a + (b - a) / 3.0;
+        let m2 = b - (b - a) / 3.0;
+
+        if elevation(m1) < elevation(m2) {
+            a = m1;
+        } else {
+            b = m2;
+        }
+    }
+
+    let t = 0.5 * (a + b);
+    let max_elevation = elevation(t);
+
+    if max_elevation > window.max_elevation {
+        window.max_elevation = max_elevation;
+        window.max_elevation_epoch = t;
+    }
+
+    window
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{
+        EARTH_MASS, EARTH_RADIUS, SECONDS_PER_DAY, SECONDS_PER_MINUTE,
+    };
+    use crate::PI;
+
+    const EARTH_ROTATION: Num = 7.292_115e-5;
+    const TOLERANCE: Num = 1e-5;
+
+    const STATION: GroundStation = GroundStation {
+        latitude: 0.5,
+        longitude: 0.0,
+        radius: EARTH_RADIUS,
+    };
+
+    #[test]
+    fn geostationary_satellite_never_sets() {
+        let geostationary = KeplerianElements {
+            semi_major_axis: 42_164_000.0,
+            ..KeplerianElements::default()
+        };
+
+        let windows = visibility_windows(
+            &geostationary,
+            EARTH_MASS,
+            &STATION,
+            EARTH_ROTATION,
+            0.2,
+            0.0,
+            SECONDS_PER_DAY,
+            60.0,
+            TOLERANCE,
+        );
+
+        assert_eq!(windows.len(), 1, "{windows:?}");
+        assert_eq!(windows[0].rise, None);
+        assert_eq!(windows[0].set, None);
+        assert!(windows[0].max_elevation > 0.9, "{windows:?}");
+    }
+
+    #[test]
+    fn polar_orbit_passes() {
+        let polar = KeplerianElements {
+            semi_major_axis: EARTH_RADIUS + 700_000.0,
+            inclination: PI / 2.0,
+            ..KeplerianElements::default()
+        };
+        let min_elevation = 0.1;
+
+        let windows = visibility_windows(
+            &polar,
+            EARTH_MASS,
+            &STATION,
+            EARTH_ROTATION,
+            min_elevation,
+            0.0,
+            SECONDS_PER_DAY,
+            30.0,
+            TOLERANCE,
+        );
+
+        assert!((2..=8).contains(&windows.len()), "{windows:?}");
+
+        let no_step = |step| {
+            visibility_windows(
+                &polar,
+                EARTH_MASS,
+                &STATION,
+                EARTH_ROTATION,
+                min_elevation,
+                0.0,
+                SECONDS_PER_DAY,
+                step,
+                TOLERANCE,
+            )
+        };
+        assert_eq!(no_step(0.0), vec![]);
+        assert_eq!(no_step(-30.0), vec![]);
+
+        for window in windows {
+            let (Some(rise), Some(set)) = (window.rise, window.set) else {
+                continue;
+            };
+
+            let duration = (set - rise) / SECONDS_PER_MINUTE;
+            assert!((1.0..16.0).contains(&duration), "{window:?}");
+
+            assert!(window.max_elevation >= min_elevation);
+            assert!((rise..=set).contains(&window.max_elevation_epoch));
+        }
+    }
+
+    #[test]
+    fn separation_on_the_same_ray_is_zero() {
+        let observer = StateVectors::default();
+        let a = StateVectors::new(vec3(1.0, 2.0, 3.0), Vec3::X);
+        let b = StateVectors::new(vec3(2.0, 4.0, 6.0), Vec3::Y);
+
+        assert!(angular_separation(&observer, &a, &b).abs() < 1e-6);
+
+        let b = StateVectors::new(vec3(-2.0, -4.0, -6.0), Vec3::Y);
+        assert!((angular_separation(&observer, &a, &b) - PI).abs() < 1e-6);
+    }
+
+    #[test]
+    fn conjunctions_repeat_every_synodic_period() {
+        let mass = 100_000_000_000.0;
+        let circular = |semi_major_axis| KeplerianElements {
+            semi_major_axis,
+            ..KeplerianElements::default()
+        };
+
+        // Close to the center, so the parallax is small
+        let observer = circular(0.01);
+        let inner = circular(1.0);
+        let outer = circular(2.0);
+
+        let n = |elements: KeplerianElements| 2.0 * PI / elements.period(mass);
+        let relative_rate = n(inner) - n(outer);
+        let synodic_period = 2.0 * PI / relative_rate;
+
+        // Aligned at t = 0, so the separation drops below the threshold
+        // right before each full relative revolution
+        let threshold = 0.1;
+        let before_alignment = threshold / relative_rate;
+
+        let find = |t_start| {
+            next_conjunction(
+                &observer,
+                &inner,
+                &outer,
+                mass,
+                threshold,
+                t_start,
+                3.0 * synodic_period,
+                TOLERANCE,
+            )
+            .unwrap()
+        };
+
+        let first = find(0.1 * synodic_period);
+        let second = find(first + 0.5 * synodic_period);
+
+        let expected = synodic_period - before_alignment;
+        assert!((first - expected).abs() < 0.02, "{first} != {expected}");
+        assert!((second - first - synodic_period).abs() < 0.02);
+
+        assert_eq!(
+            next_conjunction(
+                &observer,
+                &inner,
+                &outer,
+                mass,
+                threshold,
+                0.1 * synodic_period,
+                0.5 * synodic_period,
+                TOLERANCE,
+            ),
+            None
+        );
+    }
+}
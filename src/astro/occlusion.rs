@@ -0,0 +1,124 @@
+//! Line of sight checks against spherical bodies.
+//!
+//! A segment that only touches the surface of the body (grazes the limb)
+//! is not blocked, only segments passing strictly inside the sphere are.
+
+use crate::{Num, Vec3};
+
+/// Whether the segment between `a` and `b` avoids the body
+pub fn has_line_of_sight(
+    a: Vec3,
+    b: Vec3,
+    body_center: Vec3,
+    body_radius: Num,
+) -> bool {
+    let d = b - a;
+    let length_squared = d.length_squared();
+
+    // Closest point of the segment to the center
+    let t = if length_squared > 0.0 {
+        ((body_center - a).dot(d) / length_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let closest = a + t * d;
+
+    closest.distance(body_center) >= body_radius
+}
+
+/// The part of the segment between `a` and `b` that lies inside the body,
+/// as `(t_enter, t_exit)` parameters along the segment, `a + t * (b - a)`,
+/// clamped to [0, 1].
+///
+/// Returns `None` if the body doesn't block the segment.
+pub fn blocked_interval(
+    a: Vec3,
+    b: Vec3,
+    body_center: Vec3,
+    body_radius: Num,
+) -> Option<(Num, Num)> {
+    if has_line_of_sight(a, b, body_center, body_radius) {
+        return None;
+    }
+
+    let d = b - a;
+    let f = a - body_center;
+
+    // |f + t * d|² = r²
+    let qa = d.length_squared();
+    let qb = 2.0 * f.dot(d);
+    let qc = f.length_squared() - body_radius.powi(2);
+
+    if qa == 0.0 {
+        // A single point inside the body
+        return Some((0.0, 1.0));
+    }
+
+    let discriminant = (qb.powi(2) - 4.0 * qa * qc).max(0.0).sqrt();
+    let t_enter = (-qb - discriminant) / (2.0 * qa);
+    let t_exit = (-qb + discriminant) / (2.0 * qa);
+
+    Some((t_enter.clamp(0.0, 1.0), t_exit.clamp(0.0, 1.0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vec3;
+
+    const RADIUS: Num = 1.0;
+
+    #[test]
+    fn opposite_sides_are_blocked() {
+        let a = vec3(-2.0, 0.0, 0.0);
+        let b = vec3(2.0, 0.1, 0.0);
+
+        assert!(!has_line_of_sight(a, b, Vec3::ZERO, RADIUS));
+
+        let (enter, exit) = blocked_interval(a, b, Vec3::ZERO, RADIUS).unwrap();
+        assert!(enter > 0.2 && enter < 0.3, "{enter}");
+        assert!(exit > 0.7 && exit < 0.8, "{exit}");
+    }
+
+    #[test]
+    fn same_hemisphere_is_visible() {
+        let a = vec3(1.0, 2.0, 0.0);
+        let b = vec3(-1.0, 2.0, 0.5);
+
+        assert!(has_line_of_sight(a, b, Vec3::ZERO, RADIUS));
+        assert_eq!(blocked_interval(a, b, Vec3::ZERO, RADIUS), None);
+
+        // The body is beyond the end of the segment
+        let a = vec3(3.0, 0.0, 0.0);
+        let b = vec3(2.0, 0.0, 0.0);
+        assert!(has_line_of_sight(a, b, Vec3::ZERO, RADIUS));
+    }
+
+    #[test]
+    fn grazing_the_limb_is_not_blocked() {
+        let a = vec3(-2.0, 1.0, 0.0);
+        let b = vec3(2.0, 1.0, 0.0);
+
+        assert!(has_line_of_sight(a, b, Vec3::ZERO, RADIUS));
+        assert_eq!(blocked_interval(a, b, Vec3::ZERO, RADIUS), None);
+
+        // Slightly lower is blocked, around the tangent point
+        let a = vec3(-2.0, 0.999, 0.0);
+        let b = vec3(2.0, 0.999, 0.0);
+
+        assert!(!has_line_of_sight(a, b, Vec3::ZERO, RADIUS));
+        let (enter, exit) = blocked_interval(a, b, Vec3::ZERO, RADIUS).unwrap();
+        assert!(enter < 0.5 && exit > 0.5);
+        assert!(exit - enter < 0.05);
+    }
+
+    #[test]
+    fn endpoint_inside_the_body() {
+        let a = vec3(0.5, 0.0, 0.0);
+        let b = vec3(3.0, 0.0, 0.0);
+
+        let (enter, exit) = blocked_interval(a, b, Vec3::ZERO, RADIUS).unwrap();
+        assert_eq!(enter, 0.0);
+        assert!((exit - 0.2).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,639 @@
+//! Ready made perturbations for [`perturbed_ode_rhs`](super::perturbed_ode_rhs)
+//! and the timing of the secular J2 drifts.
+
+use super::occlusion::has_line_of_sight;
+use super::{standard_gravitational_parameter, Perturbation};
+use crate::constants::{
+    ASTRONOMICAL_UNIT, EARTH_MASS, J2000_OBLIQUITY, MOON_MASS,
+};
+use crate::math::wrap_angle;
+use crate::{vec3, KeplerianElements, Num, Vec3};
+
+/// Kepler equation tolerance of the position of the Sun
+const SUN_TOLERANCE: Num = 1e-5;
+
+/// Drift rate, relative to the one of an equatorial orbit, below which
+/// an angle is considered fixed. The rounding of cos(i) alone leaves
+/// a residual rate at the critical (or polar) inclination.
+const DRIFT_TOLERANCE: Num = 1e-5;
+
+/// Oblateness of the central body, the J2 zonal harmonic
+/// of a body symmetric around the z axis.
+///
+/// SRC: https://en.wikipedia.org/wiki/Geopotential_model
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct J2 {
+    pub j2: Num,
+    /// Equatorial radius of the central body
+    pub radius: Num,
+    /// Standard gravitational parameter of the central body
+    pub mu: Num,
+}
+
+impl J2 {
+    /// -3/2 J2 μ R² / r⁵ (x (1 - 5z²/r²), y (1 - 5z²/r²), z (3 - 5z²/r²))
+    pub fn acceleration(&self, position: Vec3) -> Vec3 {
+        let r = position.length();
+        let z2 = (position.z / r).powi(2);
+
+        -1.5 * self.j2 * self.mu * self.radius.powi(2) / r.powi(5)
+            * vec3(
+                position.x * (1.0 - 5.0 * z2),
+                position.y * (1.0 - 5.0 * z2),
+                position.z * (3.0 - 5.0 * z2),
+            )
+    }
+
+    pub fn perturbation(self) -> Perturbation {
+        Box::new(move |position, _, _| self.acceleration(position))
+    }
+}
+
+/// Atmospheric drag with an exponential atmosphere
+/// rotating with the body around the z axis.
+///
+/// SRC: https://en.wikipedia.org/wiki/Drag_equation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Drag {
+    /// Density at the reference radius, in kg/m³
+    pub reference_density: Num,
+    pub scale_height: Num,
+    /// Distance from the center of the body, not an altitude
+    pub reference_radius: Num,
+    /// Drag coefficient times the cross section over the mass, in m²/kg,
+    /// the inverse of the ballistic coefficient
+    pub cd_a_over_m: Num,
+    /// Angular velocity of the body and its atmosphere, in rad/s
+    pub body_rotation_rate: Num,
+}
+
+impl Drag {
+    /// Density of the atmosphere at the position
+    pub fn density(&self, position: Vec3) -> Num {
+        let height = position.length() - self.reference_radius;
+
+        self.reference_density * (-height / self.scale_height).exp()
+    }
+
+    /// -½ ρ v_rel |v_rel| CdA/m, with the velocity relative
+    /// to the rotating atmosphere, v_rel = v - ω × r
+    pub fn acceleration(&self, position: Vec3, velocity: Vec3) -> Vec3 {
+        let rotation = Vec3::Z * self.body_rotation_rate;
+        let relative = velocity - rotation.cross(position);
+
+        -0.5 * self.density(position)
+            * relative
+            * relative.length()
+            * self.cd_a_over_m
+    }
+
+    pub fn perturbation(self) -> Perturbation {
+        Box::new(move |position, velocity, _| {
+            self.acceleration(position, velocity)
+        })
+    }
+}
+
+/// Solar radiation pressure on a sphere (the cannonball model),
+/// pushing away from the Sun.
+///
+/// SRC: https://en.wikipedia.org/wiki/Radiation_pressure
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolarRadiationPressure {
+    /// Orbit of the Sun around the central body
+    pub sun_elements: KeplerianElements,
+    /// Mass the orbit of the Sun is propagated with
+    pub sun_mass: Num,
+    /// Radiation pressure at 1 AU from the Sun, ~4.56e-6 N/m²
+    pub pressure_at_1au: Num,
+    /// Reflectivity coefficient times the cross section over the mass,
+    /// in m²/kg
+    pub cr_a_over_m: Num,
+    /// Radius of the central body casting the shadow,
+    /// `None` to ignore the shadow
+    pub occulting_body_radius: Option<Num>,
+}
+
+impl SolarRadiationPressure {
+    pub fn sun_position(&self, epoch: Num) -> Vec3 {
+        self.sun_elements
+            .state_vectors_at_epoch(self.sun_mass, epoch, SUN_TOLERANCE)
+            .position
+    }
+
+    /// Acceleration at the position, zero in the shadow of the central
+    /// body, see [`has_line_of_sight`]
+    pub fn acceleration(&self, position: Vec3, epoch: Num) -> Vec3 {
+        let sun = self.sun_position(epoch);
+
+        if let Some(radius) = self.occulting_body_radius {
+            if !has_line_of_sight(position, sun, Vec3::ZERO, radius) {
+                return Vec3::ZERO;
+            }
+        }
+
+        let from_sun = position - sun;
+        let distance = from_sun.length();
+        let pressure =
+            self.pressure_at_1au * (ASTRONOMICAL_UNIT / distance).powi(2);
+
+        pressure * self.cr_a_over_m * from_sun / distance
+    }
+
+    pub fn perturbation(self) -> Perturbation {
+        Box::new(move |position, _, epoch| self.acceleration(position, epoch))
+    }
+}
+
+/// Kepler equation tolerance of the position of the third body
+const THIRD_BODY_TOLERANCE: Num = 1e-5;
+
+/// Point mass gravity of a third body, e.g. the Moon or the Sun,
+/// on an orbit around the central body.
+///
+/// The acceleration is the direct pull on the spacecraft minus the pull on
+/// the central body, which isn't inertial:
+/// μ₃ (r₃ₛ/|r₃ₛ|³ - r₃/|r₃|³), with r₃ₛ from the spacecraft to the body.
+///
+/// SRC: Vallado, Fundamentals of Astrodynamics and Applications,
+/// section 8.6.3
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThirdBody {
+    /// Orbit of the third body around the central body
+    pub elements: KeplerianElements,
+    /// Mass the orbit of the third body is propagated with
+    pub central_mass: Num,
+    /// Mass of the third body, its gravity is G times this
+    pub mass: Num,
+}
+
+impl ThirdBody {
+    /// The Moon on its mean orbit around the Earth, in the ecliptic
+    /// tilted by the obliquity around the x axis of an equatorial frame.
+    ///
+    /// The mean anomaly is zero at epoch zero.
+    pub fn moon() -> Self {
+        Self {
+            elements: KeplerianElements {
+                semi_major_axis: 384_400_000.0,
+                eccentricity: 0.0549,
+                inclination: J2000_OBLIQUITY,
+                ..KeplerianElements::default()
+            },
+            // The Moon orbits the Earth and the Moon combined
+            central_mass: EARTH_MASS + MOON_MASS,
+            mass: MOON_MASS,
+        }
+    }
+
+    pub fn position(&self, epoch: Num) -> Vec3 {
+        self.elements
+            .state_vectors_at_epoch(
+                self.central_mass,
+                epoch,
+                THIRD_BODY_TOLERANCE,
+            )
+            .position
+    }
+
+    /// Acceleration at the position.
+    ///
+    /// The direct term is singular at the position of the third body itself,
+    /// there it's left out and only the pull on the central body remains.
+    pub fn acceleration(&self, position: Vec3, epoch: Num) -> Vec3 {
+        let μ = standard_gravitational_parameter(self.mass);
+        let body = self.position(epoch);
+        let to_body = body - position;
+
+        let indirect = body / body.length().powi(3);
+        if to_body == Vec3::ZERO {
+            return -μ * indirect;
+        }
+
+        μ * (to_body / to_body.length().powi(3) - indirect)
+    }
+
+    pub fn perturbation(self) -> Perturbation {
+        Box::new(move |position, _, epoch| self.acceleration(position, epoch))
+    }
+}
+
+/// Next epoch, not before `after`, at which the right ascension of the
+/// ascending node drifting under J2 equals `target_raan`,
+/// see [`KeplerianElements::secular_raan_rate`].
+///
+/// Returns `None` when the node doesn't drift, e.g. on a polar orbit.
+pub fn epoch_at_raan(
+    elements: &KeplerianElements,
+    mass: Num,
+    j2: Num,
+    body_radius: Num,
+    target_raan: Num,
+    after: Num,
+) -> Option<Num> {
+    epoch_at_angle(
+        elements.right_ascension_of_the_ascending_node,
+        elements.epoch,
+        elements.secular_raan_rate(body_radius, j2, mass),
+        equatorial(elements).secular_raan_rate(body_radius, j2, mass),
+        target_raan,
+        after,
+    )
+}
+
+/// Next epoch, not before `after`, at which the argument of periapsis
+/// drifting under J2 equals `target_argp`,
+/// see [`KeplerianElements::secular_argument_of_periapsis_rate`].
+///
+/// Returns `None` when the periapsis doesn't drift,
+/// e.g. at the critical inclination.
+pub fn epoch_at_argp(
+    elements: &KeplerianElements,
+    mass: Num,
+    j2: Num,
+    body_radius: Num,
+    target_argp: Num,
+    after: Num,
+) -> Option<Num> {
+    epoch_at_angle(
+        elements.argument_of_periapsis,
+        elements.epoch,
+        elements.secular_argument_of_periapsis_rate(body_radius, j2, mass),
+        equatorial(elements).secular_argument_of_periapsis_rate(
+            body_radius,
+            j2,
+            mass,
+        ),
+        target_argp,
+        after,
+    )
+}
+
+/// Same orbit at zero inclination, where both J2 drifts are the fastest
+fn equatorial(elements: &KeplerianElements) -> KeplerianElements {
+    KeplerianElements {
+        inclination: 0.0,
+        ..*elements
+    }
+}
+
+/// Solves angle + rate * (t - epoch) ≡ target (mod 2π) for the smallest
+/// t not before `after`. `max_rate` is the scale of the rate, against
+/// which a vanishing one is detected.
+fn epoch_at_angle(
+    angle: Num,
+    epoch: Num,
+    rate: Num,
+    max_rate: Num,
+    target: Num,
+    after: Num,
+) -> Option<Num> {
+    if rate.abs() <= DRIFT_TOLERANCE * max_rate.abs() || !rate.is_finite() {
+        return None;
+    }
+
+    let angle_after = angle + rate * (after - epoch);
+    let remaining = if rate > 0.0 {
+        wrap_angle(target - angle_after)
+    } else {
+        wrap_angle(angle_after - target)
+    };
+
+    Some(after + remaining / rate.abs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::{osculating_history, secular_rates};
+    use crate::astro::{perturbed_ode_rhs, rk4_step, two_body_ode_rhs};
+    use crate::constants::{EARTH_J2, EARTH_RADIUS, SECONDS_PER_DAY, SUN_MASS};
+    use crate::math::angle_diff;
+    use crate::{KeplerianElements, StateVectors, PI, TWO_PI};
+
+    const EARTH_ROTATION_RATE: Num = 7.292_115e-5;
+    const STEPS_PER_PERIOD: usize = 300;
+    const TOLERANCE: Num = 1e-5;
+
+    fn drag(cd_a_over_m: Num) -> Drag {
+        // The 200 km band of the exponential atmosphere
+        Drag {
+            reference_density: 2.789e-10,
+            scale_height: 37_105.0,
+            reference_radius: EARTH_RADIUS + 200_000.0,
+            cd_a_over_m,
+            body_rotation_rate: EARTH_ROTATION_RATE,
+        }
+    }
+
+    fn orbit() -> KeplerianElements {
+        KeplerianElements {
+            semi_major_axis: EARTH_RADIUS + 200_000.0,
+            inclination: 0.9,
+            ..KeplerianElements::default()
+        }
+    }
+
+    /// States at every full revolution
+    fn propagate(
+        rhs: impl Fn(Num, &[Num; 6], &mut [Num; 6]),
+        revolutions: usize,
+    ) -> Vec<(Num, StateVectors)> {
+        let orbit = orbit();
+        let dt = orbit.period(EARTH_MASS) / STEPS_PER_PERIOD as Num;
+
+        let mut y = orbit
+            .state_vectors_at_epoch(EARTH_MASS, 0.0, TOLERANCE)
+            .as_array();
+        let mut samples = vec![(0.0, StateVectors::from_array(y))];
+
+        for step in 0..revolutions * STEPS_PER_PERIOD {
+            let t = step as Num * dt;
+            y = rk4_step(&rhs, t, y, dt);
+
+            if (step + 1) % STEPS_PER_PERIOD == 0 {
+                samples.push((t + dt, StateVectors::from_array(y)));
+            }
+        }
+
+        samples
+    }
+
+    #[test]
+    fn low_orbit_decays() {
+        // Loses about a kilometer per revolution
+        let rhs =
+            perturbed_ode_rhs(EARTH_MASS, vec![drag(0.01).perturbation()]);
+
+        let history = osculating_history(&propagate(rhs, 5), EARTH_MASS);
+
+        assert!(history.windows(2).all(|pair| {
+            pair[1].1.semi_major_axis < pair[0].1.semi_major_axis
+        }));
+    }
+
+    fn srp(occulting_body_radius: Option<Num>) -> SolarRadiationPressure {
+        SolarRadiationPressure {
+            // The Sun starts on the x axis
+            sun_elements: KeplerianElements {
+                semi_major_axis: ASTRONOMICAL_UNIT,
+                ..KeplerianElements::default()
+            },
+            sun_mass: SUN_MASS,
+            pressure_at_1au: 4.56e-6,
+            cr_a_over_m: 0.02,
+            occulting_body_radius,
+        }
+    }
+
+    #[test]
+    fn radiation_pressure_at_1au() {
+        let srp = srp(None);
+
+        let acceleration = srp.acceleration(Vec3::ZERO, 0.0);
+        let expected = srp.pressure_at_1au * srp.cr_a_over_m;
+
+        assert!((acceleration.length() / expected - 1.0).abs() < 1e-4);
+        assert!(acceleration.normalize().abs_diff_eq(-Vec3::X, 1e-5));
+
+        // Twice as far, a quarter of the pressure
+        let far = srp.acceleration(Vec3::X * -ASTRONOMICAL_UNIT, 0.0);
+        assert!((far.length() / expected - 0.25).abs() < 1e-4);
+    }
+
+    #[test]
+    fn radiation_pressure_switches_off_in_the_shadow() {
+        let shadowed = srp(Some(EARTH_RADIUS));
+        let orbit = orbit();
+        let r = orbit.semi_major_axis;
+
+        // Half width of the shadow seen from the orbit,
+        // around the direction away from the Sun
+        let shadow = (EARTH_RADIUS / r).asin();
+
+        for k in 0..360 {
+            let angle = (k as Num + 0.5).to_radians();
+            let position = r * Vec3::new(angle.cos(), angle.sin(), 0.0);
+
+            let from_anti_sun = position.angle_between(-Vec3::X);
+            if (from_anti_sun - shadow).abs() < 1e-2 {
+                continue;
+            }
+
+            let in_shadow = from_anti_sun < shadow;
+            let acceleration = shadowed.acceleration(position, 0.0);
+            assert_eq!(acceleration == Vec3::ZERO, in_shadow, "{k}°");
+        }
+
+        // Ignoring the shadow
+        let midnight = srp(None).acceleration(-r * Vec3::X, 0.0);
+        assert!(midnight.length() > 0.0);
+    }
+
+    #[test]
+    fn no_drag_is_two_body() {
+        let rhs = perturbed_ode_rhs(EARTH_MASS, vec![drag(0.0).perturbation()]);
+
+        assert_eq!(
+            propagate(rhs, 2),
+            propagate(two_body_ode_rhs(EARTH_MASS), 2)
+        );
+    }
+
+    #[test]
+    fn drag_opposes_the_relative_velocity() {
+        let drag = drag(0.01);
+        let position = Vec3::new(EARTH_RADIUS + 200_000.0, 0.0, 0.0);
+
+        // Co-rotating with the atmosphere
+        let velocity = Vec3::new(0.0, EARTH_ROTATION_RATE * position.x, 0.0);
+        assert_eq!(drag.acceleration(position, velocity), Vec3::ZERO);
+
+        let velocity = Vec3::new(0.0, 7_800.0, 100.0);
+        let acceleration = drag.acceleration(position, velocity);
+        assert!(acceleration.dot(velocity) < 0.0);
+
+        assert!(drag.density(position) == drag.reference_density);
+        assert!(drag.density(position * 1.01) < drag.reference_density);
+    }
+
+    #[test]
+    fn third_body_at_the_central_body_and_at_itself() {
+        let moon = ThirdBody::moon();
+        let epoch = 100_000.0;
+
+        // The central body falls with the same acceleration
+        assert_eq!(moon.acceleration(Vec3::ZERO, epoch), Vec3::ZERO);
+
+        // Singular, only the indirect term is left
+        let body = moon.position(epoch);
+        let acceleration = moon.acceleration(body, epoch);
+        assert!(acceleration.is_finite());
+        assert!(acceleration.dot(body) < 0.0);
+    }
+
+    #[test]
+    fn moon_regresses_the_node_of_geo() {
+        // The Moon in the equatorial plane, where the secular rate is simple
+        let moon = ThirdBody {
+            elements: KeplerianElements {
+                inclination: 0.0,
+                ..ThirdBody::moon().elements
+            },
+            ..ThirdBody::moon()
+        };
+        let geo = KeplerianElements {
+            semi_major_axis: 42_164_000.0,
+            inclination: 0.2,
+            right_ascension_of_the_ascending_node: 1.0,
+            ..KeplerianElements::default()
+        };
+
+        let rhs = perturbed_ode_rhs(EARTH_MASS, vec![moon.perturbation()]);
+        let dt = geo.period(EARTH_MASS) / 100.0;
+        let month = 27.3 * SECONDS_PER_DAY;
+
+        let mut y = geo
+            .state_vectors_at_epoch(EARTH_MASS, 0.0, TOLERANCE)
+            .as_array();
+        let mut samples = Vec::new();
+        let mut t = 0.0;
+        while t < month {
+            samples.push((t, StateVectors::from_array(y)));
+            y = rk4_step(&rhs, t, y, dt);
+            t += dt;
+        }
+
+        let rates = secular_rates(&osculating_history(&samples, EARTH_MASS));
+        let fitted = rates.right_ascension_of_the_ascending_node;
+
+        // -¾ μ₃/(a₃³ n) cos i (1 - e₃²)^-3/2
+        let μ3 = standard_gravitational_parameter(moon.mass);
+        let n = TWO_PI / geo.period(EARTH_MASS);
+        let e3 = moon.elements.eccentricity;
+        let expected = -0.75 * μ3 / moon.elements.semi_major_axis.powi(3) / n
+            * geo.inclination.cos()
+            / (1.0 - e3 * e3).powf(1.5);
+
+        // Around -0.1° over the month
+        assert!(fitted < 0.0, "{fitted}");
+        assert!((fitted / expected - 1.0).abs() < 0.3, "{fitted} {expected}");
+    }
+
+    fn molniya() -> KeplerianElements {
+        KeplerianElements {
+            semi_major_axis: 26_600_000.0,
+            eccentricity: 0.74,
+            // Off the critical inclination, so the periapsis drifts
+            inclination: 1.2,
+            right_ascension_of_the_ascending_node: 1.0,
+            argument_of_periapsis: 4.0,
+            epoch: 1_000.0,
+            ..KeplerianElements::default()
+        }
+    }
+
+    #[test]
+    fn epoch_at_drifting_raan_and_argp() {
+        let orbit = molniya();
+        let after = 5_000_000.0;
+
+        let rate = orbit.secular_raan_rate(EARTH_RADIUS, EARTH_J2, EARTH_MASS);
+        let t = epoch_at_raan(
+            &orbit,
+            EARTH_MASS,
+            EARTH_J2,
+            EARTH_RADIUS,
+            0.5,
+            after,
+        )
+        .unwrap();
+        assert!(t >= after);
+        // The earliest solution, within one full drift cycle
+        assert!((t - after) * rate.abs() < TWO_PI);
+        let raan = orbit.right_ascension_of_the_ascending_node
+            + rate * (t - orbit.epoch);
+        assert!(angle_diff(raan, 0.5).abs() < 1e-4, "{raan}");
+
+        let rate = orbit.secular_argument_of_periapsis_rate(
+            EARTH_RADIUS,
+            EARTH_J2,
+            EARTH_MASS,
+        );
+        // Apoapsis over the northern hemisphere
+        let t = epoch_at_argp(
+            &orbit,
+            EARTH_MASS,
+            EARTH_J2,
+            EARTH_RADIUS,
+            4.71,
+            0.0,
+        )
+        .unwrap();
+        assert!(t >= 0.0 && t * rate.abs() < TWO_PI);
+        let argp = orbit.argument_of_periapsis + rate * (t - orbit.epoch);
+        assert!(angle_diff(argp, 4.71).abs() < 1e-4, "{argp}");
+    }
+
+    #[test]
+    fn no_drift_never_reaches_the_target() {
+        let orbit = molniya();
+
+        assert_eq!(
+            epoch_at_raan(&orbit, EARTH_MASS, 0.0, EARTH_RADIUS, 0.5, 0.0),
+            None
+        );
+        assert_eq!(
+            epoch_at_argp(&orbit, EARTH_MASS, 0.0, EARTH_RADIUS, 0.5, 0.0),
+            None
+        );
+    }
+
+    #[test]
+    fn no_drift_at_the_critical_and_polar_inclinations() {
+        let critical = (1.0 / Num::sqrt(5.0)).acos();
+
+        for inclination in [critical, PI - critical] {
+            let orbit = KeplerianElements {
+                inclination,
+                ..molniya()
+            };
+
+            assert_eq!(
+                epoch_at_argp(
+                    &orbit,
+                    EARTH_MASS,
+                    EARTH_J2,
+                    EARTH_RADIUS,
+                    0.5,
+                    0.0
+                ),
+                None
+            );
+        }
+
+        let polar = KeplerianElements {
+            inclination: PI / 2.0,
+            ..molniya()
+        };
+        assert_eq!(
+            epoch_at_raan(&polar, EARTH_MASS, EARTH_J2, EARTH_RADIUS, 0.5, 0.0),
+            None
+        );
+
+        // Slightly off, the drift is slow but still there
+        let near_critical = KeplerianElements {
+            inclination: critical + 1e-3,
+            ..molniya()
+        };
+        assert!(epoch_at_argp(
+            &near_critical,
+            EARTH_MASS,
+            EARTH_J2,
+            EARTH_RADIUS,
+            0.5,
+            0.0
+        )
+        .is_some());
+    }
+}
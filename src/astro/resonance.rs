@@ -0,0 +1,57 @@
+//! Mean-motion resonances with a perturbing body.
+//!
+//! A p:q resonance is an orbit completing p revolutions
+//! while the perturber completes q.
+
+use crate::Num;
+
+/// The main Kirkwood gaps of the asteroid belt: 3:1, 5:2, 7:3 and 2:1
+/// with Jupiter
+pub const KIRKWOOD_RATIOS: [(u32, u32); 4] = [(3, 1), (5, 2), (7, 3), (2, 1)];
+
+/// Semi-major axis of the p:q resonance with a perturber orbiting
+/// at `perturber_sma`, neglecting the masses of both bodies.
+///
+/// From Kepler's third law: a = a_p (q / p)^(2/3)
+pub fn resonant_semi_major_axis(perturber_sma: Num, p: u32, q: u32) -> Num {
+    perturber_sma * (q as Num / p as Num).powf(2.0 / 3.0)
+}
+
+/// Semi-major axes of the Kirkwood gaps for the given p:q ratios,
+/// e.g. [`KIRKWOOD_RATIOS`]
+pub fn kirkwood_gap_semi_major_axes(
+    jupiter_sma: Num,
+    ratios: &[(u32, u32)],
+) -> Vec<Num> {
+    ratios
+        .iter()
+        .map(|&(p, q)| resonant_semi_major_axis(jupiter_sma, p, q))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JUPITER_SMA_AU: Num = 5.2026;
+
+    #[test]
+    fn classical_kirkwood_gaps() {
+        let gaps =
+            kirkwood_gap_semi_major_axes(JUPITER_SMA_AU, &KIRKWOOD_RATIOS);
+
+        for (gap, expected) in gaps.iter().zip([2.50, 2.82, 2.96, 3.28]) {
+            assert!((gap - expected).abs() < 0.01, "{gaps:?}");
+        }
+    }
+
+    #[test]
+    fn resonances_scale_with_the_perturber() {
+        assert_eq!(resonant_semi_major_axis(1.0, 1, 1), 1.0);
+
+        // Outer resonances lie beyond the perturber, Pluto is in 2:3
+        // with Neptune
+        let pluto = resonant_semi_major_axis(30.07, 2, 3);
+        assert!((pluto - 39.4).abs() < 0.1, "{pluto}");
+    }
+}
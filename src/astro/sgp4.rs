@@ -0,0 +1,505 @@
+//! Conversions between osculating and mean elements, e.g. for SGP4.
+//!
+//! SGP4 and TLEs work with mean elements, i.e. elements with the periodic
+//! variations caused by the oblateness of the central body averaged out.
+//!
+//! The mapping is Brouwer's first order J2 theory, with Lyddane's
+//! treatment of small eccentricities and inclinations, so it's nonsingular
+//! for circular and equatorial orbits. It covers the short-period and
+//! the long-period terms of all six elements and is accurate to the order
+//! of J2². Like the long-period terms of
+//! [`KeplerianElements::long_period_correction_j2`] it's singular
+//! at the critical inclination (cos²i = 1/5).
+//!
+//! TLEs list the Kozai mean motion instead of the Brouwer one used here,
+//! see [`kozai_mean_motion`].
+//!
+//! SRC: Schaub, H. and Junkins, J. L., Analytical Mechanics of Space
+//! Systems, Appendix F
+
+use crate::math::angle_diff;
+use crate::state_vectors::EQUATORIAL_TOLERANCE;
+use crate::{KeplerianElements, MeanMotion, Num};
+
+use super::standard_gravitational_parameter;
+
+const TOLERANCE: Num = 1e-6;
+
+/// Fixed point iterations of the Kozai mean motion
+const KOZAI_STEPS: usize = 5;
+
+/// Brouwer-Lyddane mapping, `sign` is 1 from mean to osculating elements
+/// and -1 from osculating to mean elements
+fn brouwer_lyddane(
+    elements: &KeplerianElements,
+    body_radius: Num,
+    j2: Num,
+    mass: Num,
+    sign: Num,
+) -> KeplerianElements {
+    let a = elements.semi_major_axis;
+    let e = elements.eccentricity;
+    let i = elements.inclination;
+    let Ω = elements.right_ascension_of_the_ascending_node;
+    let ω = elements.argument_of_periapsis;
+    let M = elements.mean_anomaly_at_epoch;
+    let f = elements.true_anomaly_at_epoch(mass, elements.epoch, TOLERANCE);
+
+    let γ2 = sign * 0.5 * j2 * (body_radius / a).powi(2);
+    let η = (1.0 - e.powi(2)).sqrt();
+    let γ2p = γ2 / η.powi(4);
+    let a_r = (1.0 + e * f.cos()) / η.powi(2);
+
+    let θ = i.cos();
+    let θ2 = θ.powi(2);
+    // Resonance denominator, zero at the critical inclination
+    let d = 1.0 - 5.0 * θ2;
+
+    let (sin_f, cos_f) = f.sin_cos();
+    let (sin_2ω, cos_2ω) = (2.0 * ω).sin_cos();
+    let (sin_2ωf, cos_2ωf) = (2.0 * ω + f).sin_cos();
+    let (sin_2ω2f, cos_2ω2f) = (2.0 * ω + 2.0 * f).sin_cos();
+    let (sin_2ω3f, cos_2ω3f) = (2.0 * ω + 3.0 * f).sin_cos();
+
+    let equation_of_center = angle_diff(f, M) + e * sin_f;
+    let long_period = 1.0 - 11.0 * θ2 - 40.0 * θ2.powi(2) / d;
+
+    let ap = a + a
+        * γ2
+        * ((3.0 * θ2 - 1.0) * (a_r.powi(3) - η.powi(-3))
+            + 3.0 * (1.0 - θ2) * a_r.powi(3) * cos_2ω2f);
+
+    let δe_long = γ2p / 8.0 * e * η.powi(2) * long_period * cos_2ω;
+
+    let radial =
+        3.0 * cos_f + 3.0 * e * cos_f.powi(2) + e.powi(2) * cos_f.powi(3);
+    let δe = η.powi(2) / 2.0
+        * (γ2
+            * ((3.0 * θ2 - 1.0) / η.powi(6)
+                * (e * η + e / (1.0 + η) + radial)
+                + 3.0 * (1.0 - θ2) / η.powi(6) * (e + radial) * cos_2ω2f)
+            - γ2p * (1.0 - θ2) * (3.0 * cos_2ωf + cos_2ω3f))
+        + δe_long;
+
+    // Undefined on an equatorial orbit, where it vanishes with the node
+    let δi_long = if i.sin().abs() < EQUATORIAL_TOLERANCE {
+        0.0
+    } else {
+        -e * δe_long / (η.powi(2) * i.tan())
+    };
+    let δi = δi_long
+        + γ2p / 2.0
+            * θ
+            * i.sin()
+            * (3.0 * cos_2ω2f + 3.0 * e * cos_2ωf + e * cos_2ω3f);
+
+    let short_period_sines = 3.0 * sin_2ω2f + 3.0 * e * sin_2ωf + e * sin_2ω3f;
+
+    let δΩ = -γ2p / 8.0
+        * e.powi(2)
+        * θ
+        * (11.0 + 80.0 * θ2 / d + 200.0 * θ2.powi(2) / d.powi(2))
+        * sin_2ω
+        - γ2p / 2.0 * θ * (6.0 * equation_of_center - short_period_sines);
+
+    // M + ω + Ω is well defined even where M, ω or Ω alone isn't
+    let longitude = M + ω + Ω + γ2p / 8.0 * η.powi(3) * long_period * sin_2ω
+        - γ2p / 16.0
+            * (2.0 + e.powi(2)
+                - 11.0 * (2.0 + 3.0 * e.powi(2)) * θ2
+                - 40.0 * (2.0 + 5.0 * e.powi(2)) * θ2.powi(2) / d
+                - 400.0 * e.powi(2) * θ2.powi(3) / d.powi(2))
+            * sin_2ω
+        + γ2p / 4.0
+            * (-6.0 * d * equation_of_center
+                + (3.0 - 5.0 * θ2) * short_period_sines)
+        + δΩ;
+
+    // e * δM, finite for circular orbits
+    let a_r_η2 = (a_r * η).powi(2);
+    let e_δM = γ2p / 8.0 * e * η.powi(3) * long_period * sin_2ω
+        - γ2p / 4.0
+            * η.powi(3)
+            * (2.0 * (3.0 * θ2 - 1.0) * (a_r_η2 + a_r + 1.0) * sin_f
+                + 3.0
+                    * (1.0 - θ2)
+                    * ((-a_r_η2 - a_r + 1.0) * sin_2ωf
+                        + (a_r_η2 + a_r + 1.0 / 3.0) * sin_2ω3f));
+
+    // The eccentricity vector rotated by the mean anomaly
+    let (sin_M, cos_M) = M.sin_cos();
+    let d1 = (e + δe) * sin_M + e_δM * cos_M;
+    let d2 = (e + δe) * cos_M - e_δM * sin_M;
+    let Mp = d1.atan2(d2);
+    let ep = d1.hypot(d2);
+
+    // The node vector scaled by sin(i / 2)
+    let (sin_half_i, cos_half_i) = (0.5 * i).sin_cos();
+    let (sin_Ω, cos_Ω) = Ω.sin_cos();
+    let s = sin_half_i + cos_half_i * δi / 2.0;
+    let d3 = s * sin_Ω + sin_half_i * δΩ * cos_Ω;
+    let d4 = s * cos_Ω - sin_half_i * δΩ * sin_Ω;
+    let Ωp = d3.atan2(d4);
+    let ip = 2.0 * d3.hypot(d4).min(1.0).asin();
+
+    KeplerianElements::new(
+        ap,
+        ep,
+        ip,
+        Ωp,
+        longitude - Mp - Ωp,
+        Mp,
+        elements.epoch,
+    )
+    .wrapped()
+}
+
+/// Adds the short-period and long-period J2 variations to mean elements.
+///
+/// `j2` is the second zonal harmonic of the central body,
+/// `body_radius` its equatorial radius.
+pub fn mean_to_osculating_elements(
+    elements: &KeplerianElements,
+    body_radius: Num,
+    j2: Num,
+    mass: Num,
+) -> KeplerianElements {
+    brouwer_lyddane(elements, body_radius, j2, mass, 1.0)
+}
+
+/// Removes the short-period and long-period J2 variations
+/// from osculating elements, the inverse of
+/// [`mean_to_osculating_elements`] to the first order in J2.
+pub fn osculating_to_mean_elements(
+    elements: &KeplerianElements,
+    body_radius: Num,
+    j2: Num,
+    mass: Num,
+) -> KeplerianElements {
+    brouwer_lyddane(elements, body_radius, j2, mass, -1.0)
+}
+
+/// Brouwer mean motion SGP4 recovers from the Kozai mean motion of a TLE
+///
+/// SRC: Vallado, D. A. et al. (2006), "Revisiting Spacetrack Report #3",
+/// the `initl` routine
+pub fn brouwer_mean_motion(
+    kozai: MeanMotion,
+    eccentricity: Num,
+    inclination: Num,
+    body_radius: Num,
+    j2: Num,
+    mass: Num,
+) -> MeanMotion {
+    let μ = standard_gravitational_parameter(mass);
+    let η = (1.0 - eccentricity.powi(2)).sqrt();
+
+    // In the units of the body radius
+    let ak = kozai.semi_major_axis(μ) / body_radius;
+    let d1 = 0.75 * j2 * (3.0 * inclination.cos().powi(2) - 1.0) / η.powi(3);
+
+    let δ = d1 / ak.powi(2);
+    let adel =
+        ak * (1.0 - δ.powi(2) - δ * (1.0 / 3.0 + 134.0 * δ.powi(2) / 81.0));
+    let δ = d1 / adel.powi(2);
+
+    MeanMotion::from_rad_per_sec(kozai.rad_per_sec() / (1.0 + δ))
+}
+
+/// Kozai mean motion to list in a TLE for the mean elements,
+/// e.g. from [`osculating_to_mean_elements`].
+///
+/// Inverts [`brouwer_mean_motion`] iteratively.
+pub fn kozai_mean_motion(
+    elements: &KeplerianElements,
+    body_radius: Num,
+    j2: Num,
+    mass: Num,
+) -> MeanMotion {
+    let μ = standard_gravitational_parameter(mass);
+    let brouwer = MeanMotion::from_semi_major_axis(elements.semi_major_axis, μ);
+
+    let mut kozai = brouwer;
+    for _ in 0..KOZAI_STEPS {
+        let recovered = brouwer_mean_motion(
+            kozai,
+            elements.eccentricity,
+            elements.inclination,
+            body_radius,
+            j2,
+            mass,
+        );
+
+        kozai = MeanMotion::from_rad_per_sec(
+            kozai.rad_per_sec() * brouwer.rad_per_sec()
+                / recovered.rad_per_sec(),
+        );
+    }
+
+    kozai
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::astro::perturbations::J2;
+    use crate::astro::{perturbed_ode_rhs, rk4_step};
+    use crate::constants::{EARTH_J2, EARTH_MASS, EARTH_RADIUS, G};
+    use crate::{vec3, StateVectors, TWO_PI};
+
+    const ELEMENTS: KeplerianElements = KeplerianElements {
+        eccentricity: 0.01,
+        semi_major_axis: 7_000_000.0,
+        inclination: 0.9,
+        right_ascension_of_the_ascending_node: 0.3,
+        argument_of_periapsis: 1.0,
+        mean_anomaly_at_epoch: 0.5,
+        epoch: 0.0,
+    };
+
+    fn assert_angle_eq(a: Num, b: Num, tolerance: Num) {
+        assert!(angle_diff(a, b).abs() < tolerance, "{a} != {b}");
+    }
+
+    #[test]
+    fn round_trip() {
+        for elements in [
+            ELEMENTS,
+            KeplerianElements {
+                eccentricity: 0.2,
+                inclination: 0.1,
+                ..ELEMENTS
+            },
+            KeplerianElements {
+                eccentricity: 0.0,
+                inclination: 0.0,
+                ..ELEMENTS
+            },
+        ] {
+            let osculating = mean_to_osculating_elements(
+                &elements,
+                EARTH_RADIUS,
+                EARTH_J2,
+                EARTH_MASS,
+            );
+            let mean = osculating_to_mean_elements(
+                &osculating,
+                EARTH_RADIUS,
+                EARTH_J2,
+                EARTH_MASS,
+            );
+
+            // The first order theory leaves errors in the order of J2²
+            assert!(
+                (mean.semi_major_axis - elements.semi_major_axis).abs() < 100.0
+            );
+            assert!((mean.eccentricity - elements.eccentricity).abs() < 1e-5);
+            assert!((mean.inclination - elements.inclination).abs() < 1e-5);
+            assert_angle_eq(
+                mean.right_ascension_of_the_ascending_node
+                    + mean.argument_of_periapsis
+                    + mean.mean_anomaly_at_epoch,
+                elements.right_ascension_of_the_ascending_node
+                    + elements.argument_of_periapsis
+                    + elements.mean_anomaly_at_epoch,
+                1e-5,
+            );
+        }
+    }
+
+    /// Over an orbit a, e and i stay constant and Ω, ω and M drift
+    /// at their secular rates, the osculating elements oscillate around them
+    #[test]
+    fn mean_elements_follow_the_secular_drift() {
+        let μ = standard_gravitational_parameter(EARTH_MASS);
+        let j2 = J2 {
+            j2: EARTH_J2,
+            radius: EARTH_RADIUS,
+            mu: μ,
+        };
+        let rhs = perturbed_ode_rhs(EARTH_MASS, vec![j2.perturbation()]);
+
+        let initial = KeplerianElements {
+            eccentricity: 0.1,
+            ..ELEMENTS
+        };
+        let period = initial.period(EARTH_MASS);
+        let steps = 600;
+        let dt = period / steps as Num;
+
+        let mut y = initial
+            .state_vectors_at_epoch(EARTH_MASS, 0.0, TOLERANCE)
+            .as_array();
+
+        let mut osculating = vec![];
+        let mut mean = vec![];
+
+        for step in 0..=steps {
+            let t = step as Num * dt;
+
+            let elements =
+                StateVectors::from_array(y).to_elements(EARTH_MASS, t);
+            mean.push(osculating_to_mean_elements(
+                &elements,
+                EARTH_RADIUS,
+                EARTH_J2,
+                EARTH_MASS,
+            ));
+            osculating.push(elements);
+
+            y = rk4_step(&rhs, t, y, dt);
+        }
+
+        let first = mean[0];
+        let rates = [
+            first.secular_raan_rate(EARTH_RADIUS, EARTH_J2, EARTH_MASS),
+            first.secular_argument_of_periapsis_rate(
+                EARTH_RADIUS,
+                EARTH_J2,
+                EARTH_MASS,
+            ),
+            TWO_PI / first.period(EARTH_MASS)
+                + first.secular_mean_anomaly_rate(
+                    EARTH_RADIUS,
+                    EARTH_J2,
+                    EARTH_MASS,
+                ),
+        ];
+
+        // Deviation from the first sample and the secular drift
+        let deviations = |elements: &[KeplerianElements]| {
+            let mut max = [0.0; 6];
+            for (step, e) in elements.iter().enumerate() {
+                let t = step as Num * dt;
+                let angles = [
+                    e.right_ascension_of_the_ascending_node,
+                    e.argument_of_periapsis,
+                    e.mean_anomaly_at_epoch,
+                ];
+                let initial = [
+                    first.right_ascension_of_the_ascending_node,
+                    first.argument_of_periapsis,
+                    first.mean_anomaly_at_epoch,
+                ];
+
+                let deviation = [
+                    e.semi_major_axis / first.semi_major_axis - 1.0,
+                    e.eccentricity - first.eccentricity,
+                    e.inclination - first.inclination,
+                    angle_diff(angles[0], initial[0] + rates[0] * t),
+                    angle_diff(angles[1], initial[1] + rates[1] * t),
+                    angle_diff(angles[2], initial[2] + rates[2] * t),
+                ];
+
+                for (max, deviation) in max.iter_mut().zip(deviation) {
+                    *max = Num::max(*max, deviation.abs());
+                }
+            }
+            max
+        };
+
+        let mean = deviations(&mean);
+        let osculating = deviations(&osculating);
+
+        for (mean, osculating) in mean.iter().zip(osculating) {
+            assert!(*mean < 0.1 * osculating, "{mean} vs {osculating}");
+        }
+    }
+
+    /// Case 88888 of the SGP4 verification set, the near circular orbit of
+    /// Spacetrack Report #3, with the WGS-72 constants SGP4 uses
+    ///
+    /// SRC: Vallado, D. A. et al. (2006), "Revisiting Spacetrack Report #3",
+    /// `SGP4-VER.TLE` and `tcppver.out`
+    #[test]
+    #[allow(clippy::excessive_precision)]
+    fn published_sgp4_mean_elements() {
+        const MU: Num = 3.986_008e14;
+        const RADIUS: Num = 6_378_135.0;
+        const J2: Num = 0.001_082_616;
+        const J3: Num = -0.000_002_538_81;
+        let mass = MU / G;
+
+        // 2 88888  72.8435 115.9689 0086731  52.6988 110.5714 16.05824518
+        let kozai = MeanMotion::from_rev_per_day(16.058_245_18);
+        let eccentricity = 0.008_673_1;
+        let inclination = (72.8435 as Num).to_radians();
+        let brouwer = brouwer_mean_motion(
+            kozai,
+            eccentricity,
+            inclination,
+            RADIUS,
+            J2,
+            mass,
+        );
+        let tle = KeplerianElements {
+            semi_major_axis: brouwer.semi_major_axis(MU),
+            eccentricity,
+            inclination,
+            right_ascension_of_the_ascending_node: (115.9689 as Num)
+                .to_radians(),
+            argument_of_periapsis: (52.6988 as Num).to_radians(),
+            mean_anomaly_at_epoch: (110.5714 as Num).to_radians(),
+            epoch: 0.0,
+        };
+
+        // The SGP4 state at the epoch of the TLE
+        let sgp4 = StateVectors::new(
+            1e3 * vec3(2_328.970_489_51, -5_995.220_764_16, 1_719.970_672_61),
+            1e3 * vec3(2.912_072_30, -0.983_415_46, -7.090_817_03),
+        );
+        let osculating = sgp4.to_elements(mass, 0.0);
+        let mean = osculating_to_mean_elements(&osculating, RADIUS, J2, mass);
+
+        // SGP4 adds the long-period J3 term to e·sin(ω)
+        let p = tle.semi_major_axis * (1.0 - eccentricity.powi(2));
+        let j3_term = -0.5 * J3 / J2 * inclination.sin() * RADIUS / p;
+
+        let errors = |elements: &KeplerianElements| {
+            let (sin_ω, cos_ω) = elements.argument_of_periapsis.sin_cos();
+            let (tle_sin_ω, tle_cos_ω) = tle.argument_of_periapsis.sin_cos();
+
+            [
+                (elements.semi_major_axis - tle.semi_major_axis).abs(),
+                (elements.eccentricity * cos_ω - eccentricity * tle_cos_ω)
+                    .abs(),
+                (elements.eccentricity * sin_ω
+                    - eccentricity * tle_sin_ω
+                    - j3_term)
+                    .abs(),
+                (elements.inclination - tle.inclination).abs(),
+                angle_diff(
+                    elements.right_ascension_of_the_ascending_node,
+                    tle.right_ascension_of_the_ascending_node,
+                )
+                .abs(),
+                angle_diff(
+                    elements.argument_of_periapsis
+                        + elements.mean_anomaly_at_epoch,
+                    tle.argument_of_periapsis + tle.mean_anomaly_at_epoch,
+                )
+                .abs(),
+            ]
+        };
+
+        // The osculating elements are off by several times the tolerances
+        let tolerances = [100.0, 2e-5, 2e-5, 1e-5, 3e-5, 5e-5];
+        for (mean, (osculating, tolerance)) in errors(&mean)
+            .into_iter()
+            .zip(errors(&osculating).into_iter().zip(tolerances))
+        {
+            assert!(mean < tolerance, "{mean} >= {tolerance}");
+            assert!(osculating > 3.0 * tolerance);
+        }
+
+        let mean = KeplerianElements {
+            semi_major_axis: mean.semi_major_axis,
+            ..tle
+        };
+        let kozai_of_mean = kozai_mean_motion(&mean, RADIUS, J2, mass);
+        assert!(
+            (kozai_of_mean.rad_per_sec() / kozai.rad_per_sec() - 1.0).abs()
+                < 2e-5
+        );
+    }
+}
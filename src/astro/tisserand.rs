@@ -0,0 +1,223 @@
+//! Tisserand's criterion for chaining gravity assists.
+//!
+//! An unpowered flyby of a body on a circular orbit keeps the Tisserand
+//! parameter with respect to that body constant:
+//! T = a_p / a + 2 sqrt(a / a_p (1 - e²)) cos i
+//!
+//! The inclination is measured from the orbital plane of the body.
+//!
+//! SRC: https://en.wikipedia.org/wiki/Tisserand%27s_parameter
+
+use crate::{KeplerianElements, Num, PI};
+
+/// Tisserand parameter of the orbit with respect to a body
+/// on a circular orbit of radius `body_sma`
+pub fn tisserand_parameter(elements: &KeplerianElements, body_sma: Num) -> Num {
+    let a = elements.semi_major_axis;
+    let e = elements.eccentricity;
+
+    body_sma / a
+        + 2.0
+            * (a / body_sma * (1.0 - e.powi(2))).sqrt()
+            * elements.inclination.cos()
+}
+
+/// Whether the orbit reaches the radius of the body's orbit,
+/// so that an encounter is possible at all
+pub fn crosses_body_orbit(elements: &KeplerianElements, body_sma: Num) -> bool {
+    let a = elements.semi_major_axis;
+    let e = elements.eccentricity;
+
+    a * (1.0 - e) <= body_sma && body_sma <= a * (1.0 + e)
+}
+
+/// Coplanar orbits with the Tisserand parameter `t_parameter`
+/// with respect to a body on a circular orbit of radius `reference_sma`,
+/// as (semi-major axis, eccentricity) pairs.
+///
+/// All of them share the same hyperbolic excess speed at the body,
+/// v∞ = sqrt(3 - T) in units of the orbital speed of the body, so the curve
+/// is swept by the angle between v∞ and the velocity of the body
+/// at `n_points` evenly spaced angles from 0 to π.
+/// Unbound orbits are left out.
+pub fn isogam_curve(
+    t_parameter: Num,
+    reference_sma: Num,
+    n_points: usize,
+) -> Vec<(Num, Num)> {
+    let v_inf = (3.0 - t_parameter).max(0.0).sqrt();
+    let last = n_points.saturating_sub(1).max(1) as Num;
+
+    (0..n_points)
+        .filter_map(|k| {
+            let (sin, cos) = (PI * k as Num / last).sin_cos();
+
+            // Heliocentric velocity at the body, in units where
+            // its radius and speed are both 1
+            let transverse = 1.0 + v_inf * cos;
+            let radial = v_inf * sin;
+            let v2 = transverse.powi(2) + radial.powi(2);
+
+            if v2 >= 2.0 {
+                return None;
+            }
+
+            let a = 1.0 / (2.0 - v2);
+            let e = (1.0 - transverse.powi(2) / a).max(0.0).sqrt();
+
+            Some((a * reference_sma, e))
+        })
+        .collect()
+}
+
+/// Whether every orbit of the sequence can follow the previous one
+/// by a gravity assist at a body on a circular orbit of radius `body_sma`.
+///
+/// All orbits have to cross the orbit of the body and consecutive ones
+/// have to share the Tisserand parameter within `tolerance`.
+pub fn sequence_is_connected(
+    orbits: &[&KeplerianElements],
+    body_sma: Num,
+    tolerance: Num,
+) -> bool {
+    orbits
+        .iter()
+        .all(|orbit| crosses_body_orbit(orbit, body_sma))
+        && orbits.windows(2).all(|pair| {
+            let t1 = tisserand_parameter(pair[0], body_sma);
+            let t2 = tisserand_parameter(pair[1], body_sma);
+
+            (t1 - t2).abs() <= tolerance
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::astro::{flyby::flyby, standard_gravitational_parameter};
+    use crate::constants::{ASTRONOMICAL_UNIT, SUN_MASS};
+    use crate::{StateVectors, Vec3};
+
+    const JUPITER_SMA: Num = 5.203 * ASTRONOMICAL_UNIT;
+    const JUPITER_MU: Num = 1.266_865_3e17;
+    const SATURN_SMA: Num = 9.537 * ASTRONOMICAL_UNIT;
+    const SATURN_MU: Num = 3.793_12e16;
+
+    /// Gravity assist at a body on a circular orbit, at the outbound
+    /// crossing of its orbit, turning the trajectory within the ecliptic
+    /// the way that gains the most energy while staying bound
+    fn assist(
+        orbit: &KeplerianElements,
+        body_sma: Num,
+        body_mu: Num,
+        periapsis_radius: Num,
+    ) -> KeplerianElements {
+        let (v, _) =
+            orbit.true_anomalies_at_radius(SUN_MASS, body_sma).unwrap();
+
+        let position = orbit.position_at_true_anomaly(SUN_MASS, v);
+        let velocity = orbit.velocity_at_true_anomaly(SUN_MASS, v);
+
+        let μ = standard_gravitational_parameter(SUN_MASS);
+        let body_velocity =
+            (μ / body_sma).sqrt() * Vec3::Z.cross(position).normalize();
+
+        [0.0, PI]
+            .map(|orientation| {
+                let result = flyby(
+                    velocity - body_velocity,
+                    body_mu,
+                    periapsis_radius,
+                    orientation,
+                );
+
+                StateVectors::new(
+                    position,
+                    result.heliocentric_velocity(body_velocity),
+                )
+                .to_elements(SUN_MASS, 0.0)
+            })
+            .into_iter()
+            .filter(|orbit| orbit.is_elliptical())
+            .max_by(|a, b| a.semi_major_axis.total_cmp(&b.semi_major_axis))
+            .unwrap()
+    }
+
+    #[test]
+    fn circular_reference_orbit() {
+        let circular = KeplerianElements {
+            semi_major_axis: JUPITER_SMA,
+            ..KeplerianElements::default()
+        };
+
+        assert!(
+            (tisserand_parameter(&circular, JUPITER_SMA) - 3.0).abs() < 1e-5
+        );
+    }
+
+    #[test]
+    fn isogam_keeps_the_parameter() {
+        let curve = isogam_curve(2.9, JUPITER_SMA, 50);
+        assert_eq!(curve.len(), 50);
+
+        for (a, e) in curve {
+            let orbit = KeplerianElements {
+                semi_major_axis: a,
+                eccentricity: e,
+                ..KeplerianElements::default()
+            };
+
+            let t = tisserand_parameter(&orbit, JUPITER_SMA);
+            assert!((t - 2.9).abs() < 1e-4, "{t} at {a} {e}");
+            // Up to the tangent orbits at both ends
+            assert!(a * (1.0 - e) < JUPITER_SMA * (1.0 + 1e-4));
+            assert!(a * (1.0 + e) > JUPITER_SMA * (1.0 - 1e-4));
+        }
+
+        // Fast enough to escape for some of the directions
+        assert!(isogam_curve(1.0, JUPITER_SMA, 50).len() < 50);
+    }
+
+    #[test]
+    fn voyager_grand_tour() {
+        // A Voyager-like chain: Earth to Jupiter, then Saturn.
+        // Leaves the Earth with the aphelion beyond Jupiter
+        let departure = KeplerianElements {
+            semi_major_axis: 3.5 * ASTRONOMICAL_UNIT,
+            eccentricity: 2.5 / 3.5,
+            ..KeplerianElements::default()
+        };
+
+        let after_jupiter =
+            assist(&departure, JUPITER_SMA, JUPITER_MU, 1_500_000_000.0);
+        assert!(crosses_body_orbit(&after_jupiter, SATURN_SMA));
+
+        let after_saturn =
+            assist(&after_jupiter, SATURN_SMA, SATURN_MU, 161_000_000.0);
+
+        assert!(sequence_is_connected(
+            &[&departure, &after_jupiter],
+            JUPITER_SMA,
+            1e-3
+        ));
+        assert!(sequence_is_connected(
+            &[&after_jupiter, &after_saturn],
+            SATURN_SMA,
+            1e-3
+        ));
+
+        // Saturn alone can't raise the departure orbit, it doesn't even
+        // reach it
+        assert!(!sequence_is_connected(
+            &[&departure, &after_jupiter],
+            SATURN_SMA,
+            1e-3
+        ));
+        // Nor does a Jupiter assist lead directly to the final orbit
+        assert!(!sequence_is_connected(
+            &[&departure, &after_saturn],
+            JUPITER_SMA,
+            1e-3
+        ));
+    }
+}
@@ -0,0 +1,303 @@
+//! Transfers between circular orbits, impulsive ones between coplanar
+//! orbits and low-thrust spirals with a plane change.
+//!
+//! Everything here takes the gravitational parameter `mu` directly.
+
+use crate::math::wrap_angle;
+use crate::{Num, PI};
+
+/// Number of bisection steps used to match a time of flight
+const BISECTION_STEPS: usize = 60;
+
+/// A transfer that departs tangentially from the initial circular orbit
+/// and reaches the final one after sweeping `transfer_true_anomaly`.
+///
+/// Departs from the periapsis of the transfer ellipse when going up
+/// and from its apoapsis when going down.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OneTangentTransfer {
+    pub semi_major_axis: Num,
+    pub eccentricity: Num,
+    /// Angle swept between the departure and the arrival
+    pub transfer_true_anomaly: Num,
+    /// Tangential burn at the departure
+    pub delta_v_departure: Num,
+    /// Burn at the arrival, including the change of the flight path angle
+    pub delta_v_arrival: Num,
+    /// Flight path angle on the transfer orbit at the arrival
+    pub flight_path_angle: Num,
+    pub time_of_flight: Num,
+}
+
+impl OneTangentTransfer {
+    pub fn total_delta_v(&self) -> Num {
+        self.delta_v_departure + self.delta_v_arrival
+    }
+}
+
+/// One-tangent burn transfer from a circular orbit of radius `r1`
+/// to one of radius `r2`.
+///
+/// Returns `None` if the transfer orbit for the given anomaly
+/// isn't elliptical.
+///
+/// SRC: Vallado, Fundamentals of Astrodynamics and Applications,
+/// section 6.3.2
+pub fn one_tangent(
+    mu: Num,
+    r1: Num,
+    r2: Num,
+    transfer_true_anomaly: Num,
+) -> Option<OneTangentTransfer> {
+    let ratio = r1 / r2;
+    let cos_θ = transfer_true_anomaly.cos();
+
+    let (e, departure_anomaly) = if r2 >= r1 {
+        ((1.0 - ratio) / (ratio - cos_θ), 0.0)
+    } else {
+        ((1.0 - ratio) / (cos_θ - ratio), PI)
+    };
+
+    if !(0.0..1.0).contains(&e) {
+        return None;
+    }
+
+    let a = if r2 >= r1 {
+        r1 / (1.0 - e)
+    } else {
+        r1 / (1.0 + e)
+    };
+
+    let arrival_anomaly = departure_anomaly + transfer_true_anomaly;
+
+    let v_departure = (2.0 * mu / r1 - mu / a).sqrt();
+    let v_arrival = (2.0 * mu / r2 - mu / a).sqrt();
+    let v_circular_1 = (mu / r1).sqrt();
+    let v_circular_2 = (mu / r2).sqrt();
+
+    let (sin_θ2, cos_θ2) = arrival_anomaly.sin_cos();
+    let flight_path_angle = (e * sin_θ2).atan2(1.0 + e * cos_θ2);
+
+    let delta_v_arrival = (v_arrival.powi(2) + v_circular_2.powi(2)
+        - 2.0 * v_arrival * v_circular_2 * flight_path_angle.cos())
+    .max(0.0)
+    .sqrt();
+
+    let mean_anomaly = |θ: Num| {
+        let E = 2.0
+            * ((1.0 - e).sqrt() * (θ / 2.0).sin())
+                .atan2((1.0 + e).sqrt() * (θ / 2.0).cos());
+
+        E - e * E.sin()
+    };
+    let ΔM = wrap_angle(
+        mean_anomaly(arrival_anomaly) - mean_anomaly(departure_anomaly),
+    );
+    let n = (mu / a.powi(3)).sqrt();
+
+    Some(OneTangentTransfer {
+        semi_major_axis: a,
+        eccentricity: e,
+        transfer_true_anomaly,
+        delta_v_departure: (v_departure - v_circular_1).abs(),
+        delta_v_arrival,
+        flight_path_angle,
+        time_of_flight: ΔM / n,
+    })
+}
+
+/// The Hohmann transfer, i.e. the one-tangent transfer arriving
+/// after half a revolution.
+pub fn hohmann(mu: Num, r1: Num, r2: Num) -> Option<OneTangentTransfer> {
+    // Exactly half a period, without the rounding of the anomalies
+    one_tangent(mu, r1, r2, PI).map(|transfer| OneTangentTransfer {
+        time_of_flight: hohmann_time_of_flight(mu, r1, r2),
+        ..transfer
+    })
+}
+
+/// Time of flight of the Hohmann transfer, half the period of the transfer
+/// ellipse: T/2 = π√(a_t³/μ), with a_t = (r1 + r2) / 2.
+pub fn hohmann_time_of_flight(mu: Num, r1: Num, r2: Num) -> Num {
+    PI * ((0.5 * (r1 + r2)).powi(3) / mu).sqrt()
+}
+
+/// One-tangent transfer with the given time of flight, solved
+/// for the transfer anomaly by bisection.
+///
+/// Returns `None` if no elliptical one-tangent transfer takes that long,
+/// e.g. when it's longer than the Hohmann transfer.
+pub fn one_tangent_for_time_of_flight(
+    mu: Num,
+    r1: Num,
+    r2: Num,
+    time_of_flight: Num,
+) -> Option<OneTangentTransfer> {
+    // Shorter anomalies need faster, eventually hyperbolic, transfer orbits
+    let mut lo = if r2 >= r1 {
+        (2.0 * r1 / r2 - 1.0).acos()
+    } else {
+        0.0
+    };
+    let mut hi = PI;
+
+    if time_of_flight > hohmann(mu, r1, r2)?.time_of_flight {
+        return None;
+    }
+
+    for _ in 0..BISECTION_STEPS {
+        let mid = 0.5 * (lo + hi);
+
+        match one_tangent(mu, r1, r2, mid) {
+            Some(transfer) if transfer.time_of_flight >= time_of_flight => {
+                hi = mid;
+            }
+            _ => lo = mid,
+        }
+    }
+
+    one_tangent(mu, r1, r2, hi)
+}
+
+/// Edelbaum's estimate of a continuous-thrust transfer between
+/// circular orbits, with the plane change spread over the spiral.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdelbaumEstimate {
+    pub delta_v: Num,
+}
+
+impl EdelbaumEstimate {
+    /// Duration of the transfer with a constant acceleration,
+    /// ignoring the mass of the propellant used.
+    pub fn transfer_time_for(&self, acceleration: Num) -> Num {
+        self.delta_v / acceleration
+    }
+}
+
+/// Low-thrust transfer from a circular orbit of radius `r1` to one
+/// of radius `r2`, changing the inclination by `delta_inclination`.
+///
+/// SRC: Edelbaum, Propulsion Requirements for Controllable Satellites,
+/// ARS Journal 31 (1961)
+pub fn edelbaum(
+    mu: Num,
+    r1: Num,
+    r2: Num,
+    delta_inclination: Num,
+) -> EdelbaumEstimate {
+    let v1 = (mu / r1).sqrt();
+    let v2 = (mu / r2).sqrt();
+    let cos_Δi = (PI / 2.0 * delta_inclination).cos();
+
+    let delta_v = (v1.powi(2) + v2.powi(2) - 2.0 * v1 * v2 * cos_Δi)
+        .max(0.0)
+        .sqrt();
+
+    EdelbaumEstimate { delta_v }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{SECONDS_PER_DAY, STANDARD_GRAVITY};
+
+    const EARTH_MU: Num = 3.986_004_4e14;
+    const LEO: Num = 6_678_000.0;
+    const GEO: Num = 42_164_000.0;
+
+    #[test]
+    fn hohmann_to_geo() {
+        let transfer = hohmann(EARTH_MU, LEO, GEO).unwrap();
+
+        let a = (LEO + GEO) / 2.0;
+        assert!((transfer.semi_major_axis / a - 1.0).abs() < 1e-6);
+        assert!((transfer.delta_v_departure - 2_426.0).abs() < 5.0);
+        assert!((transfer.delta_v_arrival - 1_467.0).abs() < 5.0);
+        assert!(transfer.flight_path_angle.abs() < 1e-5);
+
+        let hours = transfer.time_of_flight / 3_600.0;
+        assert!((hours - 5.28).abs() < 0.02, "{hours}");
+    }
+
+    #[test]
+    fn converges_to_hohmann() {
+        for (r1, r2) in [(LEO, GEO), (GEO, LEO)] {
+            let hohmann = hohmann(EARTH_MU, r1, r2).unwrap();
+
+            let mut last_diff = Num::MAX;
+            for Δ in [0.5, 0.1, 0.01, 0.001] {
+                let transfer = one_tangent(EARTH_MU, r1, r2, PI - Δ).unwrap();
+
+                let diff = (transfer.total_delta_v() - hohmann.total_delta_v())
+                    .abs()
+                    + (transfer.eccentricity - hohmann.eccentricity).abs();
+
+                assert!(diff < last_diff, "{transfer:?}");
+                last_diff = diff;
+
+                // Faster, but more expensive
+                assert!(transfer.time_of_flight < hohmann.time_of_flight);
+                assert!(transfer.total_delta_v() > hohmann.total_delta_v());
+            }
+
+            assert!(last_diff < 1.0, "{last_diff}");
+        }
+    }
+
+    #[test]
+    fn hyperbolic_transfers_are_rejected() {
+        // The transfer orbit for such a short arc would have to be hyperbolic
+        assert_eq!(one_tangent(EARTH_MU, LEO, GEO, 0.3), None);
+    }
+
+    #[test]
+    fn time_of_flight_round_trip() {
+        for (r1, r2) in [(LEO, GEO), (GEO, LEO)] {
+            let transfer = one_tangent(EARTH_MU, r1, r2, 2.5).unwrap();
+
+            let solved = one_tangent_for_time_of_flight(
+                EARTH_MU,
+                r1,
+                r2,
+                transfer.time_of_flight,
+            )
+            .unwrap();
+
+            assert!(
+                (solved.transfer_true_anomaly - 2.5).abs() < 1e-3,
+                "{solved:?}"
+            );
+        }
+
+        let hohmann = hohmann(EARTH_MU, LEO, GEO).unwrap();
+        assert_eq!(
+            one_tangent_for_time_of_flight(
+                EARTH_MU,
+                LEO,
+                GEO,
+                hohmann.time_of_flight * 1.1
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn edelbaum_without_plane_change() {
+        let estimate = edelbaum(EARTH_MU, LEO, GEO, 0.0);
+
+        let v1 = (EARTH_MU / LEO).sqrt();
+        let v2 = (EARTH_MU / GEO).sqrt();
+        assert!((estimate.delta_v - (v1 - v2)).abs() < 0.1);
+    }
+
+    #[test]
+    fn edelbaum_leo_to_geo() {
+        let estimate = edelbaum(EARTH_MU, LEO, GEO, (28.5 as Num).to_radians());
+        assert!((estimate.delta_v - 5_900.0).abs() < 100.0, "{estimate:?}");
+
+        // A milli-g takes about a week
+        let days = estimate.transfer_time_for(1e-3 * STANDARD_GRAVITY)
+            / SECONDS_PER_DAY;
+        assert!((days - 7.0).abs() < 0.5, "{days}");
+    }
+}
@@ -0,0 +1,149 @@
+//! Geometry of satellite passes as seen from a ground station.
+//!
+//! The north direction is taken from the z axis of the inertial frame,
+//! i.e. the rotation axis of the central body, so the azimuth is undefined
+//! for stations at the poles.
+
+use crate::math::wrap_angle;
+use crate::{Num, StateVectors, Vec3};
+
+/// Elevation of the satellite above the local horizontal plane, in radians.
+///
+/// `station_zenith` is the local up direction of the station,
+/// it doesn't need to be normalized.
+pub fn elevation_angle(
+    satellite_eci: Vec3,
+    station_eci: Vec3,
+    station_zenith: Vec3,
+) -> Num {
+    let line_of_sight = (satellite_eci - station_eci).normalize();
+    let sin_elevation = line_of_sight.dot(station_zenith.normalize());
+
+    sin_elevation.clamp(-1.0, 1.0).asin()
+}
+
+/// Azimuth of the satellite measured clockwise from north, in [0, 2π).
+pub fn azimuth_angle(
+    satellite_eci: Vec3,
+    station_eci: Vec3,
+    station_zenith: Vec3,
+) -> Num {
+    let (north, east, _) = local_horizon(station_zenith);
+    let line_of_sight = satellite_eci - station_eci;
+
+    wrap_angle(line_of_sight.dot(east).atan2(line_of_sight.dot(north)))
+}
+
+/// Rate of change of the distance between the satellite and the station,
+/// positive when moving apart.
+///
+/// Multiply by `-f / c` to get the Doppler shift of a carrier at `f`.
+pub fn range_rate(
+    satellite_sv: &StateVectors,
+    station_position: Vec3,
+    station_velocity: Vec3,
+) -> Num {
+    let relative_position = satellite_sv.position - station_position;
+    let relative_velocity = satellite_sv.velocity - station_velocity;
+
+    relative_position.dot(relative_velocity) / relative_position.length()
+}
+
+/// North, east and up unit vectors of the station
+fn local_horizon(station_zenith: Vec3) -> (Vec3, Vec3, Vec3) {
+    let up = station_zenith.normalize();
+    let north = (Vec3::Z - Vec3::Z.dot(up) * up).normalize();
+    let east = north.cross(up);
+
+    (north, east, up)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::EARTH_RADIUS;
+    use crate::{vec3, PI};
+
+    /// Station on the equator at 0° longitude
+    const STATION: Vec3 = vec3(EARTH_RADIUS, 0.0, 0.0);
+
+    fn satellite_at(
+        azimuth: Num,
+        elevation: Num,
+        station: Vec3,
+        distance: Num,
+    ) -> Vec3 {
+        let (north, east, up) = local_horizon(station);
+
+        let direction = elevation.cos()
+            * (azimuth.cos() * north + azimuth.sin() * east)
+            + elevation.sin() * up;
+
+        station + distance * direction
+    }
+
+    #[test]
+    fn directly_overhead() {
+        let satellite = STATION * 1.1;
+
+        let elevation = elevation_angle(satellite, STATION, STATION);
+        assert!((elevation - PI / 2.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn on_the_horizon() {
+        // Due north, tangent to the surface
+        let satellite = STATION + vec3(0.0, 0.0, 1_000_000.0);
+
+        assert!(elevation_angle(satellite, STATION, STATION).abs() < 1e-6);
+        assert!(azimuth_angle(satellite, STATION, STATION) < 1e-6);
+
+        // Due east
+        let satellite = STATION + vec3(0.0, 1_000_000.0, 0.0);
+        let azimuth = azimuth_angle(satellite, STATION, STATION);
+        assert!((azimuth - PI / 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn azimuth_elevation_round_trip() {
+        let latitude: Num = 0.8;
+        let longitude: Num = 2.1;
+        let station = EARTH_RADIUS
+            * vec3(
+                latitude.cos() * longitude.cos(),
+                latitude.cos() * longitude.sin(),
+                latitude.sin(),
+            );
+
+        for azimuth in [0.1, 1.0, 2.5, 3.5, 5.0, 6.2] {
+            for elevation in [-0.3, 0.0, 0.2, 0.7, 1.4] {
+                let satellite =
+                    satellite_at(azimuth, elevation, station, 2_000_000.0);
+
+                let az = azimuth_angle(satellite, station, station);
+                let el = elevation_angle(satellite, station, station);
+
+                assert!((az - azimuth).abs() < 1e-3, "{az} != {azimuth}");
+                assert!((el - elevation).abs() < 1e-3, "{el} != {elevation}");
+            }
+        }
+    }
+
+    #[test]
+    fn range_rate_of_approaching_and_receding_satellite() {
+        let station_velocity = vec3(0.0, 465.0, 0.0);
+
+        let overhead = StateVectors::new(STATION * 1.1, vec3(0.0, 7500.0, 0.0));
+        let rate = range_rate(&overhead, STATION, station_velocity);
+        assert!(rate.abs() < 1e-3);
+
+        let receding = StateVectors::new(STATION * 1.1, vec3(100.0, 0.0, 0.0));
+        let rate = range_rate(&receding, STATION, station_velocity);
+        assert!((rate - 100.0).abs() < 1e-3);
+
+        let approaching =
+            StateVectors::new(STATION * 1.1, vec3(-100.0, 0.0, 0.0));
+        let rate = range_rate(&approaching, STATION, station_velocity);
+        assert!((rate + 100.0).abs() < 1e-3);
+    }
+}
@@ -3,10 +3,39 @@ use crate::Num;
 /// Gravitational constant
 pub const G: Num = 6.67430e-11;
 
+/// Standard gravity, used to define the specific impulse in seconds
+pub const STANDARD_GRAVITY: Num = 9.80665;
+
 /// Astronomical unit in km
 pub const AU: Num = 1.496e+8;
+/// Astronomical unit in m
+#[allow(clippy::excessive_precision)]
+pub const ASTRONOMICAL_UNIT: Num = 1.495_978_707e11;
+
+/// Mass of the Earth in kg
+pub const EARTH_MASS: Num = 5.972e24;
+/// Equatorial radius of the Earth in m (WGS 84)
+pub const EARTH_RADIUS: Num = 6_378_137.0;
+/// Second zonal harmonic of the Earth's gravity field
+pub const EARTH_J2: Num = 1.08263e-3;
+/// Mass of the Sun in kg
+pub const SUN_MASS: Num = 1.989e30;
+/// Mass of the Moon in kg
+pub const MOON_MASS: Num = 7.342e22;
+
+pub const SECONDS_PER_MINUTE: Num = 60.0;
+pub const SECONDS_PER_HOUR: Num = 3_600.0;
+pub const SECONDS_PER_DAY: Num = 86_400.0;
+/// Rotation period of the Earth relative to the stars
+#[allow(clippy::excessive_precision)]
+pub const SECONDS_PER_SIDEREAL_DAY: Num = 86_164.090_5;
+/// 365.25 days
+pub const SECONDS_PER_JULIAN_YEAR: Num = 31_557_600.0;
+
+/// Obliquity of the ecliptic at J2000 in radians (23.4392811°)
+pub const J2000_OBLIQUITY: Num = 0.409_092_8;
 
-#[cfg(feature = "f32")]
+#[cfg(not(feature = "f64"))]
 pub use std::f32::consts::PI;
 #[cfg(feature = "f64")]
 pub use std::f64::consts::PI;
@@ -1,7 +1,26 @@
+use std::fmt;
+
+use crate::astro::atmosphere::Atmosphere;
 use crate::astro::{self, standard_gravitational_parameter};
+use crate::constants::{SECONDS_PER_DAY, SECONDS_PER_JULIAN_YEAR};
+use crate::math::{
+    angle_diff, fixed_point_iteration, safe_acos, wrap_angle, ConvergenceError,
+};
+use crate::state_vectors::{CIRCULAR_TOLERANCE, EQUATORIAL_TOLERANCE};
 use crate::{vec3, Mat3, Num, StateVectors, Vec3, PI, TWO_PI};
 
-#[derive(Debug, Clone, Copy)]
+/// Tolerance for considering two orbits coplanar
+const COPLANAR_TOLERANCE: Num = 1e-4;
+
+/// Convergence tolerance of the sun-synchronous frozen eccentricity
+const SUN_SYNCHRONOUS_FROZEN_TOLERANCE: Num = 1e-9;
+
+const SUN_SYNCHRONOUS_FROZEN_MAX_STEPS: usize = 50;
+
+/// Integration steps of the reentry decay time over the altitude
+const REENTRY_DECAY_STEPS: usize = 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KeplerianElements {
     pub eccentricity: Num,
@@ -9,11 +28,285 @@ pub struct KeplerianElements {
     pub inclination: Num,
     pub right_ascension_of_the_ascending_node: Num,
     pub argument_of_periapsis: Num,
+    /// Kept in [0, 2π) for elliptical orbits.
+    ///
+    /// For hyperbolic orbits the mean anomaly is not periodic and negative
+    /// values denote times before the periapsis passage, so it's not wrapped.
     pub mean_anomaly_at_epoch: Num,
     pub epoch: Num,
 }
 
+impl Default for KeplerianElements {
+    /// Unit circular equatorial orbit at epoch 0
+    fn default() -> Self {
+        Self::new(1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0)
+    }
+}
+
+/// Reason why a set of elements doesn't describe a valid orbit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidElements {
+    NotFinite,
+    NegativeEccentricity,
+    NonPositiveSemiMajorAxis,
+    InclinationOutOfRange,
+}
+
+impl fmt::Display for InvalidElements {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFinite => write!(f, "Elements are not finite"),
+            Self::NegativeEccentricity => {
+                write!(f, "Eccentricity is negative")
+            }
+            Self::NonPositiveSemiMajorAxis => {
+                write!(f, "Semi-major axis is not positive")
+            }
+            Self::InclinationOutOfRange => {
+                write!(f, "Inclination is outside of [0, π]")
+            }
+        }
+    }
+}
+
+impl std::error::Error for InvalidElements {}
+
+/// An orbit with the requested properties doesn't exist
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrbitError {
+    /// The semi-major axis isn't above the surface of the body
+    BelowSurface,
+    /// The J2 precession can't match the required rate at this altitude,
+    /// even in a polar or an equatorial orbit
+    PrecessionOutOfReach,
+    NotConverged(ConvergenceError),
+}
+
+impl fmt::Display for OrbitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BelowSurface => {
+                write!(f, "Semi-major axis is below the surface")
+            }
+            Self::PrecessionOutOfReach => {
+                write!(f, "No inclination gives the required nodal precession")
+            }
+            Self::NotConverged(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for OrbitError {}
+
+/// State at which a trajectory hits the surface of the central body
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Impact {
+    pub epoch: Num,
+    pub position: Vec3,
+    pub velocity: Vec3,
+    /// Angle between the velocity and the local horizontal,
+    /// negative when descending
+    pub flight_path_angle: Num,
+}
+
+/// Geometry of a target body seen from the orbiting spacecraft
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ObservationGeometry {
+    /// Distance between the spacecraft and the target
+    pub range: Num,
+    /// Angle at the target between the spacecraft and the Sun,
+    /// zero when the target is seen fully lit
+    pub phase_angle: Num,
+    /// Angle at the spacecraft between the Sun and the target
+    pub elongation_from_sun: Num,
+}
+
+/// Anomalies at an epoch, see [`KeplerianElements::anomalies_at_epoch`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Anomalies {
+    /// Wrapped into [0, 2π) for elliptic orbits
+    pub mean: Num,
+    /// The hyperbolic anomaly for hyperbolic orbits
+    pub eccentric: Num,
+    pub true_anomaly: Num,
+    /// Full revolutions of the unwrapped mean anomaly, so that it equals
+    /// `mean + 2π * revolutions`. `None` for hyperbolic orbits.
+    pub revolutions: Option<i64>,
+}
+
 impl KeplerianElements {
+    /// Creates the elements from the canonical order:
+    /// a, e, i, Ω, ω, M₀, t₀
+    ///
+    /// Usable in const contexts, so the values are taken as is,
+    /// without validation or angle wrapping.
+    /// Use [`KeplerianElements::wrapped`] to normalize them.
+    pub const fn new(
+        semi_major_axis: Num,
+        eccentricity: Num,
+        inclination: Num,
+        right_ascension_of_the_ascending_node: Num,
+        argument_of_periapsis: Num,
+        mean_anomaly_at_epoch: Num,
+        epoch: Num,
+    ) -> Self {
+        Self {
+            eccentricity,
+            semi_major_axis,
+            inclination,
+            right_ascension_of_the_ascending_node,
+            argument_of_periapsis,
+            mean_anomaly_at_epoch,
+            epoch,
+        }
+    }
+
+    /// Builds the elements from an array in the canonical order:
+    /// [a, e, i, Ω, ω, M₀, t₀]
+    pub const fn from_array(array: [Num; 7]) -> Self {
+        let [a, e, i, Ω, ω, M, t] = array;
+
+        Self::new(a, e, i, Ω, ω, M, t)
+    }
+
+    /// Frozen orbit around a body with the zonal harmonics `j2` and `j3`,
+    /// see [`astro::design::frozen_orbit`].
+    ///
+    /// At the critical inclination any eccentricity is frozen,
+    /// there the orbit is circular.
+    #[allow(clippy::too_many_arguments)]
+    pub fn frozen_orbit(
+        sma: Num,
+        inclination: Num,
+        raan: Num,
+        mean_anomaly: Num,
+        epoch: Num,
+        body_radius: Num,
+        j2: Num,
+        j3: Num,
+        mass: Num,
+    ) -> Self {
+        let μ = standard_gravitational_parameter(mass);
+        let solution = astro::design::frozen_orbit(
+            μ,
+            j2,
+            j3,
+            body_radius,
+            sma,
+            inclination,
+        );
+        let (e, ω) = solution.frozen.unwrap_or((0.0, PI / 2.0));
+
+        Self::new(sma, e, inclination, raan, ω, mean_anomaly, epoch)
+    }
+
+    /// Sun-synchronous frozen orbit: the J2 nodal precession matches
+    /// `rotation_rate`, the mean motion of the body around the Sun
+    /// (≈1.991e-7 rad/s for the Earth), and J3 freezes the eccentricity
+    /// as in [`astro::design::frozen_orbit`], with ω = 90° for the Earth.
+    ///
+    /// The altitude fixes everything else. Higher orbits precess slower,
+    /// so they need inclinations further from polar and soon run out of
+    /// reach, while their frozen eccentricity shrinks with R / a.
+    /// The eccentricity feeds back into the precession through
+    /// the semi-latus rectum, so both are solved together.
+    ///
+    /// The orbit is returned with Ω, M₀ and the epoch at zero.
+    pub fn sun_synchronous_frozen_orbit(
+        sma: Num,
+        body_radius: Num,
+        j2: Num,
+        j3: Num,
+        mass: Num,
+        rotation_rate: Num,
+    ) -> Result<Self, OrbitError> {
+        if sma <= body_radius {
+            return Err(OrbitError::BelowSurface);
+        }
+
+        let μ = standard_gravitational_parameter(mass);
+
+        let sun_synchronous_inclination = |e: Num| {
+            let equatorial = Self {
+                semi_major_axis: sma,
+                eccentricity: e,
+                ..Self::default()
+            };
+            // Ω̇ = Ω̇(i = 0) cos i
+            let cos_i = rotation_rate
+                / equatorial.secular_raan_rate(body_radius, j2, mass);
+
+            (cos_i.abs() <= 1.0).then(|| cos_i.acos())
+        };
+        let frozen = |inclination: Num| {
+            astro::design::frozen_orbit(
+                μ,
+                j2,
+                j3,
+                body_radius,
+                sma,
+                inclination,
+            )
+            .frozen
+            .unwrap_or((0.0, PI / 2.0))
+        };
+
+        // Out of reach stays put, and is reported below
+        let e = fixed_point_iteration(
+            |e| match sun_synchronous_inclination(e) {
+                Some(inclination) => frozen(inclination).0,
+                None => e,
+            },
+            0.0,
+            SUN_SYNCHRONOUS_FROZEN_TOLERANCE,
+            SUN_SYNCHRONOUS_FROZEN_MAX_STEPS,
+        )
+        .map_err(OrbitError::NotConverged)?;
+
+        let inclination = sun_synchronous_inclination(e)
+            .ok_or(OrbitError::PrecessionOutOfReach)?;
+        let (_, ω) = frozen(inclination);
+
+        Ok(Self::new(sma, e, inclination, 0.0, ω, 0.0, 0.0))
+    }
+
+    /// Hyperbolic trajectory approaching with the excess velocity
+    /// `v_infinity` and passing the periapsis at `periapsis_radius`
+    /// at `periapsis_epoch`.
+    ///
+    /// The orientation of the flyby follows [`astro::flyby::flyby`],
+    /// `b_plane_angle` is the angle of the B-vector from T towards R.
+    pub fn from_flyby(
+        v_infinity: Vec3,
+        periapsis_radius: Num,
+        b_plane_angle: Num,
+        mass: Num,
+        periapsis_epoch: Num,
+    ) -> Self {
+        let μ = standard_gravitational_parameter(mass);
+        let flyby =
+            astro::flyby::flyby(v_infinity, μ, periapsis_radius, b_plane_angle);
+
+        Self {
+            mean_anomaly_at_epoch: 0.0,
+            ..flyby.periapsis_state.to_elements(mass, periapsis_epoch)
+        }
+    }
+
+    /// Returns the elements in the canonical order:
+    /// [a, e, i, Ω, ω, M₀, t₀]
+    pub fn as_array(&self) -> [Num; 7] {
+        [
+            self.semi_major_axis,
+            self.eccentricity,
+            self.inclination,
+            self.right_ascension_of_the_ascending_node,
+            self.argument_of_periapsis,
+            self.mean_anomaly_at_epoch,
+            self.epoch,
+        ]
+    }
+
     pub fn angle_abs_diff(&self, other: &Self) -> Num {
         let mut diff = 0.0;
 
@@ -30,6 +323,55 @@ impl KeplerianElements {
         diff
     }
 
+    /// Returns a copy with the mean anomaly at epoch wrapped into [0, 2π).
+    ///
+    /// Hyperbolic elements are returned unchanged.
+    pub fn wrapped(mut self) -> Self {
+        if self.is_elliptical() {
+            self.mean_anomaly_at_epoch = wrap_angle(self.mean_anomaly_at_epoch);
+        }
+
+        self
+    }
+
+    /// Checks that the elements describe an orbit, the angles other than
+    /// the inclination can take any finite value.
+    pub fn validate(&self) -> Result<(), InvalidElements> {
+        if !self.as_array().iter().all(|x| x.is_finite()) {
+            return Err(InvalidElements::NotFinite);
+        }
+
+        if self.eccentricity < 0.0 {
+            return Err(InvalidElements::NegativeEccentricity);
+        }
+
+        if self.semi_major_axis <= 0.0 {
+            return Err(InvalidElements::NonPositiveSemiMajorAxis);
+        }
+
+        if !(0.0..=PI).contains(&self.inclination) {
+            return Err(InvalidElements::InclinationOutOfRange);
+        }
+
+        Ok(())
+    }
+
+    /// Same orbit, with the mean anomaly referenced to `epoch` instead.
+    pub fn advance_to_epoch(&self, mass: Num, epoch: Num) -> Self {
+        let mean_anomaly_at_epoch = if self.is_hyperbolic() {
+            self.hyperbolic_mean_anomaly(mass, epoch)
+        } else {
+            self.mean_anomaly(mass, epoch)
+        };
+
+        Self {
+            mean_anomaly_at_epoch,
+            epoch,
+            ..*self
+        }
+        .wrapped()
+    }
+
     pub fn from_state_vectors(
         state_vectors: &StateVectors,
         mass: Num,
@@ -58,11 +400,520 @@ impl KeplerianElements {
         self.perifocal_to_equatorial(Vec3::Z)
     }
 
+    /// Radius of the sphere around the central body enclosing the orbit,
+    /// i.e. the apoapsis radius. `None` for unbound orbits.
+    pub fn bounding_radius(&self) -> Option<Num> {
+        if self.is_hyperbolic() {
+            return None;
+        }
+
+        Some(self.semi_major_axis * (1.0 + self.eccentricity))
+    }
+
+    /// Axis-aligned box enclosing the orbit, as the minimum
+    /// and the maximum corner.
+    ///
+    /// The box of an ellipse is exact, spanned by the projections of
+    /// its semi-axes around its center. Hyperbolic trajectories are cut off
+    /// at `max_radius` and their box is made of `samples` points along
+    /// the arc and the periapsis, so it can be slightly smaller
+    /// than the arc.
+    /// A trajectory that never gets within `max_radius`
+    /// gets the box of its periapsis.
+    pub fn bounding_box(
+        &self,
+        mass: Num,
+        samples: usize,
+        max_radius: Num,
+    ) -> (Vec3, Vec3) {
+        let a = self.semi_major_axis;
+        let e = self.eccentricity;
+
+        if !self.is_hyperbolic() {
+            let b = a * (1.0 - e.powi(2)).sqrt();
+            let major = self.perifocal_to_equatorial(vec3(a, 0.0, 0.0));
+            let minor = self.perifocal_to_equatorial(vec3(0.0, b, 0.0));
+
+            let center = self.perifocal_to_equatorial(vec3(-a * e, 0.0, 0.0));
+            let extent = vec3(
+                major.x.hypot(minor.x),
+                major.y.hypot(minor.y),
+                major.z.hypot(minor.z),
+            );
+
+            return (center - extent, center + extent);
+        }
+
+        let v_max = self
+            .true_anomalies_at_radius(mass, max_radius)
+            .map_or(0.0, |(v, _)| v);
+        let samples = samples.max(2);
+
+        (0..samples)
+            .map(|k| {
+                let t = k as Num / (samples - 1) as Num;
+                self.position_at_true_anomaly(mass, (2.0 * t - 1.0) * v_max)
+            })
+            .chain([self.periapsis(mass)])
+            .fold((Vec3::splat(Num::MAX), Vec3::splat(Num::MIN)), |b, p| {
+                (b.0.min(p), b.1.max(p))
+            })
+    }
+
+    /// Angle between the orbital planes, i.e. between the normals,
+    /// in [0, π].
+    ///
+    /// Unlike the difference of the inclinations this accounts
+    /// for the difference of the ascending nodes too.
+    pub fn relative_inclination(&self, other: &Self) -> Num {
+        let (n1, n2) = (self.normal(), other.normal());
+
+        n1.cross(n2).length().atan2(n1.dot(n2))
+    }
+
+    /// Unit axis along the mutual line of nodes, about which the normal
+    /// of `self` rotates by [`KeplerianElements::relative_inclination`]
+    /// into the normal of `other`.
+    ///
+    /// Returns `None` when the orbits are coplanar.
+    pub fn normal_misalignment_axis(&self, other: &Self) -> Option<Vec3> {
+        let axis = self.normal().cross(other.normal());
+
+        (axis.length() >= COPLANAR_TOLERANCE).then(|| axis.normalize())
+    }
+
+    /// Speed left over far away from the body on a hyperbolic trajectory,
+    /// v∞ = sqrt(μ / a)
+    pub fn hyperbolic_excess_velocity(&self, mass: Num) -> Num {
+        debug_assert!(self.is_hyperbolic());
+
+        (standard_gravitational_parameter(mass) / self.semi_major_axis).sqrt()
+    }
+
+    /// Unit direction of the velocity far away on the approach
+    /// of a hyperbolic trajectory.
+    ///
+    /// S = P / e + sqrt(e² - 1) / e * Q,
+    /// with P towards the periapsis and Q along the velocity there.
+    pub fn incoming_asymptote(&self) -> Vec3 {
+        debug_assert!(self.is_hyperbolic());
+
+        let e = self.eccentricity;
+
+        let p = self.perifocal_to_equatorial(Vec3::X);
+        let q = self.perifocal_to_equatorial(Vec3::Y);
+
+        p / e + (e.powi(2) - 1.0).sqrt() / e * q
+    }
+
+    /// p = a * (1 - e²)
+    ///
+    /// https://en.wikipedia.org/wiki/Conic_section#Conic_parameters
+    pub fn semi_latus_rectum(&self) -> Num {
+        let a = self.semi_major_axis;
+        let e = self.eccentricity;
+
+        if self.is_hyperbolic() {
+            a * (e.powi(2) - 1.0)
+        } else {
+            a * (1.0 - e.powi(2))
+        }
+    }
+
+    /// Finds the points where two coplanar orbits cross.
+    ///
+    /// Returns pairs of true anomalies (in self, in other) of the crossings.
+    /// Returns nothing if the orbits are not coplanar or identical.
+    ///
+    /// With θ being the angle from the periapsis of self and Δ the angle
+    /// between the periapses, r1(θ) = r2(θ - Δ) reduces to
+    /// A * cos(θ) + B * sin(θ) = C
+    pub fn coplanar_intersection_true_anomalies(
+        &self,
+        other: &KeplerianElements,
+    ) -> Vec<(Num, Num)> {
+        let n1 = self.normal();
+        let n2 = other.normal();
+
+        if n1.dot(n2) < 1.0 - COPLANAR_TOLERANCE {
+            return vec![];
+        }
+
+        // Angle between the periapses
+        let p1 = self.perifocal_to_equatorial(Vec3::X);
+        let p2 = other.perifocal_to_equatorial(Vec3::X);
+        let Δ = n1.dot(p1.cross(p2)).atan2(p1.dot(p2));
+
+        let (l1, e1) = (self.semi_latus_rectum(), self.eccentricity);
+        let (l2, e2) = (other.semi_latus_rectum(), other.eccentricity);
+
+        let A = l1 * e2 * Δ.cos() - l2 * e1;
+        let B = l1 * e2 * Δ.sin();
+        let C = l2 - l1;
+
+        let R = (A.powi(2) + B.powi(2)).sqrt();
+
+        if R < Num::EPSILON || C.abs() > R {
+            return vec![];
+        }
+
+        let φ = B.atan2(A);
+        let δ = safe_acos(C / R);
+
+        let mut crossings = vec![];
+
+        for θ in [φ + δ, φ - δ] {
+            let v1 = wrap_angle(θ);
+            let v2 = wrap_angle(θ - Δ);
+
+            // Hyperbolic orbits only exist for some of the angles
+            if 1.0 + e1 * v1.cos() <= 0.0 || 1.0 + e2 * v2.cos() <= 0.0 {
+                continue;
+            }
+
+            // Tangent orbits touch in a single point
+            if δ < COPLANAR_TOLERANCE && !crossings.is_empty() {
+                continue;
+            }
+
+            crossings.push((v1, v2));
+        }
+
+        crossings
+    }
+
+    /// The two true anomalies at which the orbit passes through `radius`,
+    /// from r = p / (1 + e * cos(ν)).
+    ///
+    /// The first one is in [0, π] (moving away from the periapsis)
+    /// and the second one is its mirror image in [π, 2π].
+    /// A circular orbit of that radius is treated as the limit
+    /// of an almost circular one, i.e. returns (π/2, 3π/2).
+    ///
+    /// Returns `None` if the orbit never reaches `radius`.
+    /// The shape alone determines the anomalies, the mass is only taken
+    /// for symmetry with the other true anomaly methods.
+    pub fn true_anomalies_at_radius(
+        &self,
+        _mass: Num,
+        radius: Num,
+    ) -> Option<(Num, Num)> {
+        let e = self.eccentricity;
+
+        // e * cos(ν)
+        let e_cos = self.semi_latus_rectum() / radius - 1.0;
+
+        if e_cos.abs() > e.max(CIRCULAR_TOLERANCE) {
+            return None;
+        }
+
+        let cos = if e > 0.0 { e_cos / e } else { 0.0 };
+        let v = safe_acos(cos);
+
+        Some((v, TWO_PI - v))
+    }
+
+    /// Next impact with a spherical body of `body_radius`
+    /// at or after `after_epoch`.
+    ///
+    /// The impact is the descending crossing of the body radius.
+    /// If the trajectory is already below the surface at `after_epoch`,
+    /// e.g. when it lies entirely inside the body, the impact is
+    /// immediate and the returned state is the one at `after_epoch`.
+    /// A periapsis exactly at the surface is a grazing impact
+    /// with a zero flight path angle.
+    ///
+    /// Returns `None` if the periapsis is above the surface
+    /// or if a hyperbolic trajectory is already moving away.
+    pub fn impact(
+        &self,
+        mass: Num,
+        body_radius: Num,
+        after_epoch: Num,
+        tolerance: Num,
+    ) -> Option<Impact> {
+        let periapsis = self.semi_latus_rectum() / (1.0 + self.eccentricity);
+        if periapsis > body_radius {
+            return None;
+        }
+
+        let current = self.advance_to_epoch(mass, after_epoch);
+
+        let now = current.state_vectors_at_epoch(mass, after_epoch, tolerance);
+        if now.position.length() < body_radius {
+            let v = current.true_anomaly_at_epoch(mass, after_epoch, tolerance);
+
+            return Some(Impact {
+                epoch: after_epoch,
+                position: now.position,
+                velocity: now.velocity,
+                flight_path_angle: self.flight_path_angle_at_true_anomaly(v),
+            });
+        }
+
+        let (_, descending) = current
+            .true_anomalies_at_radius(mass, body_radius)
+            .unwrap_or((0.0, TWO_PI));
+        let epoch = current.next_epoch_at_true_anomaly(mass, descending)?;
+
+        Some(Impact {
+            epoch,
+            position: self.position_at_true_anomaly(mass, descending),
+            velocity: self.velocity_at_true_anomaly(mass, descending),
+            flight_path_angle: self
+                .flight_path_angle_at_true_anomaly(descending),
+        })
+    }
+
+    /// γ = atan2(e * sin(ν), 1 + e * cos(ν))
+    /// SRC: https://en.wikipedia.org/wiki/Elliptic_orbit#Flight_path_angle
+    pub fn flight_path_angle_at_true_anomaly(&self, v: Num) -> Num {
+        let e = self.eccentricity;
+
+        (e * v.sin()).atan2(1.0 + e * v.cos())
+    }
+
     /// https://en.wikipedia.org/wiki/Orbital_period
     pub fn period(&self, mass: Num) -> Num {
         astro::period(self.semi_major_axis, mass)
     }
 
+    /// Period in days, `None` for hyperbolic orbits
+    pub fn period_days(&self, mass: Num) -> Option<Num> {
+        self.is_elliptical()
+            .then(|| self.period(mass) / SECONDS_PER_DAY)
+    }
+
+    /// Period in Julian years, `None` for hyperbolic orbits
+    pub fn period_years(&self, mass: Num) -> Option<Num> {
+        self.is_elliptical()
+            .then(|| self.period(mass) / SECONDS_PER_JULIAN_YEAR)
+    }
+
+    /// Semi-major axis of the phasing orbit that, after `n_orbits`
+    /// revolutions, puts the spacecraft `delta_phase` radians ahead
+    /// of where it would be on this orbit (behind if negative).
+    ///
+    /// Gaining phase takes a lower, faster orbit and losing it a higher one.
+    /// Returns `None` for zero orbits, or when the phase to gain is so large
+    /// that the phasing orbit would have to pass through the center.
+    /// SRC: https://en.wikipedia.org/wiki/Orbital_phasing
+    pub fn phasing_orbit_sma_for_phase_change(
+        &self,
+        delta_phase: Num,
+        n_orbits: u32,
+        mass: Num,
+    ) -> Option<Num> {
+        if n_orbits == 0 {
+            return None;
+        }
+
+        let μ = standard_gravitational_parameter(mass);
+
+        let period = self.period(mass);
+        let phasing_period =
+            period * (1.0 - delta_phase / (TWO_PI * n_orbits as Num));
+        if phasing_period <= 0.0 {
+            return None;
+        }
+
+        let a = (μ * (phasing_period / TWO_PI).powi(2)).cbrt();
+
+        // The burn point is an apsis of the phasing orbit,
+        // the other one is at 2a - r
+        let r = self.semi_major_axis * (1.0 - self.eccentricity);
+
+        (2.0 * a > r).then_some(a)
+    }
+
+    /// Delta-v of the departure and arrival burns of the phasing
+    /// maneuver, see [`KeplerianElements::phasing_orbit_sma_for_phase_change`].
+    ///
+    /// Both burns are tangential at the periapsis (anywhere on a circular
+    /// orbit), the arrival one reverses the departure, so they're equal.
+    pub fn phasing_orbit_delta_v(
+        &self,
+        delta_phase: Num,
+        n_orbits: u32,
+        mass: Num,
+    ) -> Option<(Num, Num)> {
+        let μ = standard_gravitational_parameter(mass);
+
+        let a = self.semi_major_axis;
+        let a_phasing = self.phasing_orbit_sma_for_phase_change(
+            delta_phase,
+            n_orbits,
+            mass,
+        )?;
+        let r = a * (1.0 - self.eccentricity);
+
+        // Vis-viva at the burn point
+        let v = (μ * (2.0 / r - 1.0 / a)).sqrt();
+        let v_phasing = (μ * (2.0 / r - 1.0 / a_phasing)).sqrt();
+
+        let Δv = (v_phasing - v).abs();
+
+        Some((Δv, Δv))
+    }
+
+    /// Range, phase angle and solar elongation of a target body
+    /// at `target_position`, as seen from this orbit at `epoch`.
+    ///
+    /// The central body is taken to be the Sun, at the origin.
+    pub fn observation_geometry_at_epoch(
+        &self,
+        target_position: Vec3,
+        mass: Num,
+        epoch: Num,
+        tolerance: Num,
+    ) -> ObservationGeometry {
+        let spacecraft =
+            self.state_vectors_at_epoch(mass, epoch, tolerance).position;
+
+        let to_target = target_position - spacecraft;
+
+        ObservationGeometry {
+            range: to_target.length(),
+            phase_angle: (-to_target).angle_between(-target_position),
+            elongation_from_sun: (-spacecraft).angle_between(to_target),
+        }
+    }
+
+    /// Mean motion in revolutions per day, as used by TLEs
+    pub fn mean_motion_rev_per_day(&self, mass: Num) -> Num {
+        astro::mean_motion_rev_per_day(self.semi_major_axis, mass)
+    }
+
+    /// Period at time `t` for a semi-major axis decaying (or growing)
+    /// at a constant rate `da_dt`, e.g. due to atmospheric drag.
+    pub fn period_with_sma_decay(&self, mass: Num, da_dt: Num, t: Num) -> Num {
+        let a = self.semi_major_axis + da_dt * (t - self.epoch);
+
+        astro::period(a, mass)
+    }
+
+    /// Epoch at which the semi-major axis decays to `body_radius`
+    /// at the constant rate `da_dt`.
+    ///
+    /// Returns `None` if the orbit doesn't decay. Drag grows as the orbit
+    /// gets lower, so with the current rate this is an upper bound.
+    /// The linear model doesn't depend on the mass, it's only taken
+    /// for symmetry with [`KeplerianElements::period_with_sma_decay`].
+    pub fn predict_reentry_epoch(
+        &self,
+        _mass: Num,
+        da_dt: Num,
+        body_radius: Num,
+    ) -> Option<Num> {
+        if da_dt >= 0.0 {
+            return None;
+        }
+
+        let remaining = (self.semi_major_axis - body_radius).max(0.0);
+
+        Some(self.epoch + remaining / -da_dt)
+    }
+
+    /// Magnitude of the retrograde burn at the apoapsis that lowers
+    /// the periapsis to `target_periapsis` (a radius, not an altitude).
+    ///
+    /// A target above the apoapsis raises the opposite side instead,
+    /// e.g. for a graveyard orbit. Only meaningful for elliptical orbits.
+    pub fn delta_v_for_deorbit_burn(
+        &self,
+        target_periapsis: Num,
+        mass: Num,
+    ) -> Num {
+        let μ = standard_gravitational_parameter(mass);
+
+        let a = self.semi_major_axis;
+        let r = a * (1.0 + self.eccentricity);
+        let a_after = 0.5 * (r + target_periapsis);
+
+        // Vis-viva at the burn point
+        let v = (μ * (2.0 / r - 1.0 / a)).sqrt();
+        let v_after = (μ * (2.0 / r - 1.0 / a_after)).sqrt();
+
+        (v - v_after).abs()
+    }
+
+    /// Time for drag to bring the periapsis down to the entry interface
+    /// of the `atmosphere`, e.g. [`Atmosphere::EARTH`].
+    ///
+    /// The orbit is treated as circular at its periapsis, decaying
+    /// at da/dt = -ρ √(μa) / B, where B = m / (Cd A) is the ballistic
+    /// coefficient in kg/m². Eccentric orbits spend less time in the dense
+    /// air and last longer than this. Returns zero if the periapsis is
+    /// already below the entry interface.
+    pub fn reentry_decay_time_estimate(
+        &self,
+        mass: Num,
+        ballistic_coefficient: Num,
+        body_radius: Num,
+        atmosphere: &Atmosphere,
+    ) -> Num {
+        let μ = standard_gravitational_parameter(mass);
+
+        let entry = atmosphere.entry_interface_altitude;
+        let periapsis = self.semi_major_axis * (1.0 - self.eccentricity);
+        let altitude = periapsis - body_radius;
+
+        if altitude <= entry {
+            return 0.0;
+        }
+
+        // Midpoint rule over the altitude, dt = B / (ρ √(μa)) da
+        let step = (altitude - entry) / REENTRY_DECAY_STEPS as Num;
+        (0..REENTRY_DECAY_STEPS)
+            .map(|k| {
+                let h = entry + (k as Num + 0.5) * step;
+                let rate = (atmosphere.density)(h)
+                    * (μ * (body_radius + h)).sqrt()
+                    / ballistic_coefficient;
+
+                step / rate
+            })
+            .sum()
+    }
+
+    /// Fraction of the orbital period spent below the given radius.
+    ///
+    /// The orbit crosses `radius` at two true anomalies symmetric about
+    /// the periapsis, so the time between them follows from Kepler's equation
+    /// at the crossing point.
+    ///
+    /// `None` for parabolic and hyperbolic orbits, which have no period.
+    pub fn fraction_of_time_below_radius(
+        &self,
+        mass: Num,
+        radius: Num,
+    ) -> Option<Num> {
+        let a = self.semi_major_axis;
+        let e = self.eccentricity;
+
+        if !self.is_elliptical() {
+            return None;
+        }
+
+        if radius <= a * (1.0 - e) {
+            return Some(0.0);
+        }
+
+        if radius >= a * (1.0 + e) {
+            return Some(1.0);
+        }
+
+        // r = a * (1 - e*cos(E))
+        let E = safe_acos((1.0 - radius / a) / e);
+        let M = E - e * E.sin();
+
+        // The crossings are at -M and M
+        let h = self.specific_angular_momentum(mass);
+        let time_below = 2.0 * M / astro::elliptic::mean_motion(h, e, mass);
+
+        Some(time_below / self.period(mass))
+    }
+
     /// https://en.wikipedia.org/wiki/Mean_anomaly
     pub fn mean_anomaly(&self, mass: Num, epoch: Num) -> Num {
         let h = self.specific_angular_momentum(mass);
@@ -74,6 +925,54 @@ impl KeplerianElements {
             + astro::elliptic::mean_motion(h, e, mass) * epoch_diff
     }
 
+    /// Revolutions elapsed between `self.epoch` and `epoch`, n·Δt / 2π,
+    /// including the fraction of the current one.
+    ///
+    /// Zero for hyperbolic orbits.
+    pub fn revolutions_since_epoch(&self, mass: Num, epoch: Num) -> Num {
+        if self.is_hyperbolic() {
+            return 0.0;
+        }
+
+        (epoch - self.epoch) / self.period(mass)
+    }
+
+    /// Mean, eccentric and true anomalies at `epoch`.
+    ///
+    /// Wrapping the mean anomaly loses the count of revolutions
+    /// of long propagations, it's kept in [`Anomalies::revolutions`].
+    pub fn anomalies_at_epoch(
+        &self,
+        mass: Num,
+        epoch: Num,
+        tolerance: Num,
+    ) -> Anomalies {
+        let e = self.eccentricity;
+
+        if self.is_hyperbolic() {
+            let M = self.hyperbolic_mean_anomaly(mass, epoch);
+            let F = astro::hyperbolic::estimate_anomaly(M, e, tolerance);
+
+            return Anomalies {
+                mean: M,
+                eccentric: F,
+                true_anomaly: astro::hyperbolic::true_anomaly(F, e),
+                revolutions: None,
+            };
+        }
+
+        let unwrapped = self.mean_anomaly(mass, epoch);
+        let M = wrap_angle(unwrapped);
+        let E = astro::elliptic::estimate_anomaly(M, e, tolerance);
+
+        Anomalies {
+            mean: M,
+            eccentric: E,
+            true_anomaly: astro::elliptic::true_anomaly(E, e),
+            revolutions: Some(((unwrapped - M) / TWO_PI).round() as i64),
+        }
+    }
+
     /// Hyperbolic mean anomaly
     /// SRC: https://orbital-mechanics.space/time-since-periapsis-and-keplers-equation/hyperbolic-trajectories.html#equation-eq-hyperbolic-mean-anomaly
     pub fn hyperbolic_mean_anomaly(&self, mass: Num, epoch: Num) -> Num {
@@ -139,6 +1038,47 @@ impl KeplerianElements {
         }
     }
 
+    /// Calculates the state vectors of many orbits at the same epoch.
+    ///
+    /// Kepler's equation for the elliptic orbits is solved in a batch,
+    /// see [`astro::elliptic::estimate_anomaly_batch`].
+    pub fn propagate_many_simd(
+        elements: &[Self],
+        mass: Num,
+        epoch: Num,
+        tolerance: Num,
+    ) -> Vec<StateVectors> {
+        let elliptic = elements.iter().filter(|e| e.is_elliptical());
+
+        let Ms: Vec<Num> = elliptic
+            .clone()
+            .map(|e| e.mean_anomaly(mass, epoch))
+            .collect();
+        let es: Vec<Num> = elliptic.map(|e| e.eccentricity).collect();
+
+        let mut Es =
+            astro::elliptic::estimate_anomaly_batch(&Ms, &es, tolerance)
+                .into_iter();
+
+        elements
+            .iter()
+            .map(|elements| {
+                if elements.is_hyperbolic() {
+                    return elements
+                        .state_vectors_at_epoch(mass, epoch, tolerance);
+                }
+
+                let E = Es.next().unwrap();
+                let v = astro::elliptic::true_anomaly(E, elements.eccentricity);
+
+                StateVectors {
+                    position: elements.position_at_true_anomaly(mass, v),
+                    velocity: elements.velocity_at_true_anomaly(mass, v),
+                }
+            })
+            .collect()
+    }
+
     #[inline]
     pub fn position_at_true_anomaly(&self, mass: Num, v: Num) -> Vec3 {
         let e = self.eccentricity;
@@ -168,6 +1108,39 @@ impl KeplerianElements {
         self.perifocal_to_equatorial(vec3(vp, vq, 0.0))
     }
 
+    /// Burn of `dv_magnitude` along the velocity at true anomaly `v`
+    pub fn dv_prograde_at_true_anomaly(
+        &self,
+        dv_magnitude: Num,
+        v: Num,
+        mass: Num,
+    ) -> Vec3 {
+        dv_magnitude * self.velocity_at_true_anomaly(mass, v).normalize()
+    }
+
+    /// Burn of `dv_magnitude` along the orbit normal at true anomaly `v`.
+    ///
+    /// The direction doesn't depend on `v`, which is only taken
+    /// for symmetry with the other burns.
+    pub fn dv_normal_at_true_anomaly(
+        &self,
+        dv_magnitude: Num,
+        _v: Num,
+        _mass: Num,
+    ) -> Vec3 {
+        dv_magnitude * self.normal()
+    }
+
+    /// Burn of `dv_magnitude` away from the central body at true anomaly `v`
+    pub fn dv_radial_at_true_anomaly(
+        &self,
+        dv_magnitude: Num,
+        v: Num,
+        mass: Num,
+    ) -> Vec3 {
+        dv_magnitude * self.position_at_true_anomaly(mass, v).normalize()
+    }
+
     #[inline(always)]
     pub fn perifocal_to_equatorial(&self, perifocal: Vec3) -> Vec3 {
         let mut m = Mat3::IDENTITY;
@@ -215,6 +1188,387 @@ impl KeplerianElements {
         }
     }
 
+    /// Smallest epoch, not before `self.epoch`, at which the true anomaly
+    /// equals `v`.
+    ///
+    /// Returns `None` for hyperbolic orbits if `v` lies beyond
+    /// the asymptotes or if the body has already passed it.
+    pub fn next_epoch_at_true_anomaly(&self, mass: Num, v: Num) -> Option<Num> {
+        let e = self.eccentricity;
+        let h = self.specific_angular_momentum(mass);
+
+        if self.is_hyperbolic() {
+            if 1.0 + e * v.cos() <= 0.0 {
+                return None;
+            }
+
+            // Within (-π, π) so that the half angle stays between the asymptotes
+            let v = wrap_angle(v + PI) - PI;
+            let F = astro::hyperbolic::hyperbolic_anomaly(v, e);
+            let M = e * F.sinh() - F;
+
+            let n = astro::hyperbolic::mean_motion(h, e, mass);
+            let dt = (M - self.mean_anomaly_at_epoch) / n;
+
+            (dt >= 0.0).then_some(self.epoch + dt)
+        } else {
+            let E = astro::elliptic::eccentric_anomaly(v, e);
+            let M = E - e * E.sin();
+
+            let n = astro::elliptic::mean_motion(h, e, mass);
+            let ΔM = wrap_angle(M - self.mean_anomaly_at_epoch);
+
+            Some(self.epoch + ΔM / n)
+        }
+    }
+
+    /// Angle from the ascending node to the position, u = ω + ν.
+    ///
+    /// Well defined for circular orbits, unlike ω and ν on their own.
+    pub fn argument_of_latitude(
+        &self,
+        mass: Num,
+        epoch: Num,
+        tolerance: Num,
+    ) -> Num {
+        let v = self.true_anomaly_at_epoch(mass, epoch, tolerance);
+
+        wrap_angle(self.argument_of_periapsis + v)
+    }
+
+    /// ϖ = Ω + ω
+    /// SRC: https://en.wikipedia.org/wiki/Longitude_of_the_periapsis
+    pub fn longitude_of_periapsis(&self) -> Num {
+        wrap_angle(
+            self.right_ascension_of_the_ascending_node
+                + self.argument_of_periapsis,
+        )
+    }
+
+    /// L = Ω + ω + M
+    /// SRC: https://en.wikipedia.org/wiki/Mean_longitude
+    pub fn mean_longitude(&self, mass: Num, epoch: Num) -> Num {
+        wrap_angle(
+            self.longitude_of_periapsis() + self.mean_anomaly(mass, epoch),
+        )
+    }
+
+    /// λ = Ω + ω + ν
+    /// SRC: https://en.wikipedia.org/wiki/True_longitude
+    pub fn true_longitude(&self, mass: Num, epoch: Num, tolerance: Num) -> Num {
+        let v = self.true_anomaly_at_epoch(mass, epoch, tolerance);
+
+        wrap_angle(self.longitude_of_periapsis() + v)
+    }
+
+    /// Resonant argument of the j:k mean motion resonance with `other`,
+    /// φ = j λ - k λ' - (j - k) ϖ, where λ and ϖ are the mean longitude
+    /// and the longitude of periapsis of `self` and λ' the mean longitude
+    /// of `other`.
+    ///
+    /// E.g. for Pluto in the 3:2 resonance with Neptune, `self` is Pluto,
+    /// j = 3 and k = 2. Mean longitudes don't need Kepler's equation
+    /// to be solved, so the tolerance is unused.
+    ///
+    /// SRC: https://en.wikipedia.org/wiki/Orbital_resonance
+    pub fn resonant_argument(
+        &self,
+        other: &KeplerianElements,
+        j: i32,
+        k: i32,
+        mass: Num,
+        epoch: Num,
+        _tolerance: Num,
+    ) -> Num {
+        let (j, k) = (j as Num, k as Num);
+
+        wrap_angle(
+            j * self.mean_longitude(mass, epoch)
+                - k * other.mean_longitude(mass, epoch)
+                - (j - k) * self.longitude_of_periapsis(),
+        )
+    }
+
+    /// Whether the resonant argument librates, i.e. stays within
+    /// a bounded range, between `epoch_start` and `epoch_end`,
+    /// as opposed to circulating through all angles.
+    ///
+    /// The argument is sampled every `dt`, which must be short enough for
+    /// it to change by less than half a turn between the samples.
+    /// Returns `None` when `dt` isn't positive.
+    #[allow(clippy::too_many_arguments)]
+    pub fn is_in_resonance(
+        &self,
+        other: &KeplerianElements,
+        j: i32,
+        k: i32,
+        mass: Num,
+        epoch_start: Num,
+        epoch_end: Num,
+        dt: Num,
+        tolerance: Num,
+    ) -> Option<bool> {
+        if dt.is_nan() || dt <= 0.0 {
+            return None;
+        }
+
+        let φ =
+            |epoch| self.resonant_argument(other, j, k, mass, epoch, tolerance);
+
+        let mut previous = φ(epoch_start);
+        let mut unwrapped: Num = 0.0;
+        let (mut min, mut max): (Num, Num) = (0.0, 0.0);
+
+        let mut epoch = epoch_start;
+        while epoch < epoch_end {
+            epoch = (epoch + dt).min(epoch_end);
+
+            let current = φ(epoch);
+            unwrapped += angle_diff(current, previous);
+            previous = current;
+
+            min = min.min(unwrapped);
+            max = max.max(unwrapped);
+
+            if max - min >= TWO_PI {
+                return Some(false);
+            }
+        }
+
+        Some(true)
+    }
+
+    /// Secular drift rate of the right ascension of the ascending node
+    /// due to the oblateness (J2) of the central body
+    /// SRC: https://en.wikipedia.org/wiki/Nodal_precession
+    pub fn secular_raan_rate(
+        &self,
+        body_radius: Num,
+        j2: Num,
+        mass: Num,
+    ) -> Num {
+        let (n, k) = self.j2_rate_factors(body_radius, j2, mass);
+
+        -1.5 * n * k * self.inclination.cos()
+    }
+
+    /// Secular drift rate of the argument of periapsis due to J2
+    /// SRC: https://en.wikipedia.org/wiki/Apsidal_precession
+    pub fn secular_argument_of_periapsis_rate(
+        &self,
+        body_radius: Num,
+        j2: Num,
+        mass: Num,
+    ) -> Num {
+        let (n, k) = self.j2_rate_factors(body_radius, j2, mass);
+
+        0.75 * n * k * (5.0 * self.inclination.cos().powi(2) - 1.0)
+    }
+
+    /// Secular J2 correction of the mean motion, i.e. the drift of the mean
+    /// anomaly on top of the Keplerian mean motion
+    /// SRC: Vallado, Fundamentals of Astrodynamics and Applications, eq. 9-41
+    pub fn secular_mean_anomaly_rate(
+        &self,
+        body_radius: Num,
+        j2: Num,
+        mass: Num,
+    ) -> Num {
+        let (n, k) = self.j2_rate_factors(body_radius, j2, mass);
+        let η = (1.0 - self.eccentricity.powi(2)).sqrt();
+
+        0.75 * n * k * η * (3.0 * self.inclination.cos().powi(2) - 1.0)
+    }
+
+    /// Mean elements `dt` seconds later, with the secular J2 drifts of Ω, ω
+    /// and M applied. The J2 has no secular effect on a, e and i.
+    ///
+    /// Only meaningful for elliptical orbits.
+    pub fn propagate_secular_j2(
+        &self,
+        dt: Num,
+        mass: Num,
+        body_radius: Num,
+        j2: Num,
+    ) -> Self {
+        let (n, _) = self.j2_rate_factors(body_radius, j2, mass);
+
+        let Ω_rate = self.secular_raan_rate(body_radius, j2, mass);
+        let ω_rate =
+            self.secular_argument_of_periapsis_rate(body_radius, j2, mass);
+        let M_rate = n + self.secular_mean_anomaly_rate(body_radius, j2, mass);
+
+        Self {
+            right_ascension_of_the_ascending_node: wrap_angle(
+                self.right_ascension_of_the_ascending_node + Ω_rate * dt,
+            ),
+            argument_of_periapsis: wrap_angle(
+                self.argument_of_periapsis + ω_rate * dt,
+            ),
+            mean_anomaly_at_epoch: self.mean_anomaly_at_epoch + M_rate * dt,
+            epoch: self.epoch + dt,
+            ..*self
+        }
+        .wrapped()
+    }
+
+    /// Long-period J2 correction of the right ascension of the ascending node
+    /// at time `t`, i.e. the oscillation around the secular drift.
+    ///
+    /// See [`KeplerianElements::long_period_correction_j2`].
+    pub fn long_period_raan_correction(
+        &self,
+        t: Num,
+        body_radius: Num,
+        j2: Num,
+        mass: Num,
+    ) -> Num {
+        self.long_period_terms(t, body_radius, j2, mass).Ω
+    }
+
+    /// Long-period J2 correction of the inclination at time `t`
+    pub fn long_period_inclination_correction(
+        &self,
+        t: Num,
+        body_radius: Num,
+        j2: Num,
+        mass: Num,
+    ) -> Num {
+        self.long_period_terms(t, body_radius, j2, mass).i
+    }
+
+    /// Long-period J2 correction of the argument of periapsis at time `t`
+    pub fn long_period_argument_of_periapsis_correction(
+        &self,
+        t: Num,
+        body_radius: Num,
+        j2: Num,
+        mass: Num,
+    ) -> Num {
+        self.long_period_terms(t, body_radius, j2, mass).ω
+    }
+
+    /// Long-period J2 correction of the eccentricity at time `t`
+    pub fn long_period_eccentricity_correction(
+        &self,
+        t: Num,
+        body_radius: Num,
+        j2: Num,
+        mass: Num,
+    ) -> Num {
+        self.long_period_terms(t, body_radius, j2, mass).e
+    }
+
+    /// Analytical evolution of the elements under J2 up to time `t`:
+    /// the secular drift of Ω and ω plus Brouwer's long-period terms
+    /// of e, i, Ω and ω.
+    ///
+    /// The long-period terms of J2 go with cos(2ω) and sin(2ω)
+    /// and are singular at the critical inclination (cos²i = 1/5).
+    /// The mean anomaly and the epoch are left unchanged.
+    ///
+    /// SRC: Brouwer, D. (1959), "Solution of the problem of artificial
+    /// satellite theory without drag", The Astronomical Journal 64, 378
+    pub fn long_period_correction_j2(
+        &self,
+        t: Num,
+        body_radius: Num,
+        j2: Num,
+        mass: Num,
+    ) -> Self {
+        let dt = t - self.epoch;
+        let terms = self.long_period_terms(t, body_radius, j2, mass);
+
+        let Ω = self.right_ascension_of_the_ascending_node
+            + self.secular_raan_rate(body_radius, j2, mass) * dt;
+        let ω = self.argument_of_periapsis
+            + self.secular_argument_of_periapsis_rate(body_radius, j2, mass)
+                * dt;
+
+        Self {
+            eccentricity: self.eccentricity + terms.e,
+            inclination: self.inclination + terms.i,
+            right_ascension_of_the_ascending_node: wrap_angle(Ω + terms.Ω),
+            argument_of_periapsis: wrap_angle(ω + terms.ω),
+            ..*self
+        }
+    }
+
+    /// Mean motion and J2 * (R / p)², common to the secular rates
+    fn j2_rate_factors(
+        &self,
+        body_radius: Num,
+        j2: Num,
+        mass: Num,
+    ) -> (Num, Num) {
+        let μ = standard_gravitational_parameter(mass);
+        let a = self.semi_major_axis;
+        let p = self.semi_latus_rectum();
+
+        let n = (μ / a.powi(3)).sqrt();
+
+        (n, j2 * (body_radius / p).powi(2))
+    }
+
+    fn long_period_terms(
+        &self,
+        t: Num,
+        body_radius: Num,
+        j2: Num,
+        mass: Num,
+    ) -> LongPeriodTerms {
+        let e = self.eccentricity;
+        let i = self.inclination;
+        let η2 = 1.0 - e.powi(2);
+        let θ = i.cos();
+        let θ2 = θ.powi(2);
+
+        // Brouwer's γ₂' = J2/2 * (R / p)²
+        let gamma = 0.5 * j2 * (body_radius / self.semi_latus_rectum()).powi(2);
+
+        let ω = self.argument_of_periapsis
+            + self.secular_argument_of_periapsis_rate(body_radius, j2, mass)
+                * (t - self.epoch);
+        let (sin_2ω, cos_2ω) = (2.0 * ω).sin_cos();
+
+        // Resonance denominator, zero at the critical inclination
+        let d = 1.0 - 5.0 * θ2;
+
+        let Δe = (gamma / 8.0)
+            * e
+            * η2
+            * (1.0 - 11.0 * θ2 - 40.0 * θ2.powi(2) / d)
+            * cos_2ω;
+
+        // Undefined on an equatorial orbit, where the node and
+        // the inclination correction vanish
+        let Δi = if i.sin().abs() < EQUATORIAL_TOLERANCE {
+            0.0
+        } else {
+            -e * Δe / (η2 * i.tan())
+        };
+
+        let ΔΩ = -(gamma / 8.0)
+            * e.powi(2)
+            * θ
+            * (11.0 + 80.0 * θ2 / d + 200.0 * θ2.powi(2) / d.powi(2))
+            * sin_2ω;
+
+        let Δω = -(gamma / 16.0)
+            * (2.0 + e.powi(2)
+                - 11.0 * (2.0 + 3.0 * e.powi(2)) * θ2
+                - 40.0 * (2.0 + 5.0 * e.powi(2)) * θ2.powi(2) / d
+                - 400.0 * e.powi(2) * θ2.powi(3) / d.powi(2))
+            * sin_2ω;
+
+        LongPeriodTerms {
+            e: Δe,
+            i: Δi,
+            Ω: ΔΩ,
+            ω: Δω,
+        }
+    }
+
     pub fn is_elliptical(&self) -> bool {
         self.eccentricity < 1.0
     }
@@ -224,3 +1578,23 @@ impl KeplerianElements {
         self.eccentricity >= 1.0
     }
 }
+
+/// Long-period variations of the elements
+struct LongPeriodTerms {
+    e: Num,
+    i: Num,
+    Ω: Num,
+    ω: Num,
+}
+
+impl From<[Num; 7]> for KeplerianElements {
+    fn from(array: [Num; 7]) -> Self {
+        Self::from_array(array)
+    }
+}
+
+impl From<KeplerianElements> for [Num; 7] {
+    fn from(elements: KeplerianElements) -> Self {
+        elements.as_array()
+    }
+}
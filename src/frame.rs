@@ -0,0 +1,239 @@
+//! Reference frame tagging.
+//!
+//! Plain [`KeplerianElements`] and [`StateVectors`] are relative to
+//! whatever frame their values were given in. The framed wrappers carry
+//! the frame along, convert between frames and refuse to mix them.
+
+use std::fmt;
+
+use crate::{KeplerianElements, Mat3, Num, StateVectors};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Frame {
+    /// The ecliptic plane, x axis towards the vernal equinox
+    Ecliptic,
+    /// Equator of the central body, tilted by `obliquity` around
+    /// the x axis of the ecliptic frame
+    Equatorial { obliquity: Num },
+    /// Any other frame, given as the rotation from the ecliptic frame
+    Custom(Mat3),
+}
+
+impl Frame {
+    /// Rotation from the ecliptic frame into this frame
+    pub fn from_ecliptic(&self) -> Mat3 {
+        match *self {
+            Self::Ecliptic => Mat3::IDENTITY,
+            Self::Equatorial { obliquity } => Mat3::from_rotation_x(obliquity),
+            Self::Custom(m) => m,
+        }
+    }
+
+    /// Rotation from this frame into `other`
+    pub fn rotation_to(&self, other: &Frame) -> Mat3 {
+        other.from_ecliptic() * self.from_ecliptic().transpose()
+    }
+}
+
+/// Two framed values in different frames were combined
+///
+/// The frames are boxed since a custom frame makes them too large
+/// to pass around in every `Result`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameMismatch {
+    pub left: Box<Frame>,
+    pub right: Box<Frame>,
+}
+
+impl fmt::Display for FrameMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Frame mismatch: {:?} vs {:?}", self.left, self.right)
+    }
+}
+
+impl std::error::Error for FrameMismatch {}
+
+fn ensure_same_frame(left: &Frame, right: &Frame) -> Result<(), FrameMismatch> {
+    if left == right {
+        Ok(())
+    } else {
+        Err(FrameMismatch {
+            left: Box::new(*left),
+            right: Box::new(*right),
+        })
+    }
+}
+
+/// State vectors tagged with their reference frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FramedState {
+    pub frame: Frame,
+    pub state: StateVectors,
+}
+
+impl FramedState {
+    pub fn new(frame: Frame, state: StateVectors) -> Self {
+        Self { frame, state }
+    }
+
+    pub fn to_frame(&self, frame: Frame) -> Self {
+        let m = self.frame.rotation_to(&frame);
+
+        Self {
+            frame,
+            state: StateVectors::new(
+                m * self.state.position,
+                m * self.state.velocity,
+            ),
+        }
+    }
+
+    /// State of `self` relative to `other`
+    pub fn relative_to(
+        &self,
+        other: &Self,
+    ) -> Result<StateVectors, FrameMismatch> {
+        ensure_same_frame(&self.frame, &other.frame)?;
+
+        Ok(StateVectors::new(
+            self.state.position - other.state.position,
+            self.state.velocity - other.state.velocity,
+        ))
+    }
+}
+
+/// Keplerian elements tagged with their reference frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FramedElements {
+    pub frame: Frame,
+    pub elements: KeplerianElements,
+}
+
+impl FramedElements {
+    pub fn new(frame: Frame, elements: KeplerianElements) -> Self {
+        Self { frame, elements }
+    }
+
+    /// Converts the elements through the state vectors at their epoch
+    pub fn to_frame(&self, frame: Frame, mass: Num, tolerance: Num) -> Self {
+        let epoch = self.elements.epoch;
+
+        let state = self.state_vectors_at_epoch(mass, epoch, tolerance);
+        let state = state.to_frame(frame).state;
+
+        Self {
+            frame,
+            elements: state.to_elements(mass, epoch),
+        }
+    }
+
+    pub fn state_vectors_at_epoch(
+        &self,
+        mass: Num,
+        epoch: Num,
+        tolerance: Num,
+    ) -> FramedState {
+        FramedState::new(
+            self.frame,
+            self.elements.state_vectors_at_epoch(mass, epoch, tolerance),
+        )
+    }
+
+    pub fn coplanar_intersection_true_anomalies(
+        &self,
+        other: &Self,
+    ) -> Result<Vec<(Num, Num)>, FrameMismatch> {
+        ensure_same_frame(&self.frame, &other.frame)?;
+
+        Ok(self
+            .elements
+            .coplanar_intersection_true_anomalies(&other.elements))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{ASTRONOMICAL_UNIT, J2000_OBLIQUITY, SUN_MASS};
+    use crate::utils::{ecliptic_to_equatorial, equatorial_to_ecliptic};
+    use crate::{vec3, Vec3};
+
+    const TOLERANCE: Num = 1e-5;
+
+    const EQUATORIAL: Frame = Frame::Equatorial {
+        obliquity: J2000_OBLIQUITY,
+    };
+
+    const EARTH: KeplerianElements = KeplerianElements::new(
+        ASTRONOMICAL_UNIT,
+        0.0167,
+        0.0,
+        0.0,
+        1.993,
+        6.259,
+        0.0,
+    );
+
+    #[test]
+    fn earth_normal_is_tilted_by_the_obliquity() {
+        let ecliptic = FramedElements::new(Frame::Ecliptic, EARTH);
+        let equatorial = ecliptic.to_frame(EQUATORIAL, SUN_MASS, TOLERANCE);
+
+        let normal = equatorial.elements.normal();
+
+        assert!((normal.angle_between(Vec3::Z) - J2000_OBLIQUITY).abs() < 1e-4);
+        assert!(
+            (equatorial.elements.inclination - J2000_OBLIQUITY).abs() < 1e-4
+        );
+
+        // The ecliptic pole lies at a right ascension of 270°
+        assert!(normal.y < 0.0);
+        assert!(normal.x.abs() < 1e-4);
+
+        let expected = ecliptic_to_equatorial(EARTH.normal(), J2000_OBLIQUITY);
+        assert!(normal.distance(expected) < 1e-4);
+    }
+
+    #[test]
+    fn round_trips_are_identity() {
+        let v = vec3(0.3, -1.2, 2.5);
+
+        let round_trip = equatorial_to_ecliptic(
+            ecliptic_to_equatorial(v, J2000_OBLIQUITY),
+            J2000_OBLIQUITY,
+        );
+        assert!(round_trip.distance(v) < 1e-6);
+
+        let custom = Frame::Custom(Mat3::from_rotation_z(0.7));
+        let state = FramedState::new(
+            Frame::Ecliptic,
+            StateVectors::new(v, vec3(1.0, 2.0, 3.0)),
+        );
+        let round_trip = state
+            .to_frame(EQUATORIAL)
+            .to_frame(custom)
+            .to_frame(Frame::Ecliptic);
+
+        assert_eq!(round_trip.frame, Frame::Ecliptic);
+        assert!(round_trip.state.abs_diff(&state.state) < 1e-5);
+    }
+
+    #[test]
+    fn mixing_frames_is_an_error() {
+        let ecliptic = FramedElements::new(Frame::Ecliptic, EARTH);
+        let equatorial = FramedElements::new(EQUATORIAL, EARTH);
+
+        assert_eq!(
+            ecliptic.coplanar_intersection_true_anomalies(&equatorial),
+            Err(FrameMismatch {
+                left: Box::new(Frame::Ecliptic),
+                right: Box::new(EQUATORIAL),
+            })
+        );
+
+        let a = ecliptic.state_vectors_at_epoch(SUN_MASS, 0.0, TOLERANCE);
+        let b = equatorial.state_vectors_at_epoch(SUN_MASS, 0.0, TOLERANCE);
+        assert!(a.relative_to(&b).is_err());
+        assert!(a.relative_to(&b.to_frame(Frame::Ecliptic)).is_ok());
+    }
+}
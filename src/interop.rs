@@ -0,0 +1,101 @@
+//! Conversions to and from other math libraries.
+//!
+//! `Vec3` conversions to and from `mint` are provided by glam itself
+//! when the `mint` feature is enabled.
+
+use crate::{Num, StateVectors};
+
+#[cfg(feature = "mint")]
+impl StateVectors {
+    pub fn from_mint(
+        position: mint::Vector3<Num>,
+        velocity: mint::Vector3<Num>,
+    ) -> Self {
+        Self {
+            position: position.into(),
+            velocity: velocity.into(),
+        }
+    }
+
+    /// Returns the position and velocity
+    pub fn to_mint(&self) -> (mint::Vector3<Num>, mint::Vector3<Num>) {
+        (self.position.into(), self.velocity.into())
+    }
+}
+
+#[cfg(feature = "mint")]
+impl From<(mint::Vector3<Num>, mint::Vector3<Num>)> for StateVectors {
+    fn from(
+        (position, velocity): (mint::Vector3<Num>, mint::Vector3<Num>),
+    ) -> Self {
+        Self::from_mint(position, velocity)
+    }
+}
+
+#[cfg(feature = "mint")]
+impl From<StateVectors> for (mint::Vector3<Num>, mint::Vector3<Num>) {
+    fn from(state_vectors: StateVectors) -> Self {
+        state_vectors.to_mint()
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl StateVectors {
+    pub fn from_nalgebra(
+        position: &nalgebra::Vector3<Num>,
+        velocity: &nalgebra::Vector3<Num>,
+    ) -> Self {
+        Self {
+            position: crate::vec3(position.x, position.y, position.z),
+            velocity: crate::vec3(velocity.x, velocity.y, velocity.z),
+        }
+    }
+
+    /// Returns the position and velocity
+    pub fn to_nalgebra(
+        &self,
+    ) -> (nalgebra::Vector3<Num>, nalgebra::Vector3<Num>) {
+        (
+            nalgebra::Vector3::from(self.position.to_array()),
+            nalgebra::Vector3::from(self.velocity.to_array()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vec3;
+
+    const SV: StateVectors = StateVectors {
+        position: vec3(1.5, -2.25, 3.125),
+        velocity: vec3(-0.1, 0.2, 1e6),
+    };
+
+    #[cfg(feature = "mint")]
+    #[test]
+    fn mint_round_trip() {
+        let (p, v) = SV.to_mint();
+        assert_eq!(p, mint::Vector3::from([1.5, -2.25, 3.125]));
+        assert_eq!(v, mint::Vector3::from([-0.1, 0.2, 1e6]));
+
+        let tuple: (mint::Vector3<Num>, mint::Vector3<Num>) = SV.into();
+        assert_eq!(StateVectors::from(tuple), SV);
+    }
+
+    #[cfg(feature = "nalgebra")]
+    #[test]
+    fn nalgebra_round_trip() {
+        let (p, v) = SV.to_nalgebra();
+
+        // Push the state through some nalgebra math that doesn't change it
+        let rotation = nalgebra::Rotation3::from_axis_angle(
+            &nalgebra::Vector3::z_axis(),
+            0.0,
+        );
+        let p = rotation * p;
+        let v = nalgebra::Matrix3::identity() * v;
+
+        assert_eq!(StateVectors::from_nalgebra(&p, &v), SV);
+    }
+}
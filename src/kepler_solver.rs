@@ -0,0 +1,116 @@
+use crate::astro;
+use crate::{KeplerianElements, Num, StateVectors};
+
+/// Largest change in mean anomaly between two solves (in radians)
+/// for which the previous solution is used as the initial guess.
+const MAX_WARM_START_STEP: Num = 1.0;
+
+/// Solves Kepler's equation for a sequence of epochs,
+/// using the previous solution to seed the next one.
+///
+/// When propagating an orbit over a sequence of close epochs
+/// (e.g. animation frames, played forwards or backwards) the previous
+/// eccentric anomaly advanced by the change in mean anomaly
+/// is a very good initial guess, so most solves converge
+/// in one or two iterations.
+///
+/// Falls back to a cold start when the epoch jumps too far
+/// or the elements change.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct KeplerSolver {
+    last: Option<LastSolve>,
+    iterations: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct LastSolve {
+    elements: KeplerianElements,
+    mass: Num,
+    mean_anomaly: Num,
+    eccentric_anomaly: Num,
+}
+
+impl KeplerSolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forgets the previous solution
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+
+    /// Number of iterations taken by the last solve
+    pub fn last_iterations(&self) -> usize {
+        self.iterations
+    }
+
+    /// Eccentric anomaly of an elliptical orbit at the given epoch
+    pub fn eccentric_anomaly(
+        &mut self,
+        elements: &KeplerianElements,
+        mass: Num,
+        epoch: Num,
+        tolerance: Num,
+    ) -> Num {
+        let M = elements.mean_anomaly(mass, epoch);
+        let e = elements.eccentricity;
+
+        let E0 = match self.last {
+            Some(last)
+                if last.elements == *elements
+                    && last.mass == mass
+                    && (M - last.mean_anomaly).abs() <= MAX_WARM_START_STEP =>
+            {
+                // dE/dM = 1 / (1 - e*cos(E))
+                let E = last.eccentric_anomaly;
+                E + (M - last.mean_anomaly) / (1.0 - e * E.cos())
+            }
+            _ => M,
+        };
+
+        let (E, iterations) =
+            astro::elliptic::estimate_anomaly_with_guess(M, e, E0, tolerance);
+
+        self.iterations = iterations;
+        self.last = Some(LastSolve {
+            elements: *elements,
+            mass,
+            mean_anomaly: M,
+            eccentric_anomaly: E,
+        });
+
+        E
+    }
+
+    pub fn true_anomaly_at_epoch(
+        &mut self,
+        elements: &KeplerianElements,
+        mass: Num,
+        epoch: Num,
+        tolerance: Num,
+    ) -> Num {
+        if elements.is_hyperbolic() {
+            return elements.true_anomaly_at_epoch(mass, epoch, tolerance);
+        }
+
+        let E = self.eccentric_anomaly(elements, mass, epoch, tolerance);
+
+        astro::elliptic::true_anomaly(E, elements.eccentricity)
+    }
+
+    pub fn state_vectors_at_epoch(
+        &mut self,
+        elements: &KeplerianElements,
+        mass: Num,
+        epoch: Num,
+        tolerance: Num,
+    ) -> StateVectors {
+        let v = self.true_anomaly_at_epoch(elements, mass, epoch, tolerance);
+
+        StateVectors {
+            position: elements.position_at_true_anomaly(mass, v),
+            velocity: elements.velocity_at_true_anomaly(mass, v),
+        }
+    }
+}
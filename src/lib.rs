@@ -1,27 +1,51 @@
+//! Precision is picked by the features: `f64` wins whenever it's enabled,
+//! so a dependency graph enabling both `f32` and `f64` still compiles.
+//! Without either the crate uses `f32`.
+
 #![allow(non_snake_case)]
 
 #[cfg(feature = "f64")]
 pub use glam::{dvec3 as vec3, DMat3 as Mat3, DVec3 as Vec3};
-#[cfg(feature = "f32")]
+#[cfg(not(feature = "f64"))]
 pub use glam::{vec3, Mat3, Vec3};
 
-#[cfg(feature = "f32")]
+#[cfg(not(feature = "f64"))]
 pub type Num = f32;
 
 #[cfg(feature = "f64")]
 pub type Num = f64;
 
+pub mod analysis;
+#[cfg(feature = "approx")]
+mod approx_eq;
 pub mod astro;
 pub mod constants;
 pub mod elements;
+pub mod frame;
+#[cfg(any(feature = "mint", feature = "nalgebra"))]
+mod interop;
+pub mod kepler_solver;
 pub mod math;
+pub mod mean_motion;
+pub mod orbit;
+pub mod propagation;
+#[cfg(feature = "rand")]
+pub mod random;
 pub mod state_vectors;
+pub mod system;
+pub mod trajectory;
 pub mod utils;
 
 use constants::{PI, TWO_PI};
 
 pub use self::elements::KeplerianElements;
-pub use self::state_vectors::StateVectors;
+pub use self::frame::{Frame, FramedElements, FramedState};
+pub use self::kepler_solver::KeplerSolver;
+pub use self::mean_motion::MeanMotion;
+pub use self::orbit::{CachedOrbit, Orbit};
+#[cfg(feature = "rand")]
+pub use self::random::ElementSigmas;
+pub use self::state_vectors::{Conic, ConversionOptions, StateVectors};
 
 #[cfg(test)]
 mod tests {
@@ -29,11 +53,24 @@ mod tests {
 
     use super::*;
 
+    use crate::astro::atmosphere::Atmosphere;
+    use crate::constants::{
+        ASTRONOMICAL_UNIT, EARTH_J2, EARTH_MASS, EARTH_RADIUS, SUN_MASS,
+    };
+
     const MASS: Num = 100_000_000_000.0;
     const EPOCH: Num = 0.0;
     const MAX_ABS_DIFF: Num = 0.0001;
     const TOLERANCE: Num = 0.0001;
 
+    #[test]
+    fn precision_follows_the_features() {
+        let expected = if cfg!(feature = "f64") { 8 } else { 4 };
+
+        assert_eq!(std::mem::size_of::<Num>(), expected);
+        assert_eq!(std::mem::size_of::<Vec3>(), 3 * expected);
+    }
+
     fn test_back_and_forth_conversion(
         original: KeplerianElements,
         mass: Num,
@@ -43,6 +80,14 @@ mod tests {
 
         let elements = KeplerianElements::from_state_vectors(&sv, mass, epoch);
 
+        if elements.is_elliptical() {
+            let M = elements.mean_anomaly_at_epoch;
+            assert!(
+                (0.0..TWO_PI).contains(&M),
+                "Mean anomaly {M} not in [0, 2π)"
+            );
+        }
+
         let sv_converted =
             elements.state_vectors_at_epoch(mass, epoch, TOLERANCE);
 
@@ -113,6 +158,161 @@ mod tests {
         );
     }
 
+    #[test_case(0.0, 0.7 ; "circular")]
+    #[test_case(0.2, 0.0 ; "equatorial")]
+    #[test_case(0.0, 0.0 ; "circular equatorial")]
+    #[test_case(0.2, PI ; "retrograde equatorial")]
+    #[test_case(0.0, PI ; "retrograde circular equatorial")]
+    fn conversion_degenerate_angles(eccentricity: Num, inclination: Num) {
+        let original = KeplerianElements {
+            eccentricity,
+            semi_major_axis: 1.0,
+            inclination,
+            right_ascension_of_the_ascending_node: 0.4,
+            argument_of_periapsis: 0.9,
+            mean_anomaly_at_epoch: 2.1,
+            epoch: 0.0,
+        };
+
+        test_back_and_forth_conversion(original, MASS, EPOCH);
+
+        let sv = original.state_vectors_at_epoch(MASS, EPOCH, TOLERANCE);
+        let elements = sv.to_elements(MASS, EPOCH);
+
+        if eccentricity == 0.0 {
+            assert_eq!(elements.eccentricity, 0.0);
+            assert_eq!(elements.argument_of_periapsis, 0.0);
+        }
+        if inclination.sin().abs() < 1e-6 {
+            assert_eq!(elements.right_ascension_of_the_ascending_node, 0.0);
+        }
+
+        // The true longitude is always defined for prograde orbits
+        if inclination < PI / 2.0 {
+            let diff = math::angle_diff(
+                elements.true_longitude(MASS, EPOCH, TOLERANCE),
+                original.true_longitude(MASS, EPOCH, TOLERANCE),
+            );
+            assert!(diff.abs() < 1e-3, "{elements:?}");
+        }
+    }
+
+    #[test]
+    fn conversion_rounding_at_the_acos_limits() {
+        let μ = astro::standard_gravitational_parameter(MASS);
+
+        for k in 0..500 {
+            let θ = k as Num * 0.0317;
+            let r = 1.0 + k as Num * 0.013;
+            let speed = (μ / r).sqrt() * (1.0 + (k % 7) as Num * 0.01);
+            // Either exactly in the plane or a few ULPs off it
+            let z = [0.0, Num::EPSILON, -Num::EPSILON][k % 3];
+
+            for direction in [1.0, -1.0] {
+                let sv = StateVectors::new(
+                    r * vec3(θ.cos(), θ.sin(), z),
+                    direction * speed * vec3(-θ.sin(), θ.cos(), z),
+                );
+
+                let elements = sv.to_elements(MASS, EPOCH);
+                assert_eq!(elements.validate(), Ok(()), "{sv:?}");
+
+                let converted =
+                    elements.state_vectors_at_epoch(MASS, EPOCH, TOLERANCE);
+                assert!(
+                    sv.abs_diff(&converted) < 1e-3 * r,
+                    "{sv:?} != {converted:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn nearly_equatorial_conversion_is_stable() {
+        let original = KeplerianElements {
+            eccentricity: 0.1,
+            semi_major_axis: 1.0,
+            inclination: 1e-9,
+            right_ascension_of_the_ascending_node: 0.4,
+            argument_of_periapsis: 0.9,
+            mean_anomaly_at_epoch: 2.1,
+            epoch: 0.0,
+        };
+        let sv = original.state_vectors_at_epoch(MASS, EPOCH, TOLERANCE);
+
+        let elements = sv.to_elements(MASS, EPOCH);
+        assert_eq!(elements.right_ascension_of_the_ascending_node, 0.0);
+        assert!(
+            math::angle_diff(
+                elements.longitude_of_periapsis(),
+                original.longitude_of_periapsis()
+            )
+            .abs()
+                < 1e-4,
+            "{elements:?}"
+        );
+        assert!(
+            math::angle_diff(elements.mean_anomaly_at_epoch, 2.1).abs() < 1e-3
+        );
+
+        for dv in [Vec3::X, Vec3::Y, Vec3::Z, vec3(-1.0, 1.0, -1.0)] {
+            let perturbed =
+                StateVectors::new(sv.position, sv.velocity + 1e-10 * dv);
+            let perturbed = perturbed.to_elements(MASS, EPOCH);
+
+            assert!(
+                perturbed.angle_abs_diff(&elements) < 1e-4,
+                "{perturbed:?} != {elements:?}"
+            );
+        }
+
+        // A stricter threshold keeps the noisy node
+        let strict = ConversionOptions {
+            equatorial_threshold: 0.0,
+            ..ConversionOptions::default()
+        };
+        let elements = sv.to_elements_with(MASS, EPOCH, &strict);
+        assert!(
+            math::angle_diff(
+                elements.longitude_of_periapsis(),
+                original.longitude_of_periapsis()
+            )
+            .abs()
+                < 1e-4
+        );
+    }
+
+    // In f64 that's e = 1e-7
+    #[test_case(1.0 ; "unit")]
+    #[test_case(7e6 ; "leo")]
+    #[test_case(1.5e11 ; "heliocentric")]
+    fn slightly_eccentric_orbits_are_not_circular(semi_major_axis: Num) {
+        let e = 10.0 * state_vectors::CIRCULAR_TOLERANCE;
+        let original = KeplerianElements {
+            eccentricity: e,
+            semi_major_axis,
+            inclination: 0.5,
+            argument_of_periapsis: 1.0,
+            ..KeplerianElements::default()
+        };
+        let sv = original.state_vectors_at_epoch(MASS, EPOCH, TOLERANCE);
+
+        let elements = sv.to_elements(MASS, EPOCH);
+        assert!(
+            (elements.eccentricity / e - 1.0).abs() < 0.2,
+            "{elements:?}"
+        );
+
+        // Unless the threshold says otherwise
+        let loose = ConversionOptions {
+            circular_threshold: 100.0 * e,
+            ..ConversionOptions::default()
+        };
+        let elements = sv.to_elements_with(MASS, EPOCH, &loose);
+        assert_eq!(elements.eccentricity, 0.0);
+        assert_eq!(elements.argument_of_periapsis, 0.0);
+    }
+
     #[test]
     fn conversion_arbitrary() {
         test_back_and_forth_conversion(
@@ -154,4 +354,1957 @@ mod tests {
             exp
         );
     }
+
+    #[test]
+    fn conversion_negative_mean_anomaly() {
+        test_back_and_forth_conversion(
+            KeplerianElements {
+                eccentricity: 0.2,
+                semi_major_axis: 1.0,
+                inclination: 0.4,
+                right_ascension_of_the_ascending_node: 0.5,
+                argument_of_periapsis: 0.3,
+                mean_anomaly_at_epoch: -0.468,
+                epoch: 0.0,
+            },
+            MASS,
+            EPOCH,
+        );
+    }
+
+    #[test_case(-0.468, TWO_PI - 0.468)]
+    #[test_case(7.0, 7.0 - TWO_PI)]
+    #[test_case(-3.0 * TWO_PI, 0.0)]
+    fn wrapped_mean_anomaly(M: Num, exp: Num) {
+        let elements = KeplerianElements {
+            eccentricity: 0.2,
+            semi_major_axis: 1.0,
+            inclination: 0.0,
+            right_ascension_of_the_ascending_node: 0.0,
+            argument_of_periapsis: 0.0,
+            mean_anomaly_at_epoch: M,
+            epoch: 0.0,
+        }
+        .wrapped();
+
+        let M = elements.mean_anomaly_at_epoch;
+        assert!((M - exp).abs() < MAX_ABS_DIFF, "{M} not equal {exp}");
+    }
+
+    #[test]
+    fn wrapped_keeps_hyperbolic_mean_anomaly() {
+        let elements = KeplerianElements {
+            eccentricity: 1.5,
+            semi_major_axis: 1.0,
+            inclination: 0.0,
+            right_ascension_of_the_ascending_node: 0.0,
+            argument_of_periapsis: 0.0,
+            mean_anomaly_at_epoch: -10.0,
+            epoch: 0.0,
+        }
+        .wrapped();
+
+        assert_eq!(elements.mean_anomaly_at_epoch, -10.0);
+    }
+
+    #[test]
+    fn deorbit_and_graveyard_burns() {
+        const GEO: Num = 42_164_000.0;
+
+        let circular = |radius: Num| KeplerianElements {
+            semi_major_axis: radius,
+            ..KeplerianElements::default()
+        };
+
+        // Lowering the periapsis of the ISS orbit into the atmosphere
+        let leo = circular(EARTH_RADIUS + 400_000.0);
+        let dv =
+            leo.delta_v_for_deorbit_burn(EARTH_RADIUS + 50_000.0, EARTH_MASS);
+        assert!((dv - 102.0).abs() < 2.0, "{dv}");
+
+        // A GEO graveyard orbit 300 km higher takes two burns, ~11 m/s
+        let graveyard = GEO + 300_000.0;
+        let first =
+            circular(GEO).delta_v_for_deorbit_burn(graveyard, EARTH_MASS);
+        let transfer = KeplerianElements {
+            semi_major_axis: 0.5 * (GEO + graveyard),
+            eccentricity: 150_000.0 / (0.5 * (GEO + graveyard)),
+            ..KeplerianElements::default()
+        };
+        let second = transfer.delta_v_for_deorbit_burn(graveyard, EARTH_MASS);
+
+        let total = first + second;
+        assert!((total - 11.0).abs() < 0.5, "{first} + {second}");
+    }
+
+    #[test]
+    fn reentry_decay_of_a_leo() {
+        let leo = KeplerianElements {
+            semi_major_axis: EARTH_RADIUS + 400_000.0,
+            ..KeplerianElements::default()
+        };
+        let years = |ballistic_coefficient: Num| {
+            leo.reentry_decay_time_estimate(
+                EARTH_MASS,
+                ballistic_coefficient,
+                EARTH_RADIUS,
+                &Atmosphere::EARTH,
+            ) / constants::SECONDS_PER_JULIAN_YEAR
+        };
+
+        // From compact satellites to dense ones
+        for ballistic_coefficient in [100.0, 200.0, 300.0] {
+            let years = years(ballistic_coefficient);
+            assert!((0.5..5.0).contains(&years), "{years}");
+        }
+        assert!(years(100.0) < years(200.0));
+
+        // Already entering
+        let lowered = KeplerianElements {
+            semi_major_axis: EARTH_RADIUS + 225_000.0,
+            eccentricity: 175_000.0 / (EARTH_RADIUS + 225_000.0),
+            ..KeplerianElements::default()
+        };
+        assert_eq!(
+            lowered.reentry_decay_time_estimate(
+                EARTH_MASS,
+                100.0,
+                EARTH_RADIUS,
+                &Atmosphere::EARTH,
+            ),
+            0.0
+        );
+    }
+
+    #[test]
+    fn reentry_decay_follows_the_atmosphere() {
+        fn denser(altitude: Num) -> Num {
+            2.0 * astro::atmosphere::density(altitude)
+        }
+
+        let leo = KeplerianElements {
+            semi_major_axis: EARTH_RADIUS + 400_000.0,
+            ..KeplerianElements::default()
+        };
+        let decay = |atmosphere: &Atmosphere| {
+            leo.reentry_decay_time_estimate(
+                EARTH_MASS,
+                100.0,
+                EARTH_RADIUS,
+                atmosphere,
+            )
+        };
+
+        let earth = decay(&Atmosphere::EARTH);
+        let denser = decay(&Atmosphere {
+            density: denser,
+            ..Atmosphere::EARTH
+        });
+        assert!((denser / earth - 0.5).abs() < 1e-5, "{denser} / {earth}");
+
+        // Entering at the orbit itself
+        let high_entry = decay(&Atmosphere {
+            entry_interface_altitude: 400_000.0,
+            ..Atmosphere::EARTH
+        });
+        assert_eq!(high_entry, 0.0);
+    }
+
+    #[test_case(0.5, 0.0)]
+    #[test_case(0.999, 0.0)]
+    #[test_case(1.001, 1.0)]
+    #[test_case(2.0, 1.0)]
+    fn fraction_of_time_below_radius_circular(radius: Num, exp: Num) {
+        let elements = KeplerianElements {
+            eccentricity: 0.0,
+            semi_major_axis: 1.0,
+            inclination: 0.0,
+            right_ascension_of_the_ascending_node: 0.0,
+            argument_of_periapsis: 0.0,
+            mean_anomaly_at_epoch: 0.0,
+            epoch: 0.0,
+        };
+
+        let fraction = elements.fraction_of_time_below_radius(MASS, radius);
+
+        assert_eq!(fraction, Some(exp));
+    }
+
+    #[test_case(0.1)]
+    #[test_case(0.5)]
+    #[test_case(0.9)]
+    fn fraction_of_time_below_semi_major_axis(e: Num) {
+        let elements = KeplerianElements {
+            eccentricity: e,
+            semi_major_axis: 1.0,
+            inclination: 0.3,
+            right_ascension_of_the_ascending_node: 0.2,
+            argument_of_periapsis: 1.0,
+            mean_anomaly_at_epoch: 0.0,
+            epoch: 0.0,
+        };
+
+        // r = a at E = ±π/2, so M = ±(π/2 - e)
+        let exp = 0.5 - e / PI;
+        let fraction =
+            elements.fraction_of_time_below_radius(MASS, 1.0).unwrap();
+
+        assert!(
+            (fraction - exp).abs() < MAX_ABS_DIFF,
+            "Fraction {fraction} not equal {exp}"
+        );
+    }
+
+    #[test_case(1.0 ; "parabolic")]
+    #[test_case(1.5 ; "hyperbolic")]
+    fn fraction_of_time_below_radius_of_an_open_orbit(e: Num) {
+        let elements = KeplerianElements {
+            eccentricity: e,
+            ..Default::default()
+        };
+
+        assert_eq!(elements.fraction_of_time_below_radius(MASS, 2.0), None);
+    }
+
+    #[test]
+    fn elements_array_ordering() {
+        let elements =
+            KeplerianElements::from([1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
+
+        assert_eq!(elements.semi_major_axis, 1.0);
+        assert_eq!(elements.eccentricity, 2.0);
+        assert_eq!(elements.inclination, 3.0);
+        assert_eq!(elements.right_ascension_of_the_ascending_node, 4.0);
+        assert_eq!(elements.argument_of_periapsis, 5.0);
+        assert_eq!(elements.mean_anomaly_at_epoch, 6.0);
+        assert_eq!(elements.epoch, 7.0);
+
+        let array: [Num; 7] = elements.into();
+        assert_eq!(array, [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
+    }
+
+    #[test]
+    fn state_vectors_array_ordering() {
+        let sv = StateVectors::from([1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+
+        assert_eq!(sv.position, vec3(1.0, 2.0, 3.0));
+        assert_eq!(sv.velocity, vec3(4.0, 5.0, 6.0));
+
+        let array: [Num; 6] = sv.into();
+        assert_eq!(array, [1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    }
+
+    #[test]
+    fn array_round_trip() {
+        let elements = KeplerianElements {
+            eccentricity: 0.123,
+            semi_major_axis: 1.0,
+            inclination: 1.2,
+            right_ascension_of_the_ascending_node: 0.5,
+            argument_of_periapsis: 0.3,
+            mean_anomaly_at_epoch: 1.01,
+            epoch: 0.1,
+        };
+
+        let array = elements.as_array();
+        assert_eq!(KeplerianElements::from_array(array).as_array(), array);
+
+        let sv = elements.state_vectors_at_epoch(MASS, EPOCH, TOLERANCE);
+        let round_trip = StateVectors::from_array(sv.as_array());
+        assert_eq!(round_trip.position, sv.position);
+        assert_eq!(round_trip.velocity, sv.velocity);
+    }
+
+    #[test]
+    fn validate_elements() {
+        use crate::elements::InvalidElements;
+
+        let valid = KeplerianElements::default();
+        assert_eq!(valid.validate(), Ok(()));
+
+        let cases = [
+            (
+                KeplerianElements {
+                    eccentricity: -0.1,
+                    ..valid
+                },
+                InvalidElements::NegativeEccentricity,
+            ),
+            (
+                KeplerianElements {
+                    semi_major_axis: 0.0,
+                    ..valid
+                },
+                InvalidElements::NonPositiveSemiMajorAxis,
+            ),
+            (
+                KeplerianElements {
+                    inclination: 4.0,
+                    ..valid
+                },
+                InvalidElements::InclinationOutOfRange,
+            ),
+            (
+                KeplerianElements {
+                    argument_of_periapsis: Num::NAN,
+                    ..valid
+                },
+                InvalidElements::NotFinite,
+            ),
+        ];
+
+        for (elements, error) in cases {
+            assert_eq!(elements.validate(), Err(error));
+        }
+    }
+
+    #[test]
+    fn default_elements() {
+        let elements = KeplerianElements::default();
+
+        assert_eq!(elements.semi_major_axis, 1.0);
+        assert_eq!(elements.eccentricity, 0.0);
+        assert_eq!(elements.inclination, 0.0);
+        assert_eq!(elements.epoch, 0.0);
+        assert_eq!(
+            elements,
+            KeplerianElements::from_array([1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0])
+        );
+    }
+    const DEG: Num = PI / 180.0;
+
+    /// Approximate J2000 elements of the planets
+    const SOLAR_SYSTEM: [KeplerianElements; 8] = [
+        KeplerianElements::new(
+            0.387 * ASTRONOMICAL_UNIT,
+            0.2056,
+            7.005 * DEG,
+            48.33 * DEG,
+            29.12 * DEG,
+            174.8 * DEG,
+            0.0,
+        ),
+        KeplerianElements::new(
+            0.723 * ASTRONOMICAL_UNIT,
+            0.0068,
+            3.39 * DEG,
+            76.68 * DEG,
+            54.88 * DEG,
+            50.1 * DEG,
+            0.0,
+        ),
+        KeplerianElements::new(
+            1.0 * ASTRONOMICAL_UNIT,
+            0.0167,
+            0.0,
+            0.0,
+            114.2 * DEG,
+            358.6 * DEG,
+            0.0,
+        ),
+        KeplerianElements::new(
+            1.524 * ASTRONOMICAL_UNIT,
+            0.0934,
+            1.85 * DEG,
+            49.56 * DEG,
+            286.5 * DEG,
+            19.4 * DEG,
+            0.0,
+        ),
+        KeplerianElements::new(
+            5.203 * ASTRONOMICAL_UNIT,
+            0.0489,
+            1.303 * DEG,
+            100.46 * DEG,
+            273.87 * DEG,
+            20.0 * DEG,
+            0.0,
+        ),
+        KeplerianElements::new(
+            9.537 * ASTRONOMICAL_UNIT,
+            0.0565,
+            2.485 * DEG,
+            113.67 * DEG,
+            339.39 * DEG,
+            317.0 * DEG,
+            0.0,
+        ),
+        KeplerianElements::new(
+            19.19 * ASTRONOMICAL_UNIT,
+            0.0457,
+            0.773 * DEG,
+            74.0 * DEG,
+            96.99 * DEG,
+            142.2 * DEG,
+            0.0,
+        ),
+        KeplerianElements::new(
+            30.07 * ASTRONOMICAL_UNIT,
+            0.0113,
+            1.77 * DEG,
+            131.78 * DEG,
+            273.19 * DEG,
+            256.2 * DEG,
+            0.0,
+        ),
+    ];
+
+    const ORIGIN: StateVectors = StateVectors::new(Vec3::ZERO, Vec3::ZERO);
+
+    #[test]
+    fn const_solar_system() {
+        assert_eq!(ORIGIN, StateVectors::default());
+
+        // One Earth year later
+        let epoch = SOLAR_SYSTEM[2].period(SUN_MASS);
+
+        for planet in &SOLAR_SYSTEM {
+            let a = planet.semi_major_axis;
+            let e = planet.eccentricity;
+
+            let sv = planet.state_vectors_at_epoch(SUN_MASS, epoch, TOLERANCE);
+            let r = sv.position.distance(ORIGIN.position);
+
+            assert!(r >= a * (1.0 - e) * 0.999, "{r} below periapsis");
+            assert!(r <= a * (1.0 + e) * 1.001, "{r} above apoapsis");
+        }
+
+        // The Earth is back where it started
+        let earth = SOLAR_SYSTEM[2];
+        let start = earth.state_vectors_at_epoch(SUN_MASS, 0.0, TOLERANCE);
+        let end = earth.state_vectors_at_epoch(SUN_MASS, epoch, TOLERANCE);
+        assert!(
+            start.position.distance(end.position) < 1e-4 * ASTRONOMICAL_UNIT
+        );
+    }
+
+    #[test]
+    fn period_in_human_units() {
+        use constants::{G, SECONDS_PER_DAY};
+
+        // Heliocentric gravitational constant
+        let sun_mass = 1.327_124_4e20 / G;
+
+        let earth = KeplerianElements {
+            semi_major_axis: ASTRONOMICAL_UNIT,
+            eccentricity: 0.0167,
+            ..KeplerianElements::default()
+        };
+
+        let days = earth.period_days(sun_mass).unwrap();
+        assert!((days - 365.25).abs() < 0.1, "{days}");
+
+        let years = earth.period_years(sun_mass).unwrap();
+        assert!((years - 1.0).abs() < 1e-3, "{years}");
+
+        let rev_per_day =
+            astro::mean_motion_rev_per_day(earth.semi_major_axis, sun_mass);
+        assert!((rev_per_day * days - 1.0).abs() < 1e-4);
+        assert_eq!(
+            constants::SECONDS_PER_JULIAN_YEAR,
+            365.25 * SECONDS_PER_DAY
+        );
+
+        let hyperbolic = KeplerianElements {
+            eccentricity: 1.5,
+            ..earth
+        };
+        assert_eq!(hyperbolic.period_days(sun_mass), None);
+        assert_eq!(hyperbolic.period_years(sun_mass), None);
+    }
+
+    #[test]
+    fn iss_decay() {
+        use constants::{SECONDS_PER_DAY, SECONDS_PER_MINUTE};
+
+        let iss = KeplerianElements {
+            semi_major_axis: EARTH_RADIUS + 420_000.0,
+            eccentricity: 0.0005,
+            inclination: 0.9013,
+            ..KeplerianElements::default()
+        };
+
+        // Without reboosts the ISS loses about 2 km per month
+        let da_dt = -2_000.0 / (30.0 * SECONDS_PER_DAY);
+
+        let period = iss.period_with_sma_decay(EARTH_MASS, da_dt, 0.0);
+        assert!((period / SECONDS_PER_MINUTE - 92.9).abs() < 0.2);
+
+        // A month later the period is shorter by dT = 3T / 2a da,
+        // from Kepler's third law (King-Hele, Satellite Orbits
+        // in an Atmosphere, 1987, ch. 3)
+        let month = 30.0 * SECONDS_PER_DAY;
+        let later = iss.period_with_sma_decay(EARTH_MASS, da_dt, month);
+        let expected = 1.5 * period / iss.semi_major_axis * -da_dt * month;
+        assert!(
+            ((period - later) / expected - 1.0).abs() < 0.01,
+            "{} vs {expected}",
+            period - later
+        );
+
+        // King-Hele's lifetime of a circular orbit in an exponential
+        // atmosphere is the scale height over the decay rate, about
+        // 29 months with the 58.5 km of the 400 km band. The drag estimate
+        // with the ballistic coefficient matching the observed decay agrees,
+        // the linear extrapolation is only an upper bound.
+        let scale_height = 58_515.0;
+        let king_hele = scale_height / -da_dt;
+
+        let μ = astro::standard_gravitational_parameter(EARTH_MASS);
+        let ballistic_coefficient = astro::atmosphere::density(420_000.0)
+            * (μ * iss.semi_major_axis).sqrt()
+            / -da_dt;
+        let drag = iss.reentry_decay_time_estimate(
+            EARTH_MASS,
+            ballistic_coefficient,
+            EARTH_RADIUS,
+            &Atmosphere::EARTH,
+        );
+        assert!((drag / king_hele - 1.0).abs() < 0.25, "{drag} {king_hele}");
+
+        let linear = iss
+            .predict_reentry_epoch(EARTH_MASS, da_dt, EARTH_RADIUS + 120_000.0)
+            .unwrap();
+        assert!(linear > 4.0 * king_hele, "{linear}");
+
+        assert_eq!(
+            iss.predict_reentry_epoch(EARTH_MASS, 0.0, EARTH_RADIUS),
+            None
+        );
+        assert_eq!(
+            iss.predict_reentry_epoch(EARTH_MASS, da_dt, iss.semi_major_axis),
+            Some(0.0)
+        );
+    }
+
+    #[test]
+    fn longitudes_of_circular_equatorial_orbit() {
+        let elements = KeplerianElements {
+            eccentricity: 0.0,
+            semi_major_axis: 1.0,
+            inclination: 0.0,
+            right_ascension_of_the_ascending_node: 0.7,
+            argument_of_periapsis: 0.3,
+            mean_anomaly_at_epoch: 1.01,
+            epoch: 0.0,
+        };
+        let Ω = elements.right_ascension_of_the_ascending_node;
+
+        for epoch in [0.0, 0.5, 3.0, 10.0] {
+            let λ = elements.true_longitude(MASS, epoch, TOLERANCE);
+            let L = elements.mean_longitude(MASS, epoch);
+            let u = elements.argument_of_latitude(MASS, epoch, TOLERANCE);
+
+            assert!((0.0..TWO_PI).contains(&λ));
+            assert!(math::angle_diff(λ, L).abs() < 1e-4, "{λ} != {L}");
+            assert!(math::angle_diff(λ - Ω, u).abs() < 1e-4, "{λ} != {u}");
+        }
+
+        assert!((elements.longitude_of_periapsis() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn argument_of_latitude_at_periapsis() {
+        let elements = KeplerianElements {
+            eccentricity: 0.3,
+            semi_major_axis: 1.0,
+            inclination: 1.2,
+            right_ascension_of_the_ascending_node: 0.5,
+            argument_of_periapsis: 0.3,
+            mean_anomaly_at_epoch: 1.01,
+            epoch: 0.0,
+        };
+
+        // At the periapsis u = ω
+        let n = elements.mean_anomaly(MASS, 1.0) - 1.01;
+        let periapsis_epoch = -1.01 / n;
+        let u = elements.argument_of_latitude(MASS, periapsis_epoch, TOLERANCE);
+        assert!(math::angle_diff(u, 0.3).abs() < 1e-4, "{u}");
+    }
+
+    #[test_case(0.3 ; "elliptic")]
+    #[test_case(1.4 ; "hyperbolic")]
+    fn osculating_elements_at_reference_epoch(eccentricity: Num) {
+        let elements = KeplerianElements {
+            eccentricity,
+            semi_major_axis: 1.0,
+            inclination: 1.2,
+            right_ascension_of_the_ascending_node: 0.5,
+            argument_of_periapsis: 0.3,
+            mean_anomaly_at_epoch: 1.01,
+            epoch: 0.0,
+        };
+        let current_epoch = 0.2;
+        let sv =
+            elements.state_vectors_at_epoch(MASS, current_epoch, TOLERANCE);
+
+        for reference_epoch in [0.0, current_epoch, -0.5, 1.5] {
+            let rebased =
+                sv.osculating_elements_at(MASS, current_epoch, reference_epoch);
+
+            assert_eq!(rebased.epoch, reference_epoch);
+            assert_eq!(
+                rebased.semi_major_axis,
+                sv.to_elements(MASS, 0.0).semi_major_axis
+            );
+
+            let sv_rebased =
+                rebased.state_vectors_at_epoch(MASS, current_epoch, TOLERANCE);
+
+            assert!(
+                sv.abs_diff(&sv_rebased) < MAX_ABS_DIFF,
+                "{sv:?} != {sv_rebased:?} for reference epoch {reference_epoch}"
+            );
+        }
+    }
+
+    #[test_case(0.3, 0.0)]
+    #[test_case(0.3, 2.0)]
+    #[test_case(0.3, 4.5)]
+    #[test_case(0.3, -1.0)]
+    #[test_case(1.4, 1.0)]
+    #[test_case(1.4, 2.0)]
+    fn next_epoch_at_true_anomaly(eccentricity: Num, v: Num) {
+        let elements = KeplerianElements {
+            eccentricity,
+            semi_major_axis: 1.0,
+            inclination: 1.2,
+            right_ascension_of_the_ascending_node: 0.5,
+            argument_of_periapsis: 0.3,
+            mean_anomaly_at_epoch: 0.2,
+            epoch: 0.7,
+        };
+
+        let epoch = elements.next_epoch_at_true_anomaly(MASS, v).unwrap();
+        assert!(epoch >= elements.epoch);
+        if elements.is_elliptical() {
+            assert!(epoch < elements.epoch + elements.period(MASS));
+        }
+
+        let true_anomaly =
+            elements.true_anomaly_at_epoch(MASS, epoch, TOLERANCE);
+        assert!(
+            math::angle_diff(true_anomaly, v).abs() < 1e-3,
+            "{true_anomaly} != {v}"
+        );
+    }
+
+    #[test]
+    fn next_epoch_at_true_anomaly_hyperbolic_unreachable() {
+        let elements = KeplerianElements {
+            eccentricity: 1.4,
+            semi_major_axis: 1.0,
+            inclination: 1.2,
+            right_ascension_of_the_ascending_node: 0.5,
+            argument_of_periapsis: 0.3,
+            mean_anomaly_at_epoch: 0.2,
+            epoch: 0.7,
+        };
+
+        // Beyond the asymptotes, at ±2.37 rad
+        assert_eq!(elements.next_epoch_at_true_anomaly(MASS, 2.5), None);
+        assert_eq!(elements.next_epoch_at_true_anomaly(MASS, PI), None);
+        // Already passed
+        assert_eq!(elements.next_epoch_at_true_anomaly(MASS, -1.0), None);
+    }
+
+    #[test_case(vec3(1.0, 0.2, -0.3), vec3(0.1, 2.0, 0.5) ; "arbitrary")]
+    #[test_case(vec3(1.0, 2.0, 3.0), vec3(-2.0, -4.0, -6.0) ; "radial")]
+    #[test_case(Vec3::ZERO, vec3(0.0, 1.0, 0.0) ; "zero position")]
+    fn rtn_matrix_is_orthonormal(position: Vec3, velocity: Vec3) {
+        let sv = StateVectors::new(position, velocity);
+        let m = sv.rtn_matrix();
+
+        assert!((m.transpose() * m).abs_diff_eq(Mat3::IDENTITY, 1e-6));
+        assert!((m.determinant() - 1.0).abs() < 1e-6);
+
+        let v = vec3(0.3, -1.2, 4.0);
+        let round_trip = sv.from_rtn_frame(sv.to_rtn_frame(v));
+        assert!(round_trip.abs_diff_eq(v, 1e-5), "{round_trip:?}");
+    }
+
+    #[test]
+    fn velocity_in_rtn_frame() {
+        let elements = KeplerianElements {
+            eccentricity: 0.3,
+            semi_major_axis: 1.0,
+            inclination: 1.2,
+            right_ascension_of_the_ascending_node: 0.5,
+            argument_of_periapsis: 0.3,
+            mean_anomaly_at_epoch: 0.0,
+            epoch: 0.0,
+        };
+
+        for epoch in [0.0, 0.3, 1.1, 2.0] {
+            let sv = elements.state_vectors_at_epoch(MASS, epoch, TOLERANCE);
+            let v = sv.to_rtn_frame(sv.velocity);
+
+            assert!(v.y > 0.0, "{v:?}");
+            assert!(v.z.abs() < 1e-5, "{v:?}");
+            assert!((v.length() - sv.velocity.length()).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn true_anomalies_at_radius_circular() {
+        let elements = KeplerianElements {
+            semi_major_axis: 2.0,
+            inclination: 0.4,
+            ..KeplerianElements::default()
+        };
+
+        let (v1, v2) = elements.true_anomalies_at_radius(MASS, 2.0).unwrap();
+        assert!((v2 - v1 - PI).abs() < 1e-6, "{v1} {v2}");
+
+        assert_eq!(elements.true_anomalies_at_radius(MASS, 1.9), None);
+        assert_eq!(elements.true_anomalies_at_radius(MASS, 2.1), None);
+    }
+
+    #[test_case(0.4 ; "elliptic")]
+    #[test_case(1.5 ; "hyperbolic")]
+    fn true_anomalies_at_radius(eccentricity: Num) {
+        let elements = KeplerianElements {
+            eccentricity,
+            semi_major_axis: 2.0,
+            inclination: 0.4,
+            ..KeplerianElements::default()
+        };
+        let periapsis = elements.periapsis(MASS).length();
+
+        for radius in [1.01 * periapsis, 1.5 * periapsis, 2.0 * periapsis] {
+            let (v1, v2) =
+                elements.true_anomalies_at_radius(MASS, radius).unwrap();
+
+            // Periapsis lies between the second and the first one,
+            // wrapping through zero, and apoapsis between them
+            assert!((0.0..=PI).contains(&v1), "{v1}");
+            assert!((PI..=TWO_PI).contains(&v2), "{v2}");
+            assert!((v1 + v2 - TWO_PI).abs() < 1e-5);
+
+            for v in [v1, v2] {
+                let r = elements.position_at_true_anomaly(MASS, v).length();
+                assert!((r / radius - 1.0).abs() < 1e-4, "{r} != {radius}");
+            }
+        }
+
+        assert_eq!(
+            elements.true_anomalies_at_radius(MASS, 0.9 * periapsis),
+            None
+        );
+        if elements.is_elliptical() {
+            let apoapsis = elements.apoapsis(MASS).length();
+            assert_eq!(
+                elements.true_anomalies_at_radius(MASS, 1.1 * apoapsis),
+                None
+            );
+        }
+    }
+
+    #[test_case(0.5, 1.5, 0.8 ; "suborbital")]
+    #[test_case(0.5, 4.0, 0.8 ; "suborbital after apoapsis")]
+    #[test_case(1.5, -1.0, 1.0 ; "hyperbolic")]
+    fn impact(eccentricity: Num, mean_anomaly_at_epoch: Num, radius: Num) {
+        let elements = KeplerianElements {
+            eccentricity,
+            semi_major_axis: 1.0,
+            inclination: 0.7,
+            right_ascension_of_the_ascending_node: 0.2,
+            argument_of_periapsis: 1.1,
+            mean_anomaly_at_epoch,
+            epoch: 0.0,
+        };
+
+        let impact = elements.impact(MASS, radius, 0.1, TOLERANCE).unwrap();
+
+        assert!(impact.epoch >= 0.1);
+        assert!((impact.position.length() - radius).abs() < 1e-5);
+        assert!(impact.flight_path_angle < 0.0, "{impact:?}");
+
+        let sv = elements.state_vectors_at_epoch(MASS, impact.epoch, TOLERANCE);
+        assert!(sv.position.abs_diff_eq(impact.position, MAX_ABS_DIFF));
+        assert!(sv.velocity.abs_diff_eq(impact.velocity, MAX_ABS_DIFF));
+    }
+
+    #[test]
+    fn impact_edge_cases() {
+        let elements = KeplerianElements {
+            eccentricity: 0.5,
+            semi_major_axis: 1.0,
+            ..KeplerianElements::default()
+        };
+
+        // Periapsis above the surface
+        assert_eq!(elements.impact(MASS, 0.4, 0.0, TOLERANCE), None);
+
+        // Entirely below the surface
+        let impact = elements.impact(MASS, 2.0, 0.3, TOLERANCE).unwrap();
+        assert_eq!(impact.epoch, 0.3);
+
+        // Grazing the surface at the periapsis
+        let impact = elements.impact(MASS, 0.5, 0.3, TOLERANCE).unwrap();
+        let period = elements.period(MASS);
+        assert!((impact.epoch - period).abs() < 1e-4, "{impact:?}");
+        assert!(impact.flight_path_angle.abs() < 1e-4);
+
+        // Hyperbolic, already moving away
+        let escaping = KeplerianElements {
+            eccentricity: 1.5,
+            mean_anomaly_at_epoch: 0.5,
+            ..elements
+        };
+        assert_eq!(escaping.impact(MASS, 0.6, 0.0, TOLERANCE), None);
+    }
+
+    #[test_case(0.3, 1)]
+    #[test_case(-0.5, 3)]
+    fn phasing_orbit(delta_phase: Num, n_orbits: u32) {
+        let orbit = KeplerianElements {
+            semi_major_axis: 1.0,
+            inclination: 0.3,
+            ..KeplerianElements::default()
+        };
+
+        let a = orbit
+            .phasing_orbit_sma_for_phase_change(delta_phase, n_orbits, MASS)
+            .unwrap();
+        assert_eq!(a < orbit.semi_major_axis, delta_phase > 0.0);
+
+        let (departure, arrival) = orbit
+            .phasing_orbit_delta_v(delta_phase, n_orbits, MASS)
+            .unwrap();
+        assert_eq!(departure, arrival);
+
+        // Burn at the start, slowing down into a lower orbit to gain phase
+        let sv = orbit.state_vectors_at_epoch(MASS, EPOCH, TOLERANCE);
+        let Δv = if delta_phase > 0.0 {
+            -departure
+        } else {
+            departure
+        };
+        let phasing = StateVectors::new(
+            sv.position,
+            sv.velocity + Δv * sv.velocity.normalize(),
+        )
+        .to_elements(MASS, EPOCH);
+
+        assert!((phasing.semi_major_axis - a).abs() < 1e-4);
+
+        let t = n_orbits as Num * phasing.period(MASS);
+        let chaser = phasing.state_vectors_at_epoch(MASS, t, TOLERANCE);
+        let reference = orbit.state_vectors_at_epoch(MASS, t, TOLERANCE);
+
+        // Back at the burn point, ahead of where it would have been
+        assert!(chaser.position.abs_diff_eq(sv.position, 1e-3));
+        let phase = reference
+            .position
+            .cross(chaser.position)
+            .dot(orbit.normal())
+            .atan2(reference.position.dot(chaser.position));
+        assert!((phase - delta_phase).abs() < 1e-3, "{phase}");
+    }
+
+    #[test_case(0.5, 0 ; "no orbits")]
+    #[test_case(TWO_PI, 1 ; "zero period")]
+    #[test_case(5.0, 1 ; "through the center")]
+    fn phasing_orbit_out_of_reach(delta_phase: Num, n_orbits: u32) {
+        let orbit = KeplerianElements {
+            semi_major_axis: 1.0,
+            ..KeplerianElements::default()
+        };
+
+        assert_eq!(
+            orbit.phasing_orbit_sma_for_phase_change(
+                delta_phase,
+                n_orbits,
+                MASS
+            ),
+            None
+        );
+        assert_eq!(
+            orbit.phasing_orbit_delta_v(delta_phase, n_orbits, MASS),
+            None
+        );
+    }
+
+    #[test_case(0.3, 0.0 ; "equal")]
+    #[test_case(0.3, 0.4 ; "small node difference")]
+    #[test_case(1.2, 2.0 ; "large node difference")]
+    #[test_case(2.5, 1.0 ; "retrograde")]
+    fn relative_inclination(i: Num, ΔΩ: Num) {
+        let a = KeplerianElements {
+            semi_major_axis: 1.0,
+            inclination: i,
+            right_ascension_of_the_ascending_node: 0.2,
+            ..KeplerianElements::default()
+        };
+        let b = KeplerianElements {
+            right_ascension_of_the_ascending_node: 0.2 + ΔΩ,
+            ..a
+        };
+
+        let expected =
+            math::safe_acos(i.cos().powi(2) + i.sin().powi(2) * ΔΩ.cos());
+        let relative = a.relative_inclination(&b);
+        assert!((relative - expected).abs() < 1e-3, "{relative} {expected}");
+
+        let Some(axis) = a.normal_misalignment_axis(&b) else {
+            assert_eq!(ΔΩ, 0.0);
+            return;
+        };
+
+        // The axis lies in both planes and rotates one normal into the other
+        assert!(axis.dot(a.normal()).abs() < 1e-5);
+        assert!(axis.dot(b.normal()).abs() < 1e-5);
+        let rotated = Mat3::from_axis_angle(axis, relative) * a.normal();
+        assert!(rotated.abs_diff_eq(b.normal(), 1e-4), "{rotated}");
+    }
+
+    #[test_case(vec3(3.0, -1.0, 0.5), 2.0, 0.0 ; "in the reference plane")]
+    #[test_case(vec3(-2.0, 4.0, -1.0), 5.0, 1.0 ; "inclined")]
+    #[test_case(vec3(0.5, 0.5, 3.0), 1.5, -2.5 ; "steep approach")]
+    fn flyby_round_trip(v_infinity: Vec3, periapsis_radius: Num, angle: Num) {
+        let epoch = 3.0;
+        let elements = KeplerianElements::from_flyby(
+            v_infinity,
+            periapsis_radius,
+            angle,
+            MASS,
+            epoch,
+        );
+        assert!(elements.is_hyperbolic());
+
+        let v_inf = elements.hyperbolic_excess_velocity(MASS);
+        assert!((v_inf / v_infinity.length() - 1.0).abs() < 1e-4, "{v_inf}");
+        assert!(elements
+            .incoming_asymptote()
+            .abs_diff_eq(v_infinity.normalize(), 1e-4));
+
+        let b_plane = astro::b_plane::from_hyperbolic_elements(&elements, MASS);
+        assert!(math::angle_diff(b_plane.theta, angle).abs() < 1e-3);
+
+        let sv = elements.state_vectors_at_epoch(MASS, epoch, TOLERANCE);
+        let r = sv.position.length();
+        assert!((r / periapsis_radius - 1.0).abs() < 1e-4, "{r}");
+        assert!(
+            sv.position.normalize().dot(sv.velocity.normalize()).abs() < 1e-4
+        );
+    }
+
+    #[test]
+    fn neptune_pluto_resonance() {
+        let year = constants::SECONDS_PER_JULIAN_YEAR;
+
+        let neptune = KeplerianElements {
+            semi_major_axis: 30.07 * ASTRONOMICAL_UNIT,
+            eccentricity: 0.009,
+            inclination: 0.031,
+            right_ascension_of_the_ascending_node: 2.3,
+            argument_of_periapsis: 4.6,
+            mean_anomaly_at_epoch: 4.5,
+            epoch: 0.0,
+        };
+        // Exactly at the 3:2 commensurability, 3 n = 2 n'
+        let pluto = KeplerianElements {
+            semi_major_axis: neptune.semi_major_axis
+                * (1.5 as Num).powf(2.0 / 3.0),
+            eccentricity: 0.249,
+            inclination: 0.299,
+            right_ascension_of_the_ascending_node: 1.925,
+            argument_of_periapsis: 1.987,
+            mean_anomaly_at_epoch: 0.25,
+            epoch: 0.0,
+        };
+
+        // The argument stays put
+        let φ0 =
+            pluto.resonant_argument(&neptune, 3, 2, SUN_MASS, 0.0, TOLERANCE);
+        let φ1 = pluto.resonant_argument(
+            &neptune,
+            3,
+            2,
+            SUN_MASS,
+            1_000.0 * year,
+            TOLERANCE,
+        );
+        assert!(math::angle_diff(φ0, φ1).abs() < 1e-2, "{φ0} {φ1}");
+
+        let resonant = |pluto: &KeplerianElements, dt: Num| {
+            pluto.is_in_resonance(
+                &neptune,
+                3,
+                2,
+                SUN_MASS,
+                0.0,
+                10_000.0 * year,
+                dt,
+                TOLERANCE,
+            )
+        };
+        assert_eq!(resonant(&pluto, year), Some(true));
+
+        // Slightly further out the argument circulates
+        let detuned = KeplerianElements {
+            semi_major_axis: pluto.semi_major_axis * 1.02,
+            ..pluto
+        };
+        assert_eq!(resonant(&detuned, year), Some(false));
+
+        // And Neptune isn't resonant with itself at 3:2
+        assert_eq!(
+            neptune.is_in_resonance(
+                &neptune,
+                3,
+                2,
+                SUN_MASS,
+                0.0,
+                10_000.0 * year,
+                year,
+                TOLERANCE,
+            ),
+            Some(false)
+        );
+
+        // The sampling has to move forward
+        assert_eq!(resonant(&pluto, 0.0), None);
+        assert_eq!(resonant(&pluto, -year), None);
+    }
+
+    #[test]
+    fn observation_geometry() {
+        let orbit = KeplerianElements {
+            semi_major_axis: 1.0,
+            ..KeplerianElements::default()
+        };
+        let geometry = |target: Vec3| {
+            orbit.observation_geometry_at_epoch(target, MASS, EPOCH, TOLERANCE)
+        };
+
+        // Opposition, the spacecraft between the Sun and the target
+        let opposition = geometry(vec3(2.0, 0.0, 0.0));
+        assert!((opposition.range - 1.0).abs() < MAX_ABS_DIFF);
+        assert!(opposition.phase_angle.abs() < 1e-3, "{opposition:?}");
+        assert!((opposition.elongation_from_sun - PI).abs() < 1e-3);
+
+        // The target between the spacecraft and the Sun, seen unlit
+        let conjunction = geometry(vec3(0.5, 0.0, 0.0));
+        assert!(
+            (conjunction.phase_angle - PI).abs() < 1e-3,
+            "{conjunction:?}"
+        );
+        assert!(conjunction.elongation_from_sun.abs() < 1e-3);
+
+        let quadrature = geometry(vec3(1.0, 1.0, 0.0));
+        assert!((quadrature.phase_angle - PI / 4.0).abs() < 1e-3);
+        assert!((quadrature.elongation_from_sun - PI / 2.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn orbit_delegates_to_elements() {
+        let elements = KeplerianElements {
+            eccentricity: 0.123,
+            semi_major_axis: 1.0,
+            inclination: 1.2,
+            right_ascension_of_the_ascending_node: 0.5,
+            argument_of_periapsis: 0.3,
+            mean_anomaly_at_epoch: 1.01,
+            epoch: 0.1,
+        };
+
+        let orbit = Orbit::from_mass(elements, MASS);
+        let mass = orbit.mass();
+        let epoch = 0.7;
+
+        assert!((mass - MASS).abs() / MASS < MAX_ABS_DIFF);
+        assert_eq!(orbit.ascending_node(), elements.ascending_node(mass));
+        assert_eq!(orbit.descending_node(), elements.descending_node(mass));
+        assert_eq!(orbit.periapsis(), elements.periapsis(mass));
+        assert_eq!(orbit.apoapsis(), elements.apoapsis(mass));
+        assert_eq!(orbit.normal(), elements.normal());
+        assert_eq!(orbit.period(), elements.period(mass));
+        assert_eq!(
+            orbit.fraction_of_time_below_radius(0.9),
+            elements.fraction_of_time_below_radius(mass, 0.9)
+        );
+        assert_eq!(
+            orbit.mean_anomaly(epoch),
+            elements.mean_anomaly(mass, epoch)
+        );
+        assert_eq!(
+            orbit.estimate_eccentric_anomaly(epoch, TOLERANCE),
+            elements.estimate_eccentric_anomaly(mass, epoch, TOLERANCE)
+        );
+        assert_eq!(
+            orbit.true_anomaly_at(epoch, TOLERANCE),
+            elements.true_anomaly_at_epoch(mass, epoch, TOLERANCE)
+        );
+        assert_eq!(
+            orbit.position_at_true_anomaly(0.4),
+            elements.position_at_true_anomaly(mass, 0.4)
+        );
+        assert_eq!(
+            orbit.velocity_at_true_anomaly(0.4),
+            elements.velocity_at_true_anomaly(mass, 0.4)
+        );
+        assert_eq!(
+            orbit.specific_angular_momentum(),
+            elements.specific_angular_momentum(mass)
+        );
+
+        let sv = orbit.state_vectors_at(epoch, TOLERANCE);
+        let exp = elements.state_vectors_at_epoch(mass, epoch, TOLERANCE);
+        assert_eq!(sv.position, exp.position);
+        assert_eq!(sv.velocity, exp.velocity);
+    }
+
+    #[test]
+    fn orbit_delegates_to_hyperbolic_elements() {
+        let elements = KeplerianElements {
+            eccentricity: 1.5,
+            semi_major_axis: 1.0,
+            inclination: 0.2,
+            right_ascension_of_the_ascending_node: 0.5,
+            argument_of_periapsis: 0.3,
+            mean_anomaly_at_epoch: -0.5,
+            epoch: 0.0,
+        };
+
+        let orbit = Orbit::from_mu(elements, MASS * constants::G);
+        let mass = orbit.mass();
+
+        assert_eq!(
+            orbit.hyperbolic_mean_anomaly(0.3),
+            elements.hyperbolic_mean_anomaly(mass, 0.3)
+        );
+        assert_eq!(
+            orbit.estimate_hyperbolic_anomaly(0.3, TOLERANCE),
+            elements.estimate_hyperbolic_anomaly(mass, 0.3, TOLERANCE)
+        );
+    }
+
+    #[test]
+    fn cached_orbit_hit() {
+        let elements = KeplerianElements {
+            eccentricity: 0.123,
+            semi_major_axis: 1.0,
+            inclination: 1.2,
+            right_ascension_of_the_ascending_node: 0.5,
+            argument_of_periapsis: 0.3,
+            mean_anomaly_at_epoch: 1.01,
+            epoch: 0.1,
+        };
+
+        let mut orbit = CachedOrbit::new(elements, MASS);
+        assert_eq!(orbit.cached_epoch(), None);
+
+        let sv = orbit.state_vectors_at_epoch(0.5, TOLERANCE);
+        assert_eq!(orbit.cached_epoch(), Some(0.5));
+
+        let exp = elements.state_vectors_at_epoch(MASS, 0.5, TOLERANCE);
+        assert_eq!(sv.position, exp.position);
+        assert_eq!(sv.velocity, exp.velocity);
+
+        // A different tolerance still hits the cache
+        let cached = orbit.state_vectors_at_epoch(0.5, 1.0);
+        assert_eq!(cached.position, sv.position);
+        assert_eq!(cached.velocity, sv.velocity);
+
+        orbit.state_vectors_at_epoch(0.6, TOLERANCE);
+        assert_eq!(orbit.cached_epoch(), Some(0.6));
+    }
+
+    #[test]
+    fn cached_orbit_invalidation() {
+        let elements = KeplerianElements {
+            eccentricity: 0.123,
+            semi_major_axis: 1.0,
+            inclination: 1.2,
+            right_ascension_of_the_ascending_node: 0.5,
+            argument_of_periapsis: 0.3,
+            mean_anomaly_at_epoch: 1.01,
+            epoch: 0.1,
+        };
+
+        let mut orbit = CachedOrbit::new(elements, MASS);
+        let sv = orbit.state_vectors_at_epoch(0.5, TOLERANCE);
+
+        orbit.elements_mut().semi_major_axis = 2.0;
+        assert_eq!(orbit.cached_epoch(), None);
+
+        let modified = orbit.state_vectors_at_epoch(0.5, TOLERANCE);
+        assert_ne!(modified.position, sv.position);
+
+        orbit.set_elements(elements);
+        assert_eq!(orbit.cached_epoch(), None);
+
+        let restored = orbit.state_vectors_at_epoch(0.5, TOLERANCE);
+        assert_eq!(restored.position, sv.position);
+
+        orbit.set_mass(MASS * 2.0);
+        assert_eq!(orbit.cached_epoch(), None);
+    }
+
+    #[test]
+    fn propagate_many_simd_matches_scalar() {
+        let elements: Vec<_> = (0..37)
+            .map(|i| {
+                let i = i as Num;
+
+                KeplerianElements {
+                    // Every fifth orbit is hyperbolic
+                    eccentricity: if i % 5.0 == 0.0 { 1.2 } else { i / 40.0 },
+                    semi_major_axis: 1.0 + i,
+                    inclination: 0.1 * i,
+                    right_ascension_of_the_ascending_node: 0.2 * i,
+                    argument_of_periapsis: 0.3 * i,
+                    mean_anomaly_at_epoch: 0.4 * i,
+                    epoch: 0.0,
+                }
+            })
+            .collect();
+
+        let batch =
+            KeplerianElements::propagate_many_simd(&elements, MASS, 0.5, 1e-6);
+
+        for (elements, sv) in elements.iter().zip(batch) {
+            let exp = elements.state_vectors_at_epoch(MASS, 0.5, 1e-6);
+
+            assert!(
+                sv.position.abs_diff_eq(exp.position, MAX_ABS_DIFF),
+                "Position {:?} not equal {:?}",
+                sv.position,
+                exp.position
+            );
+        }
+    }
+
+    #[test]
+    fn concentric_circles_dont_intersect() {
+        let inner = KeplerianElements {
+            eccentricity: 0.0,
+            semi_major_axis: 1.0,
+            inclination: 0.0,
+            right_ascension_of_the_ascending_node: 0.0,
+            argument_of_periapsis: 0.0,
+            mean_anomaly_at_epoch: 0.0,
+            epoch: 0.0,
+        };
+        let outer = KeplerianElements {
+            semi_major_axis: 2.0,
+            ..inner
+        };
+
+        assert!(inner
+            .coplanar_intersection_true_anomalies(&outer)
+            .is_empty());
+    }
+
+    #[test]
+    fn non_coplanar_orbits_dont_intersect() {
+        let a = KeplerianElements {
+            eccentricity: 0.5,
+            semi_major_axis: 1.0,
+            inclination: 0.0,
+            right_ascension_of_the_ascending_node: 0.0,
+            argument_of_periapsis: 0.0,
+            mean_anomaly_at_epoch: 0.0,
+            epoch: 0.0,
+        };
+        let b = KeplerianElements {
+            inclination: 0.5,
+            ..a
+        };
+
+        assert!(a.coplanar_intersection_true_anomalies(&b).is_empty());
+    }
+
+    #[test]
+    fn rotated_eccentric_orbits_intersect() {
+        let a = KeplerianElements {
+            eccentricity: 0.5,
+            semi_major_axis: 1.0,
+            inclination: 0.3,
+            right_ascension_of_the_ascending_node: 0.2,
+            argument_of_periapsis: 0.0,
+            mean_anomaly_at_epoch: 0.0,
+            epoch: 0.0,
+        };
+        let b = KeplerianElements {
+            argument_of_periapsis: PI / 2.0,
+            ..a
+        };
+
+        let mut crossings = a.coplanar_intersection_true_anomalies(&b);
+        crossings.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+        // Symmetric about the bisector of the periapses
+        let exp =
+            [(PI / 4.0, 7.0 * PI / 4.0), (5.0 * PI / 4.0, 3.0 * PI / 4.0)];
+
+        assert_eq!(crossings.len(), 2);
+        for ((v1, v2), (exp1, exp2)) in crossings.into_iter().zip(exp) {
+            assert!((v1 - exp1).abs() < MAX_ABS_DIFF, "{v1} not equal {exp1}");
+            assert!((v2 - exp2).abs() < MAX_ABS_DIFF, "{v2} not equal {exp2}");
+
+            let p1 = a.position_at_true_anomaly(MASS, v1);
+            let p2 = b.position_at_true_anomaly(MASS, v2);
+            assert!(
+                p1.abs_diff_eq(p2, MAX_ABS_DIFF),
+                "{p1:?} not equal {p2:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn warm_started_solves_match_cold_solves() {
+        let elements = KeplerianElements {
+            eccentricity: 0.6,
+            semi_major_axis: 1.0,
+            inclination: 1.2,
+            right_ascension_of_the_ascending_node: 0.5,
+            argument_of_periapsis: 0.3,
+            mean_anomaly_at_epoch: 1.01,
+            epoch: 0.1,
+        };
+
+        let mut solver = KeplerSolver::new();
+        let frame = elements.period(MASS) / 200.0;
+
+        let mut warm_iterations = 0;
+        let mut cold_iterations = 0;
+
+        for i in 0..200 {
+            let epoch = i as Num * frame;
+
+            let E = solver.eccentric_anomaly(&elements, MASS, epoch, 1e-5);
+            let M = elements.mean_anomaly(MASS, epoch);
+            let (exp, iterations) =
+                astro::elliptic::estimate_anomaly_with_guess(
+                    M,
+                    elements.eccentricity,
+                    M,
+                    1e-5,
+                );
+
+            assert!((E - exp).abs() < 1e-4, "{E} not equal {exp}");
+
+            if i > 0 {
+                assert!(solver.last_iterations() <= 2);
+                warm_iterations += solver.last_iterations();
+                cold_iterations += iterations;
+            }
+        }
+
+        assert!(warm_iterations < cold_iterations);
+    }
+
+    #[test]
+    fn kepler_solver_retraces_backwards() {
+        let elements = KeplerianElements {
+            eccentricity: 0.6,
+            semi_major_axis: 1.0,
+            inclination: 1.2,
+            right_ascension_of_the_ascending_node: 0.5,
+            argument_of_periapsis: 0.3,
+            mean_anomaly_at_epoch: 1.01,
+            epoch: 0.1,
+        };
+
+        let mut solver = KeplerSolver::new();
+        let frame = elements.period(MASS) / 200.0;
+
+        // Forwards, then backwards to before the epoch of the elements
+        let frames = (0..200).chain((-100..200).rev());
+
+        let mut forwards = Vec::new();
+        for (k, i) in frames.enumerate() {
+            let epoch = i as Num * frame;
+            let sv =
+                solver.state_vectors_at_epoch(&elements, MASS, epoch, 1e-5);
+            let exp = elements.state_vectors_at_epoch(MASS, epoch, 1e-5);
+
+            assert!(sv.abs_diff(&exp) < 1e-3, "{sv:?} != {exp:?}");
+            if k > 0 {
+                assert!(solver.last_iterations() <= 2);
+            }
+
+            if k < 200 {
+                forwards.push(sv);
+            } else if i >= 0 {
+                // The same path, the other way
+                let previous = forwards[i as usize];
+                assert!(sv.position.abs_diff_eq(previous.position, 1e-4));
+            }
+        }
+    }
+
+    #[test]
+    fn kepler_solver_cold_starts_on_jumps() {
+        let elements = KeplerianElements {
+            eccentricity: 0.6,
+            semi_major_axis: 1.0,
+            inclination: 1.2,
+            right_ascension_of_the_ascending_node: 0.5,
+            argument_of_periapsis: 0.3,
+            mean_anomaly_at_epoch: 1.01,
+            epoch: 0.1,
+        };
+        let period = elements.period(MASS);
+
+        let mut solver = KeplerSolver::new();
+
+        for epoch in [0.0, -period * 0.3, period * 10.3] {
+            let sv =
+                solver.state_vectors_at_epoch(&elements, MASS, epoch, 1e-6);
+            let exp = elements.state_vectors_at_epoch(MASS, epoch, 1e-6);
+
+            assert!(
+                sv.position.abs_diff_eq(exp.position, MAX_ABS_DIFF),
+                "Position {:?} not equal {:?}",
+                sv.position,
+                exp.position
+            );
+        }
+    }
+
+    #[test]
+    fn secular_j2_drift_of_the_iss() {
+        use constants::SECONDS_PER_DAY;
+
+        let iss = KeplerianElements {
+            eccentricity: 0.0005,
+            semi_major_axis: 6_778_000.0,
+            inclination: 51.64 * PI / 180.0,
+            right_ascension_of_the_ascending_node: 1.0,
+            argument_of_periapsis: 0.5,
+            mean_anomaly_at_epoch: 0.0,
+            epoch: 0.0,
+        };
+
+        let month = 30.0 * SECONDS_PER_DAY;
+        let later =
+            iss.propagate_secular_j2(month, EARTH_MASS, EARTH_RADIUS, EARTH_J2);
+
+        // The ISS orbit regresses by about 5° per day
+        let drift = -5.0 * PI / 180.0 * 30.0;
+        let Ω = later.right_ascension_of_the_ascending_node;
+        assert!(
+            math::angle_diff(Ω, 1.0 + drift).abs() < 0.5 * PI / 180.0,
+            "{Ω}"
+        );
+
+        assert_eq!(later.epoch, month);
+        assert_eq!(later.semi_major_axis, iss.semi_major_axis);
+        assert_eq!(later.eccentricity, iss.eccentricity);
+        assert_eq!(later.inclination, iss.inclination);
+        assert!((0.0..TWO_PI).contains(&later.mean_anomaly_at_epoch));
+
+        // Without J2 only the mean anomaly moves
+        let keplerian =
+            iss.propagate_secular_j2(month, EARTH_MASS, EARTH_RADIUS, 0.0);
+        assert_eq!(keplerian.right_ascension_of_the_ascending_node, 1.0);
+        assert_eq!(keplerian.argument_of_periapsis, 0.5);
+        let M = iss
+            .advance_to_epoch(EARTH_MASS, month)
+            .mean_anomaly_at_epoch;
+        assert!(
+            math::angle_diff(keplerian.mean_anomaly_at_epoch, M).abs() < 1e-2
+        );
+    }
+
+    #[test]
+    fn frozen_orbit_keeps_its_mean_eccentricity() {
+        use astro::perturbations::J2;
+        use astro::{
+            perturbed_ode_rhs, rk4_step, standard_gravitational_parameter,
+        };
+
+        const EARTH_J3: Num = -2.5327e-6;
+
+        let μ = standard_gravitational_parameter(EARTH_MASS);
+
+        let j2 = J2 {
+            j2: EARTH_J2,
+            radius: EARTH_RADIUS,
+            mu: μ,
+        };
+        let zonal = Box::new(move |r: Vec3, _, _| {
+            let d = r.length();
+            let z = r.z / d;
+
+            let j3 = -2.5 * EARTH_J3 * μ * EARTH_RADIUS.powi(3) / d.powi(5)
+                * vec3(
+                    r.x / d * (3.0 * z - 7.0 * z.powi(3)),
+                    r.y / d * (3.0 * z - 7.0 * z.powi(3)),
+                    6.0 * z.powi(2) - 7.0 * z.powi(4) - 0.6,
+                );
+
+            j2.acceleration(r) + j3
+        });
+        let rhs = perturbed_ode_rhs(EARTH_MASS, vec![zonal]);
+
+        // Mean eccentricity over each of a few revolutions
+        let mean_eccentricities = |elements: KeplerianElements| {
+            let period = elements.period(EARTH_MASS);
+            let steps = 300;
+            let dt = period / steps as Num;
+
+            let mut y = elements
+                .state_vectors_at_epoch(EARTH_MASS, 0.0, TOLERANCE)
+                .as_array();
+
+            (0..5)
+                .map(|revolution| {
+                    let mut sum = 0.0;
+
+                    for step in 0..steps {
+                        let t = (revolution * steps + step) as Num * dt;
+                        sum += StateVectors::from_array(y)
+                            .to_elements(EARTH_MASS, t)
+                            .eccentricity;
+
+                        y = rk4_step(&rhs, t, y, dt);
+                    }
+
+                    sum / steps as Num
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let frozen = KeplerianElements::frozen_orbit(
+            EARTH_RADIUS + 800_000.0,
+            98.6 * PI / 180.0,
+            1.0,
+            0.0,
+            0.0,
+            EARTH_RADIUS,
+            EARTH_J2,
+            EARTH_J3,
+            EARTH_MASS,
+        );
+        assert_eq!(frozen.argument_of_periapsis, PI / 2.0);
+
+        let not_frozen = KeplerianElements {
+            argument_of_periapsis: 0.0,
+            ..frozen
+        };
+
+        let spread = |values: Vec<Num>| {
+            let max = values.iter().cloned().fold(Num::MIN, Num::max);
+            let min = values.iter().cloned().fold(Num::MAX, Num::min);
+            max - min
+        };
+
+        // The averaged osculating eccentricity isn't the mean one,
+        // only its constancy is checked
+        let frozen_spread = spread(mean_eccentricities(frozen));
+        let drifting_spread = spread(mean_eccentricities(not_frozen));
+
+        assert!(frozen_spread < 2e-6, "{frozen_spread}");
+        assert!(
+            frozen_spread < 0.2 * drifting_spread,
+            "{frozen_spread} {drifting_spread}"
+        );
+    }
+
+    // Published inclinations of Sentinel-2 and RapidEye
+    #[test_case(786_000.0, 98.562 ; "sentinel_2")]
+    #[test_case(630_000.0, 97.8 ; "rapid_eye")]
+    fn sun_synchronous_frozen_orbit(altitude: Num, inclination_deg: Num) {
+        const EARTH_J3: Num = -2.5327e-6;
+        const EARTH_SOLAR_MEAN_MOTION: Num = 1.991e-7;
+
+        let orbit = KeplerianElements::sun_synchronous_frozen_orbit(
+            EARTH_RADIUS + altitude,
+            EARTH_RADIUS,
+            EARTH_J2,
+            EARTH_J3,
+            EARTH_MASS,
+            EARTH_SOLAR_MEAN_MOTION,
+        )
+        .unwrap();
+
+        let inclination = orbit.inclination.to_degrees();
+        assert!(
+            (inclination - inclination_deg).abs() < 0.15,
+            "{inclination}"
+        );
+        assert!(
+            (orbit.eccentricity - 1.04e-3).abs() < 3e-5,
+            "{}",
+            orbit.eccentricity
+        );
+        assert_eq!(orbit.argument_of_periapsis, PI / 2.0);
+
+        let precession =
+            orbit.secular_raan_rate(EARTH_RADIUS, EARTH_J2, EARTH_MASS);
+        assert!(
+            (precession / EARTH_SOLAR_MEAN_MOTION - 1.0).abs() < 1e-4,
+            "{precession}"
+        );
+
+        // Too high for J2 to keep up
+        assert_eq!(
+            KeplerianElements::sun_synchronous_frozen_orbit(
+                EARTH_RADIUS * 2.0,
+                EARTH_RADIUS,
+                EARTH_J2,
+                EARTH_J3,
+                EARTH_MASS,
+                EARTH_SOLAR_MEAN_MOTION,
+            ),
+            Err(elements::OrbitError::PrecessionOutOfReach)
+        );
+    }
+
+    #[test]
+    fn long_period_j2_oscillates_around_the_secular_drift() {
+        let elements = KeplerianElements {
+            eccentricity: 0.1,
+            semi_major_axis: 7_500_000.0,
+            inclination: 0.5,
+            right_ascension_of_the_ascending_node: 0.5,
+            argument_of_periapsis: 0.3,
+            mean_anomaly_at_epoch: 1.01,
+            epoch: 0.0,
+        };
+        let args = (EARTH_RADIUS, EARTH_J2, EARTH_MASS);
+
+        let Ω_rate = elements.secular_raan_rate(args.0, args.1, args.2);
+        let ω_rate =
+            elements.secular_argument_of_periapsis_rate(args.0, args.1, args.2);
+
+        // Nodal regression of a prograde orbit, apsidal advance below
+        // the critical inclination
+        assert!(Ω_rate < 0.0);
+        assert!(ω_rate > 0.0);
+
+        // The terms go with 2ω, so they repeat every half turn of ω
+        let period = PI / ω_rate;
+
+        let corrections = |t: Num| {
+            [
+                elements.long_period_raan_correction(t, args.0, args.1, args.2),
+                elements.long_period_inclination_correction(
+                    t, args.0, args.1, args.2,
+                ),
+                elements.long_period_argument_of_periapsis_correction(
+                    t, args.0, args.1, args.2,
+                ),
+                elements.long_period_eccentricity_correction(
+                    t, args.0, args.1, args.2,
+                ),
+            ]
+        };
+
+        let samples = 64;
+        let mut sums = [0.0; 4];
+
+        for n in 0..samples {
+            let t = period * n as Num / samples as Num;
+
+            let now = corrections(t);
+            let next_period = corrections(t + period);
+            let half_period = corrections(t + period / 2.0);
+
+            for k in 0..4 {
+                let amplitude = 1e-3 * now[k].abs().max(1e-9);
+                assert!((now[k] - next_period[k]).abs() < amplitude.max(1e-7));
+                assert!((now[k] + half_period[k]).abs() < amplitude.max(1e-7));
+                sums[k] += now[k];
+            }
+
+            // The total correction is the secular drift plus the oscillation
+            let total =
+                elements.long_period_correction_j2(t, args.0, args.1, args.2);
+            let secular =
+                elements.right_ascension_of_the_ascending_node + Ω_rate * t;
+            assert!(
+                math::angle_diff(
+                    total.right_ascension_of_the_ascending_node,
+                    secular
+                )
+                .abs()
+                    <= now[0].abs() + 1e-6
+            );
+        }
+
+        // Nonzero, but zero on average
+        let correction = corrections(period / 8.0);
+        assert!(correction.iter().all(|c| c.abs() > 1e-9), "{correction:?}");
+        for sum in sums {
+            assert!((sum / samples as Num).abs() < 1e-7);
+        }
+    }
+
+    /// The long-period terms are what remains of the Brouwer-Lyddane
+    /// mapping once the short-period terms average out over an orbit,
+    /// near the critical inclination where the long-period terms dominate
+    #[test]
+    fn long_period_j2_matches_the_averaged_mean_to_osculating_mapping() {
+        let elements = KeplerianElements {
+            eccentricity: 0.02,
+            semi_major_axis: 7_500_000.0,
+            inclination: 1.1,
+            right_ascension_of_the_ascending_node: 0.5,
+            argument_of_periapsis: 0.4,
+            mean_anomaly_at_epoch: 0.0,
+            epoch: 0.0,
+        };
+        let args = (EARTH_RADIUS, EARTH_J2, EARTH_MASS);
+
+        let samples = 720;
+        let mut averages = [0.0; 4];
+        for n in 0..samples {
+            let mean = KeplerianElements {
+                mean_anomaly_at_epoch: TWO_PI * n as Num / samples as Num,
+                ..elements
+            };
+            let osculating = astro::sgp4::mean_to_osculating_elements(
+                &mean, args.0, args.1, args.2,
+            );
+
+            let deviations = [
+                math::angle_diff(
+                    osculating.right_ascension_of_the_ascending_node,
+                    mean.right_ascension_of_the_ascending_node,
+                ),
+                osculating.inclination - mean.inclination,
+                math::angle_diff(
+                    osculating.argument_of_periapsis,
+                    mean.argument_of_periapsis,
+                ),
+                osculating.eccentricity - mean.eccentricity,
+            ];
+            for (average, deviation) in averages.iter_mut().zip(deviations) {
+                *average += deviation / samples as Num;
+            }
+        }
+
+        let corrections = [
+            elements.long_period_raan_correction(0.0, args.0, args.1, args.2),
+            elements.long_period_inclination_correction(
+                0.0, args.0, args.1, args.2,
+            ),
+            elements.long_period_argument_of_periapsis_correction(
+                0.0, args.0, args.1, args.2,
+            ),
+            elements.long_period_eccentricity_correction(
+                0.0, args.0, args.1, args.2,
+            ),
+        ];
+        for (average, correction) in averages.into_iter().zip(corrections) {
+            assert!(
+                (average - correction).abs() < 0.25 * correction.abs(),
+                "{average} != {correction}"
+            );
+        }
+    }
+
+    #[test_case(0.0 ; "prograde")]
+    #[test_case(PI ; "retrograde")]
+    fn long_period_j2_of_an_equatorial_orbit(inclination: Num) {
+        let elements = KeplerianElements {
+            eccentricity: 0.1,
+            semi_major_axis: 7_500_000.0,
+            inclination,
+            argument_of_periapsis: 0.3,
+            ..KeplerianElements::default()
+        };
+
+        let t = 100_000.0;
+        assert_eq!(
+            elements.long_period_inclination_correction(
+                t,
+                EARTH_RADIUS,
+                EARTH_J2,
+                EARTH_MASS
+            ),
+            0.0
+        );
+
+        let corrected = elements.long_period_correction_j2(
+            t,
+            EARTH_RADIUS,
+            EARTH_J2,
+            EARTH_MASS,
+        );
+        assert!(corrected.as_array().iter().all(|x| x.is_finite()));
+        assert_eq!(corrected.inclination, inclination);
+    }
+
+    #[test]
+    fn revolutions_are_counted_past_the_wrap() {
+        let mass = EARTH_MASS;
+        let elements = KeplerianElements {
+            semi_major_axis: 7_000_000.0,
+            eccentricity: 0.1,
+            mean_anomaly_at_epoch: 0.5,
+            epoch: 100.0,
+            ..KeplerianElements::default()
+        };
+        let period = elements.period(mass);
+        let epoch = elements.epoch + 2.5 * period;
+
+        let revolutions = elements.revolutions_since_epoch(mass, epoch);
+        assert!((revolutions - 2.5).abs() < 1e-5, "{revolutions}");
+
+        let anomalies = elements.anomalies_at_epoch(mass, epoch, TOLERANCE);
+        assert!((anomalies.mean - (0.5 + PI)).abs() < 1e-3, "{anomalies:?}");
+        assert_eq!(anomalies.revolutions, Some(2));
+
+        let v = elements.true_anomaly_at_epoch(mass, epoch, TOLERANCE);
+        assert!(math::angle_diff(anomalies.true_anomaly, v).abs() < 1e-3);
+
+        let hyperbolic = KeplerianElements {
+            eccentricity: 1.5,
+            ..elements
+        };
+        assert_eq!(hyperbolic.revolutions_since_epoch(mass, epoch), 0.0);
+        let anomalies = hyperbolic.anomalies_at_epoch(mass, epoch, TOLERANCE);
+        assert_eq!(anomalies.revolutions, None);
+    }
+
+    #[test_case(0.0 ; "at periapsis")]
+    #[test_case(1.0 ; "ascending")]
+    #[test_case(4.0 ; "descending")]
+    fn burn_sets_the_opposite_apsis(v: Num) {
+        let mass = EARTH_MASS;
+        let elements = KeplerianElements {
+            semi_major_axis: 8_000_000.0,
+            eccentricity: 0.1,
+            inclination: 0.5,
+            ..KeplerianElements::default()
+        };
+        let sv = StateVectors::new(
+            elements.position_at_true_anomaly(mass, v),
+            elements.velocity_at_true_anomaly(mass, v),
+        );
+        let r = sv.position.length();
+
+        let burn = |dv: Num| {
+            let prograde = sv.to_rtn_frame(sv.velocity.normalize());
+            sv.apply_delta_v_rtn(prograde * dv).to_elements(mass, 0.0)
+        };
+
+        for target in [1.2 * r, 3.0 * r] {
+            let dv = sv.dv_to_set_apoapsis(mass, target).unwrap();
+            let raised = burn(dv);
+            let apoapsis = raised.semi_major_axis * (1.0 + raised.eccentricity);
+            assert!((apoapsis / target - 1.0).abs() < 1e-4, "{raised:?}");
+        }
+
+        for target in [0.6 * r, 0.8 * r] {
+            let dv = sv.dv_to_set_periapsis(mass, target).unwrap();
+            assert!(dv < 0.0);
+            let lowered = burn(dv);
+            let periapsis =
+                lowered.semi_major_axis * (1.0 - lowered.eccentricity);
+            assert!((periapsis / target - 1.0).abs() < 1e-4, "{lowered:?}");
+        }
+
+        assert_eq!(sv.dv_to_set_apoapsis(mass, 0.9 * r), None);
+        assert_eq!(sv.dv_to_set_periapsis(mass, 1.1 * r), None);
+    }
+
+    #[test_case(0.0, 0.0 ; "circular equatorial")]
+    #[test_case(0.3, 0.7 ; "eccentric inclined")]
+    #[test_case(0.9, 2.0 ; "very eccentric retrograde")]
+    fn bounding_box_encloses_the_orbit(e: Num, i: Num) {
+        let elements = KeplerianElements {
+            semi_major_axis: 10_000.0,
+            eccentricity: e,
+            inclination: i,
+            right_ascension_of_the_ascending_node: if e > 0.0 {
+                1.0
+            } else {
+                0.0
+            },
+            argument_of_periapsis: if e > 0.0 { 2.0 } else { 0.0 },
+            ..KeplerianElements::default()
+        };
+        let (min, max) = elements.bounding_box(MASS, 0, 0.0);
+
+        let a = elements.semi_major_axis;
+        let slack = Vec3::splat(1e-4 * a);
+        let (mut lo, mut hi) = (Vec3::splat(Num::MAX), Vec3::splat(Num::MIN));
+        for k in 0..3600 {
+            let v = k as Num / 3600.0 * TWO_PI;
+            let p = elements.position_at_true_anomaly(MASS, v);
+            assert!(p.cmpge(min - slack).all() && p.cmple(max + slack).all());
+
+            lo = lo.min(p);
+            hi = hi.max(p);
+        }
+        // Tight, not just enclosing
+        assert!(lo.abs_diff_eq(min, 1e-3 * a), "{lo} {min}");
+        assert!(hi.abs_diff_eq(max, 1e-3 * a), "{hi} {max}");
+
+        assert_eq!(elements.bounding_radius(), Some(a * (1.0 + e)));
+
+        if e == 0.0 {
+            assert_eq!((min, max), (vec3(-a, -a, 0.0), vec3(a, a, 0.0)));
+        }
+    }
+
+    #[test]
+    fn hyperbolic_bounding_box_is_cut_off() {
+        let elements = KeplerianElements {
+            semi_major_axis: 10_000.0,
+            eccentricity: 1.5,
+            inclination: 0.4,
+            ..KeplerianElements::default()
+        };
+        assert_eq!(elements.bounding_radius(), None);
+
+        let max_radius = 100_000.0;
+        let (min, max) = elements.bounding_box(MASS, 200, max_radius);
+
+        let corner = min.abs().max(max.abs());
+        assert!(corner.length() > 0.5 * max_radius, "{corner}");
+        assert!(corner.max_element() <= 1.0001 * max_radius, "{corner}");
+
+        // The periapsis is on the +x axis
+        let periapsis = elements.periapsis(MASS);
+        assert!(periapsis.cmpge(min).all() && periapsis.cmple(max).all());
+    }
+
+    #[test_case(1.0, 1.0, Conic::Circular ; "circular")]
+    #[test_case(Num::sqrt(2.0), 1.0, Conic::Parabolic ; "escape speed")]
+    #[test_case(0.99 * Num::sqrt(2.0), 1.0, Conic::Elliptic ; "below escape")]
+    #[test_case(1.01 * Num::sqrt(2.0), 1.0, Conic::Hyperbolic ; "above escape")]
+    #[test_case(1.0, 0.0, Conic::Rectilinear ; "radial drop")]
+    #[test_case(0.0, 0.0, Conic::Rectilinear ; "at rest")]
+    fn classify_from_energy(speed: Num, horizontal: Num, expected: Conic) {
+        let mass = EARTH_MASS;
+        let r = 7_000_000.0;
+        let circular =
+            (astro::standard_gravitational_parameter(mass) / r).sqrt();
+
+        // Falling straight down unless horizontal
+        let direction =
+            vec3(horizontal - 1.0, horizontal, 0.0).normalize_or_zero();
+        let sv =
+            StateVectors::new(vec3(r, 0.0, 0.0), direction * speed * circular);
+
+        assert_eq!(sv.classify(mass, 1e-4), expected);
+    }
+
+    #[test_case(1.01 ; "barely hyperbolic")]
+    #[test_case(2.0 ; "fast escape")]
+    fn to_elements_far_along_the_asymptote(eccentricity: Num) {
+        for M in [-500.0, -50.0, 0.5, 50.0, 500.0] {
+            let elements = KeplerianElements {
+                eccentricity,
+                semi_major_axis: 2.0,
+                inclination: 0.4,
+                mean_anomaly_at_epoch: M,
+                ..KeplerianElements::default()
+            };
+
+            let sv = elements.state_vectors_at_epoch(MASS, 0.0, TOLERANCE);
+            let back = sv.to_elements(MASS, 0.0).mean_anomaly_at_epoch;
+
+            assert!((back / M - 1.0).abs() < 1e-2, "{back} != {M}");
+        }
+    }
 }
@@ -1,7 +1,30 @@
-use crate::Num;
+use std::fmt;
+
+use crate::{Num, PI, TWO_PI};
 
 const MAX_STEPS: usize = 100_000;
 
+/// An iterative method failed to converge
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConvergenceError {
+    /// Number of steps taken
+    pub steps: usize,
+    /// The last approximation
+    pub x: Num,
+}
+
+impl fmt::Display for ConvergenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Failed to converge after {} iterations (x = {})",
+            self.steps, self.x
+        )
+    }
+}
+
+impl std::error::Error for ConvergenceError {}
+
 /// Approximates the root of a function using the Newton-Raphson method.
 ///
 /// # Arguments
@@ -18,15 +41,103 @@ pub fn newton_approx(
     x0: Num,
     epsilon: Num,
 ) -> Num {
+    newton_approx_counted(f, f_prime, x0, epsilon).0
+}
+
+/// Same as [`newton_approx`] but also returns the number of iterations taken.
+pub fn newton_approx_counted(
+    f: impl Fn(Num) -> Num,
+    f_prime: impl Fn(Num) -> Num,
+    x0: Num,
+    epsilon: Num,
+) -> (Num, usize) {
+    newton_approx_fused_counted(|x| (f(x), f_prime(x)), x0, epsilon)
+}
+
+/// Same as [`newton_approx`] but with the function and its derivative
+/// evaluated by a single closure.
+///
+/// Useful when both share expensive terms, e.g. the sine and cosine
+/// of the same argument in Kepler's equation.
+pub fn newton_approx_fused(
+    f_and_f_prime: impl Fn(Num) -> (Num, Num),
+    x0: Num,
+    epsilon: Num,
+) -> Num {
+    newton_approx_fused_counted(f_and_f_prime, x0, epsilon).0
+}
+
+/// Same as [`newton_approx_fused`] but also returns
+/// the number of iterations taken.
+pub fn newton_approx_fused_counted(
+    f_and_f_prime: impl Fn(Num) -> (Num, Num),
+    x0: Num,
+    epsilon: Num,
+) -> (Num, usize) {
+    let mut x = x0;
+
+    for step in 0..MAX_STEPS {
+        let (f, f_prime) = f_and_f_prime(x);
+        let x_next = x - f / f_prime;
+
+        let error = (x_next - x).abs();
+
+        if error < epsilon {
+            return (x_next, step + 1);
+        }
+
+        x = x_next;
+    }
+
+    panic!(
+        "Failed to converge after {} iterations (x0 = {}, x = {})",
+        MAX_STEPS, x0, x
+    );
+}
+
+/// Approximates the root of a function using Halley's method.
+///
+/// Converges cubically, so it takes fewer iterations than Newton's method
+/// where the function is strongly curved, at the cost of the second
+/// derivative.
+///
+/// # Arguments
+/// f - The function to approximate the root of.
+/// f_prime - The derivative of the function.
+/// f_prime2 - The second derivative of the function.
+/// x0 - The initial guess.
+/// epsilon - The maximum error allowed.
+///
+/// SRC: https://en.wikipedia.org/wiki/Halley%27s_method
+pub fn halley_approx(
+    f: impl Fn(Num) -> Num,
+    f_prime: impl Fn(Num) -> Num,
+    f_prime2: impl Fn(Num) -> Num,
+    x0: Num,
+    epsilon: Num,
+) -> Num {
+    halley_approx_counted(f, f_prime, f_prime2, x0, epsilon).0
+}
+
+/// Same as [`halley_approx`] but also returns the number of iterations taken.
+pub fn halley_approx_counted(
+    f: impl Fn(Num) -> Num,
+    f_prime: impl Fn(Num) -> Num,
+    f_prime2: impl Fn(Num) -> Num,
+    x0: Num,
+    epsilon: Num,
+) -> (Num, usize) {
     let mut x = x0;
 
-    for _ in 0..MAX_STEPS {
-        let x_next = x - f(x) / f_prime(x);
+    for step in 0..MAX_STEPS {
+        let (f, f_prime, f_prime2) = (f(x), f_prime(x), f_prime2(x));
+        let x_next =
+            x - 2.0 * f * f_prime / (2.0 * f_prime.powi(2) - f * f_prime2);
 
         let error = (x_next - x).abs();
 
         if error < epsilon {
-            return x_next;
+            return (x_next, step + 1);
         }
 
         x = x_next;
@@ -37,3 +148,188 @@ pub fn newton_approx(
         MAX_STEPS, x0, x
     );
 }
+
+/// Approximates the fixed point of a function, i.e. x such that f(x) = x.
+///
+/// # Arguments
+/// f - The function to approximate the fixed point of.
+/// x0 - The initial guess.
+/// epsilon - The maximum error allowed.
+/// max_steps - The maximum number of iterations.
+///
+/// # Returns
+/// The approximate fixed point of the function
+pub fn fixed_point_iteration(
+    f: impl Fn(Num) -> Num,
+    x0: Num,
+    epsilon: Num,
+    max_steps: usize,
+) -> Result<Num, ConvergenceError> {
+    let mut x = x0;
+
+    for _ in 0..max_steps {
+        let x_next = f(x);
+
+        if (x_next - x).abs() < epsilon {
+            return Ok(x_next);
+        }
+
+        x = x_next;
+    }
+
+    Err(ConvergenceError {
+        steps: max_steps,
+        x,
+    })
+}
+
+/// Approximates the minimum of a unimodal function on [a, b]
+/// using the golden section search.
+///
+/// # Arguments
+/// f - The function to minimize.
+/// a, b - The bounds of the interval.
+/// steps - The number of times the interval is shrunk.
+///
+/// SRC: https://en.wikipedia.org/wiki/Golden-section_search
+pub fn golden_section_search(
+    f: impl Fn(Num) -> Num,
+    mut a: Num,
+    mut b: Num,
+    steps: usize,
+) -> Num {
+    let ratio = (Num::sqrt(5.0) - 1.0) / 2.0;
+
+    for _ in 0..steps {
+        let m1 = b - ratio * (b - a);
+        let m2 = a + ratio * (b - a);
+
+        if f(m1) < f(m2) {
+            b = m2;
+        } else {
+            a = m1;
+        }
+    }
+
+    0.5 * (a + b)
+}
+
+/// acos with the argument clamped to [-1, 1], so that values pushed
+/// slightly out of range by rounding don't produce NaN.
+pub(crate) fn safe_acos(x: Num) -> Num {
+    x.clamp(-1.0, 1.0).acos()
+}
+
+/// Wraps an angle into the [0, 2π) range.
+pub fn wrap_angle(x: Num) -> Num {
+    let wrapped = x.rem_euclid(TWO_PI);
+
+    // rem_euclid can round up to exactly 2π for tiny negative inputs
+    if wrapped >= TWO_PI {
+        0.0
+    } else {
+        wrapped
+    }
+}
+
+/// Smallest signed difference between two angles, in the [-π, π) range.
+pub fn angle_diff(a: Num, b: Num) -> Num {
+    wrap_angle(a - b + PI) - PI
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    /// Kepler's equation, counting the evaluated sines and cosines
+    fn kepler(
+        e: Num,
+        M: Num,
+        count: &Cell<usize>,
+    ) -> (
+        impl Fn(Num) -> Num + '_,
+        impl Fn(Num) -> Num + '_,
+        impl Fn(Num) -> Num + '_,
+    ) {
+        let f = move |E: Num| {
+            count.set(count.get() + 1);
+            E - e * E.sin() - M
+        };
+        let f_prime = move |E: Num| {
+            count.set(count.get() + 1);
+            1.0 - e * E.cos()
+        };
+        let f_prime2 = move |E: Num| {
+            count.set(count.get() + 1);
+            e * E.sin()
+        };
+
+        (f, f_prime, f_prime2)
+    }
+
+    #[test]
+    fn fused_newton_matches_newton() {
+        for (e, M) in [(0.1, 1.0), (0.5, 3.0), (0.9, 0.2), (0.99, 5.0)] {
+            let count = Cell::new(0);
+            let (f, f_prime, _) = kepler(e, M, &count);
+            let (E, steps) = newton_approx_counted(f, f_prime, M, 1e-5);
+            let separate = count.get();
+
+            count.set(0);
+            let (E_fused, steps_fused) = newton_approx_fused_counted(
+                |E| {
+                    count.set(count.get() + 1);
+                    let (sin, cos) = E.sin_cos();
+                    (E - e * sin - M, 1.0 - e * cos)
+                },
+                M,
+                1e-5,
+            );
+
+            assert_eq!(E, E_fused);
+            assert_eq!(steps, steps_fused);
+            assert!(count.get() < separate);
+        }
+    }
+
+    #[test]
+    fn halley_converges_faster_at_high_eccentricity() {
+        for M in [0.05, 0.5, 2.0, 5.0] {
+            let e = 0.99;
+            let count = Cell::new(0);
+
+            let (f, f_prime, f_prime2) = kepler(e, M, &count);
+            let (E, newton_steps) =
+                newton_approx_counted(&f, &f_prime, M, 1e-5);
+            let (E_halley, halley_steps) =
+                halley_approx_counted(f, f_prime, f_prime2, M, 1e-5);
+
+            assert!((E - E_halley).abs() < 1e-4, "{E} != {E_halley}");
+            assert!(halley_steps <= newton_steps, "M = {M}");
+        }
+    }
+
+    #[test]
+    fn golden_section_search_finds_the_minimum() {
+        let x = golden_section_search(|x| (x - 1.3).powi(2), 0.0, 5.0, 60);
+
+        assert!((x - 1.3).abs() < 1e-4, "{x}");
+    }
+
+    #[test]
+    fn fixed_point_iteration_converges() {
+        // The Dottie number
+        let x = fixed_point_iteration(|x| x.cos(), 1.0, 1e-6, 1000).unwrap();
+
+        assert!((x - 0.739085).abs() < 1e-4);
+    }
+
+    #[test]
+    fn fixed_point_iteration_diverges() {
+        let err = fixed_point_iteration(|x| x + 1.0, 0.0, 1e-6, 10);
+
+        assert_eq!(err, Err(ConvergenceError { steps: 10, x: 10.0 }));
+    }
+}
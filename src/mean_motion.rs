@@ -0,0 +1,73 @@
+use crate::constants::SECONDS_PER_DAY;
+use crate::{Num, TWO_PI};
+
+/// Mean motion of an orbit, stored in radians per second.
+///
+/// TLEs give it in revolutions per day, this type does the conversion.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct MeanMotion(Num);
+
+impl MeanMotion {
+    pub const fn from_rad_per_sec(n: Num) -> Self {
+        Self(n)
+    }
+
+    pub fn from_rev_per_day(n: Num) -> Self {
+        Self(n * TWO_PI / SECONDS_PER_DAY)
+    }
+
+    /// Mean motion of an elliptical orbit, n = √(μ/a³)
+    pub fn from_semi_major_axis(a: Num, mu: Num) -> Self {
+        Self((mu / a.powi(3)).sqrt())
+    }
+
+    pub fn rad_per_sec(&self) -> Num {
+        self.0
+    }
+
+    pub fn rev_per_day(&self) -> Num {
+        self.0 * SECONDS_PER_DAY / TWO_PI
+    }
+
+    /// Semi-major axis of the orbit with this mean motion, a = ∛(μ/n²)
+    pub fn semi_major_axis(&self, mu: Num) -> Num {
+        (mu / self.0.powi(2)).cbrt()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::G;
+    use crate::KeplerianElements;
+
+    /// Earth's standard gravitational parameter
+    const EARTH_MU: Num = 3.986_004_4e14;
+
+    #[test]
+    fn iss_mean_motion() {
+        let n = MeanMotion::from_rev_per_day(15.5);
+        let a = n.semi_major_axis(EARTH_MU);
+
+        assert!((a - 6_780_000.0).abs() < 20_000.0, "{a}");
+
+        let round_trip = MeanMotion::from_semi_major_axis(a, EARTH_MU);
+        assert!((round_trip.rev_per_day() - 15.5).abs() < 1e-4);
+        assert!((round_trip.rad_per_sec() - n.rad_per_sec()).abs() < 1e-8);
+
+        let elements = KeplerianElements {
+            semi_major_axis: a,
+            ..KeplerianElements::default()
+        };
+        let rev_per_day = elements.mean_motion_rev_per_day(EARTH_MU / G);
+        assert!((rev_per_day - 15.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn unit_conversion() {
+        let n = MeanMotion::from_rad_per_sec(TWO_PI / SECONDS_PER_DAY);
+
+        assert!((n.rev_per_day() - 1.0).abs() < 1e-6);
+        assert_eq!(MeanMotion::from_rev_per_day(1.0), n);
+    }
+}
@@ -0,0 +1,221 @@
+use crate::constants::G;
+use crate::{KeplerianElements, Num, StateVectors, Vec3};
+
+/// Keplerian elements bundled together with the gravitational parameter
+/// of the central body.
+///
+/// Exposes the same methods as [`KeplerianElements`] without the need
+/// to pass the mass around.
+#[derive(Debug, Clone, Copy)]
+pub struct Orbit {
+    pub elements: KeplerianElements,
+    /// Standard gravitational parameter of the central body
+    pub mu: Num,
+}
+
+impl Orbit {
+    pub fn from_mass(elements: KeplerianElements, mass: Num) -> Self {
+        Self {
+            elements,
+            mu: G * mass,
+        }
+    }
+
+    pub fn from_mu(elements: KeplerianElements, mu: Num) -> Self {
+        Self { elements, mu }
+    }
+
+    pub fn from_state_vectors(
+        state_vectors: &StateVectors,
+        mu: Num,
+        time: Num,
+    ) -> Self {
+        let elements = state_vectors.to_elements(mu / G, time);
+
+        Self { elements, mu }
+    }
+
+    /// Mass of the central body
+    pub fn mass(&self) -> Num {
+        self.mu / G
+    }
+
+    pub fn ascending_node(&self) -> Vec3 {
+        self.elements.ascending_node(self.mass())
+    }
+
+    pub fn descending_node(&self) -> Vec3 {
+        self.elements.descending_node(self.mass())
+    }
+
+    pub fn periapsis(&self) -> Vec3 {
+        self.elements.periapsis(self.mass())
+    }
+
+    pub fn apoapsis(&self) -> Vec3 {
+        self.elements.apoapsis(self.mass())
+    }
+
+    pub fn normal(&self) -> Vec3 {
+        self.elements.normal()
+    }
+
+    pub fn period(&self) -> Num {
+        self.elements.period(self.mass())
+    }
+
+    pub fn period_days(&self) -> Option<Num> {
+        self.elements.period_days(self.mass())
+    }
+
+    pub fn period_years(&self) -> Option<Num> {
+        self.elements.period_years(self.mass())
+    }
+
+    pub fn fraction_of_time_below_radius(&self, radius: Num) -> Option<Num> {
+        self.elements
+            .fraction_of_time_below_radius(self.mass(), radius)
+    }
+
+    pub fn mean_anomaly(&self, epoch: Num) -> Num {
+        self.elements.mean_anomaly(self.mass(), epoch)
+    }
+
+    pub fn hyperbolic_mean_anomaly(&self, epoch: Num) -> Num {
+        self.elements.hyperbolic_mean_anomaly(self.mass(), epoch)
+    }
+
+    pub fn estimate_eccentric_anomaly(
+        &self,
+        epoch: Num,
+        tolerance: Num,
+    ) -> Num {
+        self.elements
+            .estimate_eccentric_anomaly(self.mass(), epoch, tolerance)
+    }
+
+    pub fn estimate_hyperbolic_anomaly(
+        &self,
+        epoch: Num,
+        tolerance: Num,
+    ) -> Num {
+        self.elements
+            .estimate_hyperbolic_anomaly(self.mass(), epoch, tolerance)
+    }
+
+    pub fn state_vectors_at(&self, epoch: Num, tolerance: Num) -> StateVectors {
+        self.elements
+            .state_vectors_at_epoch(self.mass(), epoch, tolerance)
+    }
+
+    pub fn position_at_true_anomaly(&self, v: Num) -> Vec3 {
+        self.elements.position_at_true_anomaly(self.mass(), v)
+    }
+
+    pub fn velocity_at_true_anomaly(&self, v: Num) -> Vec3 {
+        self.elements.velocity_at_true_anomaly(self.mass(), v)
+    }
+
+    pub fn specific_angular_momentum(&self) -> Num {
+        self.elements.specific_angular_momentum(self.mass())
+    }
+
+    pub fn true_anomaly_at(&self, epoch: Num, tolerance: Num) -> Num {
+        self.elements
+            .true_anomaly_at_epoch(self.mass(), epoch, tolerance)
+    }
+
+    pub fn argument_of_latitude(&self, epoch: Num, tolerance: Num) -> Num {
+        self.elements
+            .argument_of_latitude(self.mass(), epoch, tolerance)
+    }
+
+    pub fn mean_longitude(&self, epoch: Num) -> Num {
+        self.elements.mean_longitude(self.mass(), epoch)
+    }
+
+    pub fn true_longitude(&self, epoch: Num, tolerance: Num) -> Num {
+        self.elements.true_longitude(self.mass(), epoch, tolerance)
+    }
+}
+
+/// Keplerian elements that memoize the last computed state vectors.
+///
+/// Calling [`CachedOrbit::state_vectors_at_epoch`] again with the same epoch
+/// returns the cached result. Only the epoch is part of the cache key,
+/// the tolerance of later calls is ignored on a cache hit.
+#[derive(Debug, Clone, Copy)]
+pub struct CachedOrbit {
+    elements: KeplerianElements,
+    mass: Num,
+    cached_epoch: Option<Num>,
+    cached_sv: Option<StateVectors>,
+}
+
+impl CachedOrbit {
+    pub fn new(elements: KeplerianElements, mass: Num) -> Self {
+        Self {
+            elements,
+            mass,
+            cached_epoch: None,
+            cached_sv: None,
+        }
+    }
+
+    pub fn elements(&self) -> &KeplerianElements {
+        &self.elements
+    }
+
+    pub fn mass(&self) -> Num {
+        self.mass
+    }
+
+    /// Epoch of the currently cached state vectors, if any
+    pub fn cached_epoch(&self) -> Option<Num> {
+        self.cached_epoch
+    }
+
+    pub fn set_elements(&mut self, elements: KeplerianElements) {
+        self.elements = elements;
+        self.invalidate();
+    }
+
+    pub fn set_mass(&mut self, mass: Num) {
+        self.mass = mass;
+        self.invalidate();
+    }
+
+    /// Gives mutable access to the elements, invalidating the cache
+    pub fn elements_mut(&mut self) -> &mut KeplerianElements {
+        self.invalidate();
+        &mut self.elements
+    }
+
+    pub fn invalidate(&mut self) {
+        self.cached_epoch = None;
+        self.cached_sv = None;
+    }
+
+    pub fn state_vectors_at_epoch(
+        &mut self,
+        epoch: Num,
+        tolerance: Num,
+    ) -> StateVectors {
+        if let (Some(cached_epoch), Some(sv)) =
+            (self.cached_epoch, self.cached_sv)
+        {
+            if cached_epoch == epoch {
+                return sv;
+            }
+        }
+
+        let sv = self
+            .elements
+            .state_vectors_at_epoch(self.mass, epoch, tolerance);
+
+        self.cached_epoch = Some(epoch);
+        self.cached_sv = Some(sv);
+
+        sv
+    }
+}
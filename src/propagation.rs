@@ -0,0 +1,281 @@
+//! Propagation that reports events along the way, e.g. every apsis
+//! or node passage over a week.
+//!
+//! Every event is a function of the state that changes sign when the event
+//! occurs. The function is checked at regular checkpoints and
+//! the occurrences are bisected in time between them.
+
+use crate::{astro, Num, StateVectors};
+
+/// Checkpoints per revolution, an event function has to change sign
+/// at most once between two of them
+const CHECKPOINTS_PER_PERIOD: usize = 64;
+
+/// Number of bisection steps locating an occurrence between checkpoints
+const BISECTION_STEPS: usize = 60;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Event {
+    /// The radial velocity turns positive
+    PeriapsisPassage,
+    /// The radial velocity turns negative
+    ApoapsisPassage,
+    /// Crossing the reference plane northwards
+    AscendingNode,
+    /// Crossing the reference plane southwards
+    DescendingNode,
+    /// Crossing the given distance from the central body, either way
+    RadiusCrossing(Num),
+    /// The function crossing zero, either way
+    Custom(fn(&StateVectors) -> Num),
+}
+
+impl Event {
+    fn value(&self, state: &StateVectors) -> Num {
+        match self {
+            Self::PeriapsisPassage | Self::ApoapsisPassage => {
+                state.position.dot(state.velocity)
+            }
+            Self::AscendingNode | Self::DescendingNode => state.position.z,
+            Self::RadiusCrossing(radius) => state.position.length() - radius,
+            Self::Custom(f) => f(state),
+        }
+    }
+
+    /// Whether the event occurs between the values, an occurrence exactly
+    /// at a checkpoint counts for the interval ending there
+    fn occurs(&self, before: Num, after: Num) -> bool {
+        let rising = before < 0.0 && after >= 0.0;
+        let falling = before > 0.0 && after <= 0.0;
+
+        match self {
+            Self::PeriapsisPassage | Self::AscendingNode => rising,
+            Self::ApoapsisPassage | Self::DescendingNode => falling,
+            Self::RadiusCrossing(_) | Self::Custom(_) => rising || falling,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EventOccurrence {
+    /// Index of the event in the list passed to [`propagate_with_events`]
+    pub event: usize,
+    /// Seconds since the start of the propagation
+    pub epoch: Num,
+    pub state: StateVectors,
+}
+
+/// Propagates `sv` on its Kepler orbit for `duration` seconds and passes
+/// every occurrence of the `events` to `sink`, in chronological order.
+///
+/// The propagation starts at epoch zero, an occurrence exactly at the start
+/// isn't reported.
+pub fn propagate_with_events(
+    sv: StateVectors,
+    mass: Num,
+    duration: Num,
+    tolerance: Num,
+    events: &[Event],
+    mut sink: impl FnMut(EventOccurrence),
+) {
+    let elements = sv.to_elements(mass, 0.0);
+    let state_at =
+        |epoch: Num| elements.state_vectors_at_epoch(mass, epoch, tolerance);
+
+    // 2π / n for both elliptic and hyperbolic orbits
+    let revolution = astro::period(elements.semi_major_axis.abs(), mass);
+    let step = checkpoint_step(revolution, duration);
+
+    let mut t0 = 0.0;
+    let mut values: Vec<Num> = events.iter().map(|e| e.value(&sv)).collect();
+
+    while t0 < duration {
+        let t1 = (t0 + step).min(duration);
+
+        // The step is lost in the rounding of a long duration
+        if t1 <= t0 {
+            break;
+        }
+
+        let state = state_at(t1);
+
+        let mut occurrences = Vec::new();
+        for (index, event) in events.iter().enumerate() {
+            let before = values[index];
+            let after = event.value(&state);
+            values[index] = after;
+
+            if !event.occurs(before, after) {
+                continue;
+            }
+
+            let (mut lo, mut hi) = (t0, t1);
+            for _ in 0..BISECTION_STEPS {
+                let mid = 0.5 * (lo + hi);
+
+                if event.occurs(before, event.value(&state_at(mid))) {
+                    hi = mid;
+                } else {
+                    lo = mid;
+                }
+            }
+
+            occurrences.push(EventOccurrence {
+                event: index,
+                epoch: hi,
+                state: state_at(hi),
+            });
+        }
+
+        occurrences.sort_by(|a, b| a.epoch.total_cmp(&b.epoch));
+        occurrences.into_iter().for_each(&mut sink);
+
+        t0 = t1;
+    }
+}
+
+/// Time between checkpoints. Without a usable period, e.g. on a parabolic
+/// orbit, the checkpoints are spread over the duration instead.
+fn checkpoint_step(revolution: Num, duration: Num) -> Num {
+    let step = revolution / CHECKPOINTS_PER_PERIOD as Num;
+
+    if step.is_finite() && step > 0.0 {
+        step
+    } else {
+        duration / CHECKPOINTS_PER_PERIOD as Num
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::EARTH_MASS;
+    use crate::{KeplerianElements, PI};
+
+    const TOLERANCE: Num = 1e-5;
+
+    fn orbit() -> KeplerianElements {
+        KeplerianElements {
+            semi_major_axis: 10_000_000.0,
+            eccentricity: 0.3,
+            inclination: 0.4,
+            right_ascension_of_the_ascending_node: 1.0,
+            argument_of_periapsis: 0.5,
+            mean_anomaly_at_epoch: 1.0,
+            epoch: 0.0,
+        }
+    }
+
+    fn collect(
+        orbit: &KeplerianElements,
+        duration: Num,
+        events: &[Event],
+    ) -> Vec<EventOccurrence> {
+        let sv = orbit.state_vectors_at_epoch(EARTH_MASS, 0.0, TOLERANCE);
+
+        let mut occurrences = Vec::new();
+        propagate_with_events(
+            sv,
+            EARTH_MASS,
+            duration,
+            TOLERANCE,
+            events,
+            |occurrence| occurrences.push(occurrence),
+        );
+
+        occurrences
+    }
+
+    #[test]
+    fn apsides_over_three_periods() {
+        let orbit = orbit();
+        let period = orbit.period(EARTH_MASS);
+
+        let events = [Event::PeriapsisPassage, Event::ApoapsisPassage];
+        let occurrences = collect(&orbit, 3.0 * period, &events);
+
+        for (event, v) in [(0, 0.0), (1, PI)] {
+            let epochs = occurrences
+                .iter()
+                .filter(|o| o.event == event)
+                .map(|o| o.epoch)
+                .collect::<Vec<_>>();
+            assert_eq!(epochs.len(), 3, "{occurrences:?}");
+
+            let first =
+                orbit.next_epoch_at_true_anomaly(EARTH_MASS, v).unwrap();
+            for (k, epoch) in epochs.iter().enumerate() {
+                let expected = first + k as Num * period;
+                assert!((epoch - expected).abs() < 1e-3 * period, "{epoch}");
+            }
+        }
+
+        // Alternating, in chronological order
+        assert!(occurrences.windows(2).all(|pair| {
+            pair[0].epoch < pair[1].epoch && pair[0].event != pair[1].event
+        }));
+    }
+
+    #[test]
+    fn nodes_and_radius() {
+        let orbit = orbit();
+        let period = orbit.period(EARTH_MASS);
+
+        let events = [
+            Event::AscendingNode,
+            Event::DescendingNode,
+            Event::RadiusCrossing(orbit.semi_major_axis),
+        ];
+        let occurrences = collect(&orbit, 2.0 * period, &events);
+
+        let count = |event: usize| {
+            occurrences.iter().filter(|o| o.event == event).count()
+        };
+        assert_eq!((count(0), count(1), count(2)), (2, 2, 4));
+
+        for occurrence in &occurrences {
+            let state = occurrence.state;
+            match events[occurrence.event] {
+                Event::AscendingNode => assert!(state.velocity.z > 0.0),
+                Event::DescendingNode => assert!(state.velocity.z < 0.0),
+                _ => {}
+            }
+
+            let value = events[occurrence.event].value(&state);
+            let scale = match events[occurrence.event] {
+                Event::RadiusCrossing(r) => r,
+                _ => state.position.length(),
+            };
+            assert!(value.abs() < 1e-4 * scale, "{occurrence:?}");
+        }
+    }
+
+    #[test]
+    fn custom_event() {
+        let orbit = orbit();
+        let period = orbit.period(EARTH_MASS);
+
+        fn x_axis_plane(state: &StateVectors) -> Num {
+            state.position.x
+        }
+
+        let occurrences =
+            collect(&orbit, period, &[Event::Custom(x_axis_plane)]);
+        assert_eq!(occurrences.len(), 2);
+    }
+
+    #[test]
+    fn checkpoints_without_a_usable_period() {
+        for revolution in [0.0, -1.0, Num::NAN, Num::INFINITY] {
+            assert_eq!(checkpoint_step(revolution, 640.0), 10.0);
+        }
+
+        assert_eq!(checkpoint_step(64.0, 640.0), 1.0);
+    }
+
+    #[test]
+    fn zero_duration() {
+        let occurrences = collect(&orbit(), 0.0, &[Event::PeriapsisPassage]);
+        assert!(occurrences.is_empty());
+    }
+}
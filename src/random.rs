@@ -0,0 +1,242 @@
+//! Random perturbations of orbits for Monte Carlo dispersions.
+
+use rand::Rng;
+
+use crate::elements::InvalidElements;
+use crate::math::wrap_angle;
+use crate::{vec3, KeplerianElements, Num, StateVectors, PI, TWO_PI};
+
+/// Standard deviations of the Gaussian perturbations of each element.
+///
+/// The epoch is never perturbed.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ElementSigmas {
+    pub eccentricity: Num,
+    pub semi_major_axis: Num,
+    pub inclination: Num,
+    pub right_ascension_of_the_ascending_node: Num,
+    pub argument_of_periapsis: Num,
+    pub mean_anomaly_at_epoch: Num,
+}
+
+impl KeplerianElements {
+    /// Draws a perturbed copy of the elements.
+    ///
+    /// The eccentricity and the semi-major axis are drawn from Gaussians
+    /// truncated to valid values, the inclination is reflected back into
+    /// [0, π] and the other angles are wrapped. So the result always passes
+    /// [`KeplerianElements::validate`], and invalid nominal elements are
+    /// rejected up front.
+    pub fn perturbed<R: Rng>(
+        &self,
+        rng: &mut R,
+        sigmas: &ElementSigmas,
+    ) -> Result<Self, InvalidElements> {
+        self.validate()?;
+
+        let eccentricity = truncated_gaussian(
+            rng,
+            self.eccentricity,
+            sigmas.eccentricity,
+            |e| e >= 0.0,
+        );
+        let semi_major_axis = truncated_gaussian(
+            rng,
+            self.semi_major_axis,
+            sigmas.semi_major_axis,
+            |a| a > 0.0,
+        );
+
+        let inclination =
+            (self.inclination + sigmas.inclination * gaussian(rng)) % TWO_PI;
+        let inclination = inclination.abs();
+        let inclination = if inclination > PI {
+            TWO_PI - inclination
+        } else {
+            inclination
+        };
+
+        let mut perturb =
+            |value: Num, sigma: Num| wrap_angle(value + sigma * gaussian(rng));
+
+        let right_ascension_of_the_ascending_node = perturb(
+            self.right_ascension_of_the_ascending_node,
+            sigmas.right_ascension_of_the_ascending_node,
+        );
+        let argument_of_periapsis =
+            perturb(self.argument_of_periapsis, sigmas.argument_of_periapsis);
+
+        let mean_anomaly_at_epoch = self.mean_anomaly_at_epoch
+            + sigmas.mean_anomaly_at_epoch * gaussian(rng);
+
+        Ok(Self {
+            eccentricity,
+            semi_major_axis,
+            inclination,
+            right_ascension_of_the_ascending_node,
+            argument_of_periapsis,
+            mean_anomaly_at_epoch,
+            epoch: self.epoch,
+        }
+        .wrapped())
+    }
+}
+
+impl StateVectors {
+    /// Draws a perturbed copy of the state,
+    /// with independent Gaussian errors on each component.
+    pub fn perturbed<R: Rng>(
+        &self,
+        rng: &mut R,
+        position_sigma: Num,
+        velocity_sigma: Num,
+    ) -> Self {
+        let mut noise = |sigma: Num| {
+            sigma * vec3(gaussian(rng), gaussian(rng), gaussian(rng))
+        };
+
+        let position = self.position + noise(position_sigma);
+        let velocity = self.velocity + noise(velocity_sigma);
+
+        Self::new(position, velocity)
+    }
+}
+
+/// Standard normal sample, using the Box-Muller transform
+fn gaussian<R: Rng>(rng: &mut R) -> Num {
+    // 1 - u is in (0, 1], so the logarithm is finite
+    let u1: Num = 1.0 - rng.gen::<Num>();
+    let u2: Num = rng.gen();
+
+    (-2.0 * u1.ln()).sqrt() * (TWO_PI * u2).cos()
+}
+
+/// Redraws of [`truncated_gaussian`] before giving up on the sample
+const MAX_ATTEMPTS: usize = 64;
+
+/// Gaussian sample redrawn until it's `valid`.
+///
+/// The nominal value has to be valid, so at least half of the draws are.
+/// A non-finite sigma never gives a valid finite draw, so after
+/// [`MAX_ATTEMPTS`] the nominal value is returned.
+fn truncated_gaussian<R: Rng>(
+    rng: &mut R,
+    mean: Num,
+    sigma: Num,
+    valid: impl Fn(Num) -> bool,
+) -> Num {
+    (0..MAX_ATTEMPTS)
+        .map(|_| mean + sigma * gaussian(rng))
+        .find(|&sample| sample.is_finite() && valid(sample))
+        .unwrap_or(mean)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::Vec3;
+
+    const SAMPLES: usize = 10_000;
+
+    #[test]
+    fn perturbed_elements_are_valid_and_centered() {
+        let mut rng = StdRng::seed_from_u64(1337);
+
+        // Close to the edges, so truncation and wrapping kick in
+        let nominal = KeplerianElements {
+            eccentricity: 0.3,
+            semi_major_axis: 7_000_000.0,
+            inclination: 0.05,
+            right_ascension_of_the_ascending_node: 1.0,
+            argument_of_periapsis: 2.0,
+            mean_anomaly_at_epoch: 3.0,
+            epoch: 5.0,
+        };
+        let sigmas = ElementSigmas {
+            eccentricity: 0.01,
+            semi_major_axis: 1_000.0,
+            inclination: 0.1,
+            right_ascension_of_the_ascending_node: 0.01,
+            argument_of_periapsis: 0.01,
+            mean_anomaly_at_epoch: 0.01,
+        };
+
+        let mut mean = [0.0; 7];
+        for _ in 0..SAMPLES {
+            let sample = nominal.perturbed(&mut rng, &sigmas).unwrap();
+            assert_eq!(sample.validate(), Ok(()), "{sample:?}");
+            assert_eq!(sample.epoch, nominal.epoch);
+
+            for (mean, x) in mean.iter_mut().zip(sample.as_array()) {
+                *mean += x / SAMPLES as Num;
+            }
+        }
+
+        let [a, e, _, Ω, ω, M, _] = mean;
+        assert!((e - 0.3).abs() < 1e-3, "{e}");
+        assert!((a / 7_000_000.0 - 1.0).abs() < 1e-4, "{a}");
+        assert!((Ω - 1.0).abs() < 1e-3, "{Ω}");
+        assert!((ω - 2.0).abs() < 1e-3, "{ω}");
+        assert!((M - 3.0).abs() < 1e-3, "{M}");
+    }
+
+    #[test]
+    fn eccentricity_stays_non_negative() {
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let circular = KeplerianElements::default();
+        let sigmas = ElementSigmas {
+            eccentricity: 0.1,
+            inclination: 0.1,
+            mean_anomaly_at_epoch: 1.0,
+            ..ElementSigmas::default()
+        };
+
+        for _ in 0..SAMPLES {
+            let sample = circular.perturbed(&mut rng, &sigmas).unwrap();
+            assert_eq!(sample.validate(), Ok(()), "{sample:?}");
+        }
+    }
+
+    #[test]
+    fn invalid_nominal_or_sigmas_terminate() {
+        let mut rng = StdRng::seed_from_u64(3);
+
+        let negative_sma = KeplerianElements {
+            semi_major_axis: -1.0,
+            ..KeplerianElements::default()
+        };
+        assert_eq!(
+            negative_sma.perturbed(&mut rng, &ElementSigmas::default()),
+            Err(InvalidElements::NonPositiveSemiMajorAxis)
+        );
+
+        let nominal = KeplerianElements::default();
+        let sigmas = ElementSigmas {
+            semi_major_axis: Num::INFINITY,
+            ..ElementSigmas::default()
+        };
+        let sample = nominal.perturbed(&mut rng, &sigmas).unwrap();
+        assert_eq!(sample.semi_major_axis, nominal.semi_major_axis);
+    }
+
+    #[test]
+    fn perturbed_state_vectors_are_centered() {
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let nominal = StateVectors::new(vec3(1.0, 2.0, 3.0), Vec3::X);
+
+        let mut mean = StateVectors::default();
+        for _ in 0..SAMPLES {
+            let sample = nominal.perturbed(&mut rng, 0.1, 0.01);
+            mean.position += sample.position / SAMPLES as Num;
+            mean.velocity += sample.velocity / SAMPLES as Num;
+        }
+
+        assert!(mean.position.abs_diff_eq(nominal.position, 5e-3));
+        assert!(mean.velocity.abs_diff_eq(nominal.velocity, 5e-4));
+    }
+}
@@ -1,7 +1,57 @@
 use crate::astro::standard_gravitational_parameter;
-use crate::{KeplerianElements, Num, Vec3, TWO_PI};
+use crate::math::wrap_angle;
+use crate::{vec3, KeplerianElements, Mat3, Num, Vec3};
 
-#[derive(Debug, Default, Clone, Copy)]
+/// Default threshold of [`ConversionOptions::circular_threshold`],
+/// 1e-8 or the rounding noise of the eccentricity vector
+/// if that's larger (as it is for f32)
+pub const CIRCULAR_TOLERANCE: Num = max(1e-8, 64.0 * Num::EPSILON);
+
+/// Default threshold of [`ConversionOptions::equatorial_threshold`],
+/// 1e-8 rad or the rounding noise if that's larger
+pub const EQUATORIAL_TOLERANCE: Num = max(1e-8, 64.0 * Num::EPSILON);
+
+const fn max(a: Num, b: Num) -> Num {
+    if a > b {
+        a
+    } else {
+        b
+    }
+}
+
+/// Thresholds below which [`StateVectors::to_elements_with`] treats
+/// an orbit as circular or equatorial
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConversionOptions {
+    /// Orbits with a lower eccentricity are treated as circular
+    pub circular_threshold: Num,
+    /// Orbits with a lower inclination, or a lower difference
+    /// from π for retrograde ones, are treated as equatorial (in radians)
+    pub equatorial_threshold: Num,
+}
+
+impl Default for ConversionOptions {
+    fn default() -> Self {
+        Self {
+            circular_threshold: CIRCULAR_TOLERANCE,
+            equatorial_threshold: EQUATORIAL_TOLERANCE,
+        }
+    }
+}
+
+/// Kind of the conic section traced by a state, see [`StateVectors::classify`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Conic {
+    Circular,
+    Elliptic,
+    Parabolic,
+    Hyperbolic,
+    /// No angular momentum, falling straight towards or away from
+    /// the central body
+    Rectilinear,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StateVectors {
     pub position: Vec3,
@@ -9,17 +59,214 @@ pub struct StateVectors {
 }
 
 impl StateVectors {
-    pub fn new(position: Vec3, velocity: Vec3) -> Self {
+    pub const fn new(position: Vec3, velocity: Vec3) -> Self {
         Self { position, velocity }
     }
 
+    /// Builds the state vectors from an array in the canonical order:
+    /// [x, y, z, vx, vy, vz]
+    pub const fn from_array(array: [Num; 6]) -> Self {
+        let [x, y, z, vx, vy, vz] = array;
+
+        Self::new(vec3(x, y, z), vec3(vx, vy, vz))
+    }
+
+    /// Returns the state vectors in the canonical order:
+    /// [x, y, z, vx, vy, vz]
+    pub fn as_array(&self) -> [Num; 6] {
+        let [x, y, z] = self.position.to_array();
+        let [vx, vy, vz] = self.velocity.to_array();
+
+        [x, y, z, vx, vy, vz]
+    }
+
     pub fn abs_diff(&self, other: &Self) -> Num {
         self.position.distance(other.position)
             + self.velocity.distance(other.velocity)
     }
 
+    /// Rotation from the RTN frame to the inertial one, its columns are
+    /// the radial, transverse and normal unit vectors.
+    ///
+    /// The normal is along the angular momentum and the transverse axis
+    /// completes the right-handed basis, so it points along the motion.
+    /// For purely radial motion (velocity parallel to the position)
+    /// there's no orbital plane and an arbitrary normal perpendicular
+    /// to the position is picked instead. A zero position falls back
+    /// to the x axis as the radial direction.
+    pub fn rtn_matrix(&self) -> Mat3 {
+        let radial = self.position.try_normalize().unwrap_or(Vec3::X);
+        let normal = radial
+            .cross(self.velocity)
+            .try_normalize()
+            .unwrap_or_else(|| radial.any_orthonormal_vector());
+        let transverse = normal.cross(radial);
+
+        Mat3::from_cols(radial, transverse, normal)
+    }
+
+    /// Expresses an inertial vector in the RTN frame of this state
+    pub fn to_rtn_frame(&self, v: Vec3) -> Vec3 {
+        self.rtn_matrix().transpose() * v
+    }
+
+    /// Expresses a vector given in the RTN frame of this state
+    /// in the inertial frame
+    pub fn from_rtn_frame(&self, v: Vec3) -> Vec3 {
+        self.rtn_matrix() * v
+    }
+
+    /// State right after an impulsive burn given in the RTN frame
+    pub fn apply_delta_v_rtn(&self, delta_v_rtn: Vec3) -> Self {
+        Self::new(
+            self.position,
+            self.velocity + self.from_rtn_frame(delta_v_rtn),
+        )
+    }
+
+    /// Prograde delta-v, negative for a retrograde burn, that puts
+    /// the apoapsis at `target_apoapsis_radius`.
+    ///
+    /// The burn keeps the flight path angle, so it also works away from
+    /// the apsides. Returns `None` unless the target is above the current
+    /// radius.
+    pub fn dv_to_set_apoapsis(
+        &self,
+        mass: Num,
+        target_apoapsis_radius: Num,
+    ) -> Option<Num> {
+        if target_apoapsis_radius <= self.position.length() {
+            return None;
+        }
+
+        self.dv_to_set_apsis(mass, target_apoapsis_radius)
+    }
+
+    /// Prograde delta-v, negative for a retrograde burn, that puts
+    /// the periapsis at `target_periapsis_radius`.
+    ///
+    /// Returns `None` unless the target is below the current radius times
+    /// the cosine of the flight path angle, the lowest periapsis
+    /// reachable when burning straight down the velocity
+    /// is higher than that.
+    pub fn dv_to_set_periapsis(
+        &self,
+        mass: Num,
+        target_periapsis_radius: Num,
+    ) -> Option<Num> {
+        let horizontal_radius = self.horizontal_radius();
+
+        if target_periapsis_radius <= 0.0
+            || target_periapsis_radius >= horizontal_radius
+        {
+            return None;
+        }
+
+        self.dv_to_set_apsis(mass, target_periapsis_radius)
+    }
+
+    /// |r| cos γ = |r × v| / |v|, the angular momentum over the speed
+    fn horizontal_radius(&self) -> Num {
+        self.position.cross(self.velocity).length() / self.velocity.length()
+    }
+
+    /// Conserves the angular momentum and the energy between here and
+    /// the apsis at `radius`, with the speed here as the unknown:
+    /// v² = 2μ rₐ (r - rₐ) / (r (r_h² - rₐ²)), r_h = r cos γ
+    fn dv_to_set_apsis(&self, mass: Num, radius: Num) -> Option<Num> {
+        let μ = standard_gravitational_parameter(mass);
+        let r = self.position.length();
+        let r_h = self.horizontal_radius();
+
+        let speed_squared = 2.0 * μ * radius * (r - radius)
+            / (r * (r_h - radius) * (r_h + radius));
+        if !(speed_squared.is_finite() && speed_squared > 0.0) {
+            return None;
+        }
+
+        Some(speed_squared.sqrt() - self.velocity.length())
+    }
+
+    /// Kind of the orbit, from the specific energy and angular momentum
+    /// alone, without the full conversion to elements.
+    ///
+    /// `epsilon` is dimensionless: the energy counts as zero (parabolic)
+    /// within ±epsilon of the local potential μ/r, the eccentricity
+    /// below it counts as circular and the angular momentum below
+    /// epsilon·r·v as none.
+    pub fn classify(&self, mass: Num, epsilon: Num) -> Conic {
+        let μ = standard_gravitational_parameter(mass);
+        let (r, v) = (self.position.length(), self.velocity.length());
+
+        let h = self.position.cross(self.velocity).length();
+        if h <= epsilon * r * v {
+            return Conic::Rectilinear;
+        }
+
+        // Relative to μ/r, avoiding μ² which overflows f32
+        let energy = v.powi(2) / 2.0 * r / μ - 1.0;
+        if energy.abs() <= epsilon {
+            return Conic::Parabolic;
+        }
+
+        let e = ((v.powi(2) - μ / r) * self.position
+            - self.position.dot(self.velocity) * self.velocity)
+            / μ;
+        if e.length() < epsilon {
+            Conic::Circular
+        } else if energy < 0.0 {
+            Conic::Elliptic
+        } else {
+            Conic::Hyperbolic
+        }
+    }
+
+    /// Inertial velocity expressed in the LVLH frame of this state,
+    /// see [`crate::utils::eci_to_lvlh`] for the axes
+    pub fn velocity_in_lvlh(&self) -> Vec3 {
+        crate::utils::eci_to_lvlh(self) * self.velocity
+    }
+
+    /// Osculating elements of the state at `current_epoch`,
+    /// with the mean anomaly referenced to `reference_epoch`.
+    pub fn osculating_elements_at(
+        &self,
+        mass: Num,
+        current_epoch: Num,
+        reference_epoch: Num,
+    ) -> KeplerianElements {
+        self.to_elements(mass, current_epoch)
+            .advance_to_epoch(mass, reference_epoch)
+    }
+
+    /// Converts the state vectors to osculating elements,
+    /// with the default [`ConversionOptions`].
     pub fn to_elements(&self, mass: Num, time: Num) -> KeplerianElements {
-        // Position magnitude
+        self.to_elements_with(mass, time, &ConversionOptions::default())
+    }
+
+    /// Converts the state vectors to osculating elements.
+    ///
+    /// Some of the angles are undefined for circular or equatorial orbits,
+    /// so they're replaced by the ones that still are:
+    /// - equatorial (i below the equatorial threshold): there's no node line,
+    ///   Ω is 0 and ω is measured from the x axis,
+    ///   i.e. it becomes the longitude of periapsis
+    /// - circular (e below the circular threshold): there's no periapsis,
+    ///   e and ω are 0 and the true anomaly is measured from the node line,
+    ///   i.e. it becomes the argument of latitude
+    /// - both: Ω, ω are 0 and the true anomaly becomes the true longitude
+    ///
+    /// All angles are measured around the angular momentum,
+    /// so retrograde orbits are handled the same way.
+    pub fn to_elements_with(
+        &self,
+        mass: Num,
+        time: Num,
+        options: &ConversionOptions,
+    ) -> KeplerianElements {
+        let μ = standard_gravitational_parameter(mass);
+
         let rv = self.position;
         let r = rv.length();
         let vv = self.velocity;
@@ -29,67 +276,39 @@ impl StateVectors {
         // This vector should point in the normal direction of the orbit
         let hv = rv.cross(vv);
         let h = hv.length();
+        // Same direction as hv / h, with a fallback for radial motion
+        let normal = self.rtn_matrix().z_axis;
 
-        // N vector - vector that lies on the node line in the direction of the ascending node
-        let mut nv = Vec3::Z.cross(hv);
+        // Vector on the node line in the direction of the ascending node
+        let nv = Vec3::Z.cross(normal);
 
-        // If inclinations is 0 this vector will be zero as well
-        // since a lot of other arguments depend on this vector
-        // we set it to the X axis
-        if nv.length() < Num::EPSILON {
-            nv = Vec3::X;
-        }
-
-        let nv = nv.normalize();
+        // Inclination
+        // Equation is i = arccos(hz / h), but acos loses precision close
+        // to the equatorial orbits, so it's computed from both sin and cos
+        let i = nv.length().atan2(normal.z);
+        let is_equatorial = nv.length() < options.equatorial_threshold.sin();
+        let node = if is_equatorial {
+            Vec3::X
+        } else {
+            nv.normalize()
+        };
 
         // Eccentricity
-        let μ = standard_gravitational_parameter(mass);
-
         let ev = (1.0 / μ) * ((v_mag.powi(2) - (μ / r)) * rv - rv.dot(vv) * vv);
-        let e = ev.length();
+        let is_circular = ev.length() < options.circular_threshold;
+        let e = if is_circular { 0.0 } else { ev.length() };
+        let periapsis = if is_circular { node } else { ev / e };
 
         let is_hyperbolic = e >= 1.0; // or parabolic
 
         // Right ascension of the ascending node
-
-        // Inclination
-        // Equation is i = arccos(hz / h)
-        let i = (hv.z / h).acos();
-
-        // We find the angle between the node line & the X axis
-        let mut Ω = (nv.x).acos();
-
-        if nv.y < 0.0 {
-            Ω = TWO_PI - Ω;
-        }
-
-        if i.abs() < Num::EPSILON {
-            Ω = 0.0;
-        }
+        let Ω = wrap_angle(node.y.atan2(node.x));
 
         // Argument of periapsis
-        let mut ω = (ev / e).dot(nv).acos();
-
-        // An edge case for a zero inclination orbit
-        // If an orbit has zero inclination,
-        // the z component of the eccentricity vector
-        // is zero.
-        //
-        // But we can still do a quadrant check using the y component
-        if i.abs() < Num::EPSILON {
-            if ev.y < 0.0 {
-                ω = TWO_PI - ω;
-            }
-        } else {
-            if ev.z < 0.0 {
-                ω = TWO_PI - ω;
-            }
-        }
+        let ω = angle_around(node, periapsis, normal);
 
-        if e == 0.0 {
-            // For a circular orbit the argument of periapsis is undefined
-            ω = 0.0
-        }
+        // True anomaly
+        let v = angle_around(periapsis, rv / r, normal);
 
         // Semi-major axis
         let a = if is_hyperbolic {
@@ -98,18 +317,15 @@ impl StateVectors {
             (h.powi(2) / μ) / (1.0 - e.powi(2))
         };
 
-        // True anomaly
-        let mut v = (rv / r).dot(ev / e).acos();
-
-        if ((rv / r).dot(vv / v_mag)) < 0.0 {
-            v = TWO_PI - v;
-        }
-
         // Mean anomaly calculation
+        // The hyperbolic mean anomaly is unbounded so we only wrap the elliptic one
         let M = if is_hyperbolic {
-            calculate_hyperbolic_mean_anomaly(e, v)
+            // From r·v = √(μa) e sinh(F), the true anomaly is ill-conditioned
+            // far out along the asymptotes
+            let sinh_F = rv.dot(vv) / (e * (μ * a).sqrt());
+            e * sinh_F - sinh_F.asinh()
         } else {
-            calculate_elliptical_mean_anomaly(e, v)
+            wrap_angle(calculate_elliptical_mean_anomaly(e, v))
         };
 
         KeplerianElements {
@@ -124,14 +340,21 @@ impl StateVectors {
     }
 }
 
-// Hyperbolic mean anomaly calculation
-fn calculate_hyperbolic_mean_anomaly(e: Num, v: Num) -> Num {
-    let term1 = (e * (e.powi(2) - 1.0).sqrt() * v.sin()) / (1.0 + e * v.cos());
-    let term2_numerator = (e + 1.0).sqrt() + (e - 1.0).sqrt() * (v / 2.0).tan();
-    let term2_denominator =
-        (e + 1.0).sqrt() - (e - 1.0).sqrt() * (v / 2.0).tan();
+/// Angle from `from` to `to` counter-clockwise around `axis`, in [0, 2π)
+fn angle_around(from: Vec3, to: Vec3, axis: Vec3) -> Num {
+    wrap_angle(from.cross(to).dot(axis).atan2(from.dot(to)))
+}
 
-    term1 - (term2_numerator / term2_denominator).ln()
+impl From<[Num; 6]> for StateVectors {
+    fn from(array: [Num; 6]) -> Self {
+        Self::from_array(array)
+    }
+}
+
+impl From<StateVectors> for [Num; 6] {
+    fn from(state_vectors: StateVectors) -> Self {
+        state_vectors.as_array()
+    }
 }
 
 // Elliptical mean anomaly calculation
@@ -0,0 +1,529 @@
+//! Hierarchies of bodies orbiting each other, e.g. a star with its planets
+//! and their moons.
+//!
+//! Every body orbits its parent, with elements relative to it.
+//! Bodies without a parent are the roots and sit at the origin.
+
+use std::fmt;
+
+#[cfg(feature = "catalog")]
+pub mod catalog;
+
+#[cfg(feature = "catalog")]
+pub use self::catalog::CatalogError;
+
+use crate::{KeplerianElements, Num, StateVectors};
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Body {
+    pub name: String,
+    pub mass: Num,
+    pub radius: Option<Num>,
+    /// Index of the body this one orbits, `None` for a root
+    pub parent: Option<usize>,
+    /// Orbit around the parent, ignored for a root
+    pub elements: KeplerianElements,
+}
+
+/// Invalid parent links between bodies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemError {
+    UnknownParent { body: usize, parent: usize },
+    Cycle { body: usize },
+}
+
+impl fmt::Display for SystemError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownParent { body, parent } => {
+                write!(f, "Body {body} orbits a nonexistent body {parent}")
+            }
+            Self::Cycle { body } => {
+                write!(f, "Body {body} is part of a cycle of parents")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SystemError {}
+
+/// Serialized as the list of its bodies,
+/// deserializing checks the parents like [`BodySystem::from_bodies`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(try_from = "Vec<Body>", into = "Vec<Body>")
+)]
+pub struct BodySystem {
+    bodies: Vec<Body>,
+}
+
+impl TryFrom<Vec<Body>> for BodySystem {
+    type Error = SystemError;
+
+    fn try_from(bodies: Vec<Body>) -> Result<Self, Self::Error> {
+        Self::from_bodies(bodies)
+    }
+}
+
+impl From<BodySystem> for Vec<Body> {
+    fn from(system: BodySystem) -> Self {
+        system.bodies
+    }
+}
+
+impl BodySystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds the system from bodies in any order,
+    /// rejecting unknown parents and cycles.
+    pub fn from_bodies(bodies: Vec<Body>) -> Result<Self, SystemError> {
+        for (body, b) in bodies.iter().enumerate() {
+            let mut current = b.parent;
+            let mut steps = 0;
+
+            while let Some(parent) = current {
+                let Some(p) = bodies.get(parent) else {
+                    return Err(SystemError::UnknownParent { body, parent });
+                };
+
+                // A chain longer than the number of bodies has to loop
+                steps += 1;
+                if parent == body || steps > bodies.len() {
+                    return Err(SystemError::Cycle { body });
+                }
+
+                current = p.parent;
+            }
+        }
+
+        Ok(Self { bodies })
+    }
+
+    /// Adds a body and returns its index.
+    ///
+    /// The parent has to be added first, so no cycle can form.
+    pub fn add_body(&mut self, body: Body) -> Result<usize, SystemError> {
+        let index = self.bodies.len();
+
+        if let Some(parent) = body.parent {
+            if parent >= index {
+                return Err(SystemError::UnknownParent {
+                    body: index,
+                    parent,
+                });
+            }
+        }
+
+        self.bodies.push(body);
+
+        Ok(index)
+    }
+
+    pub fn bodies(&self) -> &[Body] {
+        &self.bodies
+    }
+
+    pub fn body(&self, index: usize) -> Option<&Body> {
+        self.bodies.get(index)
+    }
+
+    /// Index of the first body with the given name
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.bodies.iter().position(|body| body.name == name)
+    }
+
+    /// State of the body relative to the root of its hierarchy,
+    /// the sum of the states relative to the parents up the chain.
+    ///
+    /// `mass_lookup` gives the mass of a body by its index, each orbit
+    /// is propagated around the mass of its parent.
+    /// See [`BodySystem::all_global_states`] for the masses of the bodies.
+    ///
+    /// # Panics
+    /// If the body doesn't exist.
+    pub fn global_state(
+        &self,
+        body: usize,
+        mass_lookup: impl Fn(usize) -> Num,
+        epoch: Num,
+        tolerance: Num,
+    ) -> StateVectors {
+        let mut state = StateVectors::default();
+        let mut current = body;
+
+        while let Some(parent) = self.bodies[current].parent {
+            let relative = self.bodies[current]
+                .elements
+                .state_vectors_at_epoch(mass_lookup(parent), epoch, tolerance);
+
+            state.position += relative.position;
+            state.velocity += relative.velocity;
+            current = parent;
+        }
+
+        state
+    }
+
+    /// Global states of all the bodies, see [`BodySystem::global_state`],
+    /// or [`BodySystem::barycentric_states`] when `barycentric` is set.
+    ///
+    /// Every orbit is propagated once, the states of the parents
+    /// are shared by their children.
+    pub fn all_global_states(
+        &self,
+        epoch: Num,
+        tolerance: Num,
+        barycentric: bool,
+    ) -> Vec<StateVectors> {
+        if barycentric {
+            return self.barycentric_states(epoch, tolerance);
+        }
+
+        let relative = self.relative_states(epoch, tolerance, false);
+
+        self.global_states_from_relative(&relative)
+    }
+
+    /// Global states of all the bodies from their states relative to
+    /// the parents, `None` for the roots, e.g. propagated elsewhere
+    /// with a warm started [`crate::KeplerSolver`] per body.
+    ///
+    /// # Panics
+    /// If `relative` doesn't have an entry for every body.
+    pub fn global_states_from_relative(
+        &self,
+        relative: &[Option<StateVectors>],
+    ) -> Vec<StateVectors> {
+        assert_eq!(relative.len(), self.bodies.len());
+
+        let offsets = vec![StateVectors::default(); self.bodies.len()];
+
+        self.resolve_all(relative, &offsets)
+    }
+
+    /// Global states where every parent and child orbit their common
+    /// barycenter, e.g. Pluto and Charon, or a binary star.
+    ///
+    /// For each pair the parent is offset by -m_child / (m_parent + m_child)
+    /// of the relative state, so the child is offset by the complementary
+    /// fraction. The offsets of a parent with several children add up,
+    /// and its children follow the offset parent down the tree.
+    /// Unlike in [`BodySystem::global_state`], the relative orbit of a pair
+    /// is propagated around their combined mass, μ = G(m_parent + m_child).
+    pub fn barycentric_states(
+        &self,
+        epoch: Num,
+        tolerance: Num,
+    ) -> Vec<StateVectors> {
+        let relative = self.relative_states(epoch, tolerance, true);
+        let mut offsets = vec![StateVectors::default(); self.bodies.len()];
+
+        for (b, relative) in self.bodies.iter().zip(&relative) {
+            let (Some(parent), Some(relative)) = (b.parent, relative) else {
+                continue;
+            };
+
+            let fraction = b.mass / (self.bodies[parent].mass + b.mass);
+
+            offsets[parent].position -= fraction * relative.position;
+            offsets[parent].velocity -= fraction * relative.velocity;
+        }
+
+        self.resolve_all(&relative, &offsets)
+    }
+
+    /// States relative to the parents, `None` for the roots.
+    /// With `combined_mass` the orbits are propagated around the masses
+    /// of both the parent and the body.
+    fn relative_states(
+        &self,
+        epoch: Num,
+        tolerance: Num,
+        combined_mass: bool,
+    ) -> Vec<Option<StateVectors>> {
+        self.bodies
+            .iter()
+            .map(|b| {
+                b.parent.map(|parent| {
+                    let mut mass = self.bodies[parent].mass;
+                    if combined_mass {
+                        mass += b.mass;
+                    }
+
+                    b.elements.state_vectors_at_epoch(mass, epoch, tolerance)
+                })
+            })
+            .collect()
+    }
+
+    fn resolve_all(
+        &self,
+        relative: &[Option<StateVectors>],
+        offsets: &[StateVectors],
+    ) -> Vec<StateVectors> {
+        let mut states = vec![None; self.bodies.len()];
+
+        for body in 0..self.bodies.len() {
+            self.resolve(body, &mut states, relative, offsets);
+        }
+
+        states.into_iter().map(Option::unwrap).collect()
+    }
+
+    fn resolve(
+        &self,
+        body: usize,
+        states: &mut [Option<StateVectors>],
+        relative: &[Option<StateVectors>],
+        offsets: &[StateVectors],
+    ) -> StateVectors {
+        if let Some(state) = states[body] {
+            return state;
+        }
+
+        let mut state = offsets[body];
+        if let (Some(parent), Some(to_parent)) =
+            (self.bodies[body].parent, relative[body])
+        {
+            let parent_state = self.resolve(parent, states, relative, offsets);
+
+            state.position += parent_state.position + to_parent.position;
+            state.velocity += parent_state.velocity + to_parent.velocity;
+        }
+
+        states[body] = Some(state);
+
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOLERANCE: Num = 1e-5;
+
+    fn body(name: &str, mass: Num, parent: Option<usize>, a: Num) -> Body {
+        Body {
+            name: name.to_owned(),
+            mass,
+            radius: None,
+            parent,
+            elements: KeplerianElements {
+                semi_major_axis: a,
+                eccentricity: 0.1,
+                inclination: 0.2,
+                mean_anomaly_at_epoch: 1.0,
+                ..KeplerianElements::default()
+            },
+        }
+    }
+
+    fn sun_planet_moon() -> BodySystem {
+        let mut system = BodySystem::new();
+
+        let sun = system.add_body(body("Sun", 1e12, None, 0.0)).unwrap();
+        let planet = system
+            .add_body(body("Planet", 1e9, Some(sun), 100.0))
+            .unwrap();
+        system
+            .add_body(body("Moon", 1e6, Some(planet), 2.0))
+            .unwrap();
+
+        system
+    }
+
+    #[test]
+    fn moon_is_placed_relative_to_the_planet() {
+        let system = sun_planet_moon();
+        let epoch = 12.0;
+        let mass = |i: usize| system.bodies()[i].mass;
+
+        let planet = system.global_state(1, mass, epoch, TOLERANCE);
+        let moon = system.global_state(2, mass, epoch, TOLERANCE);
+
+        let relative = system.bodies()[2]
+            .elements
+            .state_vectors_at_epoch(1e9, epoch, TOLERANCE);
+
+        assert_eq!(moon.position, planet.position + relative.position);
+        assert_eq!(moon.velocity, planet.velocity + relative.velocity);
+
+        let sun = system.global_state(0, mass, epoch, TOLERANCE);
+        assert_eq!(sun, StateVectors::default());
+
+        let all = system.all_global_states(epoch, TOLERANCE, false);
+        assert_eq!(all, vec![sun, planet, moon]);
+    }
+
+    #[test]
+    fn global_states_from_propagated_relative_states() {
+        let system = sun_planet_moon();
+        let epoch = 12.0;
+        let bodies = system.bodies();
+
+        let relative: Vec<_> = bodies
+            .iter()
+            .map(|b| {
+                b.parent.map(|parent| {
+                    b.elements.state_vectors_at_epoch(
+                        bodies[parent].mass,
+                        epoch,
+                        TOLERANCE,
+                    )
+                })
+            })
+            .collect();
+
+        assert_eq!(
+            system.global_states_from_relative(&relative),
+            system.all_global_states(epoch, TOLERANCE, false)
+        );
+    }
+
+    #[test]
+    fn cycles_are_rejected() {
+        let bodies = vec![
+            body("A", 1.0, Some(2), 1.0),
+            body("B", 1.0, Some(0), 1.0),
+            body("C", 1.0, Some(1), 1.0),
+        ];
+        assert_eq!(
+            BodySystem::from_bodies(bodies),
+            Err(SystemError::Cycle { body: 0 })
+        );
+
+        let own_parent = vec![body("A", 1.0, Some(0), 1.0)];
+        assert_eq!(
+            BodySystem::from_bodies(own_parent),
+            Err(SystemError::Cycle { body: 0 })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserializing_checks_the_parents() {
+        let system = sun_planet_moon();
+        let json = serde_json::to_string(&system).unwrap();
+        assert_eq!(serde_json::from_str::<BodySystem>(&json).unwrap(), system);
+
+        let cyclic = serde_json::to_string(&vec![
+            body("A", 1.0, Some(1), 1.0),
+            body("B", 1.0, Some(0), 1.0),
+        ])
+        .unwrap();
+        let error = serde_json::from_str::<BodySystem>(&cyclic).unwrap_err();
+        assert!(error.to_string().contains("cycle"), "{error}");
+
+        let unknown =
+            serde_json::to_string(&vec![body("A", 1.0, Some(3), 1.0)]).unwrap();
+        let error = serde_json::from_str::<BodySystem>(&unknown).unwrap_err();
+        assert!(error.to_string().contains("nonexistent"), "{error}");
+    }
+
+    #[test]
+    fn unknown_parents_are_rejected() {
+        let bodies =
+            vec![body("A", 1.0, None, 1.0), body("B", 1.0, Some(5), 1.0)];
+        assert_eq!(
+            BodySystem::from_bodies(bodies),
+            Err(SystemError::UnknownParent { body: 1, parent: 5 })
+        );
+
+        let mut system = BodySystem::new();
+        assert_eq!(
+            system.add_body(body("A", 1.0, Some(0), 1.0)),
+            Err(SystemError::UnknownParent { body: 0, parent: 0 })
+        );
+    }
+
+    #[test]
+    fn bodies_in_any_order() {
+        let bodies = vec![
+            body("Moon", 1e6, Some(2), 2.0),
+            body("Sun", 1e12, None, 0.0),
+            body("Planet", 1e9, Some(1), 100.0),
+        ];
+
+        let system = BodySystem::from_bodies(bodies).unwrap();
+        let ordered = sun_planet_moon();
+
+        let states = system.all_global_states(3.0, TOLERANCE, false);
+        let expected = ordered.all_global_states(3.0, TOLERANCE, false);
+        assert_eq!(states, vec![expected[2], expected[0], expected[1]]);
+    }
+
+    #[test]
+    fn pluto_charon_orbit_their_barycenter() {
+        let mut system = BodySystem::new();
+
+        let pluto =
+            system.add_body(body("Pluto", 1.303e22, None, 0.0)).unwrap();
+        let charon = system
+            .add_body(body("Charon", 1.586e21, Some(pluto), 19_591_000.0))
+            .unwrap();
+
+        let barycenter = |states: &[StateVectors]| {
+            let (m1, m2) = (system.bodies()[0].mass, system.bodies()[1].mass);
+            (m1 * states[pluto].position + m2 * states[charon].position)
+                / (m1 + m2)
+        };
+
+        let period = system.bodies()[charon].elements.period(1.303e22);
+        let epochs = [0.0, 0.3 * period, 0.7 * period];
+
+        for epoch in epochs {
+            let barycentric = system.barycentric_states(epoch, TOLERANCE);
+            let center = barycenter(&barycentric);
+            assert!(center.length() < 1e-6 * 19_591_000.0, "{center}");
+
+            // The pair follows the relative orbit around their total mass
+            let relative =
+                barycentric[charon].position - barycentric[pluto].position;
+            let expected = system.bodies()[charon]
+                .elements
+                .state_vectors_at_epoch(1.303e22 + 1.586e21, epoch, TOLERANCE);
+            assert!(relative.abs_diff_eq(expected.position, 1.0));
+
+            assert_eq!(
+                system.all_global_states(epoch, TOLERANCE, true),
+                barycentric
+            );
+        }
+
+        // Pluto sits still in the hierarchical layout, so the barycenter moves
+        let centers = epochs.map(|epoch| {
+            barycenter(&system.all_global_states(epoch, TOLERANCE, false))
+        });
+        assert!(centers[0].distance(centers[1]) > 1e-2 * 19_591_000.0);
+    }
+
+    #[test]
+    fn pluto_charon_period() {
+        use crate::constants::SECONDS_PER_DAY;
+
+        let mut system = BodySystem::new();
+        let pluto =
+            system.add_body(body("Pluto", 1.303e22, None, 0.0)).unwrap();
+
+        let mut charon = body("Charon", 1.586e21, Some(pluto), 19_591_000.0);
+        charon.elements.eccentricity = 0.0;
+        let charon = system.add_body(charon).unwrap();
+
+        let separation = |epoch: Num| {
+            let states = system.barycentric_states(epoch, TOLERANCE);
+            states[charon].position - states[pluto].position
+        };
+
+        // Charon's observed period of 6.387 days, around Pluto's mass alone
+        // it would take 6.76 days
+        let period = 6.387 * SECONDS_PER_DAY;
+        let drift = separation(period).distance(separation(0.0));
+        assert!(drift < 1e-2 * 19_591_000.0, "{drift}");
+    }
+}
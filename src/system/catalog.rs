@@ -0,0 +1,688 @@
+//! Loading systems from catalog files.
+//!
+//! A catalog declares the angle units once, for the whole file,
+//! and lists the bodies in any order:
+//!
+//! ```toml
+//! angle_units = "degrees"
+//!
+//! [[bodies]]
+//! name = "Kerbol"
+//! mass = 1.7565459e28
+//!
+//! [[bodies]]
+//! name = "Kerbin"
+//! mu = 3.5316e12
+//! radius = 600000.0
+//! parent = "Kerbol"
+//! elements = { semi_major_axis = 13599840256.0, eccentricity = 0.0, inclination = 0.0, right_ascension_of_the_ascending_node = 0.0, argument_of_periapsis = 0.0, mean_anomaly_at_epoch = 180.0 }
+//! ```
+//!
+//! Each body gives either its `mass` or its gravitational parameter `mu`.
+//! The epoch of the elements defaults to zero. JSON catalogs have
+//! the same structure.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::Deserialize;
+
+use super::{Body, BodySystem, SystemError};
+use crate::constants::G;
+use crate::elements::InvalidElements;
+use crate::{KeplerianElements, Num};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CatalogError {
+    /// The file isn't valid TOML or JSON, or doesn't match the format
+    Parse(String),
+    DuplicateBody {
+        name: String,
+    },
+    UnknownParent {
+        body: String,
+        parent: String,
+    },
+    /// Neither or both of `mass` and `mu` were given
+    AmbiguousMass {
+        body: String,
+    },
+    /// A body with a parent has no elements
+    MissingElements {
+        body: String,
+    },
+    InvalidElements {
+        body: String,
+        error: InvalidElements,
+    },
+    Cycle {
+        body: String,
+    },
+}
+
+impl fmt::Display for CatalogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(error) => write!(f, "Invalid catalog: {error}"),
+            Self::DuplicateBody { name } => {
+                write!(f, "Body {name} is listed more than once")
+            }
+            Self::UnknownParent { body, parent } => {
+                write!(f, "Body {body} orbits an unknown body {parent}")
+            }
+            Self::AmbiguousMass { body } => {
+                write!(f, "Body {body} needs exactly one of mass and mu")
+            }
+            Self::MissingElements { body } => {
+                write!(f, "Body {body} has a parent but no elements")
+            }
+            Self::InvalidElements {
+                body,
+                error: InvalidElements::InclinationOutOfRange,
+            } => write!(
+                f,
+                "Body {body}: {}, check the angle units of the catalog",
+                InvalidElements::InclinationOutOfRange
+            ),
+            Self::InvalidElements { body, error } => {
+                write!(f, "Body {body}: {error}")
+            }
+            Self::Cycle { body } => {
+                write!(f, "Body {body} is part of a cycle of parents")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CatalogError {}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum AngleUnits {
+    Degrees,
+    Radians,
+}
+
+impl AngleUnits {
+    fn to_radians(self, angle: Num) -> Num {
+        match self {
+            Self::Degrees => angle.to_radians(),
+            Self::Radians => angle,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Catalog {
+    angle_units: AngleUnits,
+    bodies: Vec<CatalogBody>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CatalogBody {
+    name: String,
+    mass: Option<Num>,
+    mu: Option<Num>,
+    radius: Option<Num>,
+    parent: Option<String>,
+    elements: Option<CatalogElements>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CatalogElements {
+    semi_major_axis: Num,
+    eccentricity: Num,
+    inclination: Num,
+    right_ascension_of_the_ascending_node: Num,
+    argument_of_periapsis: Num,
+    mean_anomaly_at_epoch: Num,
+    #[serde(default)]
+    epoch: Num,
+}
+
+impl BodySystem {
+    /// Loads a TOML catalog,
+    /// see the [`catalog`](crate::system::catalog) module for the format.
+    pub fn from_toml(catalog: &str) -> Result<Self, CatalogError> {
+        let catalog = toml::from_str(catalog)
+            .map_err(|error| CatalogError::Parse(error.to_string()))?;
+
+        Self::from_catalog(catalog)
+    }
+
+    /// Loads a JSON catalog,
+    /// see the [`catalog`](crate::system::catalog) module for the format.
+    pub fn from_json(catalog: &str) -> Result<Self, CatalogError> {
+        let catalog = serde_json::from_str(catalog)
+            .map_err(|error| CatalogError::Parse(error.to_string()))?;
+
+        Self::from_catalog(catalog)
+    }
+
+    fn from_catalog(catalog: Catalog) -> Result<Self, CatalogError> {
+        let mut indices = HashMap::new();
+        for (index, body) in catalog.bodies.iter().enumerate() {
+            if indices.insert(body.name.as_str(), index).is_some() {
+                return Err(CatalogError::DuplicateBody {
+                    name: body.name.clone(),
+                });
+            }
+        }
+
+        let units = catalog.angle_units;
+        let bodies = catalog
+            .bodies
+            .iter()
+            .map(|body| {
+                let name = || body.name.clone();
+
+                let mass = match (body.mass, body.mu) {
+                    (Some(mass), None) => mass,
+                    (None, Some(mu)) => mu / G,
+                    _ => {
+                        return Err(CatalogError::AmbiguousMass {
+                            body: name(),
+                        })
+                    }
+                };
+
+                let parent = body
+                    .parent
+                    .as_ref()
+                    .map(|parent| {
+                        indices.get(parent.as_str()).copied().ok_or_else(|| {
+                            CatalogError::UnknownParent {
+                                body: name(),
+                                parent: parent.clone(),
+                            }
+                        })
+                    })
+                    .transpose()?;
+
+                let elements = match (&body.elements, parent) {
+                    (Some(elements), _) => {
+                        let elements = KeplerianElements::new(
+                            elements.semi_major_axis,
+                            elements.eccentricity,
+                            units.to_radians(elements.inclination),
+                            units.to_radians(
+                                elements.right_ascension_of_the_ascending_node,
+                            ),
+                            units.to_radians(elements.argument_of_periapsis),
+                            units.to_radians(elements.mean_anomaly_at_epoch),
+                            elements.epoch,
+                        );
+
+                        elements.validate().map_err(|error| {
+                            CatalogError::InvalidElements {
+                                body: name(),
+                                error,
+                            }
+                        })?;
+
+                        elements
+                    }
+                    (None, Some(_)) => {
+                        return Err(CatalogError::MissingElements {
+                            body: name(),
+                        })
+                    }
+                    (None, None) => KeplerianElements::default(),
+                };
+
+                Ok(Body {
+                    name: name(),
+                    mass,
+                    radius: body.radius,
+                    parent,
+                    elements,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::from_bodies(bodies).map_err(|error| match error {
+            SystemError::Cycle { body } => CatalogError::Cycle {
+                body: catalog.bodies[body].name.clone(),
+            },
+            // Parents were already resolved by name
+            SystemError::UnknownParent { .. } => unreachable!(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::AU;
+
+    const TOLERANCE: Num = 1e-5;
+
+    fn assert_same_states(a: &BodySystem, b: &BodySystem) {
+        for epoch in [0.0, 1e5, 3e7] {
+            let a = a.all_global_states(epoch, TOLERANCE, false);
+            let b = b.all_global_states(epoch, TOLERANCE, false);
+
+            for (a, b) in a.iter().zip(&b) {
+                let scale = a.position.length().max(1.0);
+                assert!(a.position.abs_diff_eq(b.position, 1e-5 * scale));
+                let scale = a.velocity.length().max(1.0);
+                assert!(a.velocity.abs_diff_eq(b.velocity, 1e-5 * scale));
+            }
+            assert_eq!(a.len(), b.len());
+        }
+    }
+
+    fn body(
+        name: &str,
+        mass: Num,
+        radius: Num,
+        parent: Option<usize>,
+        elements: [Num; 6],
+    ) -> Body {
+        let [a, e, i, Ω, ω, M] = elements;
+
+        Body {
+            name: name.to_owned(),
+            mass,
+            radius: Some(radius),
+            parent,
+            elements: KeplerianElements::new(a, e, i, Ω, ω, M, 0.0),
+        }
+    }
+
+    /// The stock Kerbal Space Program system, in SI units
+    fn kerbol() -> BodySystem {
+        let deg = |x: Num| x.to_radians();
+        let mass = |mu: Num| mu / G;
+
+        let bodies = vec![
+            Body {
+                name: "Kerbol".to_owned(),
+                mass: 1.756_546e28,
+                radius: Some(261_600_000.0),
+                parent: None,
+                elements: KeplerianElements::default(),
+            },
+            body(
+                "Moho",
+                mass(1.686_093_8e11),
+                250_000.0,
+                Some(0),
+                [
+                    5_263_138_304.0,
+                    0.2,
+                    deg(7.0),
+                    deg(70.0),
+                    deg(15.0),
+                    deg(180.0),
+                ],
+            ),
+            body(
+                "Kerbin",
+                mass(3.5316e12),
+                600_000.0,
+                Some(0),
+                [13_599_840_256.0, 0.0, 0.0, 0.0, 0.0, deg(180.0)],
+            ),
+            body(
+                "Mun",
+                mass(6.513_84e10),
+                200_000.0,
+                Some(2),
+                [12_000_000.0, 0.0, 0.0, 0.0, 0.0, deg(97.4)],
+            ),
+            body(
+                "Minmus",
+                mass(1.7658e9),
+                60_000.0,
+                Some(2),
+                [47_000_000.0, 0.0, deg(6.0), deg(78.0), deg(38.0), deg(51.6)],
+            ),
+            body(
+                "Duna",
+                mass(3.013_632e11),
+                320_000.0,
+                Some(0),
+                [
+                    20_726_155_264.0,
+                    0.051,
+                    deg(0.06),
+                    deg(135.5),
+                    0.0,
+                    deg(180.0),
+                ],
+            ),
+            body(
+                "Ike",
+                mass(1.856_836_9e10),
+                130_000.0,
+                Some(5),
+                [3_200_000.0, 0.03, deg(0.2), 0.0, 0.0, deg(97.4)],
+            ),
+            body(
+                "Eve",
+                mass(8.171_73e12),
+                700_000.0,
+                Some(0),
+                [9_832_684_544.0, 0.01, deg(2.1), deg(15.0), 0.0, deg(180.0)],
+            ),
+            body(
+                "Dres",
+                mass(2.148_448_9e10),
+                138_000.0,
+                Some(0),
+                [
+                    40_839_348_203.0,
+                    0.145,
+                    deg(5.0),
+                    deg(280.0),
+                    deg(90.0),
+                    deg(180.0),
+                ],
+            ),
+            body(
+                "Jool",
+                mass(2.825_28e14),
+                6_000_000.0,
+                Some(0),
+                [68_773_560_320.0, 0.05, deg(1.304), deg(52.0), 0.0, 0.1],
+            ),
+            body(
+                "Eeloo",
+                mass(7.441_081e10),
+                210_000.0,
+                Some(0),
+                [
+                    90_118_820_000.0,
+                    0.26,
+                    deg(6.15),
+                    deg(50.0),
+                    deg(260.0),
+                    deg(180.0),
+                ],
+            ),
+        ];
+
+        BodySystem::from_bodies(bodies).unwrap()
+    }
+
+    /// The solar system of the orbit viewer example, in SI units
+    fn solar_system() -> BodySystem {
+        let deg = |x: Num| x.to_radians();
+        // The astronomical unit is in km
+        let au = AU * 1e3;
+
+        let mut system = BodySystem::new();
+        let sun = system
+            .add_body(Body {
+                name: "Sun".to_owned(),
+                mass: 1.98847e30,
+                radius: Some(695_700_000.0),
+                parent: None,
+                elements: KeplerianElements::default(),
+            })
+            .unwrap();
+
+        let planets = [
+            (
+                "Mercury",
+                3.3011e23,
+                2_439_700.0,
+                [0.38709927, 0.20563593, 0.12, 0.84, 1.35, 4.40],
+            ),
+            (
+                "Venus",
+                4.8675e24,
+                6_051_800.0,
+                [0.7233, 0.00676, 0.0593, 1.34, 2.30, 3.17],
+            ),
+            (
+                "Earth",
+                5.97237e24,
+                6_371_000.0,
+                [1.0, 0.01673, 0.01, 0.0, 1.796_467_4, 0.0],
+            ),
+            (
+                "Mars",
+                6.4171e23,
+                3_389_500.0,
+                [
+                    1.523_710_4,
+                    0.0933941,
+                    0.032_323_5,
+                    0.867_603_2,
+                    5.865_702_5,
+                    6.203_424,
+                ],
+            ),
+            (
+                "Jupiter",
+                1.8982e27,
+                69_911_000.0,
+                [
+                    5.2025,
+                    0.04854,
+                    0.022_671_8,
+                    1.750_390_7,
+                    0.249_058_5,
+                    0.599_171_5,
+                ],
+            ),
+            (
+                "Saturn",
+                5.6834e26,
+                58_232_000.0,
+                [
+                    9.5415,
+                    0.05551,
+                    0.043_528_5,
+                    1.983_392_2,
+                    1.620_712_7,
+                    0.874_060_9,
+                ],
+            ),
+            (
+                "Uranus",
+                8.6810e25,
+                25_362_000.0,
+                [
+                    19.188,
+                    0.04686,
+                    0.013_491_4,
+                    1.290_845_5,
+                    3.009_471_2,
+                    5.483_824_5,
+                ],
+            ),
+            (
+                "Neptune",
+                1.02413e26,
+                24_622_000.0,
+                [
+                    30.070,
+                    0.00895,
+                    0.030_892_3,
+                    2.300_169_4,
+                    0.814_719_7,
+                    5.309_641,
+                ],
+            ),
+        ];
+
+        for (name, mass, radius, [a, e, i, Ω, ω, M]) in planets {
+            system
+                .add_body(body(
+                    name,
+                    mass,
+                    radius,
+                    Some(sun),
+                    [a * au, e, i, Ω, ω, M],
+                ))
+                .unwrap();
+        }
+
+        let moons = [
+            (
+                "Moon",
+                "Earth",
+                7.342e22,
+                1_737_400.0,
+                [384_399_000.0, 0.0549, 5.145, 125.08, 318.15, 2.36],
+            ),
+            (
+                "Io",
+                "Jupiter",
+                8.931_938e22,
+                1_821_600.0,
+                [421_700_000.0, 0.0041, 2.2, 337.0, 84.1, 5.99],
+            ),
+            (
+                "Europa",
+                "Jupiter",
+                4.799_844e22,
+                1_560_800.0,
+                [670_900_000.0, 0.009, 2.5, 337.0, 88.97, 2.25],
+            ),
+            (
+                "Ganymede",
+                "Jupiter",
+                1.4819e23,
+                2_634_100.0,
+                [1_070_400_000.0, 0.0013, 2.2, 337.0, 192.4, 5.76],
+            ),
+            (
+                "Callisto",
+                "Jupiter",
+                1.075_938e23,
+                2_410_300.0,
+                [1_882_700_000.0, 0.0074, 2.0, 337.0, 52.64, 3.36],
+            ),
+            (
+                "Titan",
+                "Saturn",
+                1.3452e23,
+                2_574_730.0,
+                [1_221_870_000.0, 0.0288, 27.1, 169.5, 185.7, 0.28],
+            ),
+        ];
+
+        for (name, parent, mass, radius, [a, e, i, Ω, ω, M]) in moons {
+            let parent = system.index_of(parent);
+            system
+                .add_body(body(
+                    name,
+                    mass,
+                    radius,
+                    parent,
+                    [a, e, deg(i), deg(Ω), deg(ω), M],
+                ))
+                .unwrap();
+        }
+
+        system
+    }
+
+    #[test]
+    fn kerbol_catalog() {
+        let loaded = BodySystem::from_toml(include_str!(
+            "../../tests/fixtures/kerbol.toml"
+        ))
+        .unwrap();
+
+        assert_same_states(&loaded, &kerbol());
+
+        let mun = loaded.index_of("Mun").unwrap();
+        assert_eq!(loaded.bodies()[mun].parent, loaded.index_of("Kerbin"));
+        assert_eq!(loaded.bodies()[mun].radius, Some(200_000.0));
+    }
+
+    #[test]
+    fn solar_system_catalog() {
+        let loaded = BodySystem::from_json(include_str!(
+            "../../tests/fixtures/solar_system.json"
+        ))
+        .unwrap();
+
+        assert_same_states(&loaded, &solar_system());
+    }
+
+    #[test]
+    fn descriptive_errors() {
+        let catalog =
+            |bodies: &str| format!("angle_units = \"radians\"\n{bodies}");
+        let elements = |inclination: Num| {
+            format!(
+                "elements = {{ semi_major_axis = 1.0, eccentricity = 0.0, \
+                 inclination = {inclination:?}, \
+                 right_ascension_of_the_ascending_node = 0.0, \
+                 argument_of_periapsis = 0.0, mean_anomaly_at_epoch = 0.0 }}"
+            )
+        };
+        let body = |name: &str, parent: &str, inclination: Num| {
+            format!(
+                "[[bodies]]\nname = \"{name}\"\nmass = 1.0\n\
+                 parent = \"{parent}\"\n{}\n",
+                elements(inclination)
+            )
+        };
+        let root = "[[bodies]]\nname = \"Sun\"\nmass = 1.0\n";
+
+        let err = |toml: String| BodySystem::from_toml(&toml).unwrap_err();
+
+        assert_eq!(
+            err(catalog(&format!("{root}{}", body("Earth", "Vulcan", 0.1)))),
+            CatalogError::UnknownParent {
+                body: "Earth".to_owned(),
+                parent: "Vulcan".to_owned(),
+            }
+        );
+        assert_eq!(
+            err(catalog(&format!(
+                "{root}{}{}",
+                body("Earth", "Sun", 0.1),
+                body("Earth", "Sun", 0.2)
+            ))),
+            CatalogError::DuplicateBody {
+                name: "Earth".to_owned()
+            }
+        );
+
+        // An inclination given in degrees in a radians catalog
+        let error =
+            err(catalog(&format!("{root}{}", body("Earth", "Sun", 23.4))));
+        assert_eq!(
+            error,
+            CatalogError::InvalidElements {
+                body: "Earth".to_owned(),
+                error: InvalidElements::InclinationOutOfRange,
+            }
+        );
+        assert!(error.to_string().contains("Earth"));
+        assert!(error.to_string().contains("angle units"));
+
+        assert_eq!(
+            err(catalog(&format!(
+                "{}{}",
+                body("A", "B", 0.1),
+                body("B", "A", 0.1)
+            ))),
+            CatalogError::Cycle {
+                body: "A".to_owned()
+            }
+        );
+
+        assert!(matches!(
+            err("bodies = []".to_owned()),
+            CatalogError::Parse(_)
+        ));
+        assert!(matches!(
+            err(catalog(
+                "[[bodies]]\nname = \"Sun\"\nmass = 1.0\nmu = 1.0\n"
+            )),
+            CatalogError::AmbiguousMass { .. }
+        ));
+    }
+}
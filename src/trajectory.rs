@@ -0,0 +1,810 @@
+//! Patched conics: a trajectory through a [`BodySystem`] as a chain
+//! of Kepler orbits, each around the body whose sphere of influence
+//! it's in.
+//!
+//! Every segment is sampled with an adaptive step to find where it leaves
+//! the sphere of influence of its parent, enters the one of a child or
+//! hits the surface. The crossings are bisected and the state is handed
+//! over to the frame of the next parent.
+//!
+//! [`ManeuverNode`]s split the trajectory at their epochs, the next
+//! segment continues on the conic after the burn.
+
+use crate::astro::soi;
+use crate::math::golden_section_search;
+use crate::system::BodySystem;
+use crate::{vec3, KeplerianElements, Num, StateVectors, Vec3};
+
+/// Step as a fraction of r / v, the time to move by the current radius
+const STEP_FRACTION: Num = 0.05;
+
+/// Smallest step as a fraction of r / v, so a trajectory grazing
+/// a sphere of influence doesn't stall
+const MIN_STEP_FRACTION: Num = 1e-4;
+
+/// Number of bisection steps locating a crossing between samples
+const BISECTION_STEPS: usize = 60;
+
+/// Number of golden section steps refining a closest approach
+/// between samples
+const CLOSEST_APPROACH_STEPS: usize = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrajectorySettings {
+    /// The trajectory ends after this many segments
+    pub max_segments: usize,
+    /// Longest segment that doesn't loop, e.g. an escape from a root body,
+    /// in seconds
+    pub max_segment_duration: Num,
+    /// Tolerance of the Kepler equation
+    pub tolerance: Num,
+}
+
+impl Default for TrajectorySettings {
+    fn default() -> Self {
+        Self {
+            max_segments: 8,
+            max_segment_duration: 10.0
+                * crate::constants::SECONDS_PER_JULIAN_YEAR,
+            tolerance: 1e-5,
+        }
+    }
+}
+
+/// What ends a [`TrajectorySegment`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SegmentEnd {
+    /// A full revolution without leaving the sphere of influence
+    Loop,
+    /// Leaving the sphere of influence of the parent into its own parent
+    SoiExit,
+    /// Entering the sphere of influence of the given child of the parent
+    SoiEntry(usize),
+    /// Hitting the surface of the parent
+    Impact,
+    /// Reaching [`TrajectorySettings::max_segment_duration`]
+    DurationLimit,
+    /// The burn of the node with the given index
+    Maneuver(usize),
+}
+
+/// Impulsive burn at an epoch, in the RTN frame of the state at the burn,
+/// see [`StateVectors::rtn_matrix`]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ManeuverNode {
+    pub epoch: Num,
+    /// Along the transverse axis, the direction of motion
+    /// on a circular orbit
+    pub prograde: Num,
+    /// Along the angular momentum
+    pub normal: Num,
+    /// Away from the parent
+    pub radial: Num,
+}
+
+impl ManeuverNode {
+    pub fn delta_v_rtn(&self) -> Vec3 {
+        vec3(self.radial, self.prograde, self.normal)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrajectorySegment {
+    /// Index of the body the segment orbits
+    pub parent: usize,
+    /// Orbit relative to the parent, with the epoch at the start
+    pub elements: KeplerianElements,
+    pub start_epoch: Num,
+    pub end_epoch: Num,
+    pub end: SegmentEnd,
+}
+
+impl TrajectorySegment {
+    /// State relative to the parent
+    pub fn state_at(
+        &self,
+        system: &BodySystem,
+        epoch: Num,
+        tolerance: Num,
+    ) -> StateVectors {
+        let mass = system.bodies()[self.parent].mass;
+
+        self.elements.state_vectors_at_epoch(mass, epoch, tolerance)
+    }
+}
+
+/// Chain of segments starting with `state` relative to the `parent` body
+/// at `epoch`.
+///
+/// The chain ends with a loop, an impact, a segment reaching
+/// the duration limit or after the maximum number of segments.
+///
+/// # Panics
+/// If the parent doesn't exist.
+pub fn recalculate(
+    system: &BodySystem,
+    parent: usize,
+    state: StateVectors,
+    epoch: Num,
+    settings: &TrajectorySettings,
+) -> Vec<TrajectorySegment> {
+    recalculate_with_maneuvers(system, parent, state, epoch, &[], settings)
+}
+
+/// Same as [`recalculate`], with the burns of the `nodes` applied
+/// in chronological order.
+///
+/// A segment ending with a burn ends with [`SegmentEnd::Maneuver`], even
+/// if the orbit closes before, so a loop doesn't end the chain while
+/// burns are pending. Nodes before `epoch` and nodes after the end
+/// of the chain are ignored.
+pub fn recalculate_with_maneuvers(
+    system: &BodySystem,
+    parent: usize,
+    state: StateVectors,
+    epoch: Num,
+    nodes: &[ManeuverNode],
+    settings: &TrajectorySettings,
+) -> Vec<TrajectorySegment> {
+    let tolerance = settings.tolerance;
+    let bodies = system.bodies();
+
+    let mut pending: Vec<usize> = (0..nodes.len())
+        .filter(|&n| nodes[n].epoch >= epoch)
+        .collect();
+    pending.sort_by(|&a, &b| nodes[b].epoch.total_cmp(&nodes[a].epoch));
+
+    let (mut parent, mut state, mut epoch) = (parent, state, epoch);
+    let mut segments = Vec::new();
+
+    while segments.len() < settings.max_segments {
+        let next_node = pending.last().map(|&n| (nodes[n].epoch, n));
+        let segment = propagate_segment(
+            system, parent, state, epoch, next_node, settings,
+        );
+        segments.push(segment);
+
+        let end = segment.state_at(system, segment.end_epoch, tolerance);
+        epoch = segment.end_epoch;
+
+        match segment.end {
+            SegmentEnd::SoiExit => {
+                let Some(grandparent) = bodies[parent].parent else {
+                    break;
+                };
+                let offset = body_state(system, parent, epoch, tolerance);
+
+                state = StateVectors::new(
+                    end.position + offset.position,
+                    end.velocity + offset.velocity,
+                );
+                parent = grandparent;
+            }
+            SegmentEnd::SoiEntry(child) => {
+                let offset = body_state(system, child, epoch, tolerance);
+
+                state = StateVectors::new(
+                    end.position - offset.position,
+                    end.velocity - offset.velocity,
+                );
+                parent = child;
+            }
+            SegmentEnd::Maneuver(node) => {
+                pending.pop();
+                state = end.apply_delta_v_rtn(nodes[node].delta_v_rtn());
+            }
+            SegmentEnd::Loop
+            | SegmentEnd::Impact
+            | SegmentEnd::DurationLimit => break,
+        }
+    }
+
+    segments
+}
+
+/// Epochs every `interval` of flight time after the start of the chain,
+/// with the index of the segment each falls on.
+///
+/// A tick at a handoff falls on the segment ending there. The interval
+/// is doubled until there are at most `max_ticks` ticks, so the ticks stay
+/// readable however long the chain is.
+pub fn ticks(
+    segments: &[TrajectorySegment],
+    interval: Num,
+    max_ticks: usize,
+) -> Vec<(usize, Num)> {
+    let (Some(first), Some(last)) = (segments.first(), segments.last()) else {
+        return Vec::new();
+    };
+    if interval.is_nan() || interval <= 0.0 || max_ticks == 0 {
+        return Vec::new();
+    }
+
+    let span = last.end_epoch - first.start_epoch;
+    let mut interval = interval;
+    while span / interval > max_ticks as Num {
+        interval *= 2.0;
+    }
+
+    let mut ticks = Vec::new();
+    let mut segment = 0;
+    for k in 1..=max_ticks {
+        let epoch = first.start_epoch + k as Num * interval;
+        if epoch > last.end_epoch {
+            break;
+        }
+        while epoch > segments[segment].end_epoch {
+            segment += 1;
+        }
+        ticks.push((segment, epoch));
+    }
+
+    ticks
+}
+
+/// Closest approach of a trajectory to a body
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClosestApproach {
+    pub epoch: Num,
+    /// Index of the segment
+    pub segment: usize,
+    pub distance: Num,
+    pub relative_speed: Num,
+}
+
+/// Closest approach to the `target` body over the span of the chain.
+///
+/// Every segment is scanned at `samples` steps, the minimum is refined
+/// with a golden section search between the neighbouring samples.
+///
+/// # Panics
+/// If the target doesn't exist.
+pub fn closest_approach(
+    system: &BodySystem,
+    segments: &[TrajectorySegment],
+    target: usize,
+    samples: usize,
+    tolerance: Num,
+) -> Option<ClosestApproach> {
+    let mass_lookup = |body: usize| system.bodies()[body].mass;
+    let relative_state = |segment: &TrajectorySegment, epoch| {
+        let parent =
+            system.global_state(segment.parent, mass_lookup, epoch, tolerance);
+        let target = system.global_state(target, mass_lookup, epoch, tolerance);
+        let state = segment.state_at(system, epoch, tolerance);
+
+        StateVectors::new(
+            parent.position + state.position - target.position,
+            parent.velocity + state.velocity - target.velocity,
+        )
+    };
+
+    let samples = samples.max(2);
+
+    let approaches = segments.iter().enumerate().map(|(index, segment)| {
+        let duration = segment.end_epoch - segment.start_epoch;
+        let step = duration / samples as Num;
+        let distance = |epoch| relative_state(segment, epoch).position.length();
+
+        let (nearest, _) = (0..=samples)
+            .map(|k| (k, distance(segment.start_epoch + k as Num * step)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap();
+
+        let low = segment.start_epoch + nearest.saturating_sub(1) as Num * step;
+        let high =
+            segment.start_epoch + (nearest + 1).min(samples) as Num * step;
+        let epoch =
+            golden_section_search(distance, low, high, CLOSEST_APPROACH_STEPS);
+
+        let state = relative_state(segment, epoch);
+        ClosestApproach {
+            epoch,
+            segment: index,
+            distance: state.position.length(),
+            relative_speed: state.velocity.length(),
+        }
+    });
+
+    // The first of equally close approaches
+    approaches.min_by(|a, b| a.distance.total_cmp(&b.distance))
+}
+
+/// Radius of the sphere of influence of the body, infinite for a root
+pub fn soi_radius(system: &BodySystem, body: usize) -> Num {
+    let bodies = system.bodies();
+
+    match bodies[body].parent {
+        Some(parent) => soi(
+            bodies[body].elements.semi_major_axis,
+            bodies[body].mass,
+            bodies[parent].mass,
+        ),
+        None => Num::INFINITY,
+    }
+}
+
+/// State of a body relative to its parent
+fn body_state(
+    system: &BodySystem,
+    body: usize,
+    epoch: Num,
+    tolerance: Num,
+) -> StateVectors {
+    let bodies = system.bodies();
+    let Some(parent) = bodies[body].parent else {
+        return StateVectors::default();
+    };
+
+    bodies[body].elements.state_vectors_at_epoch(
+        bodies[parent].mass,
+        epoch,
+        tolerance,
+    )
+}
+
+fn propagate_segment(
+    system: &BodySystem,
+    parent: usize,
+    state: StateVectors,
+    epoch: Num,
+    next_node: Option<(Num, usize)>,
+    settings: &TrajectorySettings,
+) -> TrajectorySegment {
+    let tolerance = settings.tolerance;
+    let bodies = system.bodies();
+    let mass = bodies[parent].mass;
+
+    let elements = state.to_elements(mass, epoch);
+    let state_at = |t: Num| elements.state_vectors_at_epoch(mass, t, tolerance);
+
+    let exit_radius = soi_radius(system, parent);
+    let surface = bodies[parent].radius.unwrap_or(0.0);
+    let children: Vec<(usize, Num)> = (0..bodies.len())
+        .filter(|&b| bodies[b].parent == Some(parent))
+        .map(|b| (b, soi_radius(system, b)))
+        .collect();
+
+    // Positive until the event, the surface is index 0, the exit 1
+    // and the entries follow in the order of the children
+    let events = |t: Num, sv: &StateVectors| {
+        let r = sv.position.length();
+
+        let mut values = vec![r - surface, exit_radius - r];
+        values.extend(children.iter().map(|&(child, soi)| {
+            let child = body_state(system, child, t, tolerance);
+            sv.position.distance(child.position) - soi
+        }));
+
+        values
+    };
+    let end_of = |event: usize| match event {
+        0 => SegmentEnd::Impact,
+        1 => SegmentEnd::SoiExit,
+        k => SegmentEnd::SoiEntry(children[k - 2].0),
+    };
+
+    let period = elements.period(mass);
+    let (mut duration, mut limit_end) =
+        if elements.is_hyperbolic() || period > settings.max_segment_duration {
+            (settings.max_segment_duration, SegmentEnd::DurationLimit)
+        } else {
+            (period, SegmentEnd::Loop)
+        };
+    if let Some((node_epoch, node)) = next_node {
+        if limit_end == SegmentEnd::Loop || node_epoch - epoch < duration {
+            duration = node_epoch - epoch;
+            limit_end = SegmentEnd::Maneuver(node);
+        }
+    }
+    let last = epoch + duration;
+
+    let segment = |end_epoch: Num, end: SegmentEnd| TrajectorySegment {
+        parent,
+        elements,
+        start_epoch: epoch,
+        end_epoch,
+        end,
+    };
+
+    let mut t0 = epoch;
+    let mut sv = state;
+    let mut values = events(t0, &sv);
+
+    while t0 < last {
+        let turning_time = sv.position.length() / sv.velocity.length();
+        let mut step = STEP_FRACTION * turning_time;
+
+        // Don't step over a sphere of influence ahead
+        for (k, &(child, _)) in children.iter().enumerate() {
+            let gap = values[k + 2];
+            let child = body_state(system, child, t0, tolerance);
+            let closing = sv.velocity.distance(child.velocity);
+
+            if gap > 0.0 {
+                step = step.min(0.5 * gap / closing);
+            }
+        }
+        step = step.max(MIN_STEP_FRACTION * turning_time);
+
+        let t1 = (t0 + step).min(last);
+        let next_sv = state_at(t1);
+        let next = events(t1, &next_sv);
+
+        let first = (0..values.len())
+            .filter(|&k| values[k] > 0.0 && next[k] <= 0.0)
+            .map(|k| {
+                let (mut lo, mut hi) = (t0, t1);
+                for _ in 0..BISECTION_STEPS {
+                    let mid = 0.5 * (lo + hi);
+
+                    if events(mid, &state_at(mid))[k] <= 0.0 {
+                        hi = mid;
+                    } else {
+                        lo = mid;
+                    }
+                }
+
+                (hi, k)
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0));
+
+        if let Some((end_epoch, event)) = first {
+            return segment(end_epoch, end_of(event));
+        }
+
+        t0 = t1;
+        sv = next_sv;
+        values = next;
+    }
+
+    segment(last, limit_end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::astro::lambert;
+    use crate::constants::G;
+    use crate::system::Body;
+    use crate::Vec3;
+
+    const TOLERANCE: Num = 1e-5;
+
+    fn kerbol() -> BodySystem {
+        let body = |name: &str, mu: Num, radius, parent, a, M: Num| Body {
+            name: name.to_owned(),
+            mass: mu / G,
+            radius: Some(radius),
+            parent,
+            elements: KeplerianElements {
+                semi_major_axis: a,
+                mean_anomaly_at_epoch: M.to_radians(),
+                ..KeplerianElements::default()
+            },
+        };
+
+        BodySystem::from_bodies(vec![
+            body("Kerbol", 1.172_332_8e18, 261_600_000.0, None, 0.0, 0.0),
+            body(
+                "Kerbin",
+                3.5316e12,
+                600_000.0,
+                Some(0),
+                13_599_840_256.0,
+                180.0,
+            ),
+            body("Mun", 6.513_84e10, 200_000.0, Some(1), 12_000_000.0, 97.4),
+            body(
+                "Duna",
+                3.013_632e11,
+                320_000.0,
+                Some(0),
+                20_726_155_264.0,
+                180.0,
+            ),
+        ])
+        .unwrap()
+    }
+
+    fn global_position(
+        system: &BodySystem,
+        segment: &TrajectorySegment,
+        epoch: Num,
+    ) -> Vec3 {
+        let mass = |b: usize| system.bodies()[b].mass;
+        let parent =
+            system.global_state(segment.parent, mass, epoch, TOLERANCE);
+
+        parent.position + segment.state_at(system, epoch, TOLERANCE).position
+    }
+
+    #[test]
+    fn escape_from_kerbin() {
+        let system = kerbol();
+        let kerbin = system.index_of("Kerbin").unwrap();
+
+        // Low orbit, well above the escape speed
+        let state = StateVectors::new(
+            Vec3::new(0.0, 700_000.0, 0.0),
+            Vec3::new(4_000.0, 0.0, 0.0),
+        );
+        let segments = recalculate(
+            &system,
+            kerbin,
+            state,
+            0.0,
+            &TrajectorySettings::default(),
+        );
+
+        let parents: Vec<usize> = segments.iter().map(|s| s.parent).collect();
+        assert_eq!(parents[..2], [kerbin, 0], "{segments:?}");
+        assert_eq!(segments[0].end, SegmentEnd::SoiExit);
+
+        // Continuous at the handoff
+        let epoch = segments[0].end_epoch;
+        assert_eq!(segments[1].start_epoch, epoch);
+        let before = global_position(&system, &segments[0], epoch);
+        let after = global_position(&system, &segments[1], epoch);
+        assert!(before.distance(after) < 1e-5 * before.length());
+
+        let exit = segments[0].state_at(&system, epoch, TOLERANCE);
+        let soi = soi_radius(&system, kerbin);
+        assert!((exit.position.length() / soi - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn transfer_captured_by_duna() {
+        let system = kerbol();
+        let duna = system.index_of("Duna").unwrap();
+        let sun_mass = system.bodies()[0].mass;
+
+        // On the far side of Kerbin's orbit, aimed at Duna
+        let start = Vec3::new(13_599_840_256.0, 0.0, 0.0);
+        let time_of_flight = 4_000_000.0;
+        let target = system.bodies()[duna].elements.state_vectors_at_epoch(
+            sun_mass,
+            time_of_flight,
+            TOLERANCE,
+        );
+        let (departure, _) = lambert::solve(
+            start,
+            target.position,
+            time_of_flight,
+            sun_mass,
+            Vec3::Z,
+        )
+        .unwrap();
+
+        let segments = recalculate(
+            &system,
+            0,
+            departure,
+            0.0,
+            &TrajectorySettings::default(),
+        );
+
+        assert_eq!(segments[0].end, SegmentEnd::SoiEntry(duna));
+        assert_eq!(segments[1].parent, duna, "{segments:?}");
+
+        let entry = segments[0].end_epoch;
+        assert!(entry < time_of_flight);
+        let distance = segments[1]
+            .state_at(&system, entry, TOLERANCE)
+            .position
+            .length();
+        assert!(
+            (distance / soi_radius(&system, duna) - 1.0).abs() < 1e-3,
+            "{distance} {}",
+            soi_radius(&system, duna)
+        );
+    }
+
+    #[test]
+    fn closed_orbit_loops() {
+        let system = kerbol();
+        let kerbin = system.index_of("Kerbin").unwrap();
+
+        // Below the Mun
+        let μ = 3.5316e12;
+        let r = 1_000_000.0;
+        let state = StateVectors::new(
+            Vec3::new(r, 0.0, 0.0),
+            Vec3::new(0.0, (μ / r as Num).sqrt(), 0.0),
+        );
+
+        let segments = recalculate(
+            &system,
+            kerbin,
+            state,
+            0.0,
+            &TrajectorySettings::default(),
+        );
+
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].end, SegmentEnd::Loop);
+    }
+
+    #[test]
+    fn prograde_burn_raises_the_apoapsis() {
+        let system = kerbol();
+        let kerbin = system.index_of("Kerbin").unwrap();
+        let μ = 3.5316e12;
+
+        let r = 1_000_000.0;
+        let state = StateVectors::new(
+            Vec3::new(r, 0.0, 0.0),
+            Vec3::new(0.0, (μ / r as Num).sqrt(), 0.0),
+        );
+        let period = crate::astro::period(r, system.bodies()[kerbin].mass);
+
+        let apoapsis = |prograde: Num| {
+            let nodes = [
+                // Ignored, before the start
+                ManeuverNode {
+                    epoch: -1.0,
+                    prograde: 1_000.0,
+                    ..ManeuverNode::default()
+                },
+                ManeuverNode {
+                    epoch: 0.5 * period,
+                    prograde,
+                    ..ManeuverNode::default()
+                },
+            ];
+            let segments = recalculate_with_maneuvers(
+                &system,
+                kerbin,
+                state,
+                0.0,
+                &nodes,
+                &TrajectorySettings::default(),
+            );
+
+            assert_eq!(segments.len(), 2, "{segments:?}");
+            assert_eq!(segments[0].end, SegmentEnd::Maneuver(1));
+            assert_eq!(segments[1].start_epoch, 0.5 * period);
+            assert_eq!(segments[1].end, SegmentEnd::Loop);
+
+            let orbit = segments[1].elements;
+            orbit.semi_major_axis * (1.0 + orbit.eccentricity)
+        };
+
+        // Vis-viva, the burn point becomes the periapsis
+        let v = (μ / r).sqrt() + 100.0;
+        let a = 1.0 / (2.0 / r - v * v / μ);
+        let expected = 2.0 * a - r;
+
+        let raised = apoapsis(100.0);
+        assert!(
+            (raised / expected - 1.0).abs() < 1e-3,
+            "{raised} {expected}"
+        );
+        assert!(apoapsis(150.0) > raised);
+    }
+
+    #[test]
+    fn burns_apply_in_chronological_order() {
+        let system = kerbol();
+        let kerbin = system.index_of("Kerbin").unwrap();
+        let μ = 3.5316e12;
+
+        let r = 1_000_000.0;
+        let state = StateVectors::new(
+            Vec3::new(r, 0.0, 0.0),
+            Vec3::new(0.0, (μ / r as Num).sqrt(), 0.0),
+        );
+
+        let burn = |epoch, normal| ManeuverNode {
+            epoch,
+            normal,
+            ..ManeuverNode::default()
+        };
+        let segments = recalculate_with_maneuvers(
+            &system,
+            kerbin,
+            state,
+            0.0,
+            &[burn(2_000.0, -50.0), burn(1_000.0, 50.0)],
+            &TrajectorySettings::default(),
+        );
+
+        let ends: Vec<SegmentEnd> = segments.iter().map(|s| s.end).collect();
+        assert_eq!(
+            ends,
+            [
+                SegmentEnd::Maneuver(1),
+                SegmentEnd::Maneuver(0),
+                SegmentEnd::Loop
+            ]
+        );
+        assert!(segments[1].elements.inclination > 0.01);
+    }
+
+    #[test]
+    fn ticks_follow_the_segments() {
+        let segment = |start_epoch, end_epoch| TrajectorySegment {
+            parent: 0,
+            elements: KeplerianElements::default(),
+            start_epoch,
+            end_epoch,
+            end: SegmentEnd::SoiExit,
+        };
+        let segments = [segment(10.0, 30.0), segment(30.0, 45.0)];
+
+        assert_eq!(
+            ticks(&segments, 10.0, 100),
+            [(0, 20.0), (0, 30.0), (1, 40.0)]
+        );
+        assert_eq!(ticks(&segments, 5.0, 3), [(0, 30.0)]);
+        assert!(ticks(&segments, 0.0, 100).is_empty());
+        assert!(ticks(&[], 10.0, 100).is_empty());
+    }
+
+    #[test]
+    fn closest_approach_to_the_parent() {
+        let system = kerbol();
+        let kerbin = system.index_of("Kerbin").unwrap();
+        let μ = 3.5316e12;
+
+        let r = 1_000_000.0;
+        let v = (μ / r as Num).sqrt();
+        let state =
+            StateVectors::new(Vec3::new(r, 0.0, 0.0), Vec3::new(0.0, v, 0.0));
+        let segments = recalculate(
+            &system,
+            kerbin,
+            state,
+            0.0,
+            &TrajectorySettings::default(),
+        );
+
+        let approach =
+            closest_approach(&system, &segments, kerbin, 50, TOLERANCE)
+                .unwrap();
+        assert!((approach.distance / r - 1.0).abs() < 1e-3);
+        assert!((approach.relative_speed / v - 1.0).abs() < 1e-3);
+        assert!(closest_approach(&system, &[], kerbin, 50, TOLERANCE).is_none());
+    }
+
+    #[test]
+    fn closest_approach_of_a_transfer() {
+        let system = kerbol();
+        let duna = system.index_of("Duna").unwrap();
+        let sun_mass = system.bodies()[0].mass;
+
+        let start = Vec3::new(13_599_840_256.0, 0.0, 0.0);
+        let time_of_flight = 4_000_000.0;
+        let target = system.bodies()[duna].elements.state_vectors_at_epoch(
+            sun_mass,
+            time_of_flight,
+            TOLERANCE,
+        );
+
+        let miss = |offset: Num| {
+            let (departure, _) = lambert::solve(
+                start,
+                target.position + Vec3::new(0.0, 0.0, offset),
+                time_of_flight,
+                sun_mass,
+                Vec3::Z,
+            )
+            .unwrap();
+            let segments = recalculate(
+                &system,
+                0,
+                departure,
+                0.0,
+                &TrajectorySettings::default(),
+            );
+
+            closest_approach(&system, &segments, duna, 50, TOLERANCE)
+                .unwrap()
+                .distance
+        };
+
+        let aimed = miss(0.0);
+        assert!(aimed < 0.1 * soi_radius(&system, duna), "{aimed}");
+        assert!(miss(2.0e8) > aimed);
+    }
+}
@@ -1,13 +1,497 @@
-use crate::{Mat3, Vec3, PI};
+use std::fmt;
 
-pub fn zup2yup(p: Vec3) -> Vec3 {
-    let m = Mat3::from_rotation_x(-PI / 2.0);
+use crate::astro::elliptic::estimate_anomaly_batch;
+use crate::math::wrap_angle;
+use crate::{vec3, KeplerianElements, Mat3, Num, StateVectors, Vec3};
+
+/// Orbits handed to each task of [`kepler_to_cartesian_batch_par`]
+#[cfg(feature = "rayon")]
+const PARALLEL_CHUNK_SIZE: usize = 1024;
+
+/// Tolerance used when validating arbitrary convention matrices
+const ORTHONORMAL_TOLERANCE: Num = 1e-5;
 
-    m.mul_vec3(p)
+pub fn zup2yup(p: Vec3) -> Vec3 {
+    AxisConvention::Z_UP_TO_Y_UP.convert(p)
 }
 
 pub fn yup2zup(p: Vec3) -> Vec3 {
-    let m = Mat3::from_rotation_x(PI / 2.0);
+    AxisConvention::Z_UP_TO_Y_UP.inverse().convert(p)
+}
+
+/// Rotates a vector from the ecliptic frame into the equatorial frame
+/// tilted by `obliquity` around the shared x axis (the vernal equinox).
+///
+/// See [`crate::constants::J2000_OBLIQUITY`] for the standard value.
+pub fn ecliptic_to_equatorial(v: Vec3, obliquity: Num) -> Vec3 {
+    Mat3::from_rotation_x(obliquity).mul_vec3(v)
+}
+
+/// Inverse of [`ecliptic_to_equatorial`]
+pub fn equatorial_to_ecliptic(v: Vec3, obliquity: Num) -> Vec3 {
+    Mat3::from_rotation_x(-obliquity).mul_vec3(v)
+}
+
+/// Rotation from the inertial frame to the LVLH frame of `sv`.
+///
+/// The LVLH (local vertical, local horizontal) axes used here are:
+/// - x: nadir, towards the center of the body
+/// - y: completes the right-handed basis, i.e. against the motion
+/// - z: along the orbit normal (the angular momentum)
+///
+/// This differs from the RTN frame of [`StateVectors::rtn_matrix`],
+/// whose x axis points away from the body and y axis along the motion:
+/// LVLH = diag(-1, -1, 1) * RTN. Some texts put the nadir on the z axis
+/// instead, double check the convention when comparing results.
+///
+/// ```text
+///   motion <------(sat)------> y
+///                   |
+///                   | x (nadir)
+///                   v
+///                (body)
+///
+///   z (orbit normal) points out of the page
+/// ```
+pub fn eci_to_lvlh(sv: &StateVectors) -> Mat3 {
+    lvlh_to_eci(sv).transpose()
+}
+
+/// Inverse of [`eci_to_lvlh`], its columns are the LVLH axes
+pub fn lvlh_to_eci(sv: &StateVectors) -> Mat3 {
+    let rtn = sv.rtn_matrix();
+
+    Mat3::from_cols(-rtn.x_axis, -rtn.y_axis, rtn.z_axis)
+}
+
+/// Positions of many orbits at the same epoch, written to `out_positions`.
+///
+/// `masses` holds the mass of the central body of each orbit.
+/// The work is split in passes over the whole batch: the mean anomalies,
+/// Kepler's equation (see [`estimate_anomaly_batch`], vectorized with
+/// the `simd` feature) and finally the perifocal positions and their
+/// rotation, which need neither branches nor the true anomaly.
+/// Hyperbolic orbits are rare enough to be handled one by one.
+///
+/// See `benches/kepler_to_cartesian.rs` for the speedup over calling
+/// [`KeplerianElements::state_vectors_at_epoch`] in a loop.
+///
+/// # Panics
+/// If the slices differ in length.
+pub fn kepler_to_cartesian_batch(
+    elements: &[KeplerianElements],
+    masses: &[Num],
+    epoch: Num,
+    tolerance: Num,
+    out_positions: &mut [Vec3],
+) {
+    assert_eq!(elements.len(), masses.len());
+    assert_eq!(elements.len(), out_positions.len());
+
+    let (Ms, es): (Vec<Num>, Vec<Num>) = elements
+        .iter()
+        .zip(masses)
+        .map(|(elements, &mass)| {
+            if elements.is_hyperbolic() {
+                // Trivial placeholder, overwritten below
+                (0.0, 0.0)
+            } else {
+                let M = wrap_angle(elements.mean_anomaly(mass, epoch));
+
+                (M, elements.eccentricity)
+            }
+        })
+        .unzip();
+
+    let Es = estimate_anomaly_batch(&Ms, &es, tolerance);
+
+    for ((elements, E), position) in
+        elements.iter().zip(Es).zip(out_positions.iter_mut())
+    {
+        let a = elements.semi_major_axis;
+        let e = elements.eccentricity;
+        let (sin_E, cos_E) = E.sin_cos();
+
+        let perifocal =
+            vec3(a * (cos_E - e), a * (1.0 - e.powi(2)).sqrt() * sin_E, 0.0);
+
+        *position = elements.perifocal_to_equatorial(perifocal);
+    }
+
+    for ((elements, &mass), position) in
+        elements.iter().zip(masses).zip(out_positions.iter_mut())
+    {
+        if elements.is_hyperbolic() {
+            *position = elements
+                .state_vectors_at_epoch(mass, epoch, tolerance)
+                .position;
+        }
+    }
+}
+
+/// Same as [`kepler_to_cartesian_batch`] but split across threads
+#[cfg(feature = "rayon")]
+pub fn kepler_to_cartesian_batch_par(
+    elements: &[KeplerianElements],
+    masses: &[Num],
+    epoch: Num,
+    tolerance: Num,
+    out_positions: &mut [Vec3],
+) {
+    use rayon::prelude::*;
+
+    assert_eq!(elements.len(), masses.len());
+    assert_eq!(elements.len(), out_positions.len());
+
+    out_positions
+        .par_chunks_mut(PARALLEL_CHUNK_SIZE)
+        .zip(elements.par_chunks(PARALLEL_CHUNK_SIZE))
+        .zip(masses.par_chunks(PARALLEL_CHUNK_SIZE))
+        .for_each(|((out_positions, elements), masses)| {
+            kepler_to_cartesian_batch(
+                elements,
+                masses,
+                epoch,
+                tolerance,
+                out_positions,
+            )
+        });
+}
+
+/// A target axis, with its direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignedAxis {
+    PosX,
+    NegX,
+    PosY,
+    NegY,
+    PosZ,
+    NegZ,
+}
+
+impl SignedAxis {
+    pub fn to_vec3(self) -> Vec3 {
+        match self {
+            Self::PosX => Vec3::X,
+            Self::NegX => Vec3::NEG_X,
+            Self::PosY => Vec3::Y,
+            Self::NegY => Vec3::NEG_Y,
+            Self::PosZ => Vec3::Z,
+            Self::NegZ => Vec3::NEG_Z,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AxisConventionError {
+    /// The mapped axes aren't orthonormal, e.g. an axis is used twice
+    NotOrthonormal,
+    /// The mapping flips the handedness and reflections weren't allowed
+    Reflection,
+}
+
+impl fmt::Display for AxisConventionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotOrthonormal => write!(f, "Axes are not orthonormal"),
+            Self::Reflection => {
+                write!(f, "Axis mapping introduces a reflection")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AxisConventionError {}
+
+/// Maps the axes of this crate onto the axes of another convention,
+/// e.g. the Y-up frame of a game engine.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AxisConvention {
+    matrix: Mat3,
+}
+
+impl AxisConvention {
+    /// The identity mapping
+    pub const IDENTITY: Self = Self {
+        matrix: Mat3::IDENTITY,
+    };
+
+    /// Z-up to Y-up, keeping the frame right-handed
+    pub const Z_UP_TO_Y_UP: Self = Self {
+        matrix: Mat3::from_cols(Vec3::X, Vec3::NEG_Z, Vec3::Y),
+    };
+
+    /// `x`, `y` and `z` are the target axes the crate's x, y and z axes map to.
+    ///
+    /// Rejects mappings that flip the handedness,
+    /// see [`AxisConvention::new_allow_reflection`].
+    pub fn new(
+        x: SignedAxis,
+        y: SignedAxis,
+        z: SignedAxis,
+    ) -> Result<Self, AxisConventionError> {
+        Self::from_matrix(Self::axes_matrix(x, y, z))
+    }
+
+    /// Same as [`AxisConvention::new`], but allows switching between
+    /// right and left-handed conventions.
+    pub fn new_allow_reflection(
+        x: SignedAxis,
+        y: SignedAxis,
+        z: SignedAxis,
+    ) -> Result<Self, AxisConventionError> {
+        Self::validate(Self::axes_matrix(x, y, z), true)
+    }
+
+    /// Builds the convention from a matrix whose columns are the images
+    /// of the crate's x, y and z axes. Rejects reflections.
+    pub fn from_matrix(matrix: Mat3) -> Result<Self, AxisConventionError> {
+        Self::validate(matrix, false)
+    }
+
+    pub fn matrix(&self) -> Mat3 {
+        self.matrix
+    }
+
+    pub fn is_reflection(&self) -> bool {
+        self.matrix.determinant() < 0.0
+    }
+
+    pub fn inverse(&self) -> Self {
+        Self {
+            matrix: self.matrix.transpose(),
+        }
+    }
+
+    /// Applies `self` first, then `other`
+    pub fn then(&self, other: &Self) -> Self {
+        Self {
+            matrix: other.matrix * self.matrix,
+        }
+    }
+
+    pub fn convert(&self, p: Vec3) -> Vec3 {
+        self.matrix.mul_vec3(p)
+    }
+
+    pub fn convert_state(&self, state_vectors: &StateVectors) -> StateVectors {
+        StateVectors::new(
+            self.convert(state_vectors.position),
+            self.convert(state_vectors.velocity),
+        )
+    }
+
+    fn axes_matrix(x: SignedAxis, y: SignedAxis, z: SignedAxis) -> Mat3 {
+        Mat3::from_cols(x.to_vec3(), y.to_vec3(), z.to_vec3())
+    }
+
+    fn validate(
+        matrix: Mat3,
+        allow_reflection: bool,
+    ) -> Result<Self, AxisConventionError> {
+        let gram = matrix.transpose() * matrix;
+
+        if !gram.abs_diff_eq(Mat3::IDENTITY, ORTHONORMAL_TOLERANCE) {
+            return Err(AxisConventionError::NotOrthonormal);
+        }
+
+        let convention = Self { matrix };
+
+        if convention.is_reflection() && !allow_reflection {
+            return Err(AxisConventionError::Reflection);
+        }
+
+        Ok(convention)
+    }
+}
+
+impl Default for AxisConvention {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SignedAxis::*;
+    use super::*;
+    use crate::{vec3, PI};
+
+    /// Mix of orbits around two bodies, with a few hyperbolic ones
+    fn batch() -> (Vec<KeplerianElements>, Vec<Num>) {
+        (0..100)
+            .map(|i| {
+                let i = i as Num;
+                let elements = KeplerianElements {
+                    semi_major_axis: 1.0 + i * 0.1,
+                    eccentricity: if i % 10.0 == 0.0 {
+                        1.2
+                    } else {
+                        (i * 0.37).rem_euclid(0.9)
+                    },
+                    inclination: (i * 0.11).rem_euclid(PI),
+                    right_ascension_of_the_ascending_node: i * 0.23,
+                    argument_of_periapsis: i * 0.31,
+                    mean_anomaly_at_epoch: i * 0.57,
+                    epoch: 0.0,
+                };
+                let mass = if i < 50.0 { 1e11 } else { 3e11 };
+
+                (elements, mass)
+            })
+            .unzip()
+    }
+
+    /// All axis permutations, with signs chosen to keep them right-handed
+    const RIGHT_HANDED: [[SignedAxis; 3]; 6] = [
+        [PosX, PosY, PosZ],
+        [PosY, PosZ, PosX],
+        [PosZ, PosX, PosY],
+        [PosX, NegZ, PosY],
+        [PosY, NegX, PosZ],
+        [PosZ, PosY, NegX],
+    ];
+
+    #[test]
+    fn lvlh_is_orthonormal() {
+        let sv = StateVectors::new(vec3(1.0, 0.5, -0.2), vec3(-0.3, 2.0, 0.7));
+
+        let m = eci_to_lvlh(&sv);
+        assert!((m.transpose() * m).abs_diff_eq(Mat3::IDENTITY, 1e-6));
+        assert!((m.determinant() - 1.0).abs() < 1e-6);
+        assert!((m * lvlh_to_eci(&sv)).abs_diff_eq(Mat3::IDENTITY, 1e-6));
+
+        // Nadir and orbit normal
+        let nadir = m * -sv.position;
+        assert!(nadir.normalize().abs_diff_eq(Vec3::X, 1e-6), "{nadir:?}");
+        let normal = m * sv.position.cross(sv.velocity);
+        assert!(normal.normalize().abs_diff_eq(Vec3::Z, 1e-6), "{normal:?}");
+
+        let v = sv.velocity_in_lvlh();
+        assert!(v.y < 0.0 && v.z.abs() < 1e-6, "{v:?}");
+        assert!((v.length() - sv.velocity.length()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn right_handed_permutations() {
+        let p = vec3(0.3, -1.5, 2.0);
+
+        for [x, y, z] in RIGHT_HANDED {
+            let convention = AxisConvention::new(x, y, z).unwrap();
+            assert!(!convention.is_reflection());
+
+            assert_eq!(convention.convert(Vec3::X), x.to_vec3());
+            assert_eq!(convention.convert(Vec3::Y), y.to_vec3());
+            assert_eq!(convention.convert(Vec3::Z), z.to_vec3());
+
+            // Cross products are preserved
+            let converted_z = convention
+                .convert(Vec3::X)
+                .cross(convention.convert(Vec3::Y));
+            assert_eq!(converted_z, convention.convert(Vec3::Z));
+
+            let identity = convention.then(&convention.inverse());
+            assert_eq!(identity, AxisConvention::IDENTITY);
+            assert_eq!(convention.inverse().convert(convention.convert(p)), p);
+        }
+    }
+
+    #[test]
+    fn reflections_must_be_allowed() {
+        for [x, y, z] in RIGHT_HANDED {
+            let flipped = match x {
+                PosX => NegX,
+                PosY => NegY,
+                _ => NegZ,
+            };
+
+            assert_eq!(
+                AxisConvention::new(flipped, y, z),
+                Err(AxisConventionError::Reflection)
+            );
+
+            let convention =
+                AxisConvention::new_allow_reflection(flipped, y, z).unwrap();
+            assert!(convention.is_reflection());
+        }
+    }
+
+    #[test]
+    fn repeated_axes_are_rejected() {
+        assert_eq!(
+            AxisConvention::new_allow_reflection(PosX, NegX, PosZ),
+            Err(AxisConventionError::NotOrthonormal)
+        );
+        assert_eq!(
+            AxisConvention::from_matrix(Mat3::from_diagonal(vec3(
+                1.0, 2.0, 1.0
+            ))),
+            Err(AxisConventionError::NotOrthonormal)
+        );
+        assert!(AxisConvention::from_matrix(Mat3::from_rotation_y(0.4)).is_ok());
+    }
+
+    #[test]
+    fn zup2yup_is_a_rotation_around_x() {
+        let p = vec3(0.3, -1.5, 2.0);
+
+        let rotated = Mat3::from_rotation_x(-PI / 2.0).mul_vec3(p);
+        assert!(zup2yup(p).abs_diff_eq(rotated, 1e-6));
+        assert_eq!(yup2zup(zup2yup(p)), p);
+
+        let state = StateVectors::new(p, Vec3::Z);
+        let converted = AxisConvention::Z_UP_TO_Y_UP.convert_state(&state);
+        assert_eq!(converted.position, zup2yup(p));
+        assert_eq!(converted.velocity, Vec3::Y);
+    }
+
+    #[test]
+    fn kepler_to_cartesian_batch_matches_scalar() {
+        let (elements, masses) = batch();
+        assert!(elements.iter().any(|e| e.is_hyperbolic()));
+
+        let mut positions = vec![Vec3::ZERO; elements.len()];
+        kepler_to_cartesian_batch(
+            &elements,
+            &masses,
+            2.5,
+            1e-6,
+            &mut positions,
+        );
+
+        for ((elements, &mass), position) in
+            elements.iter().zip(&masses).zip(&positions)
+        {
+            let exp = elements.state_vectors_at_epoch(mass, 2.5, 1e-5).position;
+
+            assert!(
+                position.distance(exp) < 1e-3 * exp.length(),
+                "{elements:?}: {position} != {exp}"
+            );
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn kepler_to_cartesian_batch_par_matches_serial() {
+        let (elements, masses) = batch();
+        let elements = elements.repeat(30);
+        let masses = masses.repeat(30);
+
+        let mut serial = vec![Vec3::ZERO; elements.len()];
+        let mut parallel = vec![Vec3::ZERO; elements.len()];
+        kepler_to_cartesian_batch(&elements, &masses, 2.5, 1e-5, &mut serial);
+        kepler_to_cartesian_batch_par(
+            &elements,
+            &masses,
+            2.5,
+            1e-6,
+            &mut parallel,
+        );
 
-    m.mul_vec3(p)
+        // The SIMD lanes may split differently within the chunks
+        for (a, b) in serial.iter().zip(&parallel) {
+            assert!(a.abs_diff_eq(*b, 1e-4 * a.length()), "{a} != {b}");
+        }
+    }
 }